@@ -24,6 +24,15 @@ use proc_macro::TokenStream;
 /// }
 /// ```
 ///
+/// A reason can be given, which is shown by `cargo mutants --list --explain-skips`:
+///
+/// ```
+/// #[mutants::skip(reason = "returning false would hang")]
+/// pub fn should_stop() -> bool {
+///     true
+/// }
+/// ```
+///
 /// This is a no-op during compilation, but is seen by cargo-mutants as it processes the source.
 #[proc_macro_attribute]
 pub fn skip(_attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -0,0 +1,167 @@
+// Copyright 2025 Martin Pool
+
+//! Reuse a successful baseline from a previous run, with `--baseline=reuse`.
+//!
+//! A baseline is identified by a hash of everything that could change its result: the
+//! contents of the source tree, the cargo command lines, the Rust and Cargo environment
+//! variables, and the version of the toolchain. If any of these change, the baseline is
+//! run again.
+
+use std::env;
+use std::fs::{read, read_link};
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{ensure, Context};
+use camino::Utf8Path;
+use path_slash::PathExt;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::cargo::{cargo_argv, doctest_argv};
+use crate::copy_tree::source_tree_walk_builder;
+use crate::mutant::fnv1a_64;
+use crate::package::PackageSelection;
+use crate::{check_interrupted, Options, Phase, Result, ScenarioOutcome};
+
+/// The parts of a successful baseline that are needed to test mutants without running
+/// it again, saved in `mutants.out/baseline.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineRecord {
+    /// A hash of the source tree, options, and toolchain, from [`baseline_key`].
+    pub key: String,
+    /// The duration of the build phase in seconds, if it was run.
+    pub build_secs: Option<f64>,
+    /// The duration of the test phase in seconds, if it was run.
+    pub test_secs: Option<f64>,
+}
+
+impl BaselineRecord {
+    pub fn from_outcome(key: String, outcome: &ScenarioOutcome) -> BaselineRecord {
+        let secs = |phase| outcome.phase_duration(phase).map(|d| d.as_secs_f64());
+        BaselineRecord {
+            key,
+            build_secs: secs(Phase::Build),
+            test_secs: secs(Phase::Test),
+        }
+    }
+
+    pub fn build_duration(&self) -> Option<Duration> {
+        self.build_secs.map(Duration::from_secs_f64)
+    }
+
+    pub fn test_duration(&self) -> Option<Duration> {
+        self.test_secs.map(Duration::from_secs_f64)
+    }
+}
+
+/// Compute a key identifying everything that could change the result of the baseline
+/// for these packages.
+pub fn baseline_key(
+    root: &Utf8Path,
+    packages: &PackageSelection,
+    options: &Options,
+) -> Result<String> {
+    let mut parts = Vec::new();
+    for &phase in options.phases() {
+        parts.push(cargo_argv(packages, phase, options).join("\x1f"));
+    }
+    if options.doctests && options.phases().contains(&Phase::Test) {
+        parts.push(doctest_argv(packages, options).join("\x1f"));
+    }
+    let mut env_vars = env::vars_os()
+        .map(|(name, value)| (name.to_string_lossy().into_owned(), value))
+        .filter(|(name, _)| {
+            // Our own options don't change the baseline, or if they do they're
+            // reflected in the cargo arguments.
+            (name.starts_with("RUST") || name.starts_with("CARGO"))
+                && !name.starts_with("CARGO_MUTANTS_")
+        })
+        .map(|(name, value)| format!("{name}={}", value.to_string_lossy()))
+        .collect::<Vec<_>>();
+    env_vars.sort();
+    parts.extend(env_vars);
+    parts.push(toolchain_version(root)?);
+    let mut walk_builder = source_tree_walk_builder(root, options, &["target"]);
+    walk_builder.sort_by_file_name(Ord::cmp);
+    for entry in walk_builder.build() {
+        check_interrupted()?;
+        let entry = entry?;
+        let path = entry.path();
+        let relative_path = path
+            .strip_prefix(root)
+            .expect("entry path is in root")
+            .to_slash_lossy();
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_file() {
+            let content = read(path).with_context(|| format!("read {relative_path}"))?;
+            parts.push(format!("{relative_path}\0{:016x}", fnv1a_64(&content)));
+        } else if file_type.is_symlink() {
+            let target = read_link(path).with_context(|| format!("read link {relative_path}"))?;
+            parts.push(format!("{relative_path}\0-> {}", target.to_slash_lossy()));
+        }
+    }
+    let key = format!("{:016x}", fnv1a_64(parts.join("\n").as_bytes()));
+    debug!(?key, "computed baseline key");
+    Ok(key)
+}
+
+/// Return the verbose version of the Rust compiler that will be used in this tree.
+fn toolchain_version(root: &Utf8Path) -> Result<String> {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let output = Command::new(&rustc)
+        .arg("-vV")
+        .current_dir(root)
+        .output()
+        .with_context(|| format!("run {rustc} -vV"))?;
+    ensure!(output.status.success(), "{rustc} -vV failed");
+    String::from_utf8(output.stdout).context("rustc version is not UTF-8")
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::{create_dir_all, write};
+
+    use camino::Utf8PathBuf;
+
+    use super::*;
+    use crate::test_util::copy_of_testdata;
+
+    #[test]
+    fn key_changes_with_source_and_options() {
+        let tmp = copy_of_testdata("factorial");
+        let root = Utf8PathBuf::try_from(tmp.path().to_owned()).unwrap();
+        let options = Options::from_arg_strs(["mutants"]);
+        let key = baseline_key(&root, &PackageSelection::All, &options).unwrap();
+        assert_eq!(key.len(), 16);
+        assert_eq!(
+            baseline_key(&root, &PackageSelection::All, &options).unwrap(),
+            key,
+            "key is stable"
+        );
+
+        create_dir_all(root.join("target")).unwrap();
+        write(root.join("target/ignored.txt"), "built").unwrap();
+        assert_eq!(
+            baseline_key(&root, &PackageSelection::All, &options).unwrap(),
+            key,
+            "target directory is not included"
+        );
+
+        let features = Options::from_arg_strs(["mutants", "--features", "x"]);
+        assert_ne!(
+            baseline_key(&root, &PackageSelection::All, &features).unwrap(),
+            key,
+            "cargo arguments are included"
+        );
+
+        write(root.join("src/bin/factorial.rs"), "fn main() {}\n").unwrap();
+        assert_ne!(
+            baseline_key(&root, &PackageSelection::All, &options).unwrap(),
+            key,
+            "source is included"
+        );
+    }
+}
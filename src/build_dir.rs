@@ -4,16 +4,18 @@
 
 #![warn(clippy::pedantic)]
 
-use std::fs::write;
+use std::fs::{copy, create_dir_all, hard_link, write};
 
 use anyhow::{ensure, Context};
 use camino::{Utf8Path, Utf8PathBuf};
+use ignore::WalkBuilder;
 use tempfile::TempDir;
-use tracing::info;
+use tracing::{debug, info, warn};
 
 use crate::{
+    check_interrupted,
     console::Console,
-    copy_tree::copy_tree,
+    copy_tree::{copy_target_dir, copy_tree},
     manifest::{fix_cargo_config, fix_manifest},
     options::Options,
     workspace::Workspace,
@@ -32,6 +34,8 @@ pub struct BuildDir {
     /// object is dropped. If None, there's nothing to clean up.
     #[allow(dead_code)]
     temp_dir: Option<TempDir>,
+    /// True if this is the original source directory, rather than a scratch copy.
+    in_place: bool,
 }
 
 impl BuildDir {
@@ -47,10 +51,43 @@ impl BuildDir {
         if options.in_place {
             BuildDir::in_place(workspace.root())
         } else {
+            BuildDir::copy_from_workspace(workspace, options, console)
+        }
+    }
+
+    /// Make a build dir for a parallel job, after the baseline.
+    ///
+    /// With `--share-deps` the dependencies are linked from the baseline by the lab,
+    /// so they're not also copied here.
+    pub fn for_job(
+        workspace: &Workspace,
+        options: &Options,
+        console: &Console,
+    ) -> Result<BuildDir> {
+        if options.share_deps {
             BuildDir::copy_from(workspace.root(), options, console)
+        } else {
+            BuildDir::copy_from_workspace(workspace, options, console)
         }
     }
 
+    /// Copy the workspace into a new build dir, and copy its dependency artifacts if
+    /// `--copy-target-deps` is on.
+    fn copy_from_workspace(
+        workspace: &Workspace,
+        options: &Options,
+        console: &Console,
+    ) -> Result<BuildDir> {
+        let build_dir = BuildDir::copy_from(workspace.root(), options, console)?;
+        if options.copy_target_deps && options.baseline_from.is_none() {
+            build_dir.copy_dependencies_from(
+                workspace.target_directory(),
+                &workspace.local_crate_names(),
+            )?;
+        }
+        Ok(build_dir)
+    }
+
     /// Make a new build dir, copying from a source directory, subject to exclusions.
     pub fn copy_from(source: &Utf8Path, options: &Options, console: &Console) -> Result<BuildDir> {
         let name_base = format!("cargo-mutants-{}-", source.file_name().unwrap_or("unnamed"));
@@ -65,6 +102,13 @@ impl BuildDir {
             .context("tempdir path to UTF-8")?;
         fix_manifest(&path.join("Cargo.toml"), &source_abs)?;
         fix_cargo_config(&path, &source_abs)?;
+        if let Some(baseline_from) = &options.baseline_from {
+            ensure!(
+                baseline_from.is_dir(),
+                "--baseline-from {baseline_from:?} is not a directory"
+            );
+            copy_target_dir(baseline_from, &path.join("target"), console)?;
+        }
         let temp_dir = if options.leak_dirs {
             let _ = temp_dir.into_path();
             info!(?path, "Build directory will be leaked for inspection");
@@ -72,7 +116,11 @@ impl BuildDir {
         } else {
             Some(temp_dir)
         };
-        let build_dir = BuildDir { path, temp_dir };
+        let build_dir = BuildDir {
+            path,
+            temp_dir,
+            in_place: false,
+        };
         Ok(build_dir)
     }
 
@@ -80,6 +128,7 @@ impl BuildDir {
     pub fn in_place(source_path: &Utf8Path) -> Result<BuildDir> {
         Ok(BuildDir {
             temp_dir: None,
+            in_place: true,
             path: source_path
                 .canonicalize_utf8()
                 .context("canonicalize source path")?,
@@ -90,6 +139,122 @@ impl BuildDir {
         self.path.as_path()
     }
 
+    /// Measure the total size of files in this directory, if it's a scratch copy.
+    ///
+    /// Returns None for in-place build directories, which aren't scratch space.
+    pub fn scratch_disk_usage(&self) -> Result<Option<u64>> {
+        if self.in_place {
+            return Ok(None);
+        }
+        let mut total_bytes = 0;
+        for entry in WalkBuilder::new(&self.path).standard_filters(false).build() {
+            let entry = entry.context("Walk build directory")?;
+            if entry.file_type().is_some_and(|ft| ft.is_file()) {
+                total_bytes += entry.metadata().context("Read file metadata")?.len();
+            }
+        }
+        Ok(Some(total_bytes))
+    }
+
+    /// Hard-link the dependency artifacts from another build directory's `target`
+    /// into this one, so that they don't need to be built again.
+    ///
+    /// Artifacts of the crates in the workspace, named by `local_crate_names`, are not
+    /// linked, because they're rebuilt for each mutant and cargo might overwrite them in
+    /// place. The incremental build directory and the final outputs at the top of each
+    /// profile are also not shared.
+    pub fn link_dependencies_from(
+        &self,
+        source: &Utf8Path,
+        local_crate_names: &[String],
+    ) -> Result<()> {
+        self.transfer_dependencies(&source.join("target"), local_crate_names, Transfer::Link)
+    }
+
+    /// Copy the dependency artifacts from a cargo target directory into this build
+    /// directory, skipping the artifacts of crates in the workspace.
+    ///
+    /// If the layout of the target directory isn't recognized, nothing is copied and
+    /// the dependencies will be built from scratch.
+    pub fn copy_dependencies_from(
+        &self,
+        source_target: &Utf8Path,
+        local_crate_names: &[String],
+    ) -> Result<()> {
+        self.transfer_dependencies(source_target, local_crate_names, Transfer::Copy)
+    }
+
+    fn transfer_dependencies(
+        &self,
+        source_target: &Utf8Path,
+        local_crate_names: &[String],
+        transfer: Transfer,
+    ) -> Result<()> {
+        if !source_target.is_dir() {
+            debug!(?source_target, "No target directory to share");
+            return Ok(());
+        }
+        let dest_target = self.path.join("target");
+        let mut artifacts = Vec::new();
+        let mut any_files = false;
+        for entry in WalkBuilder::new(source_target)
+            .standard_filters(false)
+            .build()
+        {
+            check_interrupted()?;
+            let entry = entry.context("Walk target directory")?;
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            any_files = true;
+            let relative_path = Utf8Path::from_path(entry.path())
+                .context("Target path is not UTF-8")?
+                .strip_prefix(source_target)
+                .expect("entry is in target directory")
+                .to_owned();
+            if is_dependency_artifact(&relative_path, local_crate_names) {
+                artifacts.push(relative_path);
+            }
+        }
+        if artifacts.is_empty() {
+            if any_files {
+                warn!(
+                    "No dependency artifacts recognized in {source_target:?}; dependencies will be built from scratch"
+                );
+            }
+            return Ok(());
+        }
+        let mut total_bytes = 0;
+        for relative_path in &artifacts {
+            let source_path = source_target.join(relative_path);
+            let dest_path = dest_target.join(relative_path);
+            if dest_path.exists() {
+                continue;
+            }
+            create_dir_all(dest_path.parent().expect("artifact has a parent"))
+                .with_context(|| format!("Failed to create directory for {dest_path:?}"))?;
+            match transfer {
+                Transfer::Link => hard_link(&source_path, &dest_path).with_context(|| {
+                    format!("Failed to link {relative_path:?} into {dest_target:?}")
+                })?,
+                Transfer::Copy => {
+                    total_bytes += copy(&source_path, &dest_path).with_context(|| {
+                        format!("Failed to copy {relative_path:?} into {dest_target:?}")
+                    })?;
+                }
+            }
+        }
+        debug!(
+            n_artifacts = artifacts.len(),
+            total_bytes,
+            ?transfer,
+            ?source_target,
+            ?dest_target,
+            "Transferred dependency artifacts"
+        );
+        Ok(())
+    }
+
     pub fn overwrite_file(&self, relative_path: &Utf8Path, code: &str) -> Result<()> {
         let full_path = self.path.join(relative_path);
         // for safety, don't follow symlinks
@@ -99,6 +264,43 @@ impl BuildDir {
     }
 }
 
+/// How dependency artifacts are brought into a build directory.
+#[derive(Debug, Clone, Copy)]
+enum Transfer {
+    /// Hard-link the files, sharing their storage.
+    Link,
+    /// Copy the files.
+    Copy,
+}
+
+/// True if this path, relative to a target directory, is a build artifact of a
+/// dependency rather than of a crate in the workspace.
+///
+/// Dependency artifacts are under the `deps`, `build`, or `.fingerprint` directory of
+/// a profile, and are named like `libserde-0123abcd.rlib` or `serde-0123abcd`.
+fn is_dependency_artifact(relative_path: &Utf8Path, local_crate_names: &[String]) -> bool {
+    let components: Vec<&str> = relative_path.iter().collect();
+    let Some(kind_index) = components
+        .iter()
+        .position(|c| ["deps", "build", ".fingerprint"].contains(c))
+    else {
+        return false;
+    };
+    // Something like `debug/deps` or `x86_64-unknown-linux-gnu/debug/deps`.
+    if kind_index == 0 || components[..kind_index].contains(&"incremental") {
+        return false;
+    }
+    let Some(artifact_name) = components.get(kind_index + 1) else {
+        return false;
+    };
+    let Some((crate_name, _hash)) = artifact_name.rsplit_once('-') else {
+        return false;
+    };
+    let crate_name = crate_name.replace('-', "_");
+    let is_local = |name: &str| local_crate_names.iter().any(|local| local == name);
+    !(is_local(&crate_name) || crate_name.strip_prefix("lib").is_some_and(is_local))
+}
+
 #[cfg(test)]
 mod test {
     use crate::test_util::copy_of_testdata;
@@ -124,6 +326,45 @@ mod test {
         assert!(build_dir.path().join("src").is_dir());
     }
 
+    #[test]
+    fn build_dir_copy_from_with_baseline_from_target() -> Result<()> {
+        let tmp = copy_of_testdata("factorial");
+        let workspace = Workspace::open(tmp.path())?;
+        let warm_target = tempfile::tempdir()?;
+        let warm_target_path = Utf8Path::from_path(warm_target.path()).unwrap();
+        std::fs::create_dir(warm_target_path.join("debug"))?;
+        write(warm_target_path.join("debug/artifact"), "built")?;
+        let options = Options {
+            baseline_from: Some(warm_target_path.to_owned()),
+            ..Default::default()
+        };
+        let build_dir = BuildDir::copy_from(workspace.root(), &options, &Console::new())?;
+        assert_eq!(
+            std::fs::read_to_string(build_dir.path().join("target/debug/artifact"))?,
+            "built"
+        );
+        assert!(!workspace.root().join("target/debug/artifact").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn scratch_disk_usage_counts_copied_files() -> Result<()> {
+        let tmp = copy_of_testdata("factorial");
+        let workspace = Workspace::open(tmp.path())?;
+        let build_dir =
+            BuildDir::copy_from(workspace.root(), &Options::default(), &Console::new())?;
+        let before = build_dir
+            .scratch_disk_usage()?
+            .expect("scratch dir has a size");
+        assert!(before > 0);
+        write(build_dir.path().join("big"), vec![b'x'; 10_000])?;
+        assert_eq!(build_dir.scratch_disk_usage()?, Some(before + 10_000));
+
+        let in_place = BuildDir::in_place(workspace.root())?;
+        assert_eq!(in_place.scratch_disk_usage()?, None);
+        Ok(())
+    }
+
     #[test]
     fn for_baseline_in_place() -> Result<()> {
         let tmp = copy_of_testdata("factorial");
@@ -174,4 +415,64 @@ mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn dependency_artifacts_are_recognized() {
+        let local = ["factorial".to_owned(), "my_lib".to_owned()];
+        let is_dep = |path: &str| is_dependency_artifact(Utf8Path::new(path), &local);
+        assert!(is_dep("debug/deps/libserde-0123abcd.rlib"));
+        assert!(is_dep("debug/deps/serde-0123abcd.d"));
+        assert!(is_dep("debug/.fingerprint/serde-0123abcd/lib-serde"));
+        assert!(is_dep("debug/build/proc-macro2-0123abcd/out/file"));
+        assert!(is_dep(
+            "x86_64-unknown-linux-gnu/release/deps/libitoa-0123abcd.rlib"
+        ));
+        assert!(!is_dep("debug/deps/factorial-0123abcd"));
+        assert!(!is_dep("debug/deps/libmy_lib-0123abcd.rlib"));
+        assert!(!is_dep("debug/.fingerprint/my-lib-0123abcd/lib-my_lib"));
+        assert!(!is_dep("debug/factorial"));
+        assert!(!is_dep(
+            "debug/incremental/serde-0123abcd/s-xyz/dep-graph.bin"
+        ));
+        assert!(!is_dep("deps/libserde-0123abcd.rlib"));
+        assert!(!is_dep(".rustc_info.json"));
+    }
+
+    #[test]
+    fn link_dependencies_from_another_build_dir() -> Result<()> {
+        let tmp = copy_of_testdata("factorial");
+        let workspace = Workspace::open(tmp.path())?;
+        let build_dir =
+            BuildDir::copy_from(workspace.root(), &Options::default(), &Console::new())?;
+        let deps = workspace.root().join("target/debug/deps");
+        create_dir_all(&deps)?;
+        write(deps.join("libserde-0123abcd.rlib"), "dependency")?;
+        write(deps.join("factorial-0123abcd"), "local")?;
+        build_dir.link_dependencies_from(workspace.root(), &workspace.local_crate_names())?;
+        let linked = build_dir.path().join("target/debug/deps");
+        assert_eq!(
+            std::fs::read_to_string(linked.join("libserde-0123abcd.rlib"))?,
+            "dependency"
+        );
+        assert!(!linked.join("factorial-0123abcd").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn copy_dependencies_from_unrecognized_target_copies_nothing() -> Result<()> {
+        let tmp = copy_of_testdata("factorial");
+        let workspace = Workspace::open(tmp.path())?;
+        let build_dir =
+            BuildDir::copy_from(workspace.root(), &Options::default(), &Console::new())?;
+        let target = tempfile::tempdir()?;
+        let target_path = Utf8Path::from_path(target.path()).unwrap();
+        create_dir_all(target_path.join("new-layout/artifacts"))?;
+        write(
+            target_path.join("new-layout/artifacts/libserde.rlib"),
+            "dep",
+        )?;
+        build_dir.copy_dependencies_from(target_path, &workspace.local_crate_names())?;
+        assert!(!build_dir.path().join("target").exists());
+        Ok(())
+    }
 }
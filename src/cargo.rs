@@ -6,9 +6,12 @@
 #![allow(clippy::module_name_repetitions)]
 
 use std::env;
-use std::iter::once;
+use std::fs::create_dir_all;
+use std::mem::take;
+use std::sync::Once;
 use std::time::{Duration, Instant};
 
+use camino::Utf8Path;
 use tracing::{debug, debug_span, warn};
 
 use crate::build_dir::BuildDir;
@@ -19,12 +22,13 @@ use crate::outcome::{Phase, PhaseResult};
 use crate::output::ScenarioOutput;
 use crate::package::PackageSelection;
 use crate::process::{Exit, Process};
-use crate::Result;
+use crate::{Context, Result};
 
 /// Run cargo build, check, or test.
 #[allow(clippy::too_many_arguments)] // I agree it's a lot but I'm not sure wrapping in a struct would be better.
 pub fn run_cargo(
     build_dir: &BuildDir,
+    cargo_dir: Option<&Utf8Path>,
     jobserver: Option<&jobserver::Client>,
     packages: &PackageSelection,
     phase: Phase,
@@ -34,23 +38,105 @@ pub fn run_cargo(
     console: &Console,
 ) -> Result<PhaseResult> {
     let _span = debug_span!("run", ?phase).entered();
+    let argv = phase_argv(build_dir.path(), packages, phase, options);
+    if phase == Phase::Build && options.uses_nextest_archive() {
+        // nextest won't create the directory for the archive.
+        let archive_dir = build_dir.path().join(NEXTEST_ARCHIVE_PATH);
+        let archive_dir = archive_dir
+            .parent()
+            .expect("archive has a parent directory");
+        create_dir_all(archive_dir)
+            .with_context(|| format!("Failed to create directory {archive_dir}"))?;
+    }
+    run_argv(
+        build_dir,
+        cargo_dir,
+        jobserver,
+        phase,
+        argv,
+        timeout,
+        scenario_output,
+        options,
+        console,
+    )
+}
+
+/// Run the doctests with `cargo test --doc`, for `--doctests`.
+///
+/// The result is reported as part of the test phase.
+#[allow(clippy::too_many_arguments)]
+pub fn run_doctests(
+    build_dir: &BuildDir,
+    cargo_dir: Option<&Utf8Path>,
+    jobserver: Option<&jobserver::Client>,
+    packages: &PackageSelection,
+    timeout: Option<Duration>,
+    scenario_output: &mut ScenarioOutput,
+    options: &Options,
+    console: &Console,
+) -> Result<PhaseResult> {
+    let _span = debug_span!("run doctests").entered();
+    let argv = doctest_argv(packages, options);
+    run_argv(
+        build_dir,
+        cargo_dir,
+        jobserver,
+        Phase::Test,
+        argv,
+        timeout,
+        scenario_output,
+        options,
+        console,
+    )
+}
+
+/// Run a cargo command for the given phase, with the environment set up for cargo-mutants.
+///
+/// `cargo_dir` is the directory relative to the build directory where cargo runs, or
+/// `None` to run it in the root of the build directory.
+#[allow(clippy::too_many_arguments)]
+fn run_argv(
+    build_dir: &BuildDir,
+    cargo_dir: Option<&Utf8Path>,
+    jobserver: Option<&jobserver::Client>,
+    phase: Phase,
+    argv: Vec<String>,
+    timeout: Option<Duration>,
+    scenario_output: &mut ScenarioOutput,
+    options: &Options,
+    console: &Console,
+) -> Result<PhaseResult> {
     let start = Instant::now();
-    let argv = cargo_argv(packages, phase, options);
     let mut env = vec![
         // The tests might use Insta <https://insta.rs>, and we don't want it to write
         // updates to the source tree, and we *certainly* don't want it to write
         // updates and then let the test pass.
         ("INSTA_UPDATE".to_owned(), "no".to_owned()),
         ("INSTA_FORCE_PASS".to_owned(), "0".to_owned()),
+        // Output goes to the log files, which shouldn't contain escape codes, even if
+        // `CARGO_TERM_COLOR` is set to force colors in cargo-mutants' own output.
+        ("CARGO_TERM_COLOR".to_owned(), "never".to_owned()),
     ];
     if let Some(encoded_rustflags) = encoded_rustflags(options) {
         debug!(?encoded_rustflags);
         env.push(("CARGO_ENCODED_RUSTFLAGS".to_owned(), encoded_rustflags));
     }
+    if let Some(encoded_rustdocflags) = encoded_rustdocflags(options) {
+        debug!(?encoded_rustdocflags);
+        env.push((
+            "CARGO_ENCODED_RUSTDOCFLAGS".to_owned(),
+            encoded_rustdocflags,
+        ));
+    }
+    let cwd = cargo_dir.map_or_else(
+        || build_dir.path().to_owned(),
+        |cargo_dir| build_dir.path().join(cargo_dir),
+    );
+    debug!(?cwd);
     let process_status = Process::run(
         &argv,
         &env,
-        build_dir.path(),
+        &cwd,
         timeout,
         jobserver,
         scenario_output,
@@ -83,10 +169,72 @@ pub fn cargo_bin() -> String {
     env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned())
 }
 
+/// Make an argv that runs a command through the platform's shell.
+fn shell_argv(command: &str) -> Vec<String> {
+    if cfg!(windows) {
+        vec!["cmd".to_owned(), "/C".to_owned(), command.to_owned()]
+    } else {
+        vec!["sh".to_owned(), "-c".to_owned(), command.to_owned()]
+    }
+}
+
+/// The path, relative to the build directory, of the nextest archive built by
+/// `--nextest-archive`.
+const NEXTEST_ARCHIVE_PATH: &str = "target/mutants-nextest-archive.tar.zst";
+
+/// Make up the argv to run a phase in a build directory.
+///
+/// This is the same as [`cargo_argv`], except that with `--nextest-archive` the build
+/// phase writes a nextest archive into the build directory, and the test phase runs
+/// the tests from that archive.
+///
+/// When running from an archive, nextest doesn't invoke cargo at all, so the cargo
+/// options, packages, and features only apply when the archive is built.
+fn phase_argv(
+    build_dir_path: &Utf8Path,
+    packages: &PackageSelection,
+    phase: Phase,
+    options: &Options,
+) -> Vec<String> {
+    if options.uses_nextest_archive() {
+        let archive_file = build_dir_path.join(NEXTEST_ARCHIVE_PATH);
+        match phase {
+            Phase::Build => {
+                let mut argv = cargo_argv(packages, phase, options);
+                argv.push(format!("--archive-file={archive_file}"));
+                return argv;
+            }
+            Phase::Test => {
+                let mut argv = vec![
+                    cargo_bin(),
+                    "nextest".to_owned(),
+                    "run".to_owned(),
+                    format!("--archive-file={archive_file}"),
+                    format!("--workspace-remap={build_dir_path}"),
+                    "--verbose".to_owned(),
+                ];
+                argv.extend(options.additional_cargo_test_args.iter().cloned());
+                return argv;
+            }
+            Phase::Check => (),
+        }
+    }
+    cargo_argv(packages, phase, options)
+}
+
 /// Make up the argv for a cargo check/build/test invocation, including argv[0] as the
 /// cargo binary itself.
+///
+/// If a `--test-command` was given, the test phase instead runs that command in a shell.
 // (This is split out so it's easier to test.)
-fn cargo_argv(packages: &PackageSelection, phase: Phase, options: &Options) -> Vec<String> {
+pub(crate) fn cargo_argv(
+    packages: &PackageSelection,
+    phase: Phase,
+    options: &Options,
+) -> Vec<String> {
+    if let (Phase::Test, Some(test_command)) = (phase, &options.test_command) {
+        return shell_argv(test_command);
+    }
     let mut cargo_args = vec![cargo_bin()];
     match phase {
         Phase::Test => match &options.test_tool {
@@ -106,6 +254,10 @@ fn cargo_argv(packages: &PackageSelection, phase: Phase, options: &Options) -> V
                     cargo_args.push("test".to_string());
                     cargo_args.push("--no-run".to_string());
                 }
+                TestTool::Nextest if options.uses_nextest_archive() => {
+                    cargo_args.push("nextest".to_string());
+                    cargo_args.push("archive".to_string());
+                }
                 TestTool::Nextest => {
                     cargo_args.push("nextest".to_string());
                     cargo_args.push("run".to_string());
@@ -118,23 +270,61 @@ fn cargo_argv(packages: &PackageSelection, phase: Phase, options: &Options) -> V
             cargo_args.push("--tests".to_string());
         }
     }
+    push_common_args(&mut cargo_args, packages, options.test_tool, options);
+    cargo_args.extend(options.additional_cargo_args.iter().cloned());
+    if phase == Phase::Test {
+        cargo_args.extend(options.additional_cargo_test_args.iter().cloned());
+    }
+    cargo_args
+}
+
+/// Make up the argv for `cargo test --doc`, to run the doctests for `--doctests`.
+///
+/// Nextest can't run doctests, so this always uses `cargo test`. The
+/// `additional_cargo_test_args` aren't passed, because they commonly include
+/// options like `--all-targets` that conflict with `--doc`.
+pub(crate) fn doctest_argv(packages: &PackageSelection, options: &Options) -> Vec<String> {
+    let mut argv = vec![cargo_bin(), "test".to_owned(), "--doc".to_owned()];
+    push_common_args(&mut argv, packages, TestTool::Cargo, options);
+    argv.extend(options.additional_cargo_args.iter().cloned());
+    argv
+}
+
+/// Add the options for the profile, target, jobs, packages, and features that are common
+/// to all cargo invocations.
+fn push_common_args(
+    argv: &mut Vec<String>,
+    packages: &PackageSelection,
+    test_tool: TestTool,
+    options: &Options,
+) {
     if let Some(profile) = &options.profile {
-        match options.test_tool {
+        match test_tool {
             TestTool::Cargo => {
-                cargo_args.push(format!("--profile={profile}"));
+                argv.push(format!("--profile={profile}"));
             }
             TestTool::Nextest => {
-                cargo_args.push(format!("--cargo-profile={profile}"));
+                argv.push(format!("--cargo-profile={profile}"));
             }
         }
     }
-    cargo_args.push("--verbose".to_string());
+    if let Some(target) = &options.target {
+        argv.push(format!("--target={target}"));
+    }
+    if let Some(build_jobs) = options.build_jobs {
+        // Nextest's own `--jobs` is the number of test threads.
+        match test_tool {
+            TestTool::Cargo => argv.push(format!("--jobs={build_jobs}")),
+            TestTool::Nextest => argv.push(format!("--build-jobs={build_jobs}")),
+        }
+    }
+    argv.push("--verbose".to_string());
     match packages {
         PackageSelection::All => {
-            cargo_args.push("--workspace".to_string());
+            argv.push("--workspace".to_string());
         }
         PackageSelection::Explicit(packages) => {
-            cargo_args.extend(
+            argv.extend(
                 packages
                     .iter()
                     .map(|p| format!("--package={}", p.version_qualified_name())),
@@ -143,21 +333,22 @@ fn cargo_argv(packages: &PackageSelection, phase: Phase, options: &Options) -> V
     }
     let features = &options.features;
     if features.no_default_features {
-        cargo_args.push("--no-default-features".to_owned());
+        argv.push("--no-default-features".to_owned());
     }
     if features.all_features {
-        cargo_args.push("--all-features".to_owned());
+        argv.push("--all-features".to_owned());
     }
     // N.B. it can make sense to have --all-features and also explicit features from non-default packages.
-    cargo_args.extend(features.features.iter().map(|f| format!("--features={f}")));
-    cargo_args.extend(options.additional_cargo_args.iter().cloned());
-    if phase == Phase::Test {
-        cargo_args.extend(options.additional_cargo_test_args.iter().cloned());
-    }
-    cargo_args
+    argv.extend(features.features.iter().map(|f| format!("--features={f}")));
 }
 
-/// Return adjusted `CARGO_ENCODED_RUSTFLAGS`, including any changes to cap-lints.
+/// Return adjusted `CARGO_ENCODED_RUSTFLAGS`, including any flags from `--rustflags`
+/// and changes to cap-lints, or None if the inherited environment should be used
+/// unchanged.
+///
+/// Flags inherited from `CARGO_ENCODED_RUSTFLAGS` or `RUSTFLAGS` come first, then
+/// those from `--rustflags`, and then `--cap-lints=warn`, so that cap-lints applies
+/// to any lint levels set by the other flags.
 ///
 /// It seems we have to set this in the environment because Cargo doesn't expose
 /// a way to pass it in as an option from all commands?
@@ -167,37 +358,130 @@ fn cargo_argv(packages: &PackageSelection, phase: Phase, options: &Options) -> V
 /// See <https://doc.rust-lang.org/cargo/reference/environment-variables.html>
 /// <https://doc.rust-lang.org/rustc/lints/levels.html#capping-lints>
 fn encoded_rustflags(options: &Options) -> Option<String> {
-    let cap_lints_arg = "--cap-lints=warn";
+    let mut extra_args = options.rustflags.clone();
+    if options.cap_lints {
+        extra_args.push("--cap-lints=warn".to_owned());
+    }
+    adjusted_flags("CARGO_ENCODED_RUSTFLAGS", "RUSTFLAGS", &extra_args, options)
+}
+
+/// Return adjusted `CARGO_ENCODED_RUSTDOCFLAGS`, or None if the inherited environment
+/// should be used unchanged.
+fn encoded_rustdocflags(options: &Options) -> Option<String> {
+    adjusted_flags("CARGO_ENCODED_RUSTDOCFLAGS", "RUSTDOCFLAGS", &[], options)
+}
+
+/// Read compiler flags from the environment, remove any that would turn warnings into
+/// errors, and add `extra_args`.
+///
+/// Only the inherited flags are filtered: `extra_args` are always kept.
+///
+/// Mutants often cause warnings such as unused variables, so inherited flags like
+/// `-Dwarnings` would make many of them unviable. These are kept if
+/// `--respect-rustflags` is set.
+///
+/// Returns the flags in Cargo's encoded form, separated by `0x1f`, or None if they
+/// don't need to change.
+fn adjusted_flags(
+    encoded_var: &str,
+    plain_var: &str,
+    extra_args: &[String],
+    options: &Options,
+) -> Option<String> {
     let separator = "\x1f";
-    if !options.cap_lints {
-        None
-    } else if let Ok(encoded) = env::var("CARGO_ENCODED_RUSTFLAGS") {
-        if encoded.is_empty() {
-            Some(cap_lints_arg.to_owned())
-        } else {
-            Some(encoded + separator + cap_lints_arg)
+    let (var_name, mut flags): (&str, Vec<String>) = if let Ok(encoded) = env::var(encoded_var) {
+        (
+            encoded_var,
+            encoded
+                .split(separator)
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        )
+    } else if let Ok(plain) = env::var(plain_var) {
+        (
+            plain_var,
+            plain
+                .split(' ')
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        )
+    } else {
+        (plain_var, Vec::new())
+    };
+    let mut changed = false;
+    if !options.respect_rustflags {
+        let removed = remove_deny_warnings(&mut flags);
+        if !removed.is_empty() {
+            static WARN_ONCE: Once = Once::new();
+            WARN_ONCE.call_once(|| {
+                warn!(
+                    "Removed {removed:?} from {var_name} so that warnings in mutated code don't make mutants unviable; use --respect-rustflags to keep them",
+                );
+            });
+            changed = true;
         }
-    } else if let Ok(rustflags) = env::var("RUSTFLAGS") {
-        if rustflags.is_empty() {
-            Some(cap_lints_arg.to_owned())
-        } else {
-            Some(
-                rustflags
-                    .split(' ')
-                    .filter(|s| !s.is_empty())
-                    .chain(once("--cap-lints=warn"))
-                    .collect::<Vec<&str>>()
-                    .join(separator),
-            )
+    }
+    if !extra_args.is_empty() {
+        flags.extend_from_slice(extra_args);
+        changed = true;
+    }
+    changed.then(|| flags.join(separator))
+}
+
+/// Remove flags that deny or forbid all warnings, like `-Dwarnings` or `--deny warnings`,
+/// returning the removed flags.
+fn remove_deny_warnings(flags: &mut Vec<String>) -> Vec<String> {
+    let mut removed = Vec::new();
+    let mut iter = take(flags).into_iter().peekable();
+    while let Some(flag) = iter.next() {
+        match flag.as_str() {
+            "-Dwarnings" | "-Fwarnings" | "--deny=warnings" | "--forbid=warnings" => {
+                removed.push(flag);
+            }
+            "-D" | "-F" | "--deny" | "--forbid"
+                if iter.peek().is_some_and(|next| next == "warnings") =>
+            {
+                let value = iter.next().expect("peeked value");
+                removed.push(format!("{flag} {value}"));
+            }
+            _ => flags.push(flag),
         }
-    } else {
-        Some(cap_lints_arg.to_owned())
     }
+    removed
+}
+
+/// True if the output of `cargo test` shows that it ran no tests at all.
+///
+/// This looks at the `test result:` summary line printed for each test target, and
+/// returns true if there is at least one such line and none of them report any tests
+/// that passed, failed, or were measured.
+pub fn no_tests_run(test_output: &str) -> bool {
+    let mut any_results = false;
+    for line in test_output.lines() {
+        let Some(counts) = line.trim().strip_prefix("test result:") else {
+            continue;
+        };
+        any_results = true;
+        for part in counts.split(';') {
+            let mut words = part.split_whitespace().skip_while(|w| w.ends_with('.'));
+            if let (Some(count), Some("passed" | "failed" | "measured")) =
+                (words.next(), words.next())
+            {
+                if count != "0" {
+                    return false;
+                }
+            }
+        }
+    }
+    any_results
 }
 
 #[cfg(test)]
 mod test {
     use clap::Parser;
+    use indoc::indoc;
     use pretty_assertions::assert_eq;
     use rusty_fork::rusty_fork_test;
 
@@ -278,6 +562,89 @@ mod test {
         );
     }
 
+    #[test]
+    fn build_jobs_passed_to_cargo() {
+        let args = Args::try_parse_from(["mutants", "-j1", "--build-jobs=16"]).unwrap();
+        let options = Options::from_args(&args).unwrap();
+        assert_eq!(options.jobs, Some(1));
+        assert_eq!(
+            cargo_argv(&PackageSelection::All, Phase::Build, &options)[1..],
+            ["test", "--no-run", "--jobs=16", "--verbose", "--workspace"]
+        );
+        assert_eq!(
+            cargo_argv(&PackageSelection::All, Phase::Test, &options)[1..],
+            ["test", "--jobs=16", "--verbose", "--workspace"]
+        );
+
+        let args =
+            Args::try_parse_from(["mutants", "--test-tool=nextest", "--build-jobs=16"]).unwrap();
+        let options = Options::from_args(&args).unwrap();
+        assert_eq!(
+            cargo_argv(&PackageSelection::All, Phase::Build, &options)[1..],
+            [
+                "nextest",
+                "run",
+                "--no-run",
+                "--build-jobs=16",
+                "--verbose",
+                "--workspace"
+            ]
+        );
+    }
+
+    #[test]
+    fn target_passed_to_build_and_test() {
+        let args = Args::try_parse_from(["mutants", "--target=thumbv7em-none-eabi"]).unwrap();
+        let options = Options::from_args(&args).unwrap();
+        assert_eq!(
+            cargo_argv(&PackageSelection::All, Phase::Build, &options)[1..],
+            [
+                "test",
+                "--no-run",
+                "--target=thumbv7em-none-eabi",
+                "--verbose",
+                "--workspace"
+            ]
+        );
+        assert_eq!(
+            cargo_argv(&PackageSelection::All, Phase::Test, &options)[1..],
+            [
+                "test",
+                "--target=thumbv7em-none-eabi",
+                "--verbose",
+                "--workspace"
+            ]
+        );
+    }
+
+    #[test]
+    fn doctest_argv_runs_only_doctests_with_cargo() {
+        let args = Args::try_parse_from([
+            "mutants",
+            "--doctests",
+            "--test-tool=nextest",
+            "--profile=mutants",
+            "--features=x",
+            "--cargo-arg=--locked",
+            "--",
+            "--all-targets",
+        ])
+        .unwrap();
+        let options = Options::from_args(&args).unwrap();
+        assert_eq!(
+            doctest_argv(&PackageSelection::All, &options)[1..],
+            [
+                "test",
+                "--doc",
+                "--profile=mutants",
+                "--verbose",
+                "--workspace",
+                "--features=x",
+                "--locked"
+            ]
+        );
+    }
+
     #[test]
     fn no_default_features_args_passed_to_cargo() {
         let args = Args::try_parse_from(["mutants", "--no-default-features"].as_slice()).unwrap();
@@ -376,6 +743,83 @@ mod test {
         );
     }
 
+    #[test]
+    fn nextest_archive_builds_archive_and_runs_tests_from_it() {
+        let options = Options::from_arg_strs([
+            "mutants",
+            "--test-tool=nextest",
+            "--nextest-archive",
+            "--cargo-arg=--release",
+            "--",
+            "--no-fail-fast",
+        ]);
+        assert!(options.uses_nextest_archive());
+        let build_dir = Utf8Path::new("/tmp/build");
+        let archive_file = build_dir.join(NEXTEST_ARCHIVE_PATH);
+        assert_eq!(
+            phase_argv(build_dir, &PackageSelection::All, Phase::Build, &options)[1..],
+            [
+                "nextest".to_owned(),
+                "archive".to_owned(),
+                "--verbose".to_owned(),
+                "--workspace".to_owned(),
+                "--release".to_owned(),
+                format!("--archive-file={archive_file}"),
+            ]
+        );
+        assert_eq!(
+            phase_argv(build_dir, &PackageSelection::All, Phase::Test, &options)[1..],
+            [
+                "nextest".to_owned(),
+                "run".to_owned(),
+                format!("--archive-file={archive_file}"),
+                format!("--workspace-remap={build_dir}"),
+                "--verbose".to_owned(),
+                "--no-fail-fast".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn nextest_archive_is_not_used_without_nextest_or_with_check() {
+        let build_dir = Utf8Path::new("/tmp/build");
+        let options = Options::from_arg_strs(["mutants", "--nextest-archive"]);
+        assert!(!options.uses_nextest_archive());
+        assert_eq!(
+            phase_argv(build_dir, &PackageSelection::All, Phase::Test, &options)[1..],
+            ["test", "--verbose", "--workspace"]
+        );
+        let options = Options::from_arg_strs([
+            "mutants",
+            "--test-tool=nextest",
+            "--nextest-archive",
+            "--check",
+        ]);
+        assert!(!options.uses_nextest_archive());
+        assert_eq!(
+            phase_argv(build_dir, &PackageSelection::All, Phase::Check, &options)[1..],
+            ["check", "--tests", "--verbose", "--workspace"]
+        );
+    }
+
+    #[test]
+    fn test_command_replaces_cargo_test() {
+        let args =
+            Args::try_parse_from(["mutants", "--test-command", "make test"].as_slice()).unwrap();
+        let options = Options::from_args(&args).unwrap();
+        let test_argv = cargo_argv(&PackageSelection::All, Phase::Test, &options);
+        if cfg!(windows) {
+            assert_eq!(test_argv, ["cmd", "/C", "make test"]);
+        } else {
+            assert_eq!(test_argv, ["sh", "-c", "make test"]);
+        }
+        // The build still uses cargo.
+        assert_eq!(
+            cargo_argv(&PackageSelection::All, Phase::Build, &options)[1..],
+            ["test", "--no-run", "--verbose", "--workspace"]
+        );
+    }
+
     rusty_fork_test! {
         #[test]
         fn rustflags_without_cap_lints_and_no_environment_variables() {
@@ -427,12 +871,107 @@ mod test {
 
         #[test]
         fn rustflags_added_to_existing_rustflags() {
+            env::set_var("RUSTFLAGS", "--cfg foo");
+            env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+            assert_eq!(encoded_rustflags(&Options {
+                cap_lints: true,
+                ..Default::default()
+            }).unwrap(), "--cfg\x1ffoo\x1f--cap-lints=warn");
+        }
+
+        #[test]
+        fn deny_warnings_removed_from_rustflags() {
+            env::set_var("RUSTFLAGS", "-Dwarnings --cfg foo -D warnings");
+            env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+            assert_eq!(encoded_rustflags(&Options::default()).unwrap(), "--cfg\x1ffoo");
+            assert_eq!(encoded_rustflags(&Options {
+                cap_lints: true,
+                ..Default::default()
+            }).unwrap(), "--cfg\x1ffoo\x1f--cap-lints=warn");
+        }
+
+        #[test]
+        fn deny_warnings_kept_with_respect_rustflags() {
             env::set_var("RUSTFLAGS", "-Dwarnings");
             env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+            let options = Options {
+                respect_rustflags: true,
+                ..Default::default()
+            };
+            assert_eq!(encoded_rustflags(&options), None);
             assert_eq!(encoded_rustflags(&Options {
                 cap_lints: true,
+                respect_rustflags: true,
                 ..Default::default()
             }).unwrap(), "-Dwarnings\x1f--cap-lints=warn");
         }
+
+        #[test]
+        fn rustflags_option_added_after_inherited_rustflags() {
+            env::set_var("RUSTFLAGS", "-Dwarnings --cfg foo");
+            env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+            let options = Options {
+                rustflags: vec!["-C".to_owned(), "target-cpu=native".to_owned(), "-Dwarnings".to_owned()],
+                cap_lints: true,
+                ..Default::default()
+            };
+            // Deny-warnings is only removed from the inherited flags, and cap-lints comes last.
+            assert_eq!(
+                encoded_rustflags(&options).unwrap(),
+                "--cfg\x1ffoo\x1f-C\x1ftarget-cpu=native\x1f-Dwarnings\x1f--cap-lints=warn"
+            );
+        }
+
+        #[test]
+        fn rustflags_option_does_not_change_rustdocflags() {
+            env::remove_var("RUSTDOCFLAGS");
+            env::remove_var("CARGO_ENCODED_RUSTDOCFLAGS");
+            let options = Options {
+                rustflags: vec!["--cfg".to_owned(), "foo".to_owned()],
+                ..Default::default()
+            };
+            assert_eq!(encoded_rustdocflags(&options), None);
+        }
+
+        #[test]
+        fn rustflags_unchanged_without_deny_warnings() {
+            env::set_var("RUSTFLAGS", "--cfg foo");
+            env::remove_var("CARGO_ENCODED_RUSTFLAGS");
+            assert_eq!(encoded_rustflags(&Options::default()), None);
+        }
+
+        #[test]
+        fn deny_warnings_removed_from_encoded_rustdocflags() {
+            env::set_var("CARGO_ENCODED_RUSTDOCFLAGS", "--deny\x1fwarnings\x1f--forbid=warnings\x1f-Zunstable-options");
+            assert_eq!(encoded_rustdocflags(&Options::default()).unwrap(), "-Zunstable-options");
+        }
+    }
+
+    #[test]
+    fn detect_no_tests_run() {
+        let no_tests = indoc! {"
+            running 0 tests
+
+            test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+        "};
+        assert!(no_tests_run(no_tests));
+        let some_tests = indoc! {"
+            running 0 tests
+
+            test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+            running 2 tests
+            test test::one ... ok
+            test test::two ... ok
+
+            test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+        "};
+        assert!(!no_tests_run(some_tests));
+        assert!(!no_tests_run(
+            "test result: ok. 0 passed; 0 failed; 0 ignored; 1 measured; 0 filtered out"
+        ));
+        // No summary at all, perhaps from a custom test command, is not taken as no tests.
+        assert!(!no_tests_run("Compiling foo v0.1.0\n"));
     }
 }
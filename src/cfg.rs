@@ -0,0 +1,323 @@
+// Copyright 2025 Martin Pool
+
+//! Evaluate simple `#[cfg(...)]` predicates against the host platform, or the target
+//! given by `--target`, so that code that won't be compiled for that platform isn't
+//! mutated.
+//!
+//! Only well-known target cfgs are understood: `unix`, `windows`, `target_os`, and
+//! `target_family`, combined with `all`, `any`, and `not`. Anything else, such as
+//! features, can't be evaluated without more information from Cargo, and is assumed
+//! to possibly be active.
+
+use std::env::{self, consts};
+use std::process::Command;
+
+use anyhow::{ensure, Context};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, ExprLit, Lit, Meta, Token};
+use tracing::{debug, warn};
+
+use crate::pretty::ToPrettyString;
+use crate::Result;
+
+/// The platform that cfg predicates are evaluated against.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum TargetCfg {
+    /// The platform where cargo-mutants is running.
+    #[default]
+    Host,
+    /// A target given by `--target`, as described by `rustc --print cfg`.
+    Target {
+        os: Option<String>,
+        families: Vec<String>,
+    },
+    /// A target that rustc couldn't describe, so no predicates are known to be false.
+    Unknown,
+}
+
+impl TargetCfg {
+    /// Describe a target triple by asking rustc for its cfgs, or return
+    /// [`TargetCfg::Unknown`] with a warning if that fails.
+    pub fn for_target(target: &str) -> TargetCfg {
+        match print_cfg(target) {
+            Ok(output) => TargetCfg::from_print_cfg(&output),
+            Err(err) => {
+                warn!(
+                    "Failed to read cfgs for target {target:?}, so code won't be skipped based on cfg attributes: {err:#}"
+                );
+                TargetCfg::Unknown
+            }
+        }
+    }
+
+    /// Parse the output of `rustc --print cfg`.
+    fn from_print_cfg(output: &str) -> TargetCfg {
+        let value = |line: &str, name: &str| {
+            line.strip_prefix(name)?
+                .strip_prefix("=\"")?
+                .strip_suffix('"')
+                .map(str::to_owned)
+        };
+        let mut os = None;
+        let mut families = Vec::new();
+        for line in output.lines().map(str::trim) {
+            if let Some(value) = value(line, "target_os") {
+                os = Some(value);
+            } else if let Some(value) = value(line, "target_family") {
+                families.push(value);
+            }
+        }
+        TargetCfg::Target { os, families }
+    }
+
+    /// The value of `target_os`, or None if it's unknown.
+    fn os(&self) -> Option<&str> {
+        match self {
+            TargetCfg::Host => Some(consts::OS),
+            TargetCfg::Target { os, .. } => Some(os.as_deref().unwrap_or_default()),
+            TargetCfg::Unknown => None,
+        }
+    }
+
+    /// True if the target is in this `target_family`, or None if it's unknown.
+    fn has_family(&self, family: &str) -> Option<bool> {
+        match self {
+            TargetCfg::Host => Some(consts::FAMILY == family),
+            TargetCfg::Target { families, .. } => Some(families.iter().any(|f| f == family)),
+            TargetCfg::Unknown => None,
+        }
+    }
+}
+
+/// Run `rustc --print cfg` for a target.
+fn print_cfg(target: &str) -> Result<String> {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let output = Command::new(&rustc)
+        .args(["--print", "cfg", "--target", target])
+        .output()
+        .with_context(|| format!("run {rustc} --print cfg"))?;
+    ensure!(
+        output.status.success(),
+        "{rustc} --print cfg failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    String::from_utf8(output.stdout).context("rustc cfg output is not UTF-8")
+}
+
+/// True if the attribute is a `#[cfg(...)]` that is known to be false on the target.
+pub fn attr_is_inactive_cfg(attr: &Attribute, target: &TargetCfg) -> bool {
+    if !attr.path().is_ident("cfg") {
+        return false;
+    }
+    match attr.parse_args::<Meta>() {
+        Ok(meta) => eval(&meta, target) == Some(false),
+        Err(err) => {
+            debug!(
+                ?err,
+                attr = attr.to_pretty_string(),
+                "Unrecognized cfg attribute"
+            );
+            false
+        }
+    }
+}
+
+/// Evaluate a cfg predicate, returning None if it can't be evaluated.
+fn eval(meta: &Meta, target: &TargetCfg) -> Option<bool> {
+    match meta {
+        Meta::Path(path) => {
+            if path.is_ident("unix") {
+                target.has_family("unix")
+            } else if path.is_ident("windows") {
+                target.has_family("windows")
+            } else {
+                None
+            }
+        }
+        Meta::NameValue(name_value) => {
+            let Expr::Lit(ExprLit {
+                lit: Lit::Str(value),
+                ..
+            }) = &name_value.value
+            else {
+                return None;
+            };
+            if name_value.path.is_ident("target_os") {
+                target.os().map(|os| value.value() == os)
+            } else if name_value.path.is_ident("target_family") {
+                target.has_family(&value.value())
+            } else {
+                None
+            }
+        }
+        Meta::List(list) => {
+            let args = list
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()?;
+            let values: Vec<Option<bool>> = args.iter().map(|arg| eval(arg, target)).collect();
+            if list.path.is_ident("not") {
+                match values.as_slice() {
+                    [value] => value.map(|value| !value),
+                    _ => None,
+                }
+            } else if list.path.is_ident("all") {
+                // False if anything is false, even if other parts are unknown.
+                if values.contains(&Some(false)) {
+                    Some(false)
+                } else if values.iter().all(|value| *value == Some(true)) {
+                    Some(true)
+                } else {
+                    None
+                }
+            } else if list.path.is_ident("any") {
+                if values.contains(&Some(true)) {
+                    Some(true)
+                } else if values.iter().all(|value| *value == Some(false)) {
+                    Some(false)
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn attr_is_inactive_cfg(attr: &Attribute) -> bool {
+        super::attr_is_inactive_cfg(attr, &TargetCfg::Host)
+    }
+
+    #[test]
+    fn unix_and_windows() {
+        assert_eq!(
+            attr_is_inactive_cfg(&parse_quote!(#[cfg(unix)])),
+            !cfg!(unix)
+        );
+        assert_eq!(
+            attr_is_inactive_cfg(&parse_quote!(#[cfg(windows)])),
+            !cfg!(windows)
+        );
+        assert_eq!(
+            attr_is_inactive_cfg(&parse_quote!(#[cfg(not(unix))])),
+            cfg!(unix)
+        );
+    }
+
+    #[test]
+    fn target_os_and_family() {
+        assert!(attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(target_os = "no-such-os")])
+        ));
+        assert!(!attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(not(target_os = "no-such-os"))])
+        ));
+        let this_os: Meta = syn::parse_str(&format!("target_os = {:?}", consts::OS)).unwrap();
+        assert_eq!(eval(&this_os, &TargetCfg::Host), Some(true));
+        let this_family: Meta =
+            syn::parse_str(&format!("target_family = {:?}", consts::FAMILY)).unwrap();
+        assert_eq!(eval(&this_family, &TargetCfg::Host), Some(true));
+        assert!(attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(target_family = "no-such-family")])
+        ));
+    }
+
+    #[test]
+    fn combinations() {
+        assert!(attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(all(feature = "x", target_os = "no-such-os"))])
+        ));
+        assert!(!attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(any(feature = "x", target_os = "no-such-os"))])
+        ));
+        assert!(attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(any(target_os = "no-such-os", target_os = "other-os"))])
+        ));
+        assert!(!attr_is_inactive_cfg(&parse_quote!(#[cfg(all())])));
+        assert!(attr_is_inactive_cfg(&parse_quote!(#[cfg(any())])));
+    }
+
+    #[test]
+    fn unknown_predicates_are_assumed_active() {
+        assert!(!attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(feature = "nope")])
+        ));
+        assert!(!attr_is_inactive_cfg(&parse_quote!(#[cfg(test)])));
+        assert!(!attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(not(feature = "nope"))])
+        ));
+        assert!(!attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(target_arch = "nope")])
+        ));
+        assert!(!attr_is_inactive_cfg(&parse_quote!(#[allow(unix)])));
+    }
+
+    #[test]
+    fn cfgs_are_evaluated_for_a_bare_metal_target() {
+        let target = TargetCfg::from_print_cfg(
+            "panic=\"abort\"\ntarget_arch=\"arm\"\ntarget_os=\"none\"\ntarget_vendor=\"unknown\"\n",
+        );
+        assert_eq!(
+            target,
+            TargetCfg::Target {
+                os: Some("none".to_owned()),
+                families: Vec::new()
+            }
+        );
+        assert!(super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(unix)]),
+            &target
+        ));
+        assert!(super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(target_os = "linux")]),
+            &target
+        ));
+        assert!(!super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(target_os = "none")]),
+            &target
+        ));
+        assert!(!super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(not(windows))]),
+            &target
+        ));
+    }
+
+    #[test]
+    fn cfgs_are_evaluated_for_a_windows_target() {
+        let target = TargetCfg::from_print_cfg(
+            "target_family=\"windows\"\ntarget_os=\"windows\"\nwindows\n",
+        );
+        assert!(super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(unix)]),
+            &target
+        ));
+        assert!(!super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(windows)]),
+            &target
+        ));
+        assert!(!super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(target_os = "windows")]),
+            &target
+        ));
+    }
+
+    #[test]
+    fn unknown_target_has_no_inactive_cfgs() {
+        let target = TargetCfg::for_target("no-such-target-triple");
+        assert_eq!(target, TargetCfg::Unknown);
+        assert!(!super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(unix)]),
+            &target
+        ));
+        assert!(!super::attr_is_inactive_cfg(
+            &parse_quote!(#[cfg(windows)]),
+            &target
+        ));
+    }
+}
@@ -26,37 +26,82 @@ use crate::Result;
 /// can be deserialized.
 #[derive(Debug, Default, Clone, Deserialize)]
 #[serde(default, deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
+    /// Generate mutants in code under `#[cfg]` attributes for other platforms.
+    pub all_platforms: bool,
     /// Pass `--cap-lints` to rustc.
     pub cap_lints: bool,
+    /// Copy dependency artifacts from `target` to the build directory.
+    pub copy_target_deps: Option<bool>,
     /// Copy `.git` and other VCS directories to the build directory.
     pub copy_vcs: Option<bool>,
+    /// Project-specific mutations of calls to particular functions or macros.
+    pub custom_mutations: Vec<CustomMutation>,
+    /// Also run doctests with `cargo test --doc`.
+    pub doctests: bool,
     /// Generate these error values from functions returning Result.
     pub error_values: Vec<String>,
     /// Generate mutants from source files matching these globs.
     pub examine_globs: Vec<String>,
     /// Exclude mutants from source files matching these globs.
     pub exclude_globs: Vec<String>,
+    /// Stop without testing mutants if the baseline `cargo test` runs no tests.
+    pub fail_on_no_tests: bool,
+    /// Activate these features, in addition to any given by `--features`.
+    pub features: Vec<String>,
+    /// Activate all features.
+    pub all_features: bool,
+    /// Don't activate the `default` feature.
+    pub no_default_features: bool,
     /// Exclude mutants from source files matches these regexps.
     pub exclude_re: Vec<String>,
     /// Examine only mutants matching these regexps.
     pub examine_re: Vec<String>,
+    /// Exclude mutants whose ids match these regexps.
+    pub exclude_re_id: Vec<String>,
+    /// Examine only mutants whose ids match these regexps.
+    pub examine_re_id: Vec<String>,
+    /// Don't generate mutants of these genres.
+    pub exclude_genres: Vec<String>,
     /// Pass extra args to every cargo invocation.
     pub additional_cargo_args: Vec<String>,
     /// Pass extra args to cargo test.
     pub additional_cargo_test_args: Vec<String>,
+    /// Maximum build and test timeout, in seconds, as a ceiling on autoset values.
+    pub max_timeout: Option<f64>,
     /// Minimum test timeout, in seconds, as a floor on the autoset value.
     pub minimum_test_timeout: Option<f64>,
+    /// With nextest, build an archive of the test binaries and run tests from it.
+    pub nextest_archive: bool,
+    /// Generate mutants only in functions that are part of the public API.
+    pub only_public: bool,
     /// Output directory.
     pub output: Option<Utf8PathBuf>,
     /// Cargo profile.
     pub profile: Option<String>,
+    /// Target triple to build and test for.
+    pub target: Option<String>,
+    /// Keep flags that deny warnings in `RUSTFLAGS` and `RUSTDOCFLAGS`.
+    pub respect_rustflags: bool,
+    /// Additional flags for rustc, one per list element.
+    pub rustflags: Vec<String>,
+    /// Share built dependencies between parallel build directories.
+    pub share_deps: bool,
+    /// Functions whose mutants get a longer test timeout.
+    pub slow_functions: Vec<SlowFunction>,
     /// Skip calls to functions or methods with these names.
     ///
     /// This is combined with values from the --skip-calls argument.
     pub skip_calls: Vec<String>,
     /// Use built-in defaults for `skip_calls` in addition to any explicit values.
     pub skip_calls_defaults: Option<bool>,
+    /// Don't replace the body of unit functions that contain a single expression.
+    pub skip_unit_returns: bool,
+    /// Don't replace the body of a binary's `fn main`.
+    pub skip_main: bool,
+    /// Run this shell command to test each mutant, instead of `cargo test`.
+    pub test_command: Option<String>,
     /// Run tests from these packages for all mutants.
     pub test_package: Vec<String>,
     /// Choice of test tool: cargo or nextest.
@@ -71,7 +116,121 @@ pub struct Config {
     pub test_workspace: Option<bool>,
 }
 
+/// A project-specific mutation, from a `[[custom_mutations]]` table in the config.
+///
+/// Calls to functions or macros whose path ends with `match` are replaced by the
+/// `replace_with` template, in which `{args}` stands for the original arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomMutation {
+    /// The path of the function or macro to match, like `log::info`.
+    #[serde(rename = "match")]
+    pub match_path: String,
+    /// The expression that replaces the call, like `log::error!({args})`.
+    pub replace_with: String,
+}
+
+/// The placeholder for the original arguments in [`CustomMutation::replace_with`].
+pub const CUSTOM_MUTATION_ARGS: &str = "{args}";
+
+impl CustomMutation {
+    /// The identifiers in the matched path, without any trailing `!`.
+    pub fn path_segments(&self) -> Vec<&str> {
+        self.match_path
+            .trim()
+            .trim_end_matches('!')
+            .trim_start_matches("::")
+            .split("::")
+            .map(str::trim)
+            .collect()
+    }
+
+    /// Return the replacement code, given the text of the original arguments.
+    pub fn replacement(&self, args: &str) -> String {
+        self.replace_with.replace(CUSTOM_MUTATION_ARGS, args)
+    }
+
+    /// Check that the path is a Rust path and the template is a Rust expression, when
+    /// the arguments are replaced by a simple expression.
+    fn validate(&self) -> Result<()> {
+        let path = self.path_segments().join("::");
+        syn::parse_str::<syn::Path>(&path).with_context(|| {
+            format!(
+                "custom mutation match {:?} is not a function or macro path",
+                self.match_path
+            )
+        })?;
+        syn::parse_str::<syn::Expr>(&self.replacement("args")).with_context(|| {
+            format!(
+                "custom mutation replace_with {:?} is not a Rust expression",
+                self.replace_with
+            )
+        })?;
+        Ok(())
+    }
+}
+
+/// Functions that are slow to test, from a `[[slow_functions]]` table in the config.
+///
+/// Mutants in functions whose name matches the `match` regex have their test timeout
+/// multiplied by `timeout_multiplier`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlowFunction {
+    /// A regex matched against the qualified function name, like `heavy::compute`.
+    #[serde(rename = "match")]
+    pub match_re: String,
+    /// The factor by which to scale the test timeout.
+    pub timeout_multiplier: f64,
+}
+
+/// Per-package configuration, read from the `[package.metadata.mutants]` table in a
+/// package's `Cargo.toml`.
+///
+/// Each key that's set replaces the value of the same key from `.cargo/mutants.toml`
+/// for mutants in that package.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PackageConfig {
+    /// Generate these error values from functions returning Result.
+    pub error_values: Vec<String>,
+    /// Exclude mutants from source files matching these globs.
+    pub exclude_globs: Vec<String>,
+    /// Skip calls to functions or methods with these names.
+    pub skip_calls: Vec<String>,
+}
+
+impl PackageConfig {
+    /// Read the `mutants` table from the `metadata` of a package, if there is one.
+    pub fn from_package_metadata(metadata: &serde_json::Value) -> Result<Option<PackageConfig>> {
+        metadata
+            .get("mutants")
+            .map(|table| {
+                serde_json::from_value(table.clone()).context("parse package.metadata.mutants")
+            })
+            .transpose()
+    }
+}
+
 impl Config {
+    /// Return a copy of this config with the keys that are set in a package's config
+    /// replaced by the package's values.
+    pub fn with_package_config(&self, package_config: &PackageConfig) -> Config {
+        let mut config = self.clone();
+        if !package_config.error_values.is_empty() {
+            config.error_values.clone_from(&package_config.error_values);
+        }
+        if !package_config.exclude_globs.is_empty() {
+            config
+                .exclude_globs
+                .clone_from(&package_config.exclude_globs);
+        }
+        if !package_config.skip_calls.is_empty() {
+            config.skip_calls.clone_from(&package_config.skip_calls);
+        }
+        config
+    }
+
     pub fn read_file(path: &Path) -> Result<Config> {
         let toml = read_to_string(path).with_context(|| format!("read config {path:?}"))?;
         Config::from_str(&toml).with_context(|| format!("parse toml from {path:?}"))
@@ -93,6 +252,10 @@ impl FromStr for Config {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        toml::de::from_str(s).with_context(|| "parse toml")
+        let config: Config = toml::de::from_str(s).with_context(|| "parse toml")?;
+        for custom_mutation in &config.custom_mutations {
+            custom_mutation.validate()?;
+        }
+        Ok(config)
     }
 }
@@ -3,8 +3,11 @@
 //! Print messages and progress bars on the terminal.
 
 use std::borrow::Cow;
-use std::fs::File;
-use std::io;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{rename, File};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -12,11 +15,12 @@ use camino::{Utf8Path, Utf8PathBuf};
 use console::{style, StyledObject};
 use humantime::format_duration;
 use nutmeg::Destination;
-use tracing::Level;
+use serde::Serialize;
+use tracing::{debug, warn, Level};
 use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::prelude::*;
 
-use crate::options::Colors;
+use crate::options::{Colors, ListFormat, OutputFormat};
 use crate::outcome::{LabOutcome, ScenarioOutcome, SummaryOutcome};
 use crate::scenario::Scenario;
 use crate::tail_file::TailFile;
@@ -31,6 +35,13 @@ pub struct Console {
 
     /// The `mutants.out/debug.log` file, if it's open yet.
     debug_log: Arc<Mutex<Option<File>>>,
+
+    /// Where to write `mutants.out/progress.json`, once the output directory is known.
+    progress_file: Mutex<Option<ProgressFile>>,
+
+    /// If true, messages are written to stderr, because stdout is used for
+    /// machine-readable output.
+    messages_to_stderr: AtomicBool,
 }
 
 impl Console {
@@ -38,6 +49,21 @@ impl Console {
         Console {
             view: Arc::new(nutmeg::View::new(LabModel::default(), nutmeg_options())),
             debug_log: Arc::new(Mutex::new(None)),
+            progress_file: Mutex::new(None),
+            messages_to_stderr: AtomicBool::new(false),
+        }
+    }
+
+    /// Make a console that draws no progress bars, for use from the library API.
+    pub fn without_progress() -> Console {
+        Console {
+            view: Arc::new(nutmeg::View::new(
+                LabModel::default(),
+                nutmeg_options().progress_enabled(false),
+            )),
+            debug_log: Arc::new(Mutex::new(None)),
+            progress_file: Mutex::new(None),
+            messages_to_stderr: AtomicBool::new(false),
         }
     }
 
@@ -52,6 +78,7 @@ impl Console {
         self.view.update(|model| {
             model.scenario_models.push(scenario_model);
         });
+        self.write_progress();
     }
 
     /// Update that cargo finished.
@@ -76,8 +103,13 @@ impl Console {
             }
             model.remove_scenario(dir);
         });
+        self.write_progress();
+        if options.output_format == OutputFormat::JsonLines {
+            self.print_json_line(outcome);
+        }
 
-        if (outcome.mutant_caught() && !options.print_caught)
+        if (options.summary_only && (scenario.is_mutant() || outcome.success()))
+            || (outcome.mutant_caught() && !options.print_caught)
             || (outcome.scenario.is_mutant()
                 && outcome.check_or_build_failed()
                 && !options.print_unviable)
@@ -147,11 +179,13 @@ impl Console {
     }
 
     /// Update that we discovered some mutants to test.
-    pub fn discovered_mutants(&self, mutants: &[Mutant]) {
-        self.message(&format!(
-            "Found {} to test\n",
-            plural(mutants.len(), "mutant")
-        ));
+    pub fn discovered_mutants(&self, mutants: &[Mutant], options: &Options) {
+        if !options.summary_only {
+            self.message(&format!(
+                "Found {} to test\n",
+                plural(mutants.len(), "mutant")
+            ));
+        }
         let n_mutants = mutants.len();
         self.view.update(|model| {
             model.n_mutants = n_mutants;
@@ -163,6 +197,7 @@ impl Console {
     pub fn start_testing_mutants(&self, _n_mutants: usize) {
         self.view
             .update(|model| model.mutants_start_time = Some(Instant::now()));
+        self.write_progress();
     }
 
     /// A new phase of this scenario started.
@@ -170,6 +205,7 @@ impl Console {
         self.view.update(|model| {
             model.find_scenario_mut(dir).phase_started(phase);
         });
+        self.write_progress();
     }
 
     pub fn scenario_phase_finished(&self, dir: &Utf8Path, phase: Phase) {
@@ -181,11 +217,21 @@ impl Console {
     pub fn lab_finished(&self, lab_outcome: &LabOutcome, start_time: Instant, options: &Options) {
         self.view.update(|model| {
             model.scenario_models.clear();
+            model.lab_finished = true;
         });
-        self.message(&format!(
-            "{}\n",
-            lab_outcome.summary_string(start_time, options)
-        ));
+        self.write_progress();
+        if options.summary_only && options.list_format == ListFormat::Json {
+            self.view.clear();
+            println!("{}", lab_outcome.summary_json());
+        } else {
+            self.message(&format!(
+                "{}\n",
+                lab_outcome.summary_string(start_time, options)
+            ));
+            if options.missed_by_function {
+                self.message(&missed_by_function(lab_outcome, options));
+            }
+        }
     }
 
     pub fn clear(&self) {
@@ -197,7 +243,34 @@ impl Console {
         // stderr...
         // <https://github.com/sourcefrog/nutmeg/issues/11>
         self.view.clear();
-        print!("{message}");
+        if self.messages_to_stderr.load(Ordering::Relaxed) {
+            eprint!("{message}");
+        } else {
+            print!("{message}");
+        }
+    }
+
+    /// Don't draw progress bars from now on.
+    pub fn hide_progress(&self) {
+        self.view.suspend();
+    }
+
+    /// Write messages to stderr from now on, leaving stdout for machine-readable output.
+    pub fn send_messages_to_stderr(&self) {
+        self.messages_to_stderr.store(true, Ordering::Relaxed);
+    }
+
+    /// Write an outcome to stdout as one line of JSON, for `--output-format=json-lines`.
+    ///
+    /// The line is flushed immediately so that a consumer reading from a pipe sees each
+    /// outcome as soon as it's known.
+    fn print_json_line(&self, outcome: &ScenarioOutcome) {
+        let json = serde_json::to_string(outcome).expect("Serialize outcome");
+        self.view.clear();
+        let mut stdout = io::stdout().lock();
+        if let Err(err) = writeln!(stdout, "{json}").and_then(|()| stdout.flush()) {
+            debug!(?err, "Failed to write outcome to stdout");
+        }
     }
 
     pub fn tick(&self) {
@@ -222,6 +295,26 @@ impl Console {
         *self.debug_log.lock().unwrap() = Some(file);
     }
 
+    /// Start writing progress to this `progress.json` file.
+    pub fn set_progress_path(&self, path: Utf8PathBuf) {
+        *self.progress_file.lock().unwrap() = Some(ProgressFile { path });
+        self.view.update(|model| model.lab_finished = false);
+    }
+
+    /// Write the current progress to `progress.json`, if it's set.
+    ///
+    /// This is called on every change of state, so the file is never stale.
+    fn write_progress(&self) {
+        let progress_file = self.progress_file.lock().unwrap();
+        let Some(progress_file) = progress_file.as_ref() else {
+            return;
+        };
+        let progress = self.view.inspect_model(|model| model.progress());
+        if let Err(err) = progress_file.write(&progress) {
+            warn!("Failed to write {}: {err}", progress_file.path);
+        }
+    }
+
     /// Configure tracing to send messages to the console and debug log.
     ///
     /// The debug log is opened later and provided by [`Console::set_debug_log`].
@@ -376,6 +469,8 @@ struct LabModel {
     timeouts: usize,
     successes: usize,
     failures: usize,
+    /// True when all the scenarios have finished.
+    lab_finished: bool,
 }
 
 impl nutmeg::Model for LabModel {
@@ -465,6 +560,77 @@ impl LabModel {
     fn remove_scenario(&mut self, dir: &Utf8Path) {
         self.scenario_models.retain(|sm| sm.dir != *dir);
     }
+
+    fn progress(&self) -> Progress {
+        Progress {
+            total_mutants: self.n_mutants,
+            completed: self.mutants_done,
+            remaining: self.n_mutants.saturating_sub(self.mutants_done),
+            caught: self.mutants_caught,
+            missed: self.mutants_missed,
+            timeout: self.timeouts,
+            unviable: self.unviable,
+            finished: self.lab_finished,
+            running: self
+                .scenario_models
+                .iter()
+                .map(|sm| RunningScenario {
+                    build_dir: sm.dir.clone(),
+                    scenario: sm.scenario_name.clone(),
+                    mutant_id: sm.mutant_id.clone(),
+                    phase: sm.phase,
+                    phase_elapsed_secs: sm.phase_start.elapsed().as_secs_f64(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Progress of the lab, written to `mutants.out/progress.json` so that other tools
+/// can show it.
+#[derive(Debug, Serialize)]
+struct Progress {
+    total_mutants: usize,
+    completed: usize,
+    remaining: usize,
+    caught: usize,
+    missed: usize,
+    timeout: usize,
+    unviable: usize,
+    /// True when testing is complete and the file won't be updated again.
+    finished: bool,
+    /// The scenarios running now, one per job.
+    running: Vec<RunningScenario>,
+}
+
+/// A scenario that's currently running, in [`Progress`].
+#[derive(Debug, Serialize)]
+struct RunningScenario {
+    /// The build directory, which is distinct for each job.
+    build_dir: Utf8PathBuf,
+    /// The name of the mutant, or "baseline".
+    scenario: String,
+    /// The id of the mutant, as in `mutants.json`, if this is a mutant.
+    mutant_id: Option<String>,
+    /// The phase that's running, or none between phases.
+    phase: Option<Phase>,
+    phase_elapsed_secs: f64,
+}
+
+/// The `progress.json` file and when it was last written.
+struct ProgressFile {
+    path: Utf8PathBuf,
+}
+
+impl ProgressFile {
+    /// Write the progress to a temporary file and then rename it into place, so that
+    /// readers never see a partly-written file.
+    fn write(&self, progress: &Progress) -> crate::Result<()> {
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(progress)?)?;
+        rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
 }
 
 /// A Nutmeg progress model for walking the tree.
@@ -494,6 +660,9 @@ struct ScenarioModel {
     /// The directory where this is being built: unique across all models.
     dir: Utf8PathBuf,
     name: Cow<'static, str>,
+    /// The unstyled name of the scenario, for `progress.json`.
+    scenario_name: String,
+    mutant_id: Option<String>,
     phase_start: Instant,
     phase: Option<Phase>,
     /// Previously-executed phases and durations.
@@ -506,6 +675,8 @@ impl ScenarioModel {
         ScenarioModel {
             dir: dir.to_owned(),
             name: style_scenario(scenario, true),
+            scenario_name: scenario.to_string(),
+            mutant_id: scenario.mutant().map(Mutant::id),
             phase: None,
             phase_start: start,
             log_tail: TailFile::new(log_file),
@@ -597,6 +768,57 @@ fn nutmeg_options() -> nutmeg::Options {
         .destination(Destination::Stderr)
 }
 
+/// Describe the missed mutants grouped by function, for `--missed-by-function`.
+///
+/// Each function with any missed mutants gets a header with the number missed out of
+/// the number tested in that function, followed by its missed mutants. Functions are
+/// sorted by file and then by name.
+fn missed_by_function(lab_outcome: &LabOutcome, options: &Options) -> String {
+    let mut functions: BTreeMap<(String, &str), (usize, Vec<&Mutant>)> = BTreeMap::new();
+    for outcome in &lab_outcome.outcomes {
+        let Scenario::Mutant(mutant) = &outcome.scenario else {
+            continue;
+        };
+        let function_name = mutant
+            .function
+            .as_ref()
+            .map_or("", |function| function.function_name.as_str());
+        let (tested, missed) = functions
+            .entry((mutant.source_file.tree_relative_slashes(), function_name))
+            .or_default();
+        *tested += 1;
+        if outcome.summary() == SummaryOutcome::MissedMutant {
+            missed.push(mutant);
+        }
+    }
+    let mut s = String::new();
+    for ((file, function_name), (tested, mut missed)) in functions {
+        if missed.is_empty() {
+            continue;
+        }
+        if s.is_empty() {
+            s.push_str("Missed mutants by function:\n");
+        }
+        let count = if missed.len() == tested {
+            format!("all {tested} missed")
+        } else {
+            format!("{} of {tested} missed", missed.len())
+        };
+        let function_name = if function_name.is_empty() {
+            style("(outside functions)")
+        } else {
+            style(function_name).bright().magenta()
+        };
+        writeln!(s, "{file}: {function_name}: {count}").expect("write to string");
+        missed.sort_by_key(|mutant| (mutant.span.start.line, mutant.span.start.column));
+        for mutant in missed {
+            writeln!(s, "    {}", mutant.to_styled_string(options.show_line_col))
+                .expect("write to string");
+        }
+    }
+    s
+}
+
 /// Return a styled string reflecting the moral value of this outcome.
 pub fn style_outcome(outcome: &ScenarioOutcome) -> StyledObject<&'static str> {
     match outcome.summary() {
@@ -623,7 +845,7 @@ fn style_duration(duration: Duration) -> String {
         .to_string()
 }
 
-fn format_mb(bytes: u64) -> String {
+pub(crate) fn format_mb(bytes: u64) -> String {
     format!("{} MB", bytes / 1_000_000)
 }
 
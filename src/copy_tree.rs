@@ -31,8 +31,6 @@ pub fn copy_tree(
     options: &Options,
     console: &Console,
 ) -> Result<TempDir> {
-    let mut total_bytes = 0;
-    let mut total_files = 0;
     let temp_dir = tempfile::Builder::new()
         .prefix(name_base)
         .suffix(".tmp")
@@ -43,6 +41,32 @@ pub fn copy_tree(
         .try_into()
         .context("Convert path to UTF-8")?;
     console.start_copy(dest);
+    // With `--copy-target-deps`, only the dependencies are copied from `target`, later.
+    let exclude: &[&str] = if options.copy_target_deps {
+        &["target"]
+    } else {
+        &[]
+    };
+    let walk_builder = source_tree_walk_builder(from_path, options, exclude);
+    debug!(?walk_builder);
+    let (total_bytes, total_files) = copy_entries(&walk_builder, from_path, dest, console)?;
+    console.finish_copy(dest);
+    debug!(?total_bytes, ?total_files, temp_dir = ?temp_dir.path(), "Copied source tree");
+    Ok(temp_dir)
+}
+
+/// Make a walker over the files in a source tree that are copied to build directories.
+///
+/// If `gitignore` is enabled, `.gitignore` files in the tree and its parents are applied
+/// in the same way as git: nested files are combined with their parents, and negated
+/// patterns re-include files excluded by earlier patterns.
+///
+/// Entries at the top of the tree with any of the names in `exclude` are also skipped.
+pub fn source_tree_walk_builder(
+    from_path: &Utf8Path,
+    options: &Options,
+    exclude: &'static [&'static str],
+) -> WalkBuilder {
     let mut walk_builder = WalkBuilder::new(from_path);
     let copy_vcs = options.copy_vcs; // for lifetime
     walk_builder
@@ -57,8 +81,42 @@ pub fn copy_tree(
             name != "mutants.out"
                 && name != "mutants.out.old"
                 && (copy_vcs || !VCS_DIRS.contains(&name.as_ref()))
+                && !(entry.depth() == 1 && exclude.contains(&name.as_ref()))
         });
-    debug!(?walk_builder);
+    walk_builder
+}
+
+/// Copy a cargo target directory from elsewhere into `dest`, which is typically
+/// `target` within a build directory.
+///
+/// Everything is copied, regardless of ignore files. The source directory is only read,
+/// so it can be a build cache shared with other processes.
+pub fn copy_target_dir(from_path: &Utf8Path, dest: &Utf8Path, console: &Console) -> Result<()> {
+    console.start_copy(dest);
+    let mut walk_builder = WalkBuilder::new(from_path);
+    walk_builder.standard_filters(false);
+    let (total_bytes, total_files) = copy_entries(&walk_builder, from_path, dest, console)?;
+    console.finish_copy(dest);
+    debug!(
+        ?total_bytes,
+        ?total_files,
+        ?from_path,
+        ?dest,
+        "Copied target directory"
+    );
+    Ok(())
+}
+
+/// Copy everything found by a walk of `from_path` into `dest`, returning the total bytes
+/// and number of files copied.
+fn copy_entries(
+    walk_builder: &WalkBuilder,
+    from_path: &Utf8Path,
+    dest: &Utf8Path,
+    console: &Console,
+) -> Result<(u64, usize)> {
+    let mut total_bytes = 0;
+    let mut total_files = 0;
     for entry in walk_builder.build() {
         check_interrupted()?;
         let entry = entry?;
@@ -66,8 +124,8 @@ pub fn copy_tree(
             .path()
             .strip_prefix(from_path)
             .expect("entry path is in from_path");
-        let dest_path: Utf8PathBuf = temp_dir
-            .path()
+        let dest_path: Utf8PathBuf = dest
+            .as_std_path()
             .join(relative_path)
             .try_into()
             .context("Convert path to UTF-8")?;
@@ -100,9 +158,7 @@ pub fn copy_tree(
             warn!("Unexpected file type: {:?}", entry.path());
         }
     }
-    console.finish_copy(dest);
-    debug!(?total_bytes, ?total_files, temp_dir = ?temp_dir.path(), "Copied source tree");
-    Ok(temp_dir)
+    Ok((total_bytes, total_files))
 }
 
 #[cfg(test)]
@@ -193,6 +249,35 @@ mod test {
         Ok(())
     }
 
+    /// A file ignored by a broad pattern in the root `.gitignore` is copied if a nested
+    /// `.gitignore` re-includes it with a negated pattern.
+    #[test]
+    fn nested_gitignore_negation_reincludes_file() -> Result<()> {
+        let tmp_dir = TempDir::new().unwrap();
+        let tmp = Utf8PathBuf::try_from(tmp_dir.path().to_owned()).unwrap();
+        create_dir(tmp.join(".git"))?;
+        write(tmp.join(".gitignore"), "*.dat\n")?;
+        write(tmp.join("Cargo.toml"), "[package]\nname = a")?;
+        write(tmp.join("top.dat"), "ignored")?;
+        let fixtures = tmp.join("fixtures");
+        create_dir(&fixtures)?;
+        write(fixtures.join(".gitignore"), "!keep.dat\n")?;
+        write(fixtures.join("keep.dat"), "needed")?;
+        write(fixtures.join("other.dat"), "ignored")?;
+
+        let options = Options::from_arg_strs(["mutants", "--gitignore=true"]);
+        let dest_tmpdir = copy_tree(&tmp, "a", &options, &Console::new())?;
+        let dest = dest_tmpdir.path();
+        assert!(!dest.join("top.dat").exists());
+        assert!(!dest.join("fixtures/other.dat").exists());
+        assert!(
+            dest.join("fixtures/keep.dat").is_file(),
+            "keep.dat should be re-included by the nested gitignore"
+        );
+
+        Ok(())
+    }
+
     /// With `gitignore` set to `false`, patterns in that file have no effect.
     #[test]
     fn copy_without_gitignore() -> Result<()> {
@@ -0,0 +1,279 @@
+// Copyright 2025 Martin Pool
+
+//! Cache the mutants found in each source file, so that files that haven't changed
+//! needn't be parsed again on the next run.
+//!
+//! The cache is kept in `mutants.out/discovery_cache.json`. Each file's entry is keyed
+//! by a hash of its path, package, and content, the version of cargo-mutants, and the
+//! options that change which mutants are generated, so that if any of them change the
+//! file is parsed again. Entries that aren't used by a run are dropped when the cache
+//! is written back.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{create_dir_all, read_to_string};
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use camino::Utf8Path;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::mutant::{fnv1a_64, MutantRecord};
+use crate::source::SourceFile;
+use crate::visit::{ExternalModRef, IncludeRef, Skipped};
+use crate::{Context, Mutant, Options, Result, VERSION};
+
+/// The name of the cache file within `mutants.out`.
+pub const DISCOVERY_CACHE_JSON: &str = "discovery_cache.json";
+
+/// The results of walking one source file, before mutants are filtered by name.
+#[derive(Serialize, Deserialize)]
+pub struct CacheEntry<M> {
+    pub mutants: Vec<M>,
+    pub external_mods: Vec<ExternalModRef>,
+    pub includes: Vec<IncludeRef>,
+    pub skipped: Vec<Skipped>,
+}
+
+/// The contents of the cache file: a map from keys to entries.
+#[derive(Serialize, Deserialize)]
+struct CacheFile<F> {
+    files: F,
+}
+
+/// Results of walking source files, loaded from the previous run and collected from this
+/// one.
+#[derive(Default)]
+pub struct DiscoveryCache {
+    /// Entries loaded from the previous run, which are removed as they're used.
+    previous: Mutex<HashMap<String, CacheEntry<MutantRecord>>>,
+    /// Entries used or added in this run, which will be written back.
+    current: Mutex<BTreeMap<String, CacheEntry<Mutant>>>,
+    /// The number of files whose results were found in the cache.
+    hits: AtomicUsize,
+}
+
+impl DiscoveryCache {
+    /// Load the cache from a `mutants.out` directory, or return an empty cache if it
+    /// doesn't exist or can't be read.
+    pub fn load(output_dir: &Utf8Path) -> DiscoveryCache {
+        let path = output_dir.join(DISCOVERY_CACHE_JSON);
+        if !path.is_file() {
+            return DiscoveryCache::default();
+        }
+        let previous = read_to_string(&path)
+            .inspect_err(|err| debug!(?err, %path, "failed to read discovery cache"))
+            .ok()
+            .and_then(|json| {
+                serde_json::from_str::<CacheFile<HashMap<String, CacheEntry<MutantRecord>>>>(&json)
+                    .inspect_err(|err| debug!(?err, %path, "failed to parse discovery cache"))
+                    .ok()
+            })
+            .map(|cache_file| cache_file.files)
+            .unwrap_or_default();
+        DiscoveryCache {
+            previous: Mutex::new(previous),
+            ..Default::default()
+        }
+    }
+
+    /// Write the entries used in this run into a `mutants.out` directory, creating it
+    /// if necessary.
+    ///
+    /// The file is written under a unique temporary name and renamed into place, so
+    /// that concurrent runs never see, or write into, a partly written cache.
+    pub fn save(&self, output_dir: &Utf8Path) -> Result<()> {
+        create_dir_all(output_dir)
+            .with_context(|| format!("create output directory {output_dir:?}"))?;
+        let current = self.current.lock().expect("lock discovery cache");
+        debug!(
+            files = current.len(),
+            hits = self.hits(),
+            "write discovery cache"
+        );
+        let json = serde_json::to_string(&CacheFile { files: &*current })
+            .context("serialize discovery cache")?;
+        let path = output_dir.join(DISCOVERY_CACHE_JSON);
+        let mut tmp_file = tempfile::Builder::new()
+            .prefix(DISCOVERY_CACHE_JSON)
+            .suffix(".tmp")
+            .tempfile_in(output_dir)
+            .with_context(|| format!("create temporary file in {output_dir}"))?;
+        tmp_file
+            .write_all(json.as_bytes())
+            .context("write discovery cache")?;
+        tmp_file
+            .persist(&path)
+            .with_context(|| format!("rename discovery cache into {path}"))?;
+        Ok(())
+    }
+
+    /// Return the cached results for a file, if they're present and still apply.
+    pub fn get(&self, key: &str, source_file: &SourceFile) -> Option<CacheEntry<Mutant>> {
+        let entry = self
+            .previous
+            .lock()
+            .expect("lock discovery cache")
+            .remove(key)?;
+        let mutants = entry
+            .mutants
+            .into_iter()
+            .map(|record| record.into_mutant(source_file.clone()))
+            .collect::<Result<Vec<Mutant>>>()
+            .inspect_err(|err| debug!(?err, "cached mutants don't match the source"))
+            .ok()?;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(CacheEntry {
+            mutants,
+            external_mods: entry.external_mods,
+            includes: entry.includes,
+            skipped: entry.skipped,
+        })
+    }
+
+    /// Remember the results of walking a file, to be written back to the cache.
+    pub fn insert(&self, key: String, entry: CacheEntry<Mutant>) {
+        self.current
+            .lock()
+            .expect("lock discovery cache")
+            .insert(key, entry);
+    }
+
+    /// The number of files whose results were found in the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+}
+
+/// Compute the key for the results of walking a source file, whose contents are in
+/// `namespace` in a public or private module, with these options.
+///
+/// This must include every option that's read while walking the file; filters that are
+/// applied to the results afterwards, like `--re`, don't need to be included.
+pub fn cache_key(
+    source_file: &SourceFile,
+    namespace: &[String],
+    public_mod: bool,
+    options: &Options,
+) -> String {
+    let options_part = format!(
+        "{:?}",
+        (
+            options.all_platforms,
+            &options.custom_mutations,
+            &options.error_values,
+            &options.excluded_genres,
+            &options.genres,
+            options.only_public,
+            &options.skip_calls,
+            options.skip_main,
+            options.skip_unit_returns,
+            &options.target_cfg,
+        )
+    );
+    let mut input = [
+        VERSION,
+        &options_part,
+        &source_file.package.name,
+        &source_file.tree_relative_slashes(),
+        if source_file.is_top { "top" } else { "" },
+        &namespace.join("::"),
+        if public_mod { "pub" } else { "" },
+    ]
+    .join("\0");
+    input.push('\0');
+    input.push_str(source_file.code());
+    format!("{:016x}", fnv1a_64(input.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::write;
+
+    use camino::Utf8PathBuf;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_util::copy_of_testdata;
+    use crate::{Console, PackageFilter, Workspace};
+
+    fn discover_with_cache(tmp: &TempDir, options: &Options) -> (Vec<String>, DiscoveryCache) {
+        let tmp_path = Utf8PathBuf::try_from(tmp.path().to_owned()).unwrap();
+        let output_dir = tmp_path.join("mutants.out");
+        let cache = DiscoveryCache::load(&output_dir);
+        let workspace = Workspace::open(&tmp_path).unwrap();
+        let names = workspace
+            .discover_with_cache(&PackageFilter::All, options, &Console::new(), &cache)
+            .unwrap()
+            .mutants
+            .iter()
+            .map(|m| m.name(true))
+            .collect();
+        cache.save(&output_dir).unwrap();
+        (names, cache)
+    }
+
+    #[test]
+    fn unchanged_files_are_loaded_from_the_cache() {
+        let tmp = copy_of_testdata("workspace");
+        let options = Options::default();
+        let (first_names, cache) = discover_with_cache(&tmp, &options);
+        assert_eq!(cache.hits(), 0);
+        assert!(!first_names.is_empty());
+        let n_files = cache.current.lock().unwrap().len();
+
+        let (second_names, cache) = discover_with_cache(&tmp, &options);
+        assert_eq!(second_names, first_names);
+        assert_eq!(cache.hits(), n_files);
+
+        // Only the changed file is parsed again.
+        write(
+            tmp.path().join("main/src/main.rs"),
+            "fn main() {\n    println!(\"changed\");\n}\n",
+        )
+        .unwrap();
+        let (third_names, cache) = discover_with_cache(&tmp, &options);
+        assert_eq!(cache.hits(), n_files - 1);
+        assert_ne!(third_names, first_names);
+        assert_eq!(third_names, discover_with_cache(&tmp, &options).0);
+    }
+
+    #[test]
+    fn changed_options_invalidate_the_cache() {
+        let tmp = copy_of_testdata("factorial");
+        let (names, _) = discover_with_cache(&tmp, &Options::default());
+        let options = Options::from_arg_strs(["mutants", "--skip-main"]);
+        let (skip_main_names, cache) = discover_with_cache(&tmp, &options);
+        assert_eq!(cache.hits(), 0);
+        assert!(names.iter().any(|n| n.contains("replace main with ()")));
+        assert!(!skip_main_names
+            .iter()
+            .any(|n| n.contains("replace main with ()")));
+    }
+
+    #[test]
+    fn changed_target_invalidates_the_cache() {
+        let tmp = copy_of_testdata("factorial");
+        discover_with_cache(&tmp, &Options::default());
+        let options = Options::from_arg_strs(["mutants", "--target=thumbv7em-none-eabi"]);
+        let (_, cache) = discover_with_cache(&tmp, &options);
+        assert_eq!(cache.hits(), 0);
+        let (_, cache) = discover_with_cache(&tmp, &options);
+        assert!(cache.hits() > 0);
+    }
+
+    #[test]
+    fn unreadable_cache_is_ignored() {
+        let tmp = copy_of_testdata("factorial");
+        let (names, _) = discover_with_cache(&tmp, &Options::default());
+        write(
+            tmp.path().join("mutants.out").join(DISCOVERY_CACHE_JSON),
+            "not json",
+        )
+        .unwrap();
+        let (reloaded_names, cache) = discover_with_cache(&tmp, &Options::default());
+        assert_eq!(cache.hits(), 0);
+        assert_eq!(reloaded_names, names);
+    }
+}
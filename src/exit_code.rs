@@ -26,5 +26,11 @@ pub const TIMEOUT: i32 = 3;
 /// The tests are already failing in an unmutated tree.
 pub const CLEAN_TESTS_FAILED: i32 = 4;
 
+/// The tests pass in an unmutated tree, but no tests were run, so every mutant would be missed.
+pub const NO_TESTS: i32 = 5;
+
+/// Mutants were generated, but all of them were unviable, so nothing was really tested.
+pub const ALL_UNVIABLE: i32 = 6;
+
 /// An internal software error, from sysexit.
 pub const SOFTWARE: i32 = 70;
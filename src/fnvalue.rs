@@ -4,14 +4,19 @@
 
 #![warn(clippy::pedantic)]
 
+use std::collections::HashMap;
 use std::iter;
 
 use itertools::Itertools;
 use proc_macro2::TokenStream;
 use quote::quote;
+use syn::parse_quote;
+use syn::visit::Visit;
 use syn::{
-    AngleBracketedGenericArguments, AssocType, Expr, GenericArgument, Ident, Path, PathArguments,
-    ReturnType, TraitBound, Type, TypeArray, TypeImplTrait, TypeParamBound, TypeSlice, TypeTuple,
+    AngleBracketedGenericArguments, AssocType, Block, Expr, ExprPath, GenericArgument, Generics,
+    Ident, ImplItem, ImplItemConst, ItemImpl, ItemTrait, Path, PathArguments, PredicateType,
+    ReturnType, Stmt, TraitBound, TraitItem, TraitItemConst, Type, TypeArray, TypeImplTrait,
+    TypeParamBound, TypeSlice, TypeTraitObject, TypeTuple, WherePredicate,
 };
 use tracing::trace;
 
@@ -20,17 +25,353 @@ use crate::pretty::ToPrettyString;
 /// Generate replacement text for a function based on its return type.
 pub(crate) fn return_type_replacements(
     return_type: &ReturnType,
+    generics: &Generics,
     error_exprs: &[Expr],
 ) -> Vec<TokenStream> {
     match return_type {
         ReturnType::Default => vec![quote! { () }],
-        ReturnType::Type(_rarrow, type_) => type_replacements(type_, error_exprs).collect_vec(),
+        ReturnType::Type(_rarrow, type_) => {
+            type_replacements(type_, &TypeParams::new(generics), error_exprs).collect_vec()
+        }
+    }
+}
+
+/// Generate replacements for each element of a returned tuple, considered separately.
+///
+/// Returns one list of replacements per element, or an empty list if the function does
+/// not return a tuple of at least two elements.
+pub(crate) fn tuple_element_replacements(
+    return_type: &ReturnType,
+    generics: &Generics,
+    error_exprs: &[Expr],
+) -> Vec<Vec<TokenStream>> {
+    match return_type {
+        ReturnType::Type(_rarrow, type_) => match &**type_ {
+            Type::Tuple(TypeTuple { elems, .. }) if elems.len() > 1 => elems
+                .iter()
+                .map(|elem| {
+                    type_replacements(elem, &TypeParams::new(generics), error_exprs).collect_vec()
+                })
+                .collect_vec(),
+            _ => Vec::new(),
+        },
+        ReturnType::Default => Vec::new(),
+    }
+}
+
+/// Generate replacements for `Ok` and `Err` values returned early from a function
+/// that returns a `Result`.
+///
+/// Returns `None` if the function doesn't return a `Result`, or otherwise a pair of
+/// `Ok` values to replace a returned `Err`, and `Err` values to replace a returned `Ok`.
+/// `Err` values are generated from the error type, where possible, and from the
+/// configured error expressions.
+pub(crate) fn result_swap_replacements(
+    return_type: &ReturnType,
+    generics: &Generics,
+    error_exprs: &[Expr],
+) -> Option<(Vec<TokenStream>, Vec<TokenStream>)> {
+    let ReturnType::Type(_rarrow, type_) = return_type else {
+        return None;
+    };
+    let Type::Path(syn::TypePath { path, .. }) = &**type_ else {
+        return None;
+    };
+    if !path_ends_with(path, "Result") {
+        return None;
+    }
+    let generics = &TypeParams::new(generics);
+    Some((
+        result_ok_replacements(path, generics, error_exprs),
+        result_err_replacements(path, generics, error_exprs),
+    ))
+}
+
+/// Generate `Ok` values for a `Result` type.
+fn result_ok_replacements(
+    path: &Path,
+    generics: &TypeParams,
+    error_exprs: &[Expr],
+) -> Vec<TokenStream> {
+    if let Some(ok_type) = match_first_type_arg(path, "Result") {
+        type_replacements(ok_type, generics, error_exprs)
+            .map(|rep| {
+                quote! { Ok(#rep) }
+            })
+            .collect_vec()
+    } else {
+        // A result with no type arguments, like `fmt::Result`; hopefully
+        // the Ok value can be constructed with Default.
+        vec![quote! { Ok(Default::default()) }]
+    }
+}
+
+/// Generate `Err` values for a `Result` type: first any that can be guessed from the
+/// error type, then the configured error expressions.
+fn result_err_replacements(
+    path: &Path,
+    generics: &TypeParams,
+    error_exprs: &[Expr],
+) -> Vec<TokenStream> {
+    error_type_replacements(path, generics, error_exprs.is_empty())
+        .into_iter()
+        .chain(error_exprs.iter().map(|error_expr| quote! { #error_expr }))
+        .map(|error| quote! { Err(#error) })
+        .collect_vec()
+}
+
+/// Guess values of the error type of a `Result`.
+///
+/// Some common error types from the standard library, and `Result` aliases like
+/// `io::Result`, are constructed directly. For other concrete error types, if
+/// `guess_default` is true, this is `Default::default()`, which will be unviable if the
+/// type doesn't implement `Default`.
+///
+/// Nothing is generated for errors that are type parameters, or for aliases whose error
+/// type isn't known, like `anyhow::Result`: in those cases only the configured error
+/// values are used.
+fn error_type_replacements(
+    path: &Path,
+    generics: &TypeParams,
+    guess_default: bool,
+) -> Vec<TokenStream> {
+    let io_error = quote! { std::io::Error::from(std::io::ErrorKind::Other) };
+    let type_args = path
+        .segments
+        .last()
+        .map(|last| match &last.arguments {
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArgument::Type(type_) => Some(type_),
+                    _ => None,
+                })
+                .collect_vec(),
+            _ => Vec::new(),
+        })
+        .unwrap_or_default();
+    let Some(error_type) = type_args.get(1) else {
+        // An alias with the error type already filled in.
+        return if path_ends_with_segments(path, &["io", "Result"]) {
+            vec![io_error]
+        } else if path_ends_with_segments(path, &["fmt", "Result"]) {
+            vec![quote! { std::fmt::Error }]
+        } else {
+            Vec::new()
+        };
+    };
+    match error_type {
+        Type::Tuple(TypeTuple { elems, .. }) if elems.is_empty() => vec![quote! { () }],
+        Type::Path(syn::TypePath { qself: None, path }) => {
+            if match_type_param(path, generics).is_some()
+                || path_ends_with_segments(path, &["anyhow", "Error"])
+            {
+                Vec::new()
+            } else if path_ends_with_segments(path, &["io", "Error"]) {
+                vec![io_error]
+            } else if path_ends_with_segments(path, &["fmt", "Error"]) {
+                vec![quote! { #path }]
+            } else if let Some(Type::TraitObject(TypeTraitObject { bounds, .. })) =
+                match_first_type_arg(path, "Box")
+            {
+                if bounds.iter().any(|bound| {
+                    matches!(bound, TypeParamBound::Trait(trait_bound) if path_ends_with(&trait_bound.path, "Error"))
+                }) {
+                    vec![quote! { "mutated".into() }]
+                } else {
+                    Vec::new()
+                }
+            } else if guess_default {
+                vec![quote! { Default::default() }]
+            } else {
+                Vec::new()
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// True if the last segments of the path are the given identifiers.
+fn path_ends_with_segments(path: &Path, idents: &[&str]) -> bool {
+    path.segments.len() >= idents.len()
+        && path
+            .segments
+            .iter()
+            .rev()
+            .zip(idents.iter().rev())
+            .all(|(segment, ident)| segment.ident == ident)
+}
+
+/// The associated constants that can be named as `Self::NAME` inside an `impl` or
+/// `trait` block, with the type they're implemented for.
+pub(crate) struct ImplConsts {
+    pub self_type: Type,
+    pub consts: Vec<(Ident, Type)>,
+}
+
+impl ImplConsts {
+    /// Collect the associated constants from an `impl` block.
+    pub fn from_item_impl(item_impl: &ItemImpl) -> ImplConsts {
+        ImplConsts {
+            self_type: (*item_impl.self_ty).clone(),
+            consts: impl_item_consts(item_impl),
+        }
+    }
+
+    /// Add constants that aren't already known, by name.
+    fn extend(&mut self, consts: &[(Ident, Type)]) {
+        for (ident, type_) in consts {
+            if !self.consts.iter().any(|(known, _)| known == ident) {
+                self.consts.push((ident.clone(), type_.clone()));
+            }
+        }
+    }
+
+    /// Describe a type, treating the implementing type the same as `Self`.
+    fn normalized_type(&self, type_: &Type) -> String {
+        let type_name = type_.to_pretty_string();
+        if type_name == self.self_type.to_pretty_string() {
+            "Self".to_owned()
+        } else {
+            type_name
+        }
+    }
+}
+
+/// The associated constants declared anywhere in a source file, so that constants from
+/// other blocks can be used in an `impl` block.
+#[derive(Default)]
+pub(crate) struct FileConsts {
+    /// Constants from inherent `impl` blocks, by the name of the type.
+    inherent: HashMap<String, Vec<(Ident, Type)>>,
+    /// Constants declared in trait definitions, by the name of the trait.
+    traits: HashMap<String, Vec<(Ident, Type)>>,
+}
+
+impl FileConsts {
+    pub fn from_file(file: &syn::File) -> FileConsts {
+        let mut file_consts = FileConsts::default();
+        file_consts.visit_file(file);
+        file_consts
+    }
+
+    /// The constants in scope in an `impl` block: its own, those from inherent `impl`
+    /// blocks for the same type, and, for a trait impl, those declared in the trait.
+    pub fn for_impl(&self, item_impl: &ItemImpl) -> ImplConsts {
+        let mut impl_consts = ImplConsts::from_item_impl(item_impl);
+        if let Some(inherent) = self.inherent.get(&item_impl.self_ty.to_pretty_string()) {
+            impl_consts.extend(inherent);
+        }
+        if let Some((_, trait_path, _)) = &item_impl.trait_ {
+            if let Some(trait_consts) = trait_path
+                .segments
+                .last()
+                .and_then(|segment| self.traits.get(&segment.ident.to_string()))
+            {
+                impl_consts.extend(trait_consts);
+            }
+        }
+        impl_consts
+    }
+
+    /// The constants in scope in the provided methods of a trait: those it declares.
+    pub fn for_trait(item_trait: &ItemTrait) -> ImplConsts {
+        ImplConsts {
+            self_type: parse_quote! { Self },
+            consts: trait_item_consts(item_trait),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FileConsts {
+    fn visit_item_impl(&mut self, i: &'ast ItemImpl) {
+        if i.trait_.is_none() {
+            self.inherent
+                .entry(i.self_ty.to_pretty_string())
+                .or_default()
+                .extend(impl_item_consts(i));
+        }
+        syn::visit::visit_item_impl(self, i);
+    }
+
+    fn visit_item_trait(&mut self, i: &'ast ItemTrait) {
+        self.traits
+            .entry(i.ident.to_string())
+            .or_default()
+            .extend(trait_item_consts(i));
+        syn::visit::visit_item_trait(self, i);
+    }
+}
+
+fn impl_item_consts(item_impl: &ItemImpl) -> Vec<(Ident, Type)> {
+    item_impl
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Const(ImplItemConst { ident, ty, .. }) => Some((ident.clone(), ty.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn trait_item_consts(item_trait: &ItemTrait) -> Vec<(Ident, Type)> {
+    item_trait
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Const(TraitItemConst { ident, ty, .. }) => Some((ident.clone(), ty.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Generate replacements for a function whose whole body is an associated constant,
+/// like `Self::MAX`: the other associated constants of the same type that are in scope
+/// in its `impl` or `trait` block.
+pub(crate) fn assoc_const_replacements(
+    return_type: &ReturnType,
+    block: &Block,
+    impl_consts: &ImplConsts,
+) -> Vec<TokenStream> {
+    let ReturnType::Type(_rarrow, type_) = return_type else {
+        return Vec::new();
+    };
+    let [Stmt::Expr(
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }),
+        None,
+    )] = &block.stmts[..]
+    else {
+        return Vec::new();
+    };
+    let [self_segment, const_segment] = &path.segments.iter().collect_vec()[..] else {
+        return Vec::new();
+    };
+    if self_segment.ident != "Self" || !const_segment.arguments.is_none() {
+        return Vec::new();
     }
+    let type_name = impl_consts.normalized_type(type_);
+    impl_consts
+        .consts
+        .iter()
+        .filter(|(ident, const_type)| {
+            *ident != const_segment.ident && impl_consts.normalized_type(const_type) == type_name
+        })
+        .map(|(ident, _)| quote! { Self::#ident })
+        .collect_vec()
 }
 
 /// Generate some values that we hope are reasonable replacements for a type.
+///
+/// `generics` are the generic parameters of the function, which are used to find
+/// replacements for type parameters.
 #[allow(clippy::too_many_lines)]
-fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item = TokenStream> {
+fn type_replacements(
+    type_: &Type,
+    generics: &TypeParams,
+    error_exprs: &[Expr],
+) -> impl Iterator<Item = TokenStream> {
     // This could probably change to run from some configuration rather than
     // hardcoding various types, which would make it easier to support tree-specific
     // mutation values, and perhaps reduce duplication. However, it seems better
@@ -39,7 +380,9 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
     match type_ {
         Type::Path(syn::TypePath { path, .. }) => {
             // dbg!(&path);
-            if path.is_ident("bool") {
+            if let Some((ident, bounds)) = match_type_param(path, generics) {
+                type_param_replacements(ident, &bounds, generics, error_exprs)
+            } else if path.is_ident("bool") {
                 vec![quote! { true }, quote! { false }]
             } else if path.is_ident("String") {
                 vec![quote! { String::new() }, quote! { "xyzzy".into() }]
@@ -56,43 +399,41 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
             } else if path_is_float(path) {
                 vec![quote! { 0.0 }, quote! { 1.0 }, quote! { -1.0 }]
             } else if path_ends_with(path, "Result") {
-                if let Some(ok_type) = match_first_type_arg(path, "Result") {
-                    type_replacements(ok_type, error_exprs)
-                        .map(|rep| {
-                            quote! { Ok(#rep) }
-                        })
-                        .collect_vec()
-                } else {
-                    // A result with no type arguments, like `fmt::Result`; hopefully
-                    // the Ok value can be constructed with Default.
-                    vec![quote! { Ok(Default::default()) }]
-                }
-                .into_iter()
-                .chain(error_exprs.iter().map(|error_expr| {
-                    quote! { Err(#error_expr) }
-                }))
-                .collect_vec()
+                result_ok_replacements(path, generics, error_exprs)
+                    .into_iter()
+                    .chain(result_err_replacements(path, generics, error_exprs))
+                    .collect_vec()
+            } else if let Some(output_type) = match_pinned_boxed_future(path) {
+                // -> Pin<Box<dyn Future<Output = T> + Send>>, as returned by many
+                // async trait methods.
+                type_replacements(output_type, generics, error_exprs)
+                    .map(|rep| quote! { Box::pin(async { #rep }) })
+                    .collect_vec()
             } else if path_ends_with(path, "HttpResponse") {
                 vec![quote! { HttpResponse::Ok().finish() }]
             } else if let Some(some_type) = match_first_type_arg(path, "Option") {
                 iter::once(quote! { None })
-                    .chain(type_replacements(some_type, error_exprs).map(|rep| {
-                        quote! { Some(#rep) }
-                    }))
+                    .chain(
+                        type_replacements(some_type, generics, error_exprs).map(|rep| {
+                            quote! { Some(#rep) }
+                        }),
+                    )
                     .collect_vec()
             } else if let Some(element_type) = match_first_type_arg(path, "Vec") {
                 // Generate an empty Vec, and then a one-element vec for every recursive
                 // value.
                 iter::once(quote! { vec![] })
-                    .chain(type_replacements(element_type, error_exprs).map(|rep| {
-                        quote! { vec![#rep] }
-                    }))
+                    .chain(
+                        type_replacements(element_type, generics, error_exprs).map(|rep| {
+                            quote! { vec![#rep] }
+                        }),
+                    )
                     .collect_vec()
             } else if let Some(borrowed_type) = match_first_type_arg(path, "Cow") {
                 // TODO: We could specialize Cows for cases like Vec and Box where
                 // we would have to leak to make the reference; perhaps it would only
                 // look better...
-                type_replacements(borrowed_type, error_exprs)
+                type_replacements(borrowed_type, generics, error_exprs)
                     .flat_map(|rep| {
                         [
                             quote! { Cow::Borrowed(#rep) },
@@ -106,20 +447,22 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
                 // imported, but we must strip or rewrite the arguments, so that
                 // `std::sync::Arc<String>` becomes either `std::sync::Arc::<String>::new`
                 // or at least `std::sync::Arc::new`. Similarly for other types.
-                type_replacements(inner_type, error_exprs)
+                type_replacements(inner_type, generics, error_exprs)
                     .map(|rep| {
                         quote! { #container_type::new(#rep) }
                     })
                     .collect_vec()
             } else if let Some((collection_type, inner_type)) = known_collection(path) {
                 iter::once(quote! { #collection_type::new() })
-                    .chain(type_replacements(inner_type, error_exprs).map(|rep| {
-                        quote! { #collection_type::from_iter([#rep]) }
-                    }))
+                    .chain(
+                        type_replacements(inner_type, generics, error_exprs).map(|rep| {
+                            quote! { #collection_type::from_iter([#rep]) }
+                        }),
+                    )
                     .collect_vec()
             } else if let Some((collection_type, key_type, value_type)) = known_map(path) {
-                let key_reps = type_replacements(key_type, error_exprs).collect_vec();
-                let val_reps = type_replacements(value_type, error_exprs).collect_vec();
+                let key_reps = type_replacements(key_type, generics, error_exprs).collect_vec();
+                let val_reps = type_replacements(value_type, generics, error_exprs).collect_vec();
                 iter::once(quote! { #collection_type::new() })
                     .chain(
                         key_reps
@@ -134,13 +477,15 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
                 // to call it, but we strongly suspect that you could construct it from
                 // an `A`.
                 iter::once(quote! { #collection_type::new() })
-                    .chain(type_replacements(inner_type, error_exprs).flat_map(|rep| {
-                        [
-                            quote! { #collection_type::from_iter([#rep]) },
-                            quote! { #collection_type::new(#rep) },
-                            quote! { #collection_type::from(#rep) },
-                        ]
-                    }))
+                    .chain(
+                        type_replacements(inner_type, generics, error_exprs).flat_map(|rep| {
+                            [
+                                quote! { #collection_type::from_iter([#rep]) },
+                                quote! { #collection_type::new(#rep) },
+                                quote! { #collection_type::from(#rep) },
+                            ]
+                        }),
+                    )
                     .collect_vec()
             } else {
                 trace!(
@@ -156,12 +501,15 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
         // large, and values like "all zeros" and "all ones" seem likely to catch
         // lots of things.
         {
-            type_replacements(elem, error_exprs)
+            type_replacements(elem, generics, error_exprs)
                 .map(|r| quote! { [ #r; #len ] })
                 .collect_vec()
         }
         Type::Slice(TypeSlice { elem, .. }) => iter::once(quote! { Vec::leak(Vec::new()) })
-            .chain(type_replacements(elem, error_exprs).map(|r| quote! { Vec::leak(vec![ #r ]) }))
+            .chain(
+                type_replacements(elem, generics, error_exprs)
+                    .map(|r| quote! { Vec::leak(vec![ #r ]) }),
+            )
             .collect_vec(),
         Type::Reference(syn::TypeReference {
             mutability: None,
@@ -174,10 +522,11 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
             }
             Type::Slice(TypeSlice { elem, .. }) => iter::once(quote! { Vec::leak(Vec::new()) })
                 .chain(
-                    type_replacements(elem, error_exprs).map(|r| quote! { Vec::leak(vec![ #r ]) }),
+                    type_replacements(elem, generics, error_exprs)
+                        .map(|r| quote! { Vec::leak(vec![ #r ]) }),
                 )
                 .collect_vec(),
-            _ => type_replacements(elem, error_exprs)
+            _ => type_replacements(elem, generics, error_exprs)
                 .map(|rep| {
                     quote! { &#rep }
                 })
@@ -190,12 +539,13 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
         }) => match &**elem {
             Type::Slice(TypeSlice { elem, .. }) => iter::once(quote! { Vec::leak(Vec::new()) })
                 .chain(
-                    type_replacements(elem, error_exprs).map(|r| quote! { Vec::leak(vec![ #r ]) }),
+                    type_replacements(elem, generics, error_exprs)
+                        .map(|r| quote! { Vec::leak(vec![ #r ]) }),
                 )
                 .collect_vec(),
             _ => {
                 // Make &mut with static lifetime by leaking them on the heap.
-                type_replacements(elem, error_exprs)
+                type_replacements(elem, generics, error_exprs)
                     .map(|rep| {
                         quote! { Box::leak(Box::new(#rep)) }
                     })
@@ -209,7 +559,7 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
             // Generate the cartesian product of replacements of every type within the tuple.
             elems
                 .iter()
-                .map(|elem| type_replacements(elem, error_exprs).collect_vec())
+                .map(|elem| type_replacements(elem, generics, error_exprs).collect_vec())
                 .multi_cartesian_product()
                 .map(|reps| {
                     quote! { ( #( #reps ),* ) }
@@ -221,10 +571,15 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
             if let Some(item_type) = match_impl_iterator(impl_trait) {
                 iter::once(quote! { ::std::iter::empty() })
                     .chain(
-                        type_replacements(item_type, error_exprs)
+                        type_replacements(item_type, generics, error_exprs)
                             .map(|r| quote! { ::std::iter::once(#r) }),
                     )
                     .collect_vec()
+            } else if let Some(output_type) = match_impl_future(impl_trait) {
+                // -> impl Future<Output = T>
+                type_replacements(output_type, generics, error_exprs)
+                    .map(|rep| quote! { async { #rep } })
+                    .collect_vec()
             } else {
                 // TODO: Can we do anything with other impl traits?
                 vec![]
@@ -241,15 +596,142 @@ fn type_replacements(type_: &Type, error_exprs: &[Expr]) -> impl Iterator<Item =
     .into_iter()
 }
 
+/// The generic parameters in scope for a function, along with the type parameters whose
+/// replacements are being generated.
+struct TypeParams<'g> {
+    generics: &'g Generics,
+    /// Type parameters that are already being expanded, further out in the recursion,
+    /// so that bounds like `T: From<Vec<T>>` don't recurse forever.
+    expanding: Vec<&'g Ident>,
+}
+
+impl<'g> TypeParams<'g> {
+    fn new(generics: &'g Generics) -> TypeParams<'g> {
+        TypeParams {
+            generics,
+            expanding: Vec::new(),
+        }
+    }
+
+    /// Return a copy that also records that `ident` is being expanded.
+    fn expanding(&self, ident: &'g Ident) -> TypeParams<'g> {
+        TypeParams {
+            generics: self.generics,
+            expanding: self.expanding.iter().copied().chain([ident]).collect(),
+        }
+    }
+}
+
+/// If the path is a type parameter of the function, return its name and all its trait
+/// bounds, including those from the `where` clause.
+fn match_type_param<'g>(
+    path: &Path,
+    generics: &TypeParams<'g>,
+) -> Option<(&'g Ident, Vec<&'g Path>)> {
+    let generics = generics.generics;
+    let param = generics
+        .type_params()
+        .find(|param| path.is_ident(&param.ident))?;
+    let where_bounds = generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates)
+        .filter_map(|predicate| match predicate {
+            WherePredicate::Type(PredicateType {
+                bounded_ty: Type::Path(bounded),
+                bounds,
+                ..
+            }) if bounded.path.is_ident(&param.ident) => Some(bounds),
+            _ => None,
+        })
+        .flatten();
+    let bounds = param
+        .bounds
+        .iter()
+        .chain(where_bounds)
+        .filter_map(|bound| match bound {
+            TypeParamBound::Trait(TraitBound { path, .. }) => Some(path),
+            _ => None,
+        })
+        .collect_vec();
+    Some((&param.ident, bounds))
+}
+
+/// Generate replacements for a value of a generic type parameter, based on its bounds.
+///
+/// If the bounds don't show any way to construct a value then this returns nothing,
+/// because any guess would be unviable.
+fn type_param_replacements<'g>(
+    ident: &'g Ident,
+    bounds: &[&Path],
+    generics: &TypeParams<'g>,
+    error_exprs: &[Expr],
+) -> Vec<TokenStream> {
+    if generics.expanding.contains(&ident) {
+        trace!(?ident, "Type parameter is constructed from itself");
+        return Vec::new();
+    }
+    let mut reps = Vec::new();
+    for bound in bounds {
+        if path_ends_with(bound, "Default") {
+            reps.push(quote! { Default::default() });
+        } else if let Some(from_type) = match_first_type_arg(bound, "From") {
+            // Don't recurse into `T: From<U>` where `U` is another type parameter,
+            // which might loop forever.
+            if let Type::Path(from_path) = from_type {
+                if match_type_param(&from_path.path, generics).is_some() {
+                    continue;
+                }
+            }
+            reps.extend(
+                type_replacements(from_type, &generics.expanding(ident), error_exprs)
+                    .map(|rep| quote! { #ident::from(#rep) }),
+            );
+        }
+    }
+    if reps.is_empty() {
+        trace!(?ident, "No constructor found in bounds of type parameter");
+    }
+    reps
+}
+
 fn path_ends_with(path: &Path, ident: &str) -> bool {
     path.segments.last().is_some_and(|s| s.ident == ident)
 }
 
 fn match_impl_iterator(TypeImplTrait { bounds, .. }: &TypeImplTrait) -> Option<&Type> {
+    match_assoc_type_bound(bounds, "Iterator", "Item")
+}
+
+fn match_impl_future(TypeImplTrait { bounds, .. }: &TypeImplTrait) -> Option<&Type> {
+    match_assoc_type_bound(bounds, "Future", "Output")
+}
+
+/// Match `Pin<Box<dyn Future<Output = T>>>`, possibly with other bounds on the trait
+/// object, and return `T`.
+fn match_pinned_boxed_future(path: &Path) -> Option<&Type> {
+    let Type::Path(boxed) = match_first_type_arg(path, "Pin")? else {
+        return None;
+    };
+    let Type::TraitObject(TypeTraitObject { bounds, .. }) =
+        match_first_type_arg(&boxed.path, "Box")?
+    else {
+        return None;
+    };
+    match_assoc_type_bound(bounds, "Future", "Output")
+}
+
+/// Find a bound like `Iterator<Item = T>` among the bounds of an `impl` or `dyn` type,
+/// and return `T`.
+fn match_assoc_type_bound<'a>(
+    bounds: impl IntoIterator<Item = &'a TypeParamBound>,
+    trait_name: &str,
+    assoc_name: &str,
+) -> Option<&'a Type> {
     for bound in bounds {
         if let TypeParamBound::Trait(TraitBound { path, .. }) = bound {
             if let Some(last_segment) = path.segments.last() {
-                if last_segment.ident == "Iterator" {
+                if last_segment.ident == trait_name {
                     if let PathArguments::AngleBracketed(AngleBracketedGenericArguments {
                         args,
                         ..
@@ -258,7 +740,7 @@ fn match_impl_iterator(TypeImplTrait { bounds, .. }: &TypeImplTrait) -> Option<&
                         if let Some(GenericArgument::AssocType(AssocType { ident, ty, .. })) =
                             args.first()
                         {
-                            if ident == "Item" {
+                            if ident == assoc_name {
                                 return Some(ty);
                             }
                         }
@@ -441,12 +923,68 @@ fn match_first_type_arg<'p>(path: &'p Path, expected_ident: &str) -> Option<&'p
 mod test {
     use itertools::Itertools;
     use pretty_assertions::assert_eq;
-    use syn::{parse_quote, Expr, ReturnType};
+    use syn::{parse_quote, Expr, Generics, ReturnType};
 
     use crate::fnvalue::match_impl_iterator;
     use crate::pretty::ToPrettyString;
 
-    use super::{known_map, return_type_replacements};
+    use super::{
+        assoc_const_replacements, known_map, result_swap_replacements, return_type_replacements,
+        tuple_element_replacements, ImplConsts,
+    };
+
+    #[test]
+    fn assoc_const_replacements_of_the_same_type() {
+        let item_impl: syn::ItemImpl = parse_quote! {
+            impl Meters {
+                const ZERO: Meters = Meters(0.0);
+                const ONE: Self = Meters(1.0);
+                const SCALE: f64 = 1000.0;
+                const OFFSET: f64 = 0.0;
+                const UNIT: &str = "m";
+            }
+        };
+        let impl_consts = ImplConsts::from_item_impl(&item_impl);
+        let check = |return_type: ReturnType, block: syn::Block, expected: &[&str]| {
+            assert_eq!(
+                assoc_const_replacements(&return_type, &block, &impl_consts)
+                    .into_iter()
+                    .map(|t| t.to_pretty_string())
+                    .collect_vec(),
+                expected
+            );
+        };
+        check(
+            parse_quote! { -> f64 },
+            parse_quote! {{ Self::SCALE }},
+            &["Self::OFFSET"],
+        );
+        check(
+            parse_quote! { -> Self },
+            parse_quote! {{ Self::ZERO }},
+            &["Self::ONE"],
+        );
+        check(
+            parse_quote! { -> Meters },
+            parse_quote! {{ Self::ONE }},
+            &["Self::ZERO"],
+        );
+        check(
+            parse_quote! { -> &'static str },
+            parse_quote! {{ Self::UNIT }},
+            &[],
+        );
+        check(
+            parse_quote! { -> f64 },
+            parse_quote! {{ Self::SCALE * 2.0 }},
+            &[],
+        );
+        check(
+            parse_quote! { -> f64 },
+            parse_quote! {{ Other::SCALE }},
+            &[],
+        );
+    }
 
     #[test]
     fn recurse_into_result_bool() {
@@ -501,11 +1039,72 @@ mod test {
 
     #[test]
     fn result_unit_replacement() {
-        check_replacements(&parse_quote! { -> Result<(), Error> }, &[], &["Ok(())"]);
+        check_replacements(
+            &parse_quote! { -> Result<(), Error> },
+            &[],
+            &["Ok(())", "Err(Default::default())"],
+        );
 
         check_replacements(&parse_quote! { -> Result<()> }, &[], &["Ok(())"]);
     }
 
+    #[test]
+    fn result_err_replacements_from_error_type() {
+        check_replacements(
+            &parse_quote! { -> Result<u8, ()> },
+            &[],
+            &["Ok(0)", "Ok(1)", "Err(())"],
+        );
+        check_replacements(
+            &parse_quote! { -> io::Result<()> },
+            &[],
+            &[
+                "Ok(())",
+                "Err(std::io::Error::from(std::io::ErrorKind::Other))",
+            ],
+        );
+        check_replacements(
+            &parse_quote! { -> Result<(), std::fmt::Error> },
+            &[],
+            &["Ok(())", "Err(std::fmt::Error)"],
+        );
+        check_replacements(
+            &parse_quote! { -> Result<(), Box<dyn std::error::Error + Send>> },
+            &[],
+            &["Ok(())", "Err(\"mutated\".into())"],
+        );
+        check_replacements(&parse_quote! { -> anyhow::Result<()> }, &[], &["Ok(())"]);
+        check_replacements(
+            &parse_quote! { -> Result<(), anyhow::Error> },
+            &[],
+            &["Ok(())"],
+        );
+        check_generic_replacements(
+            &parse_quote! { -> Result<(), E> },
+            &parse_quote! { <E> },
+            &[],
+            &["Ok(())"],
+        );
+    }
+
+    #[test]
+    fn configured_error_values_replace_default_guess() {
+        check_replacements(
+            &parse_quote! { -> Result<(), MyError> },
+            &[parse_quote! { MyError::Mutated }],
+            &["Ok(())", "Err(MyError::Mutated)"],
+        );
+        check_replacements(
+            &parse_quote! { -> io::Result<()> },
+            &[parse_quote! { anyhow!("mutated") }],
+            &[
+                "Ok(())",
+                "Err(std::io::Error::from(std::io::ErrorKind::Other))",
+                "Err(anyhow!(\"mutated\"))",
+            ],
+        );
+    }
+
     #[test]
     fn http_response_replacement() {
         check_replacements(
@@ -524,6 +1123,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn option_of_generic_result_includes_none() {
+        // `None` depends only on the outer `Option`, even if nothing useful can be
+        // generated for the inner type.
+        check_generic_replacements(
+            &parse_quote! { -> Option<Result<T, E>> },
+            &parse_quote! { <T: Default, E> },
+            &[],
+            &["None", "Some(Ok(Default::default()))"],
+        );
+    }
+
     #[test]
     fn box_usize_replacement() {
         check_replacements(
@@ -726,6 +1337,100 @@ mod test {
         );
     }
 
+    #[test]
+    fn tuple_element_replacements_for_each_element() {
+        let reps = tuple_element_replacements(
+            &parse_quote! { -> (bool, usize) },
+            &Generics::default(),
+            &[],
+        );
+        assert_eq!(
+            reps.iter()
+                .map(|elem_reps| elem_reps
+                    .iter()
+                    .map(ToPrettyString::to_pretty_string)
+                    .collect_vec())
+                .collect_vec(),
+            [vec!["true", "false"], vec!["0", "1"]]
+        );
+    }
+
+    #[test]
+    fn no_tuple_element_replacements_for_non_tuples() {
+        assert!(
+            tuple_element_replacements(&parse_quote! { -> usize }, &Generics::default(), &[])
+                .is_empty()
+        );
+        assert!(
+            tuple_element_replacements(&parse_quote! { -> () }, &Generics::default(), &[])
+                .is_empty()
+        );
+        assert!(
+            tuple_element_replacements(&ReturnType::Default, &Generics::default(), &[]).is_empty()
+        );
+    }
+
+    #[test]
+    fn type_param_with_default_bound() {
+        check_generic_replacements(
+            &parse_quote! { -> T },
+            &parse_quote! { <T: Clone + Default> },
+            &[],
+            &["Default::default()"],
+        );
+    }
+
+    #[test]
+    fn type_param_with_from_bound_in_where_clause() {
+        let mut generics: Generics = parse_quote! { <T> };
+        generics.where_clause = Some(parse_quote! { where T: From<bool> });
+        check_generic_replacements(
+            &parse_quote! { -> Option<T> },
+            &generics,
+            &[],
+            &["None", "Some(T::from(true))", "Some(T::from(false))"],
+        );
+    }
+
+    #[test]
+    fn no_replacements_for_type_param_without_constructor() {
+        check_generic_replacements(
+            &parse_quote! { -> T },
+            &parse_quote! { <T: Clone, U: From<T>> },
+            &[],
+            &[],
+        );
+        check_generic_replacements(
+            &parse_quote! { -> U },
+            &parse_quote! { <T: Clone, U: From<T>> },
+            &[],
+            &[],
+        );
+    }
+
+    #[test]
+    fn self_referential_from_bounds_terminate() {
+        check_generic_replacements(
+            &parse_quote! { -> T },
+            &parse_quote! { <T: From<Vec<T>>> },
+            &[],
+            &["T::from(vec![])"],
+        );
+        let mut generics: Generics = parse_quote! { <T, U> };
+        generics.where_clause =
+            Some(parse_quote! { where T: From<Option<U>>, U: From<Option<T>> + Default });
+        check_generic_replacements(
+            &parse_quote! { -> T },
+            &generics,
+            &[],
+            &[
+                "T::from(None)",
+                "T::from(Some(U::from(None)))",
+                "T::from(Some(Default::default()))",
+            ],
+        );
+    }
+
     #[test]
     fn iter_replacement() {
         check_replacements(
@@ -739,6 +1444,38 @@ mod test {
         );
     }
 
+    #[test]
+    fn impl_future_replacement() {
+        check_replacements(
+            &parse_quote! { -> impl Future<Output = u32> },
+            &[],
+            &["async { 0 }", "async { 1 }"],
+        );
+        check_replacements(
+            &parse_quote! { -> impl std::future::Future<Output = Result<bool, String>> + Send },
+            &[],
+            &[
+                "async { Ok(true) }",
+                "async { Ok(false) }",
+                "async { Err(Default::default()) }",
+            ],
+        );
+    }
+
+    #[test]
+    fn pinned_boxed_future_replacement() {
+        check_replacements(
+            &parse_quote! { -> Pin<Box<dyn Future<Output = bool> + Send + 'static>> },
+            &[],
+            &["Box::pin(async { true })", "Box::pin(async { false })"],
+        );
+        check_replacements(
+            &parse_quote! { -> std::pin::Pin<Box<dyn std::future::Future<Output = ()>>> },
+            &[],
+            &["Box::pin(async { () })"],
+        );
+    }
+
     #[test]
     fn impl_matches_iterator() {
         assert_eq!(
@@ -786,9 +1523,67 @@ mod test {
         );
     }
 
+    #[test]
+    fn result_swap_replacements_for_result_types() {
+        let error_exprs = [parse_quote! { anyhow!("mutated") }];
+        let (oks, errs) = result_swap_replacements(
+            &parse_quote! { -> Result<bool, Error> },
+            &Generics::default(),
+            &error_exprs,
+        )
+        .unwrap();
+        assert_eq!(
+            oks.iter()
+                .map(ToPrettyString::to_pretty_string)
+                .collect_vec(),
+            ["Ok(true)", "Ok(false)"]
+        );
+        assert_eq!(
+            errs.iter()
+                .map(ToPrettyString::to_pretty_string)
+                .collect_vec(),
+            ["Err(anyhow!(\"mutated\"))"]
+        );
+
+        let (oks, errs) =
+            result_swap_replacements(&parse_quote! { -> fmt::Result }, &Generics::default(), &[])
+                .unwrap();
+        assert_eq!(
+            oks.iter()
+                .map(ToPrettyString::to_pretty_string)
+                .collect_vec(),
+            ["Ok(Default::default())"]
+        );
+        assert_eq!(
+            errs.iter()
+                .map(ToPrettyString::to_pretty_string)
+                .collect_vec(),
+            ["Err(std::fmt::Error)"]
+        );
+
+        assert!(result_swap_replacements(
+            &parse_quote! { -> Option<u8> },
+            &Generics::default(),
+            &[]
+        )
+        .is_none());
+        assert!(
+            result_swap_replacements(&ReturnType::Default, &Generics::default(), &[]).is_none()
+        );
+    }
+
     fn check_replacements(return_type: &ReturnType, error_exprs: &[Expr], expected: &[&str]) {
+        check_generic_replacements(return_type, &Generics::default(), error_exprs, expected);
+    }
+
+    fn check_generic_replacements(
+        return_type: &ReturnType,
+        generics: &Generics,
+        error_exprs: &[Expr],
+        expected: &[&str],
+    ) {
         assert_eq!(
-            return_type_replacements(return_type, error_exprs)
+            return_type_replacements(return_type, generics, error_exprs)
                 .into_iter()
                 .map(|t| t.to_pretty_string())
                 .collect_vec(),
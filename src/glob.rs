@@ -3,8 +3,10 @@
 //! Build globsets.
 
 use std::borrow::Cow;
+use std::fs::read_to_string;
 
-use anyhow::Context;
+use anyhow::{ensure, Context};
+use camino::Utf8Path;
 use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 
 use crate::Result;
@@ -44,6 +46,27 @@ where
     Ok(Some(builder.build().context("Failed to build glob set")?))
 }
 
+/// Read globs from a file, one per line, as for `--files-from` and `--exclude-from`.
+///
+/// Blank lines, and lines starting with `#`, are ignored.
+///
+/// It's an error if the file contains no globs, since otherwise an empty list would
+/// silently match every file.
+pub fn read_glob_file(path: &Utf8Path) -> Result<Vec<String>> {
+    let text = read_to_string(path).with_context(|| format!("Failed to read globs from {path}"))?;
+    let globs = parse_glob_list(&text);
+    ensure!(!globs.is_empty(), "No globs found in {path}");
+    Ok(globs)
+}
+
+fn parse_glob_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -182,4 +205,10 @@ mod test {
             "Glob with slashes anchors to whole path"
         );
     }
+
+    #[test]
+    fn parse_glob_list_skips_comments_and_blank_lines() {
+        let text = "# generated list\nsrc/a.rs\n\n  \n  # indented comment\nsrc/b/**\r\nconsole\n";
+        assert_eq!(parse_glob_list(text), ["src/a.rs", "src/b/**", "console"]);
+    }
 }
@@ -7,7 +7,7 @@ use std::collections::HashMap;
 use std::iter::once;
 
 use anyhow::{anyhow, bail};
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
 use indoc::formatdoc;
 use itertools::Itertools;
 use patch::{Line, Patch};
@@ -19,13 +19,7 @@ use crate::Result;
 
 /// Return only mutants to functions whose source was touched by this diff.
 pub fn diff_filter(mutants: Vec<Mutant>, diff_text: &str) -> Result<Vec<Mutant>> {
-    // Strip any "Binary files .. differ" lines because `patch` doesn't understand them at
-    // the moment; this could be removed if it's fixed in that crate.
-    let fixed_diff = diff_text
-        .lines()
-        .filter(|line| !(line.starts_with("Binary files ")))
-        .chain(once(""))
-        .join("\n");
+    let fixed_diff = fix_diff(diff_text);
 
     // Flatten the error to a string because otherwise it references the diff, and can't be returned.
     if fixed_diff.trim().is_empty() {
@@ -78,6 +72,69 @@ pub fn diff_filter(mutants: Vec<Mutant>, diff_text: &str) -> Result<Vec<Mutant>>
     Ok(matched)
 }
 
+/// Return the paths of files that exist after applying the diff, relative to the tree root.
+///
+/// Deleted files are not included, since they can't contain any mutants.
+pub fn diff_changed_paths(diff_text: &str) -> Result<Vec<Utf8PathBuf>> {
+    let fixed_diff = fix_diff(diff_text);
+    if fixed_diff.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let patches =
+        Patch::from_multiple(&fixed_diff).map_err(|err| anyhow!("Failed to parse diff: {err}"))?;
+    Ok(patches
+        .iter()
+        .map(|patch| strip_patch_path(&patch.new.path))
+        .filter(|path| *path != "/dev/null")
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// Strip any "Binary files .. differ" lines because `patch` doesn't understand them at
+/// the moment; this could be removed if it's fixed in that crate.
+///
+/// Also drop git's headers for files with no changed lines, such as pure renames
+/// (`rename from`/`rename to` with 100% similarity) or mode changes, because `patch`
+/// can't parse a header that isn't followed by any hunks. These files can't contain
+/// any changed mutants.
+///
+/// A renamed file that was also edited keeps its headers, and its hunks are matched
+/// against the new name from the `+++` line.
+fn fix_diff(diff_text: &str) -> String {
+    let lines = diff_text
+        .lines()
+        .filter(|line| !(line.starts_with("Binary files ")))
+        .collect_vec();
+    let mut sections: Vec<&[&str]> = Vec::new();
+    let mut start = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if i > start && line.starts_with("diff --git ") {
+            sections.push(&lines[start..i]);
+            start = i;
+        }
+    }
+    sections.push(&lines[start..]);
+    sections
+        .into_iter()
+        .filter(|section| {
+            let has_hunks = section.iter().any(|line| line.starts_with("@@ "));
+            if !has_hunks
+                && section
+                    .first()
+                    .is_some_and(|l| l.starts_with("diff --git "))
+            {
+                trace!(header = section[0], "skip diff section with no hunks");
+                false
+            } else {
+                true
+            }
+        })
+        .flatten()
+        .copied()
+        .chain(once(""))
+        .join("\n")
+}
+
 /// Error if the new text from the diffs doesn't match the source files.
 fn check_diff_new_text_matches(patches: &[Patch], mutants: &[Mutant]) -> Result<()> {
     let mut source_by_name: HashMap<&Utf8Path, &SourceFile> = HashMap::new();
@@ -125,52 +182,63 @@ fn strip_patch_path(path: &str) -> &Utf8Path {
 /// Code that's only included as context doesn't need to be tested.
 ///
 /// This returns a list of line numbers that are either added to the new file, or
-/// adjacent to deletions.
+/// adjacent to deletions. Lines that replace removed lines count as changed, but
+/// their neighbors don't.
+///
+/// Line numbers come from the `@@` hunk ranges, so this works for diffs with any
+/// amount of context, including none (`-U0`).
 ///
 /// (A list of ranges would be more concise but this is easier for a first version.)
 ///
 /// If a line is deleted then the range will span from the line before to the line after.
 fn affected_lines(patch: &Patch) -> Vec<usize> {
-    let mut affected_lines = Vec::new();
+    let mut affected_lines: Vec<usize> = Vec::new();
+    let mut push = |lineno: usize| {
+        debug_assert!(lineno >= 1, "{lineno}");
+        if affected_lines.last().map_or(true, |last| *last < lineno) {
+            affected_lines.push(lineno);
+        }
+    };
     for hunk in &patch.hunks {
-        let mut lineno: usize = hunk.new_range.start.try_into().unwrap();
-        // True if the previous line was deleted. If set, then the next line that exists in the
-        // new file, if there is one, will be marked as affected.
-        let mut prev_removed = false;
-        for line in &hunk.lines {
-            match line {
-                Line::Remove(_) => {
-                    prev_removed = true;
-                }
-                Line::Add(_) | Line::Context(_) => {
-                    if prev_removed {
-                        debug_assert!(
-                            affected_lines.last().map_or(true, |last| *last < lineno),
-                            "{lineno} {affected_lines:?}"
-                        );
-                        debug_assert!(lineno >= 1, "{lineno}");
-                        affected_lines.push(lineno);
-                        prev_removed = false;
-                    }
-                }
-            }
+        let start: usize = hunk.new_range.start.try_into().unwrap();
+        // If the hunk has no lines in the new file, as for a deletion with no context,
+        // its start is the line before the deletion.
+        let mut lineno = if hunk.new_range.count == 0 {
+            start + 1
+        } else {
+            start
+        };
+        let has_context = hunk
+            .lines
+            .iter()
+            .any(|line| matches!(line, Line::Context(_)));
+        let mut lines = hunk.lines.iter().peekable();
+        while let Some(line) = lines.next() {
             match line {
                 Line::Context(_) => {
                     lineno += 1;
                 }
                 Line::Add(_) => {
-                    if affected_lines.last().map_or(true, |last| *last != lineno) {
-                        affected_lines.push(lineno);
-                    }
+                    push(lineno);
                     lineno += 1;
                 }
                 Line::Remove(_) => {
-                    if lineno > 1
-                        && affected_lines
-                            .last()
-                            .map_or(true, |last| *last != (lineno - 1))
-                    {
-                        affected_lines.push(lineno - 1);
+                    while lines
+                        .next_if(|line| matches!(line, Line::Remove(_)))
+                        .is_some()
+                    {}
+                    if matches!(lines.peek(), Some(Line::Add(_))) {
+                        // The removed lines were replaced, and the new lines are marked
+                        // as they're added.
+                        continue;
+                    }
+                    if lineno > 1 {
+                        push(lineno - 1);
+                    }
+                    // Without any context we can't tell if this was the end of the file,
+                    // so also mark the following line, which might not exist.
+                    if lines.peek().is_some() || !has_context {
+                        push(lineno);
                     }
                 }
             }
@@ -218,10 +286,13 @@ fn partial_new_file<'d>(patch: &Patch<'d>) -> Vec<(usize, &'d str)> {
 mod test_super {
     use std::fs::read_to_string;
 
+    use indoc::indoc;
     use pretty_assertions::assert_eq;
     use similar::TextDiff;
 
     use super::*;
+    use crate::visit::mutate_source_str;
+    use crate::Options;
 
     #[test]
     fn patch_parse_error() {
@@ -256,6 +327,99 @@ index eb42779..a0091b7 100644
         assert_eq!(filtered.len(), 0);
     }
 
+    #[test]
+    fn renamed_and_edited_file_matches_only_edited_line() {
+        let code = indoc! {"
+            fn add(a: u32, b: u32) -> u32 {
+                a + b
+            }
+
+            fn sub(a: u32, b: u32) -> u32 {
+                a - b
+            }
+        "};
+        let diff = indoc! {"
+            diff --git a/src/old.rs b/src/main.rs
+            similarity index 90%
+            rename from src/old.rs
+            rename to src/main.rs
+            index 1111111..2222222 100644
+            --- a/src/old.rs
+            +++ b/src/main.rs
+            @@ -5,3 +5,3 @@
+             fn sub(a: u32, b: u32) -> u32 {
+            -    b - a
+            +    a - b
+             }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        let filtered = diff_filter(mutants, diff).unwrap();
+        let names = filtered.iter().map(|m| m.name(true)).collect_vec();
+        assert!(!names.is_empty());
+        assert!(
+            names.iter().all(|name| name.starts_with("src/main.rs:6:")),
+            "only mutants on the edited line should match: {names:#?}"
+        );
+    }
+
+    #[test]
+    fn pure_rename_matches_nothing() {
+        let code = indoc! {"
+            fn add(a: u32, b: u32) -> u32 {
+                a + b
+            }
+        "};
+        let diff = indoc! {"
+            diff --git a/src/old.rs b/src/main.rs
+            similarity index 100%
+            rename from src/old.rs
+            rename to src/main.rs
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert!(!mutants.is_empty());
+        assert!(diff_filter(mutants, diff).unwrap().is_empty());
+        assert!(diff_changed_paths(diff).unwrap().is_empty());
+    }
+
+    #[test]
+    fn pure_rename_before_and_after_other_changes() {
+        let diff = indoc! {"
+            diff --git a/src/a.rs b/src/b.rs
+            similarity index 100%
+            rename from src/a.rs
+            rename to src/b.rs
+            diff --git a/src/lib.rs b/src/lib.rs
+            index 1111111..2222222 100644
+            --- a/src/lib.rs
+            +++ b/src/lib.rs
+            @@ -1,1 +1,1 @@
+            -fn main() {}
+            +fn main() { }
+            diff --git a/src/c.rs b/src/d.rs
+            similarity index 100%
+            rename from src/c.rs
+            rename to src/d.rs
+        "};
+        assert_eq!(diff_changed_paths(diff).unwrap(), ["src/lib.rs"]);
+    }
+
+    #[test]
+    fn changed_paths_exclude_deleted_files() {
+        let diff = "\
+--- a/main/src/main.rs
++++ b/main/src/main.rs
+@@ -1,1 +1,1 @@
+-fn main() {}
++fn main() { }
+--- a/utils/src/old.rs
++++ /dev/null
+@@ -1,1 +0,0 @@
+-pub fn old() {}
+";
+        assert_eq!(diff_changed_paths(diff).unwrap(), ["main/src/main.rs"]);
+        assert!(diff_changed_paths("").unwrap().is_empty());
+    }
+
     fn make_diff(old: &str, new: &str) -> String {
         TextDiff::from_lines(old, new)
             .unified_diff()
@@ -345,12 +509,93 @@ index eb42779..a0091b7 100644
             println!("{diff}");
             let patch = Patch::from_single(&diff).unwrap();
             let affected = affected_lines(&patch);
-            if i > 1 {
-                // The line before the deletion also counts as affected.
-                assert_eq!(affected, &[i - 1, i, i + 1]);
-            } else {
-                assert_eq!(affected, &[i, i + 1]);
+            // Only the new lines are affected, not the unchanged lines around them.
+            assert_eq!(affected, &[i, i + 1]);
+        }
+    }
+
+    #[test]
+    fn affected_lines_independent_of_context_size() {
+        let old = (1..=20).map(|i| format!("line {i}\n")).collect_vec();
+        let mut new = old.clone();
+        new[3] = "changed 4\n".to_owned(); // modified
+        new.remove(9); // old line 10 deleted
+        new.insert(14, "inserted\n".to_owned()); // added before old line 16
+        new.pop(); // last line deleted
+        let (old, new) = (old.join(""), new.join(""));
+        let affected_with_context = |radius| {
+            let diff = TextDiff::from_lines(&old, &new)
+                .unified_diff()
+                .context_radius(radius)
+                .header("a/file.rs", "b/file.rs")
+                .to_string();
+            println!("{diff}");
+            affected_lines(&Patch::from_single(&diff).unwrap())
+        };
+        let expected = [4, 9, 10, 15, 19, 20];
+        assert_eq!(affected_with_context(0), expected);
+        assert_eq!(affected_with_context(3), expected[..5]);
+        assert_eq!(affected_with_context(5), expected[..5]);
+    }
+
+    #[test]
+    fn affected_lines_from_git_diff_without_context() {
+        // As from `git diff -U0`, where counts of 1 are omitted.
+        let diff = indoc! {"
+            --- a/src/lib.rs
+            +++ b/src/lib.rs
+            @@ -1 +0,0 @@
+            -// removed header
+            @@ -4 +3 @@
+            -    a - b
+            +    a + b
+            @@ -8,0 +8,2 @@
+            +fn new() {
+            +}
+        "};
+        let patch = Patch::from_single(diff).unwrap();
+        assert_eq!(affected_lines(&patch), [1, 3, 8, 9]);
+    }
+
+    #[test]
+    fn context_lines_in_hunk_are_not_selected() {
+        let code = indoc! {"
+            fn add(a: u32, b: u32) -> u32 {
+                a + b
+            }
+            fn sub(a: u32, b: u32) -> u32 {
+                a - b
             }
+        "};
+        let without_context = indoc! {"
+            --- a/src/main.rs
+            +++ b/src/main.rs
+            @@ -5 +5 @@
+            -    b - a
+            +    a - b
+        "};
+        // The function `add` is in the context of this hunk, but not changed.
+        let with_context = indoc! {"
+            --- a/src/main.rs
+            +++ b/src/main.rs
+            @@ -1,6 +1,6 @@
+             fn add(a: u32, b: u32) -> u32 {
+                 a + b
+             }
+             fn sub(a: u32, b: u32) -> u32 {
+            -    b - a
+            +    a - b
+             }
+        "};
+        for diff in [without_context, with_context] {
+            let mutants = mutate_source_str(code, &Options::default()).unwrap();
+            let filtered = diff_filter(mutants, diff).unwrap();
+            let names = filtered.iter().map(|m| m.name(true)).collect_vec();
+            assert!(!names.is_empty());
+            assert!(
+                names.iter().all(|name| name.starts_with("src/main.rs:5:")),
+                "only mutants on the changed line should match: {names:#?}"
+            );
         }
     }
 
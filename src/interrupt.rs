@@ -2,6 +2,9 @@
 
 //! Handle ctrl-c by setting a global atomic and checking it from long-running
 //! operations.
+//!
+//! The same mechanism is used to stop early with `--fail-fast`, without reporting
+//! it as an interruption.
 
 use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -11,18 +14,40 @@ use tracing::error;
 use crate::Result;
 
 static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static STOPPED: AtomicBool = AtomicBool::new(false);
+
+/// The message of the error returned by [`check_interrupted`] after [`stop`].
+pub const STOPPED_MESSAGE: &str = "stopped early";
 
 pub fn install_handler() {
     ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst))
         .expect("install ctrl-c handler");
 }
 
+/// Ask all long-running operations to stop, as if interrupted, but without
+/// treating it as an error.
+pub fn stop() {
+    STOPPED.store(true, Ordering::SeqCst);
+}
+
+/// True if [`stop`] has been called.
+pub fn is_stopped() -> bool {
+    STOPPED.load(Ordering::SeqCst)
+}
+
+/// Return whether [`stop`] was called, and reset it so that later runs can proceed.
+pub fn take_stopped() -> bool {
+    STOPPED.swap(false, Ordering::SeqCst)
+}
+
 /// Return an error if the program was interrupted and should exit.
 #[mutants::skip] // With this mutated too many of the tests will hang.
 pub fn check_interrupted() -> Result<()> {
     if INTERRUPTED.load(Ordering::SeqCst) {
         error!("interrupted");
         Err(anyhow!("interrupted"))
+    } else if is_stopped() {
+        Err(anyhow!(STOPPED_MESSAGE))
     } else {
         Ok(())
     }
@@ -6,19 +6,33 @@
 #![warn(clippy::pedantic)]
 
 use std::cmp::{max, min};
+use std::collections::HashMap;
 use std::panic::resume_unwind;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{thread, vec};
 
+use camino::{Utf8Path, Utf8PathBuf};
+use humantime::format_duration;
 use itertools::Itertools;
-use tracing::{debug, debug_span, error, trace, warn};
+use serde::Serialize;
+use tracing::{debug, debug_span, error, info, trace, warn};
 
+use crate::baseline::{baseline_key, BaselineRecord};
+use crate::interrupt;
+use crate::outcome::{PhaseResult, SummaryOutcome};
 use crate::{
-    cargo::run_cargo, options::TestPackages, outcome::LabOutcome, output::OutputDir,
-    package::Package, package::PackageSelection, timeouts::Timeouts, workspace::Workspace,
-    BaselineStrategy, BuildDir, Console, Context, Mutant, Options, Phase, Result, Scenario,
-    ScenarioOutcome,
+    cargo::{no_tests_run, run_cargo, run_doctests},
+    options::{TestPackages, TestTool},
+    outcome::LabOutcome,
+    output::{
+        load_baseline_test_duration, load_mutant_timings, write_plan, OutputDir, ScenarioOutput,
+    },
+    package::Package,
+    package::PackageSelection,
+    timeouts::Timeouts,
+    workspace::Workspace,
+    BaselineStrategy, BuildDir, Console, Mutant, Options, Phase, Result, Scenario, ScenarioOutcome,
 };
 
 /// Run all possible mutation experiments.
@@ -28,6 +42,7 @@ use crate::{
 ///
 /// Before testing the mutants, the lab checks that the source tree passes its tests with no
 /// mutations applied.
+#[allow(clippy::too_many_lines)] // just a lot of setup
 pub fn test_mutants(
     mut mutants: Vec<Mutant>,
     workspace: &Workspace,
@@ -37,62 +52,61 @@ pub fn test_mutants(
 ) -> Result<LabOutcome> {
     let start_time = Instant::now();
     console.set_debug_log(output_dir.open_debug_log()?);
-    if options.shuffle {
-        fastrand::shuffle(&mut mutants);
-    }
+    console.set_progress_path(output_dir.path().join("progress.json"));
+    schedule_mutants(&mut mutants, options, |mutant| {
+        output_dir.mutant_duration(mutant)
+    });
     output_dir.write_mutants_list(&mutants)?;
-    console.discovered_mutants(&mutants);
+    console.discovered_mutants(&mutants, options);
     if mutants.is_empty() {
         warn!("No mutants found under the active filters");
         return Ok(LabOutcome::default());
     }
+    let previous_baseline = output_dir.previous_baseline().cloned();
     let output_mutex = Mutex::new(output_dir);
     let baseline_build_dir = BuildDir::for_baseline(workspace, options, console)?;
     let jobserver = options
         .jobserver
-        .then(|| {
-            let n_tasks = options.jobserver_tasks.unwrap_or_else(num_cpus::get);
-            debug!(n_tasks, "starting jobserver");
-            jobserver::Client::new(n_tasks)
-        })
-        .transpose()
-        .context("Start jobserver")?;
+        .then(|| start_jobserver(options))
+        .flatten();
     let tests_for_mutant = TestsForMutant::new(options, workspace);
+    let cargo_dir = cargo_dir(&mutants, &tests_for_mutant);
+    let shared_deps = options.share_deps.then(|| SharedDeps {
+        source: baseline_build_dir.path().to_owned(),
+        local_crate_names: workspace.local_crate_names(),
+        build_lock: Mutex::new(()),
+    });
     let lab = Lab {
         output_mutex,
         jobserver,
         tests_for_mutant,
+        cargo_dir,
+        disk_usage: Mutex::new(ScratchDiskUsage::default()),
+        shared_deps,
         options,
         console,
     };
-    let timeouts = match options.baseline {
-        BaselineStrategy::Run => {
-            let outcome = lab.run_baseline(&baseline_build_dir, &mutants)?;
-            if outcome.success() {
-                Timeouts::from_baseline(&outcome, options)
-            } else {
-                error!(
-                    "cargo {phase} failed in an unmutated tree, so no mutants were tested",
-                    phase = outcome.last_phase(),
-                );
-                return Ok(lab
-                    .output_mutex
-                    .into_inner()
-                    .expect("lock output_dir")
-                    .take_lab_outcome());
-            }
-        }
-        BaselineStrategy::Skip => Timeouts::without_baseline(options),
+    let Some(timeouts) =
+        lab.baseline_timeouts(&baseline_build_dir, &mutants, workspace, previous_baseline)?
+    else {
+        let output_dir = lab.output_mutex.into_inner().expect("lock output_dir");
+        output_dir.write_final_reports()?;
+        return Ok(output_dir.take_lab_outcome());
     };
     debug!(?timeouts);
+    lab.output_mutex
+        .lock()
+        .expect("lock output_dir")
+        .lab_outcome
+        .set_timeouts(&timeouts);
 
     let build_dir_0 = Mutex::new(Some(baseline_build_dir));
     // Create n threads, each dedicated to one build directory. Each of them tries to take a
     // scenario to test off the queue, and then exits when there are no more left.
     console.start_testing_mutants(mutants.len());
-    let n_threads = max(1, min(options.jobs.unwrap_or(1), mutants.len()));
+    let n_threads = n_threads(options, mutants.len());
     let work_queue = &Mutex::new(mutants.into_iter());
-    thread::scope(|scope| -> crate::Result<()> {
+    let threads_result = thread::scope(|scope| -> crate::Result<()> {
         let mut threads = Vec::new();
         for _i_thread in 0..n_threads {
             threads.push(scope.spawn(|| -> crate::Result<()> {
@@ -100,21 +114,47 @@ pub fn test_mutants(
                 // First thread to start can use the baseline's build dir;
                 // others need to copy a new one
                 let build_dir_0 = build_dir_0.lock().expect("lock build dir 0").take(); // separate for lock
-                let build_dir = &if let Some(d) = build_dir_0 {
+                let build_dir = if let Some(d) = build_dir_0 {
                     d
                 } else {
-                    BuildDir::copy_from(workspace.root(), options, console)?
+                    let build_dir = BuildDir::for_job(workspace, options, console)?;
+                    lab.share_deps_into(&build_dir);
+                    build_dir
                 };
-                lab.run_queue(build_dir, timeouts, work_queue)
+                lab.run_queue(&build_dir, timeouts, work_queue)?;
+                // Free the disk space as soon as this worker is done, rather than waiting
+                // for all the others to finish.
+                if !options.leak_dirs {
+                    lab.disk_usage
+                        .lock()
+                        .expect("lock disk usage")
+                        .remove(build_dir.path());
+                }
+                debug!(build_dir = ?build_dir.path(), "worker finished; dropping build dir");
+                drop(build_dir);
+                Ok(())
             }));
         }
         join_threads(threads)
-    })?;
+    });
+    if threads_result.is_err() {
+        // Describe whatever was tested before the run was interrupted or failed.
+        lab.output_mutex
+            .lock()
+            .expect("lock output_dir")
+            .write_final_reports()?;
+    }
+    threads_result?;
 
-    let output_dir = lab
+    let peak_scratch_disk = lab.disk_usage.into_inner().expect("unlock disk usage").peak;
+    debug!(?peak_scratch_disk);
+    let mut output_dir = lab
         .output_mutex
         .into_inner()
         .expect("final unlock mutants queue");
+    output_dir.lab_outcome.peak_scratch_disk = peak_scratch_disk;
+    output_dir.lab_outcome.stopped_early = interrupt::take_stopped();
+    output_dir.write_final_reports()?;
     console.lab_finished(&output_dir.lab_outcome, start_time, options);
     let lab_outcome = output_dir.take_lab_outcome();
     if lab_outcome.total_mutants == 0 {
@@ -122,11 +162,192 @@ pub fn test_mutants(
         // the tree if no mutants are generated.
         warn!("No mutants were generated");
     } else if lab_outcome.unviable == lab_outcome.total_mutants {
-        warn!("No mutants were viable: perhaps there is a problem with building in a scratch directory. Look in mutants.out/log/* for more information.");
+        warn!("No mutants were viable, so nothing was tested: perhaps there is a problem with building in a scratch directory. Look in mutants.out/log/* for more information.");
     }
     Ok(lab_outcome)
 }
 
+/// The plan for testing mutants, reported by `--dry-run` and written to
+/// `mutants.out/plan.json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Plan {
+    /// The number of mutants that would be tested.
+    pub total_mutants: usize,
+    /// The number of mutants that would be tested in parallel.
+    pub jobs: usize,
+    /// The time taken by the baseline tests in the previous run, if it was recorded.
+    pub baseline_test_secs: Option<f64>,
+    /// The estimated time to test all the mutants: the baseline test time multiplied
+    /// by the number of mutants, divided by the number of jobs.
+    pub estimated_secs: Option<f64>,
+}
+
+impl Plan {
+    /// Describe the plan in a line of text.
+    pub fn summary_string(&self) -> String {
+        let estimate = match (self.estimated_secs, self.baseline_test_secs) {
+            (Some(estimated), Some(baseline)) => {
+                let whole_secs = |secs| {
+                    format_duration(Duration::from_secs(Duration::from_secs_f64(secs).as_secs()))
+                };
+                format!(
+                    ", estimated to take {} from a baseline test time of {}",
+                    whole_secs(estimated),
+                    whole_secs(baseline),
+                )
+            }
+            _ => "; no baseline test time was recorded, so the time can't be estimated".to_owned(),
+        };
+        format!(
+            "Plan: test {} mutant{} with {} job{}{estimate}\n",
+            self.total_mutants,
+            if self.total_mutants == 1 { "" } else { "s" },
+            self.jobs,
+            if self.jobs == 1 { "" } else { "s" },
+        )
+    }
+}
+
+/// Prepare to test mutants, and report the plan, without building or testing anything.
+///
+/// This copies the tree to a build directory and applies the first mutant, to check that
+/// the mutants can be applied, and then estimates the total time from the baseline test
+/// time recorded by the previous run in `output_dir`. The plan is written to
+/// `plan.json` in `output_dir`, without otherwise changing it.
+pub fn plan_mutants(
+    mut mutants: Vec<Mutant>,
+    workspace: &Workspace,
+    output_dir: &Utf8Path,
+    options: &Options,
+    console: &Console,
+) -> Result<Plan> {
+    let mutant_timings = load_mutant_timings(output_dir);
+    schedule_mutants(&mut mutants, options, |mutant| {
+        mutant_timings
+            .get(&mutant.id())
+            .map(|&secs| Duration::from_secs_f64(secs))
+    });
+    console.discovered_mutants(&mutants, options);
+    if let Some(first) = mutants.first() {
+        let build_dir = BuildDir::for_baseline(workspace, options, console)?;
+        first.apply(&build_dir, &first.mutated_code())?;
+        debug!(mutant = first.name(true), build_dir = ?build_dir.path(), "applied first mutant");
+        first.revert(&build_dir)?;
+    } else {
+        warn!("No mutants found under the active filters");
+    }
+    let jobs = n_threads(options, mutants.len());
+    let baseline_test_duration = load_baseline_test_duration(output_dir);
+    let estimated = baseline_test_duration.map(|baseline| {
+        baseline * u32::try_from(mutants.len()).unwrap_or(u32::MAX)
+            / u32::try_from(jobs).unwrap_or(u32::MAX)
+    });
+    let plan = Plan {
+        total_mutants: mutants.len(),
+        jobs,
+        baseline_test_secs: baseline_test_duration.map(|d| d.as_secs_f64()),
+        estimated_secs: estimated.map(|d| d.as_secs_f64()),
+    };
+    debug!(?plan);
+    write_plan(output_dir, &plan)?;
+    Ok(plan)
+}
+
+/// Put the mutants in the order they'll be tested, according to the options.
+fn schedule_mutants(
+    mutants: &mut [Mutant],
+    options: &Options,
+    duration: impl Fn(&Mutant) -> Option<Duration>,
+) {
+    if options.shuffle {
+        let seed = options.shuffle_seed;
+        info!("Shuffling mutants with seed {seed}; use --shuffle-seed={seed} to repeat this order");
+        fastrand::Rng::with_seed(seed).shuffle(mutants);
+    }
+    if options.fastest_first {
+        order_fastest_first(mutants, duration);
+    }
+}
+
+/// The number of threads, each with its own build directory, to test this many mutants.
+fn n_threads(options: &Options, n_mutants: usize) -> usize {
+    max(1, min(options.jobs.unwrap_or(1), n_mutants))
+}
+
+/// Start a jobserver to limit the total number of concurrent jobs in child processes.
+///
+/// If it can't be started, for example because the platform doesn't support it, the
+/// jobs run without a jobserver, as with `--jobserver=false`.
+fn start_jobserver(options: &Options) -> Option<jobserver::Client> {
+    let n_tasks = options.jobserver_tasks.unwrap_or_else(num_cpus::get);
+    debug!(n_tasks, "starting jobserver");
+    match jobserver::Client::new(n_tasks) {
+        Ok(client) => Some(client),
+        Err(err) => {
+            warn!("Failed to start jobserver, continuing without it: {err}");
+            None
+        }
+    }
+}
+
+/// Sort mutants so that those that were fastest to test in previous runs come first,
+/// so that results come in as quickly as possible.
+///
+/// Mutants with no previous timing are treated as taking the median time. The sort is
+/// stable, so mutants with the same time keep their existing order.
+fn order_fastest_first(mutants: &mut [Mutant], duration: impl Fn(&Mutant) -> Option<Duration>) {
+    let mut known = mutants.iter().filter_map(&duration).collect_vec();
+    if known.is_empty() {
+        return;
+    }
+    known.sort_unstable();
+    let median = known[known.len() / 2];
+    debug!(
+        n_known = known.len(),
+        ?median,
+        "ordering mutants fastest first"
+    );
+    mutants.sort_by_cached_key(|mutant| duration(mutant).unwrap_or(median));
+}
+
+/// Select all the packages containing any of the mutants, to test in the baseline.
+fn baseline_packages(mutants: &[Mutant]) -> PackageSelection {
+    PackageSelection::Explicit(
+        mutants
+            .iter()
+            .map(|m| Arc::clone(&m.source_file.package))
+            .sorted_by_key(|p| p.name.clone())
+            .unique()
+            .collect_vec(),
+    )
+}
+
+/// Choose the directory, relative to the build directory, in which to run cargo for the
+/// baseline and all the mutants.
+///
+/// When the baseline and every mutant test the same single package, cargo runs in that
+/// package's directory, so that it picks up any `.cargo/config.toml` there. Otherwise,
+/// everything runs in the workspace root, so that the baseline and the mutants always
+/// see the same cargo configuration.
+fn cargo_dir(mutants: &[Mutant], tests_for_mutant: &TestsForMutant) -> Option<Utf8PathBuf> {
+    let PackageSelection::Explicit(packages) = baseline_packages(mutants) else {
+        return None;
+    };
+    let [package] = packages.as_slice() else {
+        return None;
+    };
+    match tests_for_mutant {
+        TestsForMutant::Workspace => None,
+        TestsForMutant::Mutated => Some(package.relative_dir.clone()),
+        TestsForMutant::Explicit(test_packages) => match test_packages.as_slice() {
+            [test_package] if test_package.name == package.name => {
+                Some(package.relative_dir.clone())
+            }
+            _ => None,
+        },
+    }
+}
+
 #[mutants::skip] // it's a little hard to observe that the threads were collected?
 fn join_threads(threads: Vec<thread::ScopedJoinHandle<'_, Result<()>>>) -> Result<()> {
     // The errors potentially returned from `join` are a special `std::thread::Result`
@@ -140,6 +361,8 @@ fn join_threads(threads: Vec<thread::ScopedJoinHandle<'_, Result<()>>>) -> Resul
         .filter_map(|thread| match thread.join() {
             Err(panic) => resume_unwind(panic),
             Ok(Ok(())) => None,
+            // Jobs that were cancelled by `--fail-fast` are not an error.
+            Ok(Err(err)) if err.to_string() == interrupt::STOPPED_MESSAGE => None,
             Ok(Err(err)) => {
                 // To avoid console spam don't print "interrupted" errors for each thread,
                 // since that should have been printed by check_interrupted but do return them.
@@ -162,31 +385,151 @@ struct Lab<'a> {
     output_mutex: Mutex<OutputDir>,
     jobserver: Option<jobserver::Client>,
     tests_for_mutant: TestsForMutant,
+    /// The directory, relative to the build directory, where cargo runs.
+    cargo_dir: Option<Utf8PathBuf>,
+    disk_usage: Mutex<ScratchDiskUsage>,
+    shared_deps: Option<SharedDeps>,
     options: &'a Options,
     console: &'a Console,
 }
 
+/// Dependency artifacts shared from the baseline build directory, with `--share-deps`.
+struct SharedDeps {
+    /// The build directory whose `target` holds the dependencies.
+    source: Utf8PathBuf,
+    /// Names of crates in the workspace, whose artifacts are not shared.
+    local_crate_names: Vec<String>,
+    /// Held while building, so that only one build touches the shared artifacts at a time.
+    build_lock: Mutex<()>,
+}
+
 impl Lab<'_> {
-    /// Run the baseline scenario, which is the same as running `cargo test` on the unmutated
-    /// tree.
+    fn measure_disk_usage(&self, build_dir: &BuildDir) {
+        self.disk_usage
+            .lock()
+            .expect("lock disk usage")
+            .measure(build_dir);
+    }
+
+    /// Run the baseline, or reuse a matching one from the previous run, and return the
+    /// timeouts for testing mutants.
     ///
-    /// If it fails, return None, indicating that no further testing should be done.
+    /// Returns None if the baseline failed, indicating that no further testing should be done.
+    fn baseline_timeouts(
+        &self,
+        build_dir: &BuildDir,
+        mutants: &[Mutant],
+        workspace: &Workspace,
+        previous_baseline: Option<BaselineRecord>,
+    ) -> Result<Option<Timeouts>> {
+        let packages = baseline_packages(mutants);
+        let reuse_key = match self.options.baseline {
+            BaselineStrategy::Skip => return Ok(Some(Timeouts::without_baseline(self.options))),
+            BaselineStrategy::Run => None,
+            BaselineStrategy::Reuse => baseline_key(workspace.root(), &packages, self.options)
+                .inspect_err(|err| warn!("Can't reuse the baseline: {err:#}"))
+                .ok(),
+        };
+        if let Some(record) = reuse_key
+            .as_ref()
+            .and_then(|key| previous_baseline.filter(|previous| previous.key == *key))
+        {
+            info!("Reusing the baseline from the previous run, since the source, options, and toolchain are unchanged");
+            self.write_baseline_record(&record)?;
+            return Ok(Some(Timeouts::from_baseline_durations(
+                record.build_duration(),
+                record.test_duration(),
+                self.options,
+            )));
+        }
+        if reuse_key.is_some() {
+            info!("No matching baseline from the previous run, so running it now");
+        }
+        let outcome = self.run_baseline(build_dir, &packages)?;
+        self.measure_disk_usage(build_dir);
+        if !outcome.success() {
+            let phase = outcome.last_phase();
+            if let (Phase::Test, Some(test_command)) = (phase, &self.options.test_command) {
+                error!("Test command {test_command:?} failed in an unmutated tree, so no mutants were tested");
+            } else {
+                error!("cargo {phase} failed in an unmutated tree, so no mutants were tested");
+            }
+            return Ok(None);
+        }
+        if self.baseline_ran_no_tests(&outcome)? {
+            error!("No tests were run in the unmutated tree, so every mutant would be missed; no mutants were tested");
+            self.output_mutex
+                .lock()
+                .expect("lock output_dir")
+                .lab_outcome
+                .baseline_ran_no_tests = true;
+            return Ok(None);
+        }
+        if let Some(key) = reuse_key {
+            self.write_baseline_record(&BaselineRecord::from_outcome(key, &outcome))?;
+        }
+        Ok(Some(Timeouts::from_baseline(&outcome, self.options)))
+    }
+
+    /// True if `--fail-on-no-tests` is set, and the baseline ran `cargo test` and it
+    /// passed without running any tests.
     ///
-    /// If it succeeds, return the timeouts to be used for the other scenarios.
-    fn run_baseline(&self, build_dir: &BuildDir, mutants: &[Mutant]) -> Result<ScenarioOutcome> {
-        let all_mutated_packages: Vec<Arc<Package>> = mutants
-            .iter()
-            .map(|m| Arc::clone(&m.source_file.package))
-            .sorted_by_key(|p| p.name.clone())
-            .unique()
-            .collect_vec();
+    /// Output from nextest or a custom test command is not checked. The baseline only
+    /// tests the mutated packages, so this is also not checked if the mutants will be
+    /// tested against other packages.
+    fn baseline_ran_no_tests(&self, outcome: &ScenarioOutcome) -> Result<bool> {
+        if !self.options.fail_on_no_tests
+            || self.tests_for_mutant != TestsForMutant::Mutated
+            || self.options.test_tool != TestTool::Cargo
+            || self.options.test_command.is_some()
+            || !self.options.phases().contains(&Phase::Test)
+        {
+            return Ok(false);
+        }
+        Ok(no_tests_run(&outcome.get_log_content()?))
+    }
+
+    fn write_baseline_record(&self, record: &BaselineRecord) -> Result<()> {
+        self.output_mutex
+            .lock()
+            .expect("lock output_dir")
+            .write_baseline_record(record)
+    }
+
+    /// Run the baseline scenario, which is the same as running `cargo test` on the unmutated
+    /// tree.
+    fn run_baseline(
+        &self,
+        build_dir: &BuildDir,
+        packages: &PackageSelection,
+    ) -> Result<ScenarioOutcome> {
         self.make_worker(build_dir).run_one_scenario(
             &Scenario::Baseline,
-            &PackageSelection::Explicit(all_mutated_packages),
+            packages,
             Timeouts::for_baseline(self.options),
         )
     }
 
+    /// Link the dependencies built in the baseline into a new build directory, if
+    /// `--share-deps` is on.
+    ///
+    /// If this fails, the dependencies will just be built again in the new directory.
+    fn share_deps_into(&self, build_dir: &BuildDir) {
+        let Some(shared_deps) = &self.shared_deps else {
+            return;
+        };
+        // Don't walk the source while a build might be writing into it.
+        let _build_guard = shared_deps.build_lock.lock().expect("lock shared build");
+        if let Err(err) =
+            build_dir.link_dependencies_from(&shared_deps.source, &shared_deps.local_crate_names)
+        {
+            warn!(
+                "Failed to share dependencies into {:?}: {err:#}",
+                build_dir.path()
+            );
+        }
+    }
+
     /// Run until the input queue is empty.
     ///
     /// The queue, inside a mutex, can be consumed by multiple threads.
@@ -205,6 +548,9 @@ impl Lab<'_> {
             output_mutex: &self.output_mutex,
             jobserver: self.jobserver.as_ref(),
             tests_for_mutant: &self.tests_for_mutant,
+            cargo_dir: self.cargo_dir.as_deref(),
+            disk_usage: &self.disk_usage,
+            shared_deps: self.shared_deps.as_ref(),
             options: self.options,
             console: self.console,
         }
@@ -220,6 +566,9 @@ struct Worker<'a> {
     output_mutex: &'a Mutex<OutputDir>,
     jobserver: Option<&'a jobserver::Client>,
     tests_for_mutant: &'a TestsForMutant,
+    cargo_dir: Option<&'a Utf8Path>,
+    disk_usage: &'a Mutex<ScratchDiskUsage>,
+    shared_deps: Option<&'a SharedDeps>,
     options: &'a Options,
     console: &'a Console,
 }
@@ -232,10 +581,17 @@ impl Worker<'_> {
         timeouts: Timeouts,
     ) -> Result<()> {
         let _span = debug_span!("worker thread", build_dir = ?self.build_dir.path()).entered();
+        // Most of the growth of a build directory happens in the first build, so measure
+        // it then and at the end, rather than walking the directory after every mutant.
+        let mut first = true;
         loop {
             // Not a `for` statement so that we don't hold the lock
             // for the whole iteration.
+            if interrupt::is_stopped() {
+                return Ok(());
+            }
             let Some(mutant) = work_queue.lock().expect("Lock pending work queue").next() else {
+                self.measure_disk_usage();
                 return Ok(());
             };
             let _span = debug_span!("mutant", name = mutant.name(false)).entered();
@@ -246,10 +602,27 @@ impl Worker<'_> {
                 }
                 TestsForMutant::Explicit(packages) => PackageSelection::Explicit(packages.clone()),
             };
-            self.run_one_scenario(&Scenario::Mutant(mutant), &test_packages, timeouts)?;
+            let mutant_timeouts = timeouts.for_mutant(&mutant, self.options);
+            let outcome =
+                self.run_one_scenario(&Scenario::Mutant(mutant), &test_packages, mutant_timeouts)?;
+            if self.options.fail_fast && outcome.summary() == SummaryOutcome::MissedMutant {
+                info!("Stopping after the first missed mutant because of --fail-fast");
+                interrupt::stop();
+            }
+            if first {
+                self.measure_disk_usage();
+                first = false;
+            }
         }
     }
 
+    fn measure_disk_usage(&self) {
+        self.disk_usage
+            .lock()
+            .expect("lock disk usage")
+            .measure(self.build_dir);
+    }
+
     fn run_one_scenario(
         &mut self,
         scenario: &Scenario,
@@ -268,7 +641,7 @@ impl Worker<'_> {
 
         if let Some(mutant) = scenario.mutant() {
             let mutated_code = mutant.mutated_code();
-            let diff = scenario.mutant().unwrap().diff(&mutated_code);
+            let diff = self.options.mutant_diff(mutant, &mutated_code);
             scenario_output.write_diff(&diff)?;
             mutant.apply(self.build_dir, &mutated_code)?;
         }
@@ -276,30 +649,29 @@ impl Worker<'_> {
         let mut outcome = ScenarioOutcome::new(&scenario_output, scenario.clone());
         for &phase in self.options.phases() {
             self.console.scenario_phase_started(dir, phase);
-            let timeout = match phase {
-                Phase::Test => timeouts.test,
-                Phase::Build | Phase::Check => timeouts.build,
+            let phase_results = if phase == Phase::Test {
+                self.run_tests(test_packages, timeouts, &mut scenario_output)
+            } else {
+                self.run_phase(phase, test_packages, timeouts, &mut scenario_output)
+                    .map(|phase_result| vec![phase_result])
             };
-            match run_cargo(
-                self.build_dir,
-                self.jobserver,
-                test_packages,
-                phase,
-                timeout,
-                &mut scenario_output,
-                self.options,
-                self.console,
-            ) {
-                Ok(phase_result) => {
-                    let success = phase_result.is_success(); // so we can move it away
-                    outcome.add_phase_result(phase_result);
+            match phase_results {
+                Ok(phase_results) => {
+                    let success = phase_results.iter().all(PhaseResult::is_success);
+                    for phase_result in phase_results {
+                        outcome.add_phase_result(phase_result);
+                    }
                     self.console.scenario_phase_finished(dir, phase);
                     if !success {
                         break;
                     }
                 }
                 Err(err) => {
-                    error!(?err, ?phase, "scenario execution internal error");
+                    if interrupt::is_stopped() {
+                        debug!(?phase, "scenario cancelled by --fail-fast");
+                    } else {
+                        error!(?err, ?phase, "scenario execution internal error");
+                    }
                     // Some unexpected internal error that stops the program.
                     if let Some(mutant) = scenario.mutant() {
                         mutant.revert(self.build_dir)?;
@@ -308,9 +680,19 @@ impl Worker<'_> {
                 }
             }
         }
+        let retest = self.retest(
+            scenario,
+            &outcome,
+            test_packages,
+            timeouts,
+            &mut scenario_output,
+        );
         if let Some(mutant) = scenario.mutant() {
             mutant.revert(self.build_dir)?;
         }
+        for phase_result in retest? {
+            outcome.add_phase_result(phase_result);
+        }
         self.output_mutex
             .lock()
             .expect("lock output dir to add outcome")
@@ -321,6 +703,152 @@ impl Worker<'_> {
 
         Ok(outcome)
     }
+
+    /// Run one phase of a scenario, with its timeout.
+    fn run_phase(
+        &mut self,
+        phase: Phase,
+        test_packages: &PackageSelection,
+        timeouts: Timeouts,
+        scenario_output: &mut ScenarioOutput,
+    ) -> Result<PhaseResult> {
+        let timeout = match phase {
+            Phase::Test => timeouts.test,
+            Phase::Build | Phase::Check => timeouts.build,
+        };
+        // With shared dependencies, only one build runs at a time, while tests
+        // can still run in parallel.
+        let _build_guard = match (phase, self.shared_deps) {
+            (Phase::Build | Phase::Check, Some(shared_deps)) => {
+                Some(shared_deps.build_lock.lock().expect("lock shared build"))
+            }
+            _ => None,
+        };
+        run_cargo(
+            self.build_dir,
+            self.cargo_dir,
+            self.jobserver,
+            test_packages,
+            phase,
+            timeout,
+            scenario_output,
+            self.options,
+            self.console,
+        )
+    }
+
+    /// Run the tests, followed by the doctests if `--doctests` is set and the
+    /// tests passed.
+    ///
+    /// The doctests are reported as a second result from the test phase.
+    fn run_tests(
+        &mut self,
+        test_packages: &PackageSelection,
+        timeouts: Timeouts,
+        scenario_output: &mut ScenarioOutput,
+    ) -> Result<Vec<PhaseResult>> {
+        let phase_result = self.run_phase(Phase::Test, test_packages, timeouts, scenario_output)?;
+        if !phase_result.is_success()
+            || !self.options.doctests
+            || self.options.test_command.is_some()
+        {
+            return Ok(vec![phase_result]);
+        }
+        let doctest_result = run_doctests(
+            self.build_dir,
+            self.cargo_dir,
+            self.jobserver,
+            test_packages,
+            timeouts.test,
+            scenario_output,
+            self.options,
+            self.console,
+        )?;
+        Ok(vec![phase_result, doctest_result])
+    }
+
+    /// Run the tests again, to detect flaky tests, if the options ask for it.
+    ///
+    /// With `--retries`, a passing baseline is tested again that many times, and a warning
+    /// is given if any of them fail. The baseline outcome is unchanged.
+    ///
+    /// With `--confirm-caught`, a caught mutant is tested once more, and the results are
+    /// returned to be added to the outcome, so that the mutant counts as missed if the
+    /// tests pass the second time.
+    fn retest(
+        &mut self,
+        scenario: &Scenario,
+        outcome: &ScenarioOutcome,
+        test_packages: &PackageSelection,
+        timeouts: Timeouts,
+        scenario_output: &mut ScenarioOutput,
+    ) -> Result<Vec<PhaseResult>> {
+        if !self.options.phases().contains(&Phase::Test) {
+            return Ok(Vec::new());
+        }
+        match outcome.summary() {
+            SummaryOutcome::Success if !scenario.is_mutant() => {
+                let retries = self.options.baseline_retries;
+                for i in 1..=retries {
+                    scenario_output.message(&format!("retry {i} of {retries}"))?;
+                    let phase_results = self.run_tests(test_packages, timeouts, scenario_output)?;
+                    if !phase_results.iter().all(PhaseResult::is_success) {
+                        warn!(
+                            "Baseline tests passed at first but failed on retry {i} of {retries}: \
+                            the tests may be flaky, which can make mutants wrongly appear caught"
+                        );
+                        break;
+                    }
+                }
+                Ok(Vec::new())
+            }
+            SummaryOutcome::CaughtMutant if self.options.confirm_caught => {
+                scenario_output.message("test again to confirm the mutant is caught")?;
+                let phase_results = self.run_tests(test_packages, timeouts, scenario_output)?;
+                if phase_results.iter().all(PhaseResult::is_success) {
+                    warn!(
+                        "{} was caught at first but missed when tested again: the tests may be flaky",
+                        scenario
+                    );
+                }
+                Ok(phase_results)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Tracks the disk space used by scratch build directories, to report the peak.
+///
+/// Sizes are measured only occasionally, so this is an estimate.
+#[derive(Debug, Default)]
+struct ScratchDiskUsage {
+    /// The most recently measured size of each live build directory.
+    current: HashMap<Utf8PathBuf, u64>,
+    /// The largest total size seen so far, or None if no scratch directories were measured.
+    peak: Option<u64>,
+}
+
+impl ScratchDiskUsage {
+    /// Measure the size of a build directory, and update the peak total.
+    fn measure(&mut self, build_dir: &BuildDir) {
+        match build_dir.scratch_disk_usage() {
+            Ok(Some(bytes)) => self.update(build_dir.path(), bytes),
+            Ok(None) => {}
+            Err(err) => warn!(?err, "Failed to measure disk usage of build directory"),
+        }
+    }
+
+    fn update(&mut self, path: &Utf8Path, bytes: u64) {
+        self.current.insert(path.to_owned(), bytes);
+        let total = self.current.values().sum();
+        self.peak = Some(self.peak.map_or(total, |peak| max(peak, total)));
+    }
+
+    /// Stop counting a build directory that has been deleted.
+    fn remove(&mut self, path: &Utf8Path) {
+        self.current.remove(path);
+    }
 }
 
 /// Which packages to test
@@ -345,3 +873,110 @@ impl TestsForMutant {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use camino::{Utf8Path, Utf8PathBuf};
+    use indoc::indoc;
+    use itertools::Itertools;
+
+    use super::{cargo_dir, order_fastest_first, ScratchDiskUsage, TestsForMutant};
+    use crate::package::Package;
+    use crate::visit::mutate_source_str;
+    use crate::{Mutant, Options};
+
+    #[test]
+    fn cargo_dir_is_the_package_dir_only_when_every_scenario_tests_that_package() {
+        let mutants = mutate_source_str("fn a() -> u32 { 7 }", &Options::default()).unwrap();
+        let package = Arc::clone(&mutants[0].source_file.package);
+        assert_eq!(
+            cargo_dir(&mutants, &TestsForMutant::Mutated),
+            Some(package.relative_dir.clone())
+        );
+        assert_eq!(
+            cargo_dir(
+                &mutants,
+                &TestsForMutant::Explicit(vec![Arc::clone(&package)])
+            ),
+            Some(package.relative_dir.clone())
+        );
+        assert_eq!(cargo_dir(&mutants, &TestsForMutant::Workspace), None);
+        let other = Arc::new(Package {
+            name: "other".to_owned(),
+            version: "0.1.0".to_owned(),
+            relative_dir: Utf8PathBuf::from("other"),
+            top_sources: vec![Utf8PathBuf::from("other/src/lib.rs")],
+        });
+        assert_eq!(
+            cargo_dir(&mutants, &TestsForMutant::Explicit(vec![other.clone()])),
+            None
+        );
+        assert_eq!(
+            cargo_dir(&mutants, &TestsForMutant::Explicit(vec![package, other])),
+            None
+        );
+        assert_eq!(cargo_dir(&[], &TestsForMutant::Mutated), None);
+    }
+
+    #[test]
+    fn scratch_disk_usage_tracks_peak_of_live_dirs() {
+        let mut usage = ScratchDiskUsage::default();
+        assert_eq!(usage.peak, None);
+        usage.update(Utf8Path::new("/tmp/a"), 100);
+        usage.update(Utf8Path::new("/tmp/b"), 50);
+        assert_eq!(usage.peak, Some(150));
+        usage.update(Utf8Path::new("/tmp/a"), 300);
+        assert_eq!(usage.peak, Some(350));
+        usage.remove(Utf8Path::new("/tmp/a"));
+        usage.update(Utf8Path::new("/tmp/b"), 200);
+        assert_eq!(usage.peak, Some(350), "peak is not reduced");
+        usage.update(Utf8Path::new("/tmp/c"), 200);
+        assert_eq!(usage.peak, Some(400));
+    }
+
+    #[test]
+    fn order_mutants_fastest_first_with_unknown_at_median() {
+        let code = indoc! {"
+            fn a() -> u32 { 7 }
+            fn b() -> bool { true }
+        "};
+        let mut mutants = mutate_source_str(code, &Options::default()).unwrap();
+        let names = mutants.iter().map(Mutant::describe_change).collect_vec();
+        assert_eq!(
+            names,
+            [
+                "replace a -> u32 with 0",
+                "replace a -> u32 with 1",
+                "replace b -> bool with false",
+            ]
+        );
+        let secs = |name: &str| match name {
+            "replace a -> u32 with 0" => Some(30),
+            "replace a -> u32 with 1" => None,
+            "replace b -> bool with false" => Some(10),
+            _ => unreachable!(),
+        };
+        order_fastest_first(&mut mutants, |m| {
+            secs(&m.describe_change()).map(Duration::from_secs)
+        });
+        assert_eq!(
+            mutants.iter().map(Mutant::describe_change).collect_vec(),
+            [
+                "replace b -> bool with false",
+                "replace a -> u32 with 0",
+                "replace a -> u32 with 1",
+            ],
+            "the unknown mutant is treated as the median, and keeps its place after a tie"
+        );
+
+        order_fastest_first(&mut mutants, |_| None);
+        assert_eq!(
+            mutants[0].describe_change(),
+            "replace b -> bool with false",
+            "order is unchanged with no timings"
+        );
+    }
+}
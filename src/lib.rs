@@ -0,0 +1,1153 @@
+// Copyright 2021-2024 Martin Pool
+
+//! `cargo-mutants`: Find test gaps by inserting bugs.
+//!
+//! See <https://mutants.rs> for the manual and more information.
+//!
+//! Most people will use the `cargo mutants` command, but this library also exposes
+//! the discovery of mutants, so that other tools can list them without running a
+//! separate process and parsing its output:
+//!
+//! ```no_run
+//! use cargo_mutants::{discover, Options, PackageFilter, Workspace};
+//!
+//! let workspace = Workspace::open(".")?;
+//! let discovered = discover(&workspace, &PackageFilter::All, &Options::default())?;
+//! for mutant in &discovered.mutants {
+//!     println!("{}", mutant.name(true));
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! Only discovery is part of the library API for now: testing mutants is only available
+//! through the command line.
+
+#![warn(clippy::pedantic)]
+#![allow(clippy::module_name_repetitions, clippy::needless_raw_string_hashes)]
+
+mod baseline;
+mod build_dir;
+mod cargo;
+mod cfg;
+mod config;
+mod console;
+mod copy_tree;
+mod discovery_cache;
+mod exit_code;
+mod fnvalue;
+mod glob;
+mod in_diff;
+mod interrupt;
+mod lab;
+mod list;
+mod manifest;
+mod mutant;
+mod options;
+mod outcome;
+mod output;
+mod package;
+mod path;
+mod pretty;
+mod process;
+mod report;
+mod sample;
+mod sarif;
+mod scenario;
+mod shard;
+mod source;
+mod span;
+mod tail_file;
+#[cfg(test)]
+#[path = "../tests/util/mod.rs"]
+mod test_util;
+mod timeouts;
+#[cfg(feature = "tui")]
+mod tui;
+mod visit;
+mod watch;
+mod workspace;
+
+use std::env;
+use std::ffi::OsString;
+use std::fs::{read_to_string, write};
+use std::io;
+use std::process::exit;
+
+use anyhow::{anyhow, ensure, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::builder::styling::{self};
+use clap::builder::Styles;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use clap_complete::{generate, Shell};
+use color_print::cstr;
+use console::enable_console_colors;
+use output::{
+    load_missed_from_outcomes, load_previously_caught, write_output_archive, OutputDir, OUTDIR_NAME,
+};
+use tracing::{debug, info, warn};
+
+use crate::build_dir::BuildDir;
+use crate::console::Console;
+use crate::discovery_cache::DiscoveryCache;
+use crate::in_diff::{diff_changed_paths, diff_filter};
+use crate::interrupt::check_interrupted;
+use crate::lab::{plan_mutants, test_mutants};
+use crate::list::{list_files, list_mutants, list_skipped, mutant_json};
+pub use crate::mutant::{Genre, Mutant};
+pub use crate::options::Options;
+use crate::options::{Colors, ListFormat, OutputFormat, TestTool};
+pub use crate::outcome::{Phase, ScenarioOutcome};
+use crate::sample::sample;
+use crate::scenario::Scenario;
+use crate::shard::Shard;
+pub use crate::visit::Discovered;
+use crate::watch::watch;
+pub use crate::workspace::{PackageFilter, Workspace};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const NAME: &str = env!("CARGO_PKG_NAME");
+
+/// A comment marker inserted next to changes, so they can be easily found.
+static MUTATION_MARKER_COMMENT: &str = "/* ~ changed by cargo-mutants ~ */";
+
+static SPONSOR_MESSAGE: &str = cstr!("<magenta><bold>Support and accelerate cargo-mutants at <<https://github.com/sponsors/sourcefrog>></></>");
+
+#[mutants::skip] // only visual effects, not worth testing
+fn clap_styles() -> Styles {
+    styling::Styles::styled()
+        .header(styling::AnsiColor::Green.on_default() | styling::Effects::BOLD)
+        .usage(styling::AnsiColor::Green.on_default() | styling::Effects::BOLD)
+        .literal(styling::AnsiColor::Blue.on_default() | styling::Effects::BOLD)
+        .placeholder(styling::AnsiColor::Cyan.on_default())
+}
+
+#[derive(Parser)]
+#[command(name = "cargo", bin_name = "cargo", styles(clap_styles()))]
+enum Cargo {
+    #[command(name = "mutants", styles(clap_styles()))]
+    Mutants(Args),
+}
+
+#[derive(Debug, Default, ValueEnum, Clone, Copy, Eq, PartialEq)]
+pub enum BaselineStrategy {
+    /// Run tests in an unmutated tree before testing mutants.
+    #[default]
+    Run,
+
+    /// Don't run tests in an unmutated tree: assume that they pass.
+    Skip,
+
+    /// Reuse a successful baseline from the previous run, if the source tree, options,
+    /// and toolchain are unchanged; otherwise run it.
+    Reuse,
+}
+
+/// Find inadequately-tested code that can be removed without any tests failing.
+///
+/// See <https://mutants.rs/> for more information.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Parser, PartialEq, Debug)]
+#[command(
+    author,
+    about,
+    after_help = SPONSOR_MESSAGE,
+)]
+pub(crate) struct Args {
+    /// Generate mutants in code for all platforms, including code under `#[cfg]`
+    /// attributes for other operating systems, such as `#[cfg(windows)]` on Unix.
+    #[arg(long, help_heading = "Generate")]
+    all_platforms: bool,
+
+    /// Show cargo output for all invocations (very verbose).
+    #[arg(long, help_heading = "Output")]
+    all_logs: bool,
+
+    /// Baseline strategy: check that tests pass in an unmutated tree before testing mutants.
+    #[arg(long, value_enum, default_value_t = BaselineStrategy::Run, help_heading = "Execution")]
+    baseline: BaselineStrategy,
+
+    /// Copy the contents of this existing cargo target directory into each build directory,
+    /// so that the baseline build can reuse its artifacts.
+    ///
+    /// The given directory is only read, never written.
+    #[arg(long, help_heading = "Copying", conflicts_with = "in_place")]
+    baseline_from: Option<Utf8PathBuf>,
+
+    /// Turn off all rustc lints, so that denied warnings won't make mutants unviable.
+    #[arg(long, action = ArgAction::Set, help_heading = "Build")]
+    cap_lints: Option<bool>,
+
+    /// Print mutants that were caught by tests.
+    #[arg(long, short = 'v', help_heading = "Output")]
+    caught: bool,
+
+    /// Cargo check generated mutants, but don't run tests.
+    #[arg(long, help_heading = "Execution")]
+    check: bool,
+
+    /// Draw colors in output.
+    #[arg(
+        long,
+        value_enum,
+        help_heading = "Output",
+        default_value_t,
+        env = "CARGO_TERM_COLOR"
+    )]
+    colors: Colors,
+
+    /// Compare missed mutants to those in an `outcomes.json` from a previous run.
+    #[arg(long, help_heading = "Output")]
+    compare_to: Option<Utf8PathBuf>,
+
+    /// Test each caught mutant a second time, and count it as missed if the tests
+    /// pass then, to avoid crediting flaky test failures.
+    #[arg(long, help_heading = "Execution")]
+    confirm_caught: bool,
+
+    /// Copy the built dependencies from the source tree's `target` directory to the
+    /// build directories, so that they don't need to be built again.
+    ///
+    /// The artifacts of crates in the workspace are not copied, since they'll be rebuilt
+    /// for every mutant anyway.
+    #[arg(long, help_heading = "Copying", conflicts_with = "baseline_from")]
+    copy_target_deps: Option<bool>,
+
+    /// Copy `.git` and other VCS directories to the build directory.
+    ///
+    /// This is useful if you have tests that depend on the presence of these directories.
+    ///
+    /// Known VCS directories are
+    /// `.git`, `.hg`, `.bzr`, `.svn`, `_darcs`, `.pijul`.
+    #[arg(long, help_heading = "Copying", visible_alias = "copy_git")]
+    copy_vcs: Option<bool>,
+
+    /// Show the mutation diffs.
+    #[arg(long, help_heading = "Filters")]
+    diff: bool,
+
+    /// Show this many lines of context around the change in mutation diffs, in `--diff`
+    /// output and in `mutants.out`; by default 8.
+    #[arg(long, help_heading = "Output")]
+    diff_context: Option<usize>,
+
+    /// Rust crate directory to examine.
+    #[arg(
+        long,
+        short = 'd',
+        conflicts_with = "manifest_path",
+        help_heading = "Input"
+    )]
+    dir: Option<Utf8PathBuf>,
+
+    /// Also run the doctests, with `cargo test --doc`, after the other tests pass.
+    #[arg(long, help_heading = "Tests")]
+    doctests: bool,
+
+    /// Copy the tree and apply the first mutant, then report how many mutants would be
+    /// tested and an estimate of the time, without building or testing them.
+    #[arg(
+        long,
+        conflicts_with_all = ["list", "list_files", "check"],
+        help_heading = "Execution"
+    )]
+    dry_run: bool,
+
+    /// Generate autocompletions for the given shell.
+    #[arg(long)]
+    completions: Option<Shell>,
+
+    /// Return this error values from functions returning Result:
+    /// for example, `::anyhow::anyhow!("mutated")`.
+    #[arg(long, help_heading = "Generate")]
+    error: Vec<String>,
+
+    /// Regex for mutations to examine, matched against the names shown by `--list`.
+    #[arg(
+        long = "re",
+        short = 'F',
+        alias = "regex",
+        alias = "examine-regex",
+        alias = "examine-re",
+        help_heading = "Filters"
+    )]
+    examine_re: Vec<String>,
+
+    /// Regex for mutations to examine, matched against the stable mutant ids in `mutants.json`.
+    #[arg(long, help_heading = "Filters")]
+    re_id: Vec<String>,
+
+    /// Glob for files to exclude; with no glob, all files are included; globs containing
+    /// slash match the entire path. If used together with `--file` argument, then the files to be examined are matched before the files to be excluded.
+    #[arg(long, short = 'e', help_heading = "Filters")]
+    exclude: Vec<String>,
+
+    /// Read globs for files to exclude from this file, one per line, in addition to any `--exclude` globs.
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    #[arg(long, help_heading = "Filters")]
+    exclude_from: Vec<Utf8PathBuf>,
+
+    /// Regex for mutations to exclude, matched against the names shown by `--list`.
+    #[arg(long, short = 'E', alias = "exclude-regex", help_heading = "Filters")]
+    exclude_re: Vec<String>,
+
+    /// Regex for mutations to exclude, matched against the stable mutant ids in `mutants.json`.
+    #[arg(long, help_heading = "Filters")]
+    exclude_re_id: Vec<String>,
+
+    /// Don't generate mutants of these genres, like `binary-operator`.
+    ///
+    /// The list may contain comma-separated names and may be repeated.
+    #[arg(long, value_delimiter = ',', help_heading = "Generate")]
+    exclude_genre: Vec<String>,
+
+    /// With --list, show functions and other items that generated no mutants, and why.
+    #[arg(long, requires = "list", help_heading = "Execution")]
+    explain_skips: bool,
+
+    /// Stop testing as soon as one mutant is missed.
+    #[arg(long, conflicts_with = "list", help_heading = "Execution")]
+    fail_fast: bool,
+
+    /// Exit with an error only if some missed mutants were not missed in the `--compare-to` report.
+    #[arg(long, requires = "compare_to", help_heading = "Output")]
+    fail_on_new: bool,
+
+    /// Stop with exit code 5, without testing any mutants, if the baseline `cargo test` runs no tests.
+    #[arg(long, action = ArgAction::Set, help_heading = "Execution")]
+    fail_on_no_tests: Option<bool>,
+
+    /// Glob for files to examine; with no glob, all files are examined; globs containing
+    /// slash match the entire path. If used together with `--exclude` argument, then the files to be examined are matched before the files to be excluded.
+    #[arg(long, short = 'f', help_heading = "Filters")]
+    file: Vec<String>,
+
+    /// Read globs for files to examine from this file, one per line, in addition to any `--file` globs.
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    #[arg(long, help_heading = "Filters")]
+    files_from: Vec<Utf8PathBuf>,
+
+    /// Test only mutants in functions with this exact name, optionally qualified by
+    /// module or type, like `parse` or `Config::parse`.
+    #[arg(long, help_heading = "Filters")]
+    function: Vec<String>,
+
+    /// Generate only mutants of these genres; by default all genres except `literal`, `statement`, `match-arm`, and `try-operator` are generated.
+    ///
+    /// The list may contain comma-separated names and may be repeated.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        alias = "only-genre",
+        help_heading = "Generate"
+    )]
+    genre: Vec<Genre>,
+
+    /// Don't copy files matching gitignore patterns.
+    #[arg(long, action = ArgAction::Set, default_value = "true", help_heading = "Copying", group = "copy_opts")]
+    gitignore: bool,
+
+    /// Test mutations in the source tree, rather than in a copy.
+    #[arg(
+        long,
+        help_heading = "Copying",
+        conflicts_with = "jobs",
+        conflicts_with = "copy_opts"
+    )]
+    in_place: bool,
+
+    /// Skip mutants that were caught in previous runs.
+    #[arg(long, help_heading = "Filters")]
+    iterate: bool,
+
+    /// Run this many cargo build/test jobs in parallel.
+    #[arg(
+        long,
+        short = 'j',
+        env = "CARGO_MUTANTS_JOBS",
+        help_heading = "Execution"
+    )]
+    jobs: Option<usize>,
+
+    /// Pass `--jobs` to each cargo build, to limit the parallelism within it; by default
+    /// cargo uses the number of CPUs.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), help_heading = "Execution")]
+    build_jobs: Option<u32>,
+
+    /// Use a GNU Jobserver to cap concurrency between child processes.
+    #[arg(long, action = ArgAction::Set, help_heading = "Execution", default_value_t = true)]
+    jobserver: bool,
+
+    /// Allow this many jobserver tasks in parallel, across all child processes.
+    ///
+    /// By default, NCPUS.
+    #[arg(long, help_heading = "Execution")]
+    jobserver_tasks: Option<usize>,
+
+    /// Output json: for --list, or the final summary with --summary-only.
+    #[arg(long, help_heading = "Output")]
+    json: bool,
+
+    /// Format for --list and --list-files output.
+    #[arg(long, value_enum, conflicts_with = "json", help_heading = "Output")]
+    format: Option<ListFormat>,
+
+    /// Don't delete the scratch directories, for debugging.
+    #[arg(long, help_heading = "Debug")]
+    leak_dirs: bool,
+
+    /// Log level for stdout (trace, debug, info, warn, error).
+    #[arg(
+        long,
+        short = 'L',
+        default_value = "info",
+        env = "CARGO_MUTANTS_TRACE_LEVEL",
+        help_heading = "Debug"
+    )]
+    level: tracing::Level,
+
+    /// Just list possible mutants, don't run them.
+    #[arg(long, help_heading = "Execution")]
+    list: bool,
+
+    /// List source files, don't run anything.
+    #[arg(long, help_heading = "Execution")]
+    list_files: bool,
+
+    /// Test the mutants listed in this JSON file, written by `--emit-mutants`, rather than discovering them.
+    #[arg(
+        long,
+        conflicts_with_all = ["list_files", "explain_skips", "iterate", "watch"],
+        help_heading = "Execution"
+    )]
+    mutants_from: Option<Utf8PathBuf>,
+
+    /// Keep running, and test mutants in code that changes whenever source files are saved.
+    #[arg(
+        long,
+        conflicts_with_all = ["list", "list_files", "in_diff", "iterate"],
+        help_heading = "Execution"
+    )]
+    watch: bool,
+
+    /// Browse mutants interactively, and test selected mutants.
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with = "list", help_heading = "Execution")]
+    tui: bool,
+
+    /// Path to Cargo.toml for the package to mutate.
+    #[arg(long, help_heading = "Input")]
+    manifest_path: Option<Utf8PathBuf>,
+
+    /// Don't generate any genres of mutants by default: only the genres named by `--genre` are generated.
+    #[arg(long, help_heading = "Generate")]
+    no_default_mutations: bool,
+
+    /// Don't read .cargo/mutants.toml or package.metadata.mutants.
+    #[arg(long, help_heading = "Input")]
+    no_config: bool,
+
+    /// Don't copy the /target directory, and don't build the source tree first.
+    #[arg(long, help_heading = "Copying", group = "copy_opts")]
+    no_copy_target: bool,
+
+    /// Don't show the sponsorship message in help output.
+    #[arg(long, env = "CARGO_MUTANTS_NO_SPONSOR", help_heading = "Output")]
+    no_sponsor: bool,
+
+    /// Don't print times or tree sizes, to make output deterministic.
+    #[arg(long, help_heading = "Output")]
+    no_times: bool,
+
+    /// Include line & column numbers in the mutation list.
+    #[arg(long, action = ArgAction::Set, default_value = "true", help_heading = "Output")]
+    line_col: bool,
+
+    /// Write the discovered mutants as JSON to this file, and exit without testing them.
+    #[arg(long, conflicts_with_all = ["list", "list_files"], help_heading = "Output")]
+    emit_mutants: Option<Utf8PathBuf>,
+
+    /// Write the contents of mutants.out into this zip file, instead of a directory.
+    #[arg(long, help_heading = "Output")]
+    output_archive: Option<Utf8PathBuf>,
+
+    /// Only generate mutants in public functions and methods: those marked `pub`, or in
+    /// trait impls or public traits.
+    #[arg(long, help_heading = "Filters")]
+    only_public: bool,
+
+    /// Create mutants.out within this directory.
+    #[arg(
+        long,
+        short = 'o',
+        env = "CARGO_MUTANTS_OUTPUT",
+        help_heading = "Output"
+    )]
+    output: Option<Utf8PathBuf>,
+
+    /// Format for results while testing mutants: with `json-lines`, each outcome is
+    /// written to stdout as a line of JSON as soon as it's known, and other messages go
+    /// to stderr.
+    #[arg(long, value_enum, conflicts_with = "list", help_heading = "Output")]
+    output_format: Option<OutputFormat>,
+
+    /// While testing, print only the final summary of outcomes rather than a line for each
+    /// mutant; with `--json`, print the summary as a JSON object.
+    #[arg(
+        long,
+        conflicts_with_all = ["list", "list_files", "output_format"],
+        help_heading = "Output"
+    )]
+    summary_only: bool,
+
+    /// After testing, list the missed mutants grouped by function, with the number
+    /// missed out of the number tested in each function.
+    #[arg(long, help_heading = "Output")]
+    missed_by_function: bool,
+
+    /// Include only mutants in code touched by this diff.
+    #[arg(long, short = 'D', help_heading = "Filters")]
+    in_diff: Option<Utf8PathBuf>,
+
+    /// With --in-diff, only discover mutants in the packages containing changed files.
+    #[arg(
+        long,
+        requires = "in_diff",
+        conflicts_with_all = ["package", "workspace"],
+        help_heading = "Filters"
+    )]
+    include_only_changed_packages: bool,
+
+    /// Maximum timeout for builds and tests, in seconds, as an upper bound on auto-set times.
+    #[arg(long, help_heading = "Execution")]
+    max_timeout: Option<f64>,
+
+    /// Minimum timeout for tests, in seconds, as a lower bound on the auto-set time.
+    #[arg(
+        long,
+        env = "CARGO_MUTANTS_MINIMUM_TEST_TIMEOUT",
+        help_heading = "Execution"
+    )]
+    minimum_test_timeout: Option<f64>,
+
+    /// With `--test-tool=nextest`, build a nextest archive of the test binaries for
+    /// each scenario and run the tests from it, without asking cargo to check the build
+    /// again.
+    #[arg(long, help_heading = "Execution")]
+    nextest_archive: bool,
+
+    /// Only test mutants from these packages.
+    #[arg(id = "package", long, short = 'p', help_heading = "Filters")]
+    mutate_packages: Vec<String>,
+
+    /// Run mutants in random order, without putting those that were fastest in previous runs first.
+    #[arg(long, help_heading = "Execution")]
+    shuffle: bool,
+
+    /// Run mutants in the fixed order they occur in the source tree.
+    #[arg(long, help_heading = "Execution")]
+    no_shuffle: bool,
+
+    /// Seed the random order of mutants, and the random sample from `--max-mutants`, to reproduce a previous run; implies `--shuffle`.
+    #[arg(long, conflicts_with = "no_shuffle", help_heading = "Execution")]
+    shuffle_seed: Option<u64>,
+
+    /// In `--list` text output, show the signature of the mutated function and the
+    /// lines around the mutation.
+    #[arg(long, help_heading = "Output")]
+    print_mutant_context: bool,
+
+    /// Build with this cargo profile.
+    #[arg(long, help_heading = "Build")]
+    profile: Option<String>,
+
+    /// Build and test for this target triple, passed to cargo as `--target`.
+    ///
+    /// Running the tests needs a runner configured for the target in `.cargo/config.toml`,
+    /// unless the host can run its binaries.
+    #[arg(long, help_heading = "Build")]
+    target: Option<String>,
+
+    /// Keep flags from RUSTFLAGS and RUSTDOCFLAGS that deny warnings, rather than removing them.
+    #[arg(long, help_heading = "Build")]
+    respect_rustflags: bool,
+
+    /// Run the baseline tests this many more times, and warn if the results differ,
+    /// to detect flaky tests.
+    #[arg(long, default_value_t = 0, help_heading = "Execution")]
+    retries: usize,
+
+    /// Additional flags to pass to rustc in all cargo invocations, separated by spaces like
+    /// `RUSTFLAGS`; added after any inherited `RUSTFLAGS`.
+    #[arg(long, allow_hyphen_values = true, help_heading = "Build")]
+    rustflags: Vec<String>,
+
+    /// Test at most this many mutants, sampled from across the whole tree after all other filters.
+    ///
+    /// The sample is random, unless `--no-shuffle` is given, in which case mutants are
+    /// taken at an even stride through the list.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..), help_heading = "Execution")]
+    max_mutants: Option<u64>,
+
+    /// Run only one shard of all generated mutants: specify as e.g. 1/4.
+    #[arg(long, help_heading = "Execution")]
+    shard: Option<Shard>,
+
+    /// With --shard, keep all the mutants from each source file in the same shard.
+    ///
+    /// This gives better reuse of incremental builds within each shard, although the
+    /// shards may be less evenly sized.
+    #[arg(long, requires = "shard", help_heading = "Execution")]
+    shard_by_file: bool,
+
+    /// Share built dependencies between parallel build directories.
+    ///
+    /// Each new build directory hard-links the dependency artifacts from the baseline
+    /// build, and only one build runs at a time, to save disk space.
+    #[arg(long, help_heading = "Execution")]
+    share_deps: bool,
+
+    /// Skip calls to functions and methods named in this list.
+    ///
+    /// The list may contain comma-separated names and may be repeated.
+    ///
+    /// If a qualified path is given in the source then this matches only the final component,
+    /// and it ignores type parameters.
+    ///
+    /// This value is combined with the names from the config `skip_calls` key.
+    #[arg(long, help_heading = "Filters")]
+    skip_calls: Vec<String>,
+
+    /// Use built-in defaults for `skip_calls`, in addition to any explicit values.
+    ///
+    /// The default is `with_capacity`.
+    #[arg(long)]
+    skip_calls_defaults: Option<bool>,
+
+    /// Don't replace the body of functions returning `()` when it's a single expression,
+    /// since those mutants are often near no-ops.
+    #[arg(long, help_heading = "Generate")]
+    skip_unit_returns: bool,
+
+    /// Don't replace the body of a binary's `fn main` with `()`, though other mutants
+    /// inside it are still generated.
+    #[arg(long, help_heading = "Generate")]
+    skip_main: bool,
+
+    /// Run this shell command to test each mutant, instead of `cargo test`.
+    ///
+    /// The command runs in the build directory with the same environment as cargo, and
+    /// the mutant is caught if it exits with a non-zero status.
+    #[arg(long, help_heading = "Tests")]
+    test_command: Option<String>,
+
+    /// Run tests from these packages for all mutants.
+    #[arg(long, help_heading = "Tests")]
+    test_package: Vec<String>,
+
+    /// Tool used to run test suites: cargo or nextest.
+    #[arg(long, help_heading = "Execution")]
+    test_tool: Option<TestTool>,
+
+    /// Run all tests in the workspace.
+    ///
+    /// If false, only the tests in the mutated package are run.
+    ///
+    /// Overrides `--test_package`.
+    #[arg(long, help_heading = "Tests")]
+    test_workspace: Option<bool>,
+
+    /// Maximum run time for all cargo commands, in seconds.
+    #[arg(long, short = 't', help_heading = "Execution")]
+    timeout: Option<f64>,
+
+    /// Test timeout multiplier (relative to base test time).
+    #[arg(long, help_heading = "Execution", conflicts_with = "timeout")]
+    timeout_multiplier: Option<f64>,
+
+    /// Maximum run time for cargo build command, in seconds.
+    #[arg(long, help_heading = "Execution")]
+    build_timeout: Option<f64>,
+
+    /// Build timeout multiplier (relative to base build time).
+    #[arg(long, help_heading = "Execution", conflicts_with = "build_timeout")]
+    build_timeout_multiplier: Option<f64>,
+
+    /// Print mutations that failed to check or build.
+    #[arg(long, short = 'V', help_heading = "Output")]
+    unviable: bool,
+
+    /// Show version and quit.
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    version: bool,
+
+    /// Generate mutations in every package in the workspace.
+    #[arg(long, help_heading = "Filters")]
+    workspace: bool,
+
+    /// Additional args for all cargo invocations.
+    #[arg(
+        long,
+        short = 'C',
+        allow_hyphen_values = true,
+        help_heading = "Execution"
+    )]
+    cargo_arg: Vec<String>,
+
+    /// Pass remaining arguments to cargo test after all options and after `--`.
+    #[arg(last = true, help_heading = "Execution")]
+    cargo_test_args: Vec<String>,
+
+    #[command(flatten)]
+    features: Features,
+
+    /// Activate the `default` feature, even if `no_default_features` is set in the config.
+    #[arg(
+        long,
+        overrides_with = "no_default_features",
+        help_heading = "Feature Selection"
+    )]
+    default_features: bool,
+
+    /// Don't activate all features, even if `all_features` is set in the config.
+    #[arg(
+        long,
+        overrides_with = "all_features",
+        help_heading = "Feature Selection"
+    )]
+    no_all_features: bool,
+}
+
+#[derive(clap::Args, PartialEq, Eq, Debug, Default, Clone)]
+pub struct Features {
+    //---  features
+    /// Space or comma separated list of features to activate.
+    // (The features are not split or parsed, just passed through to Cargo.)
+    #[arg(long, help_heading = "Feature Selection")]
+    pub features: Vec<String>,
+
+    /// Do not activate the `default` feature.
+    #[arg(long, help_heading = "Feature Selection")]
+    pub no_default_features: bool,
+
+    /// Activate all features.
+    // (This does not conflict because this only turns on features in the top level package,
+    // and you might use --features to turn on features in dependencies.)
+    #[arg(long, help_heading = "Feature Selection")]
+    pub all_features: bool,
+}
+
+/// Build the command-line parser, optionally without the sponsorship message.
+fn cargo_command(show_sponsor: bool) -> clap::Command {
+    let command = Cargo::command();
+    if show_sponsor {
+        command
+    } else {
+        command.mut_subcommand("mutants", |subcommand| subcommand.after_help(None::<&str>))
+    }
+}
+
+/// Decide whether to show the sponsorship message in help.
+///
+/// This has to be decided before the arguments are parsed, because clap prints help
+/// as soon as it sees `--help`.
+fn show_sponsor(argv: &[OsString]) -> bool {
+    let flag = argv
+        .iter()
+        .take_while(|arg| *arg != "--")
+        .any(|arg| arg == "--no-sponsor");
+    let env = env::var("CARGO_MUTANTS_NO_SPONSOR")
+        .is_ok_and(|value| !matches!(value.as_str(), "" | "0" | "false" | "no" | "off"));
+    !(flag || env)
+}
+
+/// Parse the command line, or print help or an error and exit.
+fn parse_args() -> Args {
+    let command_line: Vec<OsString> = env::args_os().collect();
+    match cargo_command(show_sponsor(&command_line))
+        .try_get_matches_from(&command_line)
+        .and_then(|matches| Cargo::from_arg_matches(&matches))
+    {
+        Ok(Cargo::Mutants(args)) => args,
+        Err(e) => {
+            e.print().expect("Failed to show clap error message");
+            // Clap by default exits with code 2.
+            let code = match e.exit_code() {
+                2 => exit_code::USAGE,
+                0 => 0,
+                _ => exit_code::SOFTWARE,
+            };
+            exit(code);
+        }
+    }
+}
+
+/// Discover all the mutants in the selected packages of a workspace, without testing them.
+///
+/// The mutants are generated and filtered according to the options, but not by a diff,
+/// shard, or previous results, which are applied later by the command line tool.
+///
+/// # Errors
+///
+/// Fails if the package filter doesn't match the workspace, or if a source file can't
+/// be read or parsed.
+pub fn discover(
+    workspace: &Workspace,
+    package_filter: &PackageFilter,
+    options: &Options,
+) -> Result<Discovered> {
+    workspace.discover(package_filter, options, &Console::without_progress())
+}
+
+/// Run the `cargo mutants` command line tool.
+///
+/// This is the entry point for the `cargo-mutants` binary, and isn't a stable API.
+#[doc(hidden)]
+#[allow(clippy::too_many_lines)] // just a lot of setup
+#[allow(clippy::missing_errors_doc)]
+pub fn main() -> Result<()> {
+    let args = parse_args();
+
+    if args.version {
+        println!("{NAME} {VERSION}");
+        return Ok(());
+    } else if let Some(shell) = args.completions {
+        generate(shell, &mut Cargo::command(), "cargo", &mut io::stdout());
+        return Ok(());
+    }
+
+    let console = Console::new();
+    console.setup_global_trace(args.level, args.colors); // We don't have Options yet.
+    enable_console_colors(args.colors);
+    interrupt::install_handler();
+
+    let start_dir: &Utf8Path = if let Some(manifest_path) = &args.manifest_path {
+        ensure!(manifest_path.is_file(), "Manifest path is not a file");
+        manifest_path
+            .parent()
+            .ok_or(anyhow!("Manifest path has no parent"))?
+    } else if let Some(dir) = &args.dir {
+        dir
+    } else {
+        Utf8Path::new(".")
+    };
+    let workspace = Workspace::open(start_dir)?;
+    let config = if args.no_config {
+        config::Config::default()
+    } else {
+        config::Config::read_tree_config(workspace.root())?
+    };
+    debug!(?config);
+    debug!(?args.features);
+    let options = Options::new(&args, &config)?;
+    debug!(?options);
+    if options.output_format == OutputFormat::JsonLines
+        || (options.summary_only && options.list_format == ListFormat::Json)
+    {
+        console.send_messages_to_stderr();
+    }
+    if options.summary_only {
+        console.hide_progress();
+    }
+    let diff_text = args
+        .in_diff
+        .as_ref()
+        .map(|in_diff| read_to_string(in_diff).context("Failed to read filter diff"))
+        .transpose()?;
+    let package_filter = if args.include_only_changed_packages {
+        let diff_text = diff_text.as_deref().expect("--in-diff is required");
+        let changed_packages = workspace.packages_containing_paths(&diff_changed_paths(diff_text)?);
+        info!(
+            "Diff changes {} package(s): {}",
+            changed_packages.len(),
+            changed_packages.join(", ")
+        );
+        PackageFilter::Explicit(changed_packages)
+    } else if !args.mutate_packages.is_empty() {
+        PackageFilter::explicit(&args.mutate_packages)
+    } else if args.workspace {
+        PackageFilter::All
+    } else if let Some(package) = args
+        .manifest_path
+        .as_deref()
+        .map(|manifest_path| workspace.package_for_manifest(manifest_path))
+        .transpose()?
+        .flatten()
+    {
+        debug!(
+            package = package.name,
+            "Selected package from --manifest-path"
+        );
+        PackageFilter::explicit([&package.name])
+    } else {
+        PackageFilter::Auto(start_dir.to_owned())
+    };
+
+    let output_parent_dir = options
+        .output_in_dir
+        .clone()
+        .unwrap_or_else(|| workspace.root().to_owned());
+
+    if args.watch {
+        return watch(
+            &workspace,
+            &package_filter,
+            &output_parent_dir,
+            &options,
+            &console,
+        );
+    }
+
+    // Read this before running anything, because the previous report might be in the
+    // `mutants.out` that's about to be rotated away.
+    let previously_missed = args
+        .compare_to
+        .as_deref()
+        .map(load_missed_from_outcomes)
+        .transpose()?;
+
+    let mut previously_caught = None;
+    let mut skipped = None;
+    let mut discovery_cache = None;
+    let mut mutants = if let Some(mutants_from) = &args.mutants_from {
+        let mut mutants = workspace.load_mutants(mutants_from)?;
+        let n_loaded = mutants.len();
+        mutants.retain(|m| options.allows_loaded_mutant(m));
+        if let Some(diff_text) = &diff_text {
+            mutants = diff_filter(mutants, diff_text)?;
+        }
+        info!(
+            "Loaded {n_loaded} mutants from {mutants_from}, of which {} pass the filters",
+            mutants.len()
+        );
+        mutants
+    } else {
+        let cache = DiscoveryCache::load(&output_parent_dir.join(OUTDIR_NAME));
+        let mut discovered =
+            workspace.discover_with_cache(&package_filter, &options, &console, &cache)?;
+        discovery_cache = Some(cache);
+
+        if args.iterate {
+            let caught = load_previously_caught(&output_parent_dir)?;
+            if caught.is_empty() {
+                info!("No previously caught or unviable mutants found, so testing all mutants");
+            } else {
+                info!(
+                    "Iteration excludes {} previously caught or unviable mutants",
+                    caught.len()
+                );
+            }
+            discovered.remove_previously_caught(&caught);
+            previously_caught = Some(caught);
+        }
+
+        console.clear();
+        if args.list_files {
+            print!("{}", list_files(&discovered.files, &options));
+            return Ok(());
+        }
+        if args.explain_skips {
+            print!("{}", list_skipped(&discovered.skipped, &options));
+            return Ok(());
+        }
+        skipped = Some(discovered.skipped);
+        let mut mutants = discovered.mutants;
+        if let Some(diff_text) = &diff_text {
+            mutants = diff_filter(mutants, diff_text)?;
+        }
+        mutants
+    };
+    if let Some(shard) = &args.shard {
+        mutants = if args.shard_by_file {
+            shard.select_grouped(mutants, |m| m.source_file.tree_relative_path.clone())
+        } else {
+            shard.select(mutants)
+        };
+    }
+    if let Some(max_mutants) = args.max_mutants {
+        let total = mutants.len();
+        mutants = sample(
+            mutants,
+            usize::try_from(max_mutants).unwrap_or(usize::MAX),
+            options.shuffle.then_some(options.shuffle_seed),
+            |m| m.source_file.tree_relative_path.clone(),
+        );
+        if mutants.len() < total {
+            info!(
+                "Sampled {} of {total} mutants; {} were dropped by --max-mutants",
+                mutants.len(),
+                total - mutants.len()
+            );
+        }
+    }
+    if let Some(emit_mutants) = &args.emit_mutants {
+        write(
+            emit_mutants,
+            serde_json::to_string_pretty(&mutants.iter().map(mutant_json).collect::<Vec<_>>())
+                .expect("Serialize mutants"),
+        )
+        .with_context(|| format!("Failed to write mutants to {emit_mutants}"))?;
+        info!("Wrote {} mutants to {emit_mutants}", mutants.len());
+        return Ok(());
+    }
+    #[cfg(feature = "tui")]
+    if args.tui {
+        return tui::browse(&mutants, &workspace, &output_parent_dir, &options, &console);
+    }
+    if args.list {
+        print!("{}", list_mutants(&mutants, &options));
+        // Listing doesn't rotate or lock `mutants.out`, but keeping the cache up to date
+        // makes the next `--list` fast. The cache is renamed into place, so this can't
+        // corrupt it for a concurrent run.
+        if let Some(discovery_cache) = &discovery_cache {
+            if let Err(err) = discovery_cache.save(&output_parent_dir.join(OUTDIR_NAME)) {
+                warn!("Failed to write discovery cache: {err:#}");
+            }
+        }
+    } else if args.dry_run {
+        let plan = plan_mutants(
+            mutants,
+            &workspace,
+            &output_parent_dir.join(OUTDIR_NAME),
+            &options,
+            &console,
+        )?;
+        console.clear();
+        print!("{}", plan.summary_string());
+    } else {
+        // With `--output-archive`, the output is written into a temporary directory
+        // that's archived and then removed, instead of into `mutants.out`.
+        let archive_temp_dir = args
+            .output_archive
+            .as_ref()
+            .map(|_| {
+                tempfile::Builder::new()
+                    .prefix("cargo-mutants-output-")
+                    .tempdir()
+                    .context("create temporary output directory")
+            })
+            .transpose()?;
+        let lab_parent_dir = match &archive_temp_dir {
+            Some(temp_dir) => Utf8PathBuf::try_from(temp_dir.path().to_owned())
+                .context("temporary directory path is not UTF-8")?,
+            None => output_parent_dir.clone(),
+        };
+        let output_dir = OutputDir::new(&lab_parent_dir)?;
+        // Written after the output directory is rotated, so that the new cache isn't
+        // moved aside with the old output. There's no point caching into a directory
+        // that's about to be archived and removed.
+        if let (Some(discovery_cache), None) = (&discovery_cache, &archive_temp_dir) {
+            if let Err(err) = discovery_cache.save(output_dir.path()) {
+                warn!("Failed to write discovery cache: {err:#}");
+            }
+        }
+        if let Some(previously_caught) = previously_caught {
+            output_dir.write_previously_caught(&previously_caught)?;
+        }
+        if let Some(skipped) = &skipped {
+            output_dir.write_skipped(skipped)?;
+        }
+        console.set_debug_log(output_dir.open_debug_log()?);
+        let lab_result = test_mutants(mutants, &workspace, output_dir, &options, &console);
+        if let (Some(archive_path), Some(temp_dir)) = (&args.output_archive, archive_temp_dir) {
+            // Write the archive even if testing failed, since the logs may show why.
+            let archived = write_output_archive(&lab_parent_dir, archive_path);
+            if let Err(err) = temp_dir.close() {
+                warn!("Failed to remove temporary output directory: {err}");
+            }
+            match (&lab_result, archived) {
+                (Ok(_), Err(err)) => return Err(err),
+                (Err(_), Err(err)) => warn!("Failed to write output archive: {err:#}"),
+                (_, Ok(())) => (),
+            }
+        }
+        let lab_outcome = lab_result?;
+        let mut code = lab_outcome.exit_code();
+        if let (Some(previously_missed), Some(compare_to)) = (previously_missed, &args.compare_to) {
+            let comparison = lab_outcome.compare_missed(&previously_missed);
+            console.message(&format!(
+                "Compared to {compare_to}: {}\n",
+                comparison.summary_string()
+            ));
+            for name in &comparison.new_missed {
+                console.message(&format!("New survivor: {name}\n"));
+            }
+            if args.fail_on_new {
+                code = lab_outcome.exit_code_for_new_missed(&comparison);
+            }
+        }
+        exit(code);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsString;
+
+    use clap::CommandFactory;
+
+    #[test]
+    fn sponsor_message_can_be_hidden() {
+        let mutants_help = |show_sponsor| {
+            super::cargo_command(show_sponsor)
+                .find_subcommand_mut("mutants")
+                .unwrap()
+                .render_long_help()
+                .to_string()
+        };
+        assert!(mutants_help(true).contains("github.com/sponsors"));
+        assert!(!mutants_help(false).contains("github.com/sponsors"));
+
+        let argv = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        assert!(!super::show_sponsor(&argv(&[
+            "cargo",
+            "mutants",
+            "--no-sponsor",
+            "--help"
+        ])));
+        assert!(super::show_sponsor(&argv(&[
+            "cargo",
+            "mutants",
+            "--",
+            "--no-sponsor"
+        ])));
+    }
+
+    #[test]
+    fn option_help_sentence_case_without_period() {
+        let args = super::Args::command();
+        let mut problems = Vec::new();
+        for arg in args.get_arguments() {
+            if let Some(help) = arg.get_help().map(ToString::to_string) {
+                if !help.starts_with(char::is_uppercase) {
+                    problems.push(format!(
+                        "Help for {:?} does not start with a capital letter: {:?}",
+                        arg.get_id(),
+                        help
+                    ));
+                }
+                // Clap seems to automatically strip periods from the end of help text in docstrings,
+                // but let's leave this here just in case.
+                if help.ends_with('.') {
+                    problems.push(format!(
+                        "Help for {:?} ends with a period: {:?}",
+                        arg.get_id(),
+                        help
+                    ));
+                }
+                if help.is_empty() {
+                    problems.push(format!("Help for {:?} is empty", arg.get_id()));
+                }
+            } else {
+                problems.push(format!("No help for {:?}", arg.get_id()));
+            }
+        }
+        for problem in &problems {
+            eprintln!("{problem}");
+        }
+        assert!(problems.is_empty(), "Problems with help text");
+    }
+}
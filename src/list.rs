@@ -1,31 +1,46 @@
 // Copyright 2023-2024 Martin Pool
 
-//! List mutants and files as text or json.
+//! List mutants and files as text, json, or Markdown.
 
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use console::style;
 use itertools::Itertools;
 use serde_json::{json, Value};
 
-use crate::mutant::Mutant;
+use crate::mutant::{Mutant, MUTANT_CONTEXT_LINES};
+use crate::options::ListFormat;
 use crate::path::Utf8PathSlashes;
 use crate::source::SourceFile;
+use crate::visit::Skipped;
 use crate::Options;
 
+/// The version of the structure of each mutant in `--list --json` output.
+///
+/// Fields may be added without changing the version, but it's incremented whenever
+/// fields are removed or renamed or their meaning changes.
+pub const LIST_JSON_SCHEMA_VERSION: u32 = 1;
+
 /// Return a string representation of a list of mutants.
 ///
-/// The format is controlled by the `emit_json`, `emit_diffs`, `show_line_col`, and `colors` options.
+/// The format is controlled by the `list_format`, `emit_diffs`, `print_mutant_context`,
+/// `show_line_col`, and `colors` options.
 pub fn list_mutants(mutants: &[Mutant], options: &Options) -> String {
-    if options.emit_json {
+    if options.list_format == ListFormat::Markdown {
+        markdown_table(mutants, options)
+    } else if options.list_format == ListFormat::Json {
         // Panic: only if we created illegal json, which would be a bug.
         let mut list: Vec<serde_json::Value> = Vec::new();
         for mutant in mutants {
-            let mut obj = serde_json::to_value(mutant).expect("Serialize mutant");
+            let mut obj = mutant_json(mutant);
             if options.emit_diffs {
                 obj.as_object_mut().unwrap().insert(
                     "diff".to_owned(),
-                    json!(mutant.diff(&mutant.mutated_code())),
+                    json!(options.mutant_diff(mutant, &mutant.mutated_code())),
                 );
             }
             list.push(obj);
@@ -43,8 +58,16 @@ pub fn list_mutants(mutants: &[Mutant], options: &Options) -> String {
                 out.push_str(&mutant.name(options.show_line_col));
             }
             out.push('\n');
+            if options.print_mutant_context {
+                out.push_str(&mutant.source_context(MUTANT_CONTEXT_LINES));
+            }
             if options.emit_diffs {
-                out.push_str(&mutant.diff(&mutant.mutated_code()));
+                let diff = options.mutant_diff(mutant, &mutant.mutated_code());
+                if colors {
+                    out.push_str(&style_diff(&diff));
+                } else {
+                    out.push_str(&diff);
+                }
                 out.push('\n');
             }
         }
@@ -52,9 +75,108 @@ pub fn list_mutants(mutants: &[Mutant], options: &Options) -> String {
     }
 }
 
+/// Describe a mutant as JSON, including the schema version, as in `--list --json` and
+/// `mutants.json`.
+pub fn mutant_json(mutant: &Mutant) -> Value {
+    let mut obj = serde_json::to_value(mutant).expect("Serialize mutant");
+    obj.as_object_mut()
+        .expect("Mutant is serialized as an object")
+        .insert("schema_version".to_owned(), json!(LIST_JSON_SCHEMA_VERSION));
+    obj
+}
+
+/// Describe mutants as a Markdown table, optionally followed by their diffs in fenced
+/// code blocks.
+fn markdown_table(mutants: &[Mutant], options: &Options) -> String {
+    let mut out = String::with_capacity(100 * (mutants.len() + 1));
+    out.push_str("| File | Line | Genre | Description |\n");
+    out.push_str("| --- | ---: | --- | --- |\n");
+    for mutant in mutants {
+        writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            escape_markdown(&mutant.source_file.tree_relative_slashes()),
+            mutant.span.start.line,
+            mutant
+                .genre
+                .to_possible_value()
+                .expect("Genre has a value")
+                .get_name(),
+            escape_markdown(&mutant.describe_change()),
+        )
+        .unwrap();
+    }
+    if options.emit_diffs {
+        for mutant in mutants {
+            let diff = options.mutant_diff(mutant, &mutant.mutated_code());
+            let fence = code_fence(&diff);
+            write!(
+                out,
+                "\n{}:{}: {}\n\n{fence}diff\n{diff}",
+                escape_markdown(&mutant.source_file.tree_relative_slashes()),
+                mutant.span.start.line,
+                escape_markdown(&mutant.describe_change()),
+            )
+            .unwrap();
+            if !diff.ends_with('\n') {
+                out.push('\n');
+            }
+            writeln!(out, "{fence}").unwrap();
+        }
+    }
+    out
+}
+
+/// Color the lines of a unified diff, for text output when colors are on.
+fn style_diff(diff: &str) -> String {
+    diff.split_inclusive('\n')
+        .map(|line| {
+            let styled = style(line.trim_end_matches('\n')).force_styling(true);
+            let styled = if line.starts_with("---") || line.starts_with("+++") {
+                styled.bold()
+            } else if line.starts_with("@@") {
+                styled.cyan()
+            } else if line.starts_with('-') {
+                styled.red()
+            } else if line.starts_with('+') {
+                styled.green()
+            } else {
+                return line.to_owned();
+            };
+            if line.ends_with('\n') {
+                format!("{styled}\n")
+            } else {
+                styled.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Escape characters that would break a Markdown table cell or be read as formatting.
+fn escape_markdown(s: &str) -> String {
+    let mut r = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '|' | '*' | '_' | '`' | '<' | '[' | ']') {
+            r.push('\\');
+        }
+        r.push(c);
+    }
+    r
+}
+
+/// Return a backtick fence long enough to contain this text in a code block.
+fn code_fence(text: &str) -> String {
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    "`".repeat(longest_run.max(2) + 1)
+}
+
 /// List the source files as json or text.
 pub fn list_files(source_files: &[SourceFile], options: &Options) -> String {
-    if options.emit_json {
+    if options.list_format == ListFormat::Json {
         let json_list = Value::Array(
             source_files
                 .iter()
@@ -74,3 +196,127 @@ pub fn list_files(source_files: &[SourceFile], options: &Options) -> String {
             .join("")
     }
 }
+
+/// List functions and other items that generated no mutants, as json or text.
+pub fn list_skipped(skipped: &[Skipped], options: &Options) -> String {
+    if options.list_format == ListFormat::Json {
+        serde_json::to_string_pretty(skipped).expect("Serialize skipped items")
+    } else {
+        skipped
+            .iter()
+            .map(|skip| skip.describe(options.show_line_col) + "\n")
+            .join("")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::visit::mutate_source_str;
+
+    #[test]
+    fn escape_markdown_table_cells() {
+        assert_eq!(
+            escape_markdown("replace a || b with a && b in f<T>"),
+            "replace a \\|\\| b with a && b in f\\<T>"
+        );
+        assert_eq!(escape_markdown("replace * with /"), "replace \\* with /");
+    }
+
+    #[test]
+    fn style_diff_colors_changed_lines() {
+        let diff = "--- a\n+++ b\n@@ -1 +1 @@\n same\n-old\n+new\n";
+        let styled = style_diff(diff);
+        assert!(styled.contains("\x1b[31m-old\x1b[0m\n"), "{styled:?}");
+        assert!(styled.contains("\x1b[32m+new\x1b[0m\n"), "{styled:?}");
+        assert!(styled.contains("\n same\n"), "{styled:?}");
+        assert_eq!(console::strip_ansi_codes(&styled), diff);
+    }
+
+    #[test]
+    fn code_fence_is_longer_than_backticks_in_text() {
+        assert_eq!(code_fence("no backticks"), "```");
+        assert_eq!(code_fence("a ``` b ` c"), "````");
+    }
+
+    #[test]
+    fn markdown_table_of_mutants() {
+        let code = indoc! {"
+            fn either(a: bool, b: bool) -> bool {
+                a || b
+            }
+        "};
+        let options = Options {
+            list_format: ListFormat::Markdown,
+            ..Options::default()
+        };
+        let mutants = mutate_source_str(code, &options).unwrap();
+        let table = list_mutants(&mutants, &options);
+        println!("{table}");
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "| File | Line | Genre | Description |");
+        assert_eq!(lines[1], "| --- | ---: | --- | --- |");
+        assert!(lines.contains(
+            &"| src/main.rs | 2 | logical-operator | replace \\|\\| with && in either |"
+        ));
+        assert!(
+            lines.contains(&"| src/main.rs | 2 | fn-value | replace either -> bool with true |")
+        );
+        assert_eq!(lines.len(), mutants.len() + 2);
+        assert!(!table.contains("```"));
+    }
+
+    #[test]
+    fn json_list_has_schema_version() {
+        let code = indoc! {"
+            fn either(a: bool, b: bool) -> bool {
+                a || b
+            }
+        "};
+        let options = Options {
+            list_format: ListFormat::Json,
+            ..Options::default()
+        };
+        let mutants = mutate_source_str(code, &options).unwrap();
+        let json: Value = serde_json::from_str(&list_mutants(&mutants, &options)).unwrap();
+        let list = json.as_array().unwrap();
+        assert_eq!(list.len(), mutants.len());
+        for obj in list {
+            assert_eq!(obj["schema_version"], 1);
+            for field in [
+                "id",
+                "name",
+                "package",
+                "file",
+                "function",
+                "span",
+                "replacement",
+                "genre",
+            ] {
+                assert!(obj.get(field).is_some(), "{field} missing from {obj}");
+            }
+        }
+    }
+
+    #[test]
+    fn markdown_with_diffs_has_fenced_code_blocks() {
+        let code = indoc! {"
+            fn either(a: bool, b: bool) -> bool {
+                a || b
+            }
+        "};
+        let options = Options {
+            list_format: ListFormat::Markdown,
+            emit_diffs: true,
+            ..Options::default()
+        };
+        let mutants = mutate_source_str(code, &options).unwrap();
+        let out = list_mutants(&mutants, &options);
+        println!("{out}");
+        assert_eq!(out.matches("```diff\n").count(), mutants.len());
+        assert_eq!(out.matches("\n```\n").count(), mutants.len());
+        assert!(out.contains("-    a || b\n+    a && "));
+    }
+}
@@ -5,10 +5,13 @@
 use std::fmt;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Result};
+use camino::Utf8PathBuf;
+use clap::ValueEnum;
 use console::{style, StyledObject};
+use itertools::Itertools;
 use serde::ser::{SerializeStruct, Serializer};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use similar::TextDiff;
 use tracing::trace;
 
@@ -18,14 +21,122 @@ use crate::source::SourceFile;
 use crate::span::Span;
 use crate::MUTATION_MARKER_COMMENT;
 
+/// The number of lines of context shown around the change in mutant diffs, unless
+/// `--diff-context` is given.
+pub const DEFAULT_DIFF_CONTEXT: usize = 8;
+
+/// The number of lines of source shown before and after the mutated span by
+/// `--print-mutant-context`.
+pub const MUTANT_CONTEXT_LINES: usize = 3;
+
 /// Various broad categories of mutants.
-#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, ValueEnum)]
 pub enum Genre {
     /// Replace the body of a function with a fixed value.
+    #[value(alias = "function-value")]
     FnValue,
-    /// Replace `==` with `!=` and so on.
+    /// Replace `+` with `-`, `<<` with `>>`, and so on.
     BinaryOperator,
+    /// Swap `&&` and `||`.
+    LogicalOperator,
+    /// Replace `<` with `<=`, `==` with `!=`, and so on.
+    ComparisonOperator,
+    /// Delete unary operators like `!` and `-`.
     UnaryOperator,
+    /// Swap `Ok` and `Err` in `return` expressions.
+    EarlyReturn,
+    /// Change the sign or width of the type in numeric `as` casts.
+    Cast,
+    /// Swap `min` and `max` method calls, and drop one bound of `clamp`.
+    MinMax,
+    /// Negate the condition of `if` and `while` expressions.
+    Condition,
+    /// Flip `true` and `false` literals.
+    ///
+    /// This is not generated unless requested with `--genre`, because many boolean
+    /// literals are flags or defaults whose mutants are equivalent or hang.
+    BoolLiteral,
+    /// Replace integer and float literals with nearby values, zero, and one.
+    ///
+    /// This is not generated unless requested with `--genre`, because there are many
+    /// literals in typical code and many of these mutants are equivalent.
+    Literal,
+    /// Shift integer literal indexes, like `a[0]` or `a.get(0)`, up and down by one.
+    ///
+    /// This is not generated unless requested with `--genre`, because many of these
+    /// mutants just panic by indexing out of bounds.
+    Index,
+    /// Delete individual statements from blocks.
+    ///
+    /// This is not generated unless requested with `--genre`, because many of these
+    /// mutants are unviable.
+    Statement,
+    /// Replace the body of one arm of a `match` returned from a function with a fixed value.
+    ///
+    /// This is not generated unless requested with `--genre`, because it generates many
+    /// mutants that overlap with replacing the whole function body.
+    MatchArm,
+    /// Delete the `?` operator, so that errors are not propagated.
+    ///
+    /// This is not generated unless requested with `--genre`, because most of these
+    /// mutants are unviable.
+    TryOperator,
+    /// Replace the value of a `const` or `static` item with a fixed value of its type.
+    ///
+    /// This is not generated unless requested with `--genre`, because many of these
+    /// mutants are unviable, or change values that are checked at compile time.
+    ConstValue,
+    /// Delete a method call, leaving just its receiver, like `s.clone()` to `s`.
+    ///
+    /// This is not generated unless requested with `--genre`, because most of these
+    /// mutants are unviable.
+    MethodCall,
+    /// Replace arithmetic compound assignments, like `+=`, with a plain `=`.
+    ///
+    /// This is not generated unless requested with `--genre`, because these mutants
+    /// overlap with the binary operator mutants of the same assignments.
+    CompoundAssign,
+    /// Replace calls to functions or macros as configured in `custom_mutations`.
+    Custom,
+}
+
+impl Genre {
+    /// Parse a genre name, like `binary-operator`.
+    ///
+    /// Case is ignored, and underscores are accepted in place of hyphens.
+    ///
+    /// # Errors
+    ///
+    /// If the name is not a known genre, with an error listing the valid names.
+    pub fn from_name(name: &str) -> Result<Genre> {
+        <Genre as ValueEnum>::from_str(&name.trim().replace('_', "-"), true).map_err(|_| {
+            anyhow!(
+                "Unknown genre {name:?}; valid genres are: {}",
+                Genre::value_variants()
+                    .iter()
+                    .filter_map(ValueEnum::to_possible_value)
+                    .map(|value| value.get_name().to_owned())
+                    .join(", ")
+            )
+        })
+    }
+
+    /// True if this genre is generated when no genres are specified.
+    #[must_use]
+    pub fn is_default(self) -> bool {
+        !matches!(
+            self,
+            Genre::BoolLiteral
+                | Genre::Literal
+                | Genre::Index
+                | Genre::Statement
+                | Genre::MatchArm
+                | Genre::TryOperator
+                | Genre::ConstValue
+                | Genre::MethodCall
+                | Genre::CompoundAssign
+        )
+    }
 }
 
 /// A mutation applied to source code.
@@ -49,12 +160,20 @@ pub struct Mutant {
 
     /// What general category of mutant this is.
     pub genre: Genre,
+
+    /// For `FnValue` mutants that replace just one element of a returned tuple,
+    /// the index of that element.
+    pub tuple_index: Option<usize>,
+
+    /// For `MatchArm` mutants, the text of the pattern of the replaced arm.
+    pub match_arm_pattern: Option<String>,
 }
 
 /// The function containing a mutant.
 ///
 /// This is used for both mutations of the whole function, and smaller mutations within it.
-#[derive(Eq, PartialEq, Debug, Serialize)]
+/// For [`Genre::ConstValue`] mutants, it describes the `const` or `static` item instead.
+#[derive(Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Function {
     /// The function that's being mutated, including any containing namespaces.
     #[allow(clippy::struct_field_names)]
@@ -62,7 +181,8 @@ pub struct Function {
 
     /// The return type of the function, including a leading "-> ", as a fragment of Rust syntax.
     ///
-    /// Empty if the function has no return type (i.e. returns `()`).
+    /// Empty if the function has no return type (i.e. returns `()`). For a `const` or
+    /// `static` item, this is its type with a leading ": ".
     pub return_type: String,
 
     /// The span (line/column range) of the entire function.
@@ -71,6 +191,7 @@ pub struct Function {
 
 impl Mutant {
     /// Return text of the whole file with the mutation applied.
+    #[must_use]
     pub fn mutated_code(&self) -> String {
         self.span.replace(
             self.source_file.code(),
@@ -81,6 +202,7 @@ impl Mutant {
     /// Describe the mutant briefly, not including the location.
     ///
     /// The result is like `replace factorial -> u32 with Default::default()`.
+    #[must_use]
     pub fn describe_change(&self) -> String {
         self.styled_parts()
             .into_iter()
@@ -88,6 +210,7 @@ impl Mutant {
             .collect::<String>()
     }
 
+    #[must_use]
     pub fn name(&self, show_line_col: bool) -> String {
         let mut v = Vec::new();
         v.push(self.source_file.tree_relative_slashes());
@@ -107,9 +230,41 @@ impl Mutant {
         v.join("")
     }
 
+    /// Return a stable identifier for this mutant, as 16 hex digits.
+    ///
+    /// This is a hash of the file, function, genre, original and replacement text, and
+    /// the position of the mutant relative to the start of its function, so it doesn't
+    /// change when code outside the function is edited or moves the function to
+    /// different lines.
+    #[must_use]
+    pub fn id(&self) -> String {
+        let (function_name, line, column) = match &self.function {
+            Some(function) => (
+                function.function_name.as_str(),
+                self.span.start.line - function.span.start.line,
+                self.span.start.column,
+            ),
+            None => ("", self.span.start.line, self.span.start.column),
+        };
+        let tuple_index = self
+            .tuple_index
+            .map_or_else(String::new, |index| index.to_string());
+        let key = [
+            self.source_file.tree_relative_slashes().as_str(),
+            function_name,
+            &format!("{:?}", self.genre),
+            &self.original_text(),
+            &self.replacement,
+            &tuple_index,
+            &format!("{line}:{column}"),
+        ]
+        .join("\0");
+        format!("{:016x}", fnv1a_64(key.as_bytes()))
+    }
+
     /// Return a one-line description of this mutant, with coloring, including the file names
     /// and optionally the line and column.
-    pub fn to_styled_string(&self, show_line_col: bool) -> String {
+    pub(crate) fn to_styled_string(&self, show_line_col: bool) -> String {
         let mut v = Vec::new();
         v.push(self.source_file.tree_relative_slashes());
         if show_line_col {
@@ -131,26 +286,43 @@ impl Mutant {
             style(s.to_string())
         }
         let mut v: Vec<StyledObject<String>> = Vec::new();
-        if self.genre == Genre::FnValue {
+        if matches!(self.genre, Genre::FnValue | Genre::ConstValue) {
             v.push(s("replace "));
+            if let Some(index) = self.tuple_index {
+                v.push(s(format!("element {index} of ")));
+            }
             let function = self
                 .function
                 .as_ref()
                 .expect("FnValue mutant should have a function");
             v.push(s(&function.function_name).bright().magenta());
             if !function.return_type.is_empty() {
-                v.push(s(" "));
+                if self.genre == Genre::FnValue {
+                    v.push(s(" "));
+                }
                 v.push(s(&function.return_type).magenta());
             }
             v.push(s(" with "));
             v.push(s(self.replacement_text()).yellow());
+        } else if let Some(pattern) = &self.match_arm_pattern {
+            v.push(s("replace match arm "));
+            v.push(s(pattern).yellow());
+            v.push(s(" with "));
+            v.push(s(&self.replacement).bright().yellow());
+            if let Some(function) = &self.function {
+                v.push(s(" in "));
+                v.push(s(&function.function_name).bright().magenta());
+            }
         } else {
-            if self.replacement.is_empty() {
+            if self.genre == Genre::Statement {
+                v.push(s("delete statement "));
+            } else if self.replacement.is_empty() {
                 v.push(s("delete "));
             } else {
                 v.push(s("replace "));
             }
-            v.push(s(self.original_text()).yellow());
+            // Keep the name on one line, even if the original code spans several.
+            v.push(s(self.original_text().split_whitespace().join(" ")).yellow());
             if !self.replacement.is_empty() {
                 v.push(s(" with "));
                 v.push(s(&self.replacement).bright().yellow());
@@ -163,37 +335,84 @@ impl Mutant {
         v
     }
 
+    #[must_use]
     pub fn original_text(&self) -> String {
         self.span.extract(self.source_file.code())
     }
 
     /// Return the text inserted for this mutation.
+    #[must_use]
     pub fn replacement_text(&self) -> &str {
         self.replacement.as_str()
     }
 
+    /// Show the source around the mutation, for `--print-mutant-context`.
+    ///
+    /// This is the signature line of the containing function, followed by the lines of
+    /// the mutated span, marked with `>`, and `context_lines` lines before and after it.
+    /// Each line is prefixed by its line number.
+    #[must_use]
+    pub fn source_context(&self, context_lines: usize) -> String {
+        let lines: Vec<&str> = self.source_file.code().lines().collect();
+        let first = self.span.start.line.saturating_sub(context_lines).max(1);
+        let last = (self.span.end.line + context_lines).min(lines.len());
+        let width = last.to_string().len();
+        let format_line = |line_no: usize, marker: char| {
+            let line = format!("{marker} {line_no:>width$} | {}", lines[line_no - 1]);
+            format!("{}\n", line.trim_end())
+        };
+        let mut out = String::new();
+        // The line declaring the function, if it's not already in the context.
+        if let Some(signature_line) = self.function.as_ref().and_then(|function| {
+            (function.span.start.line..first)
+                .find(|&line_no| lines.get(line_no - 1).is_some_and(|l| l.contains("fn ")))
+        }) {
+            out.push_str(&format_line(signature_line, ' '));
+            if signature_line + 1 < first {
+                out.push_str("  ...\n");
+            }
+        }
+        for line_no in first..=last {
+            let marker = if (self.span.start.line..=self.span.end.line).contains(&line_no) {
+                '>'
+            } else {
+                ' '
+            };
+            out.push_str(&format_line(line_no, marker));
+        }
+        out
+    }
+
     /// Return a unified diff for the mutant.
     ///
     /// The mutated text must be passed in because we should have already computed
     /// it, and don't want to pointlessly recompute it here.
+    #[must_use]
     pub fn diff(&self, mutated_code: &str) -> String {
+        self.diff_with_context(mutated_code, DEFAULT_DIFF_CONTEXT)
+    }
+
+    /// Return a unified diff for the mutant, with this many lines of context around the
+    /// change.
+    #[must_use]
+    pub fn diff_with_context(&self, mutated_code: &str, context_lines: usize) -> String {
         let old_label = self.source_file.tree_relative_slashes();
         // There shouldn't be any newlines, but just in case...
         let new_label = self.describe_change().replace('\n', " ");
         TextDiff::from_lines(self.source_file.code(), mutated_code)
             .unified_diff()
-            .context_radius(8)
+            .context_radius(context_lines)
             .header(&old_label, &new_label)
             .to_string()
     }
 
     /// Apply this mutant to the relevant file within a `BuildDir`.
-    pub fn apply(&self, build_dir: &BuildDir, mutated_code: &str) -> Result<()> {
+    pub(crate) fn apply(&self, build_dir: &BuildDir, mutated_code: &str) -> Result<()> {
         trace!(?self, "Apply mutant");
         build_dir.overwrite_file(&self.source_file.tree_relative_path, mutated_code)
     }
 
-    pub fn revert(&self, build_dir: &BuildDir) -> Result<()> {
+    pub(crate) fn revert(&self, build_dir: &BuildDir) -> Result<()> {
         trace!(?self, "Revert mutant");
         build_dir.overwrite_file(
             &self.source_file.tree_relative_path,
@@ -203,7 +422,7 @@ impl Mutant {
 
     /// Return a string describing this mutant that's suitable for building a log file name,
     /// but can contain slashes.
-    pub fn log_file_name_base(&self) -> String {
+    pub(crate) fn log_file_name_base(&self) -> String {
         // TODO: Also include a unique number so that they can't collide, even
         // with similar mutants on the same line?
         format!(
@@ -218,33 +437,103 @@ impl Mutant {
 impl fmt::Debug for Mutant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Custom implementation to show spans more concisely
-        f.debug_struct("Mutant")
-            .field("function", &self.function)
+        let mut ds = f.debug_struct("Mutant");
+        ds.field("function", &self.function)
             .field("replacement", &self.replacement)
-            .field("genre", &self.genre)
-            .field("span", &self.span)
+            .field("genre", &self.genre);
+        if let Some(tuple_index) = self.tuple_index {
+            ds.field("tuple_index", &tuple_index);
+        }
+        if let Some(pattern) = &self.match_arm_pattern {
+            ds.field("match_arm_pattern", pattern);
+        }
+        ds.field("span", &self.span)
             .field("package_name", &self.source_file.package.name)
             .finish()
     }
 }
 
+/// Compute the 64-bit FNV-1a hash of some bytes, which is stable across platforms and
+/// versions, unlike the hashers in the standard library.
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
 impl Serialize for Mutant {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         // custom serialize to omit inessential info
-        let mut ss = serializer.serialize_struct("Mutant", 7)?;
+        let mut ss = serializer.serialize_struct("Mutant", 11)?;
+        ss.serialize_field("id", &self.id())?;
+        ss.serialize_field("name", &self.name(false))?;
         ss.serialize_field("package", &self.source_file.package.name)?;
         ss.serialize_field("file", &self.source_file.tree_relative_slashes())?;
         ss.serialize_field("function", &self.function.as_ref().map(Arc::as_ref))?;
         ss.serialize_field("span", &self.span)?;
         ss.serialize_field("replacement", &self.replacement)?;
         ss.serialize_field("genre", &self.genre)?;
+        if let Some(tuple_index) = self.tuple_index {
+            ss.serialize_field("tuple_index", &tuple_index)?;
+        } else {
+            ss.skip_field("tuple_index")?;
+        }
+        if let Some(pattern) = &self.match_arm_pattern {
+            ss.serialize_field("match_arm_pattern", pattern)?;
+        } else {
+            ss.skip_field("match_arm_pattern")?;
+        }
         ss.end()
     }
 }
 
+/// A mutant as read back from `mutants.json`, before it's matched to a source file.
+///
+/// The `name` field, and any `diff`, are ignored, since they can be recomputed.
+#[derive(Debug, Deserialize)]
+pub struct MutantRecord {
+    pub id: String,
+    pub package: String,
+    pub file: Utf8PathBuf,
+    pub function: Option<Function>,
+    pub span: Span,
+    pub replacement: String,
+    pub genre: Genre,
+    pub tuple_index: Option<usize>,
+    pub match_arm_pattern: Option<String>,
+}
+
+impl MutantRecord {
+    /// Make a mutant from this record, in the given source file.
+    ///
+    /// This fails if the mutant's id doesn't match, which most likely means the source
+    /// file changed after the mutants were written.
+    pub fn into_mutant(self, source_file: SourceFile) -> Result<Mutant> {
+        let mutant = Mutant {
+            source_file,
+            function: self.function.map(Arc::new),
+            span: self.span,
+            replacement: self.replacement,
+            genre: self.genre,
+            tuple_index: self.tuple_index,
+            match_arm_pattern: self.match_arm_pattern,
+        };
+        ensure!(
+            mutant.id() == self.id,
+            "Mutant {} doesn't match the current source of {}; has it changed since the mutants were listed?",
+            self.id,
+            self.file,
+        );
+        Ok(mutant)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use indoc::indoc;
@@ -255,6 +544,8 @@ mod test {
     use crate::visit::mutate_source_str;
     use crate::*;
 
+    use super::{fnv1a_64, DEFAULT_DIFF_CONTEXT, MUTANT_CONTEXT_LINES};
+
     #[test]
     fn discover_factorial_mutants() {
         let tmp = copy_of_testdata("factorial");
@@ -332,8 +623,11 @@ mod test {
             descriptions,
             [
                 "replace controlled_loop with ()",
+                "replace should_stop() with !(should_stop()) in controlled_loop",
+                "replace start.elapsed() > Duration::from_secs(60 * 5) with !(start.elapsed() > Duration::from_secs(60 * 5)) in controlled_loop",
                 "replace > with == in controlled_loop",
                 "replace > with < in controlled_loop",
+                "replace > with >= in controlled_loop",
                 "replace * with + in controlled_loop",
                 "replace * with / in controlled_loop",
             ]
@@ -357,6 +651,106 @@ mod test {
         assert_eq!(mutants, []);
     }
 
+    #[test]
+    fn diff_context_size() {
+        let code = indoc! { "
+            fn add(a: u32, b: u32) -> u32 {
+                let c = a;
+                let d = b;
+                c + d
+            }
+        " };
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        let mutant = mutants
+            .iter()
+            .find(|m| m.genre == Genre::BinaryOperator)
+            .unwrap();
+        let mutated_code = mutant.mutated_code();
+        assert_eq!(
+            mutant.diff(&mutated_code),
+            mutant.diff_with_context(&mutated_code, DEFAULT_DIFF_CONTEXT)
+        );
+        assert_eq!(
+            mutant.diff_with_context(&mutated_code, 0),
+            indoc! { "
+                --- src/main.rs
+                +++ replace + with - in add
+                @@ -4 +4 @@
+                -    c + d
+                +    c - /* ~ changed by cargo-mutants ~ */ d
+            " }
+        );
+        assert!(mutant
+            .diff_with_context(&mutated_code, 1)
+            .contains("@@ -3,3 +3,3 @@\n     let d = b;\n-    c + d\n"));
+    }
+
+    #[test]
+    fn source_context_shows_signature_and_marks_mutated_lines() {
+        let code = indoc! { "
+            fn add(a: u32, b: u32) -> u32 {
+                let c = a;
+                let d = b;
+                let e = 0;
+                let f = 1;
+                c + d
+            }
+        " };
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        let mutant = mutants
+            .iter()
+            .find(|m| m.genre == Genre::BinaryOperator)
+            .unwrap();
+        assert_eq!(
+            mutant.source_context(1),
+            indoc! { "
+                  1 | fn add(a: u32, b: u32) -> u32 {
+                  ...
+                  5 |     let f = 1;
+                > 6 |     c + d
+                  7 | }
+            " }
+        );
+        assert_eq!(
+            mutant.source_context(MUTANT_CONTEXT_LINES),
+            indoc! { "
+                  1 | fn add(a: u32, b: u32) -> u32 {
+                  ...
+                  3 |     let d = b;
+                  4 |     let e = 0;
+                  5 |     let f = 1;
+                > 6 |     c + d
+                  7 | }
+            " }
+        );
+        // When the signature is within the context, it's not repeated.
+        assert_eq!(
+            mutant.source_context(5),
+            indoc! { "
+                  1 | fn add(a: u32, b: u32) -> u32 {
+                  2 |     let c = a;
+                  3 |     let d = b;
+                  4 |     let e = 0;
+                  5 |     let f = 1;
+                > 6 |     c + d
+                  7 | }
+            " }
+        );
+        let mutant = &mutants[0];
+        assert_eq!(mutant.genre, Genre::FnValue);
+        assert_eq!(
+            mutant.source_context(0),
+            indoc! { "
+                  1 | fn add(a: u32, b: u32) -> u32 {
+                > 2 |     let c = a;
+                > 3 |     let d = b;
+                > 4 |     let e = 0;
+                > 5 |     let f = 1;
+                > 6 |     c + d
+            " }
+        );
+    }
+
     #[test]
     fn mutate_factorial() -> Result<()> {
         let temp = copy_of_testdata("factorial");
@@ -421,6 +815,47 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn fnv1a_64_check_values() {
+        assert_eq!(fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn mutant_ids_are_unique_and_survive_line_drift() {
+        let code = indoc! { "
+            fn add(a: u32, b: u32, c: u32) -> u32 {
+                a + b + c
+            }
+        " };
+        let ids = mutate_source_str(code, &Options::default())
+            .unwrap()
+            .iter()
+            .map(Mutant::id)
+            .collect_vec();
+        assert!(ids.iter().all(|id| id.len() == 16));
+        assert_eq!(ids.iter().unique().count(), ids.len(), "ids are unique");
+
+        let moved_code = format!("// A new comment\n\n{code}");
+        let moved_ids = mutate_source_str(&moved_code, &Options::default())
+            .unwrap()
+            .iter()
+            .map(Mutant::id)
+            .collect_vec();
+        assert_eq!(moved_ids, ids, "ids don't change when the function moves");
+
+        let changed_code = code.replace("a + b", "b + a");
+        let changed_ids = mutate_source_str(&changed_code, &Options::default())
+            .unwrap()
+            .iter()
+            .map(Mutant::id)
+            .collect_vec();
+        assert_ne!(
+            changed_ids[0], ids[0],
+            "editing the function body changes its id"
+        );
+    }
+
     fn strip_trailing_space(s: &str) -> String {
         // Split on \n so that we retain empty lines etc
         s.split('\n').map(str::trim_end).join("\n")
@@ -10,41 +10,69 @@
 
 #![warn(clippy::pedantic)]
 
+use std::collections::HashMap;
 use std::env;
 #[cfg(test)]
 use std::ffi::OsString;
 use std::time::Duration;
 
+use anyhow::{anyhow, ensure};
 use camino::{Utf8Path, Utf8PathBuf};
 use globset::GlobSet;
-use regex::RegexSet;
+use regex::{Regex, RegexSet};
 use serde::Deserialize;
 use strum::{Display, EnumString};
 use syn::Expr;
 use tracing::warn;
 
-use crate::config::Config;
-use crate::glob::build_glob_set;
-use crate::mutant::Mutant;
-use crate::{Args, BaselineStrategy, Context, Phase, Result, ValueEnum};
+use crate::cfg::TargetCfg;
+use crate::config::{Config, CustomMutation, PackageConfig};
+use crate::glob::{build_glob_set, read_glob_file};
+use crate::mutant::{Genre, Mutant, DEFAULT_DIFF_CONTEXT};
+use crate::{Args, BaselineStrategy, Context, Features, Phase, Result, ValueEnum};
 
 /// Options for mutation testing, based on both command-line arguments and the
 /// config file.
+///
+/// New fields may be added in future versions, so library users should start from
+/// [`Options::default`] and set the fields they need.
 #[derive(Default, Debug, Clone)]
 #[allow(clippy::struct_excessive_bools)]
+#[non_exhaustive]
 pub struct Options {
     /// Run tests in an unmutated tree?
     pub baseline: BaselineStrategy,
 
+    /// Generate mutants in code under `#[cfg]` attributes that are false on this platform.
+    pub all_platforms: bool,
+
+    /// Copy the contents of this target directory into each build directory.
+    pub baseline_from: Option<Utf8PathBuf>,
+
     /// Turn off all lints.
     pub cap_lints: bool,
 
     /// Don't run the tests, just see if each mutant builds.
     pub check_only: bool,
 
+    /// Copy dependency artifacts from the source tree's `target` to build directories.
+    pub copy_target_deps: bool,
+
     /// Copy `.git` and other VCS directories to build directories.
     pub copy_vcs: bool,
 
+    /// Test caught mutants again, and count them as missed unless they're caught again.
+    pub confirm_caught: bool,
+
+    /// Run doctests with `cargo test --doc` after the other tests pass.
+    pub doctests: bool,
+
+    /// Stop testing after the first missed mutant.
+    pub fail_fast: bool,
+
+    /// Stop after the baseline if `cargo test` passes without running any tests.
+    pub fail_on_no_tests: bool,
+
     /// Don't copy files matching gitignore patterns to build directories.
     pub gitignore: bool,
 
@@ -60,6 +88,15 @@ pub struct Options {
     /// Don't delete scratch directories.
     pub leak_dirs: bool,
 
+    /// Keep `-D warnings` and similar flags from `RUSTFLAGS` and `RUSTDOCFLAGS` in the environment.
+    pub respect_rustflags: bool,
+
+    /// Run the baseline tests this many more times, to detect flaky tests.
+    pub baseline_retries: usize,
+
+    /// Additional flags to pass to rustc, after any inherited from the environment.
+    pub rustflags: Vec<String>,
+
     /// The time limit for test tasks, if set.
     ///
     /// If this is not set by the user it's None, in which case there is no time limit
@@ -88,9 +125,15 @@ pub struct Options {
     /// The minimum test timeout, as a floor on the autoset value.
     pub minimum_test_timeout: Duration,
 
+    /// The maximum build and test timeout, as a ceiling on autoset values.
+    pub max_timeout: Option<Duration>,
+
     pub print_caught: bool,
     pub print_unviable: bool,
 
+    /// Show the source around each mutant in `--list` text output.
+    pub print_mutant_context: bool,
+
     pub show_times: bool,
 
     /// Show logs even from mutants that were caught, or source/unmutated builds.
@@ -105,23 +148,66 @@ pub struct Options {
     /// interesting results.
     pub shuffle: bool,
 
+    /// The seed for the random order of mutants, and for a random sample with `--max-mutants`.
+    ///
+    /// This is chosen randomly unless it's given with `--shuffle-seed`.
+    pub shuffle_seed: u64,
+
+    /// Test the mutants that were fastest in previous runs first, after shuffling.
+    ///
+    /// This is on unless a specific order was requested with `--shuffle`, `--shuffle-seed`,
+    /// or `--no-shuffle`.
+    pub fastest_first: bool,
+
+    /// Project-specific mutations of calls to particular functions or macros.
+    pub custom_mutations: Vec<CustomMutation>,
+
     /// Don't mutate arguments to functions or methods matching any of these name.
     ///
     /// This matches as a string against the last component of the path, so should not include
     /// `::`.
     pub skip_calls: Vec<String>,
 
+    /// Don't generate `()` replacements for functions returning unit whose body is a
+    /// single expression.
+    pub skip_unit_returns: bool,
+
+    /// Don't replace the body of `fn main` at the top of a binary target.
+    pub skip_main: bool,
+
+    /// Only generate mutants in functions that are part of the public API.
+    pub only_public: bool,
+
     /// Cargo profile.
     pub profile: Option<String>,
 
+    /// Regexes of function names, with the factor by which to scale the test timeout
+    /// for mutants in those functions.
+    pub slow_functions: Vec<(Regex, f64)>,
+
+    /// Target triple for cargo's `--target`.
+    pub target: Option<String>,
+
+    /// The platform against which `#[cfg]` attributes are evaluated.
+    pub target_cfg: TargetCfg,
+
     /// Additional arguments for every cargo invocation.
     pub additional_cargo_args: Vec<String>,
 
     /// Additional arguments to `cargo test`.
     pub additional_cargo_test_args: Vec<String>,
 
-    /// Selection of features for cargo.
-    pub features: super::Features,
+    /// Selection of features for cargo, from both the command line and config.
+    pub features: Features,
+
+    /// Generate only mutants of these genres; if empty, all the default genres are generated.
+    pub genres: Vec<Genre>,
+
+    /// Don't generate mutants of these genres, even if they're requested in `genres`.
+    pub excluded_genres: Vec<Genre>,
+
+    /// Don't generate the default genres when `genres` is empty.
+    pub no_default_genres: bool,
 
     /// Files to examine.
     pub examine_globset: Option<GlobSet>,
@@ -135,26 +221,85 @@ pub struct Options {
     /// Mutants to skip, as a regexp matched against the full name.
     pub exclude_names: RegexSet,
 
+    /// Mutants to examine, as a regexp matched against the mutant id.
+    pub examine_ids: RegexSet,
+
+    /// Mutants to skip, as a regexp matched against the mutant id.
+    pub exclude_ids: RegexSet,
+
+    /// Examine only mutants in functions with these names, matched against the
+    /// end of the qualified function name.
+    pub examine_functions: Vec<String>,
+
     /// Create `mutants.out` within this directory (by default, the source directory).
     pub output_in_dir: Option<Utf8PathBuf>,
 
     /// Run this many `cargo build` or `cargo test` tasks in parallel.
     pub jobs: Option<usize>,
 
+    /// Allow this many parallel jobs within each cargo build, with cargo's `--jobs`.
+    pub build_jobs: Option<u32>,
+
+    /// Hard-link dependency artifacts into each parallel build directory, and build
+    /// in only one directory at a time.
+    pub share_deps: bool,
+
     /// Insert these values as errors from functions returning `Result`.
     pub error_values: Vec<String>,
 
     /// Show ANSI colors.
     pub colors: Colors,
 
-    /// List mutants in json, etc.
-    pub emit_json: bool,
+    /// Format for listing mutants, files, and skipped items.
+    pub list_format: ListFormat,
+
+    /// Format of results written to stdout while testing.
+    pub output_format: OutputFormat,
+
+    /// Print only the final summary while testing, not a line for each mutant.
+    pub summary_only: bool,
+
+    /// After testing, list the missed mutants grouped by function.
+    pub missed_by_function: bool,
 
     /// Emit diffs showing just what changed.
     pub emit_diffs: bool,
 
+    /// Lines of context around the change in mutant diffs, if not the default.
+    pub diff_context: Option<usize>,
+
     /// The tool to use to run tests.
     pub test_tool: TestTool,
+
+    /// A shell command to run tests, instead of the test tool.
+    pub test_command: Option<String>,
+
+    /// Build a nextest archive in the build phase and run the tests from it.
+    ///
+    /// Only used when nextest is the test tool: see `Options::uses_nextest_archive`.
+    pub nextest_archive: bool,
+
+    /// Options for mutants in packages that have their own `[package.metadata.mutants]`
+    /// config, by package name.
+    pub per_package: HashMap<String, Options>,
+
+    /// Don't read `[package.metadata.mutants]` config, because of `--no-config`.
+    pub(crate) no_package_config: bool,
+
+    /// The arguments and workspace config that are combined with each package's own
+    /// config when the workspace is discovered.
+    pub(crate) package_args: PackageArgs,
+}
+
+/// The arguments that can be combined with a `[package.metadata.mutants]` config,
+/// along with the workspace config that it overrides.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct PackageArgs {
+    error: Vec<String>,
+    exclude_globs: Vec<String>,
+    skip_calls: Vec<String>,
+    skip_calls_defaults: Option<bool>,
+    config: Config,
 }
 
 /// Which packages should be tested for a given mutant?
@@ -171,6 +316,28 @@ pub enum TestPackages {
     Named(Vec<String>),
 }
 
+/// Format of the output from `--list` and similar options.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// One item per line.
+    #[default]
+    Text,
+    /// A JSON array.
+    Json,
+    /// A Markdown table, for mutants; other lists are written as text.
+    Markdown,
+}
+
+/// Format of the results written to stdout while testing mutants.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable lines describing each outcome.
+    #[default]
+    Text,
+    /// Each scenario outcome as one line of JSON, in the same form as in `outcomes.json`.
+    JsonLines,
+}
+
 /// Choice of tool to use to run tests.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumString, Display, Deserialize)]
 #[strum(serialize_all = "snake_case")]
@@ -184,6 +351,42 @@ pub enum TestTool {
     Nextest,
 }
 
+/// Compile the `slow_functions` config into regexes with their timeout multipliers.
+fn slow_functions(config: &Config) -> Result<Vec<(Regex, f64)>> {
+    config
+        .slow_functions
+        .iter()
+        .map(|slow| {
+            ensure!(
+                slow.timeout_multiplier.is_finite() && slow.timeout_multiplier > 0.0,
+                "slow_functions timeout_multiplier for {:?} must be a positive number",
+                slow.match_re
+            );
+            let regex = Regex::new(&slow.match_re)
+                .with_context(|| format!("Invalid slow_functions match {:?}", slow.match_re))?;
+            Ok((regex, slow.timeout_multiplier))
+        })
+        .collect()
+}
+
+/// Combine the names of calls to skip from the arguments and config, and the defaults.
+fn skip_calls(package_args: &PackageArgs, config: &Config) -> Vec<String> {
+    let mut skip_calls: Vec<String> = package_args
+        .skip_calls
+        .iter()
+        .chain(&config.skip_calls)
+        .cloned()
+        .collect();
+    if package_args
+        .skip_calls_defaults
+        .or(config.skip_calls_defaults)
+        .unwrap_or(true)
+    {
+        skip_calls.push("with_capacity".to_owned());
+    }
+    skip_calls
+}
+
 /// Join two slices into a new vector.
 fn join_slices(a: &[String], b: &[String]) -> Vec<String> {
     a.iter().chain(b).cloned().collect()
@@ -230,6 +433,7 @@ impl Colors {
 
 impl Options {
     /// Build options by merging command-line args and config file.
+    #[allow(clippy::too_many_lines)] // just a lot of options
     pub(crate) fn new(args: &Args, config: &Config) -> Result<Options> {
         if args.no_copy_target {
             warn!("--no-copy-target is deprecated and has no effect; target/ is never copied");
@@ -241,6 +445,9 @@ impl Options {
                 .unwrap_or(20f64),
         );
 
+        let all_platforms = args.all_platforms || config.all_platforms;
+        let target = args.target.as_ref().or(config.target.as_ref()).cloned();
+
         // If either command line argument is set, it overrides the config.
         let test_package = if args.test_workspace == Some(true) {
             TestPackages::Workspace
@@ -260,20 +467,18 @@ impl Options {
             TestPackages::Mutated
         };
 
-        let mut skip_calls: Vec<String> = args
-            .skip_calls
-            .iter()
-            .flat_map(|s| s.split(','))
-            .map(ToString::to_string)
-            .chain(config.skip_calls.iter().cloned())
-            .collect();
-        if args
-            .skip_calls_defaults
-            .or(config.skip_calls_defaults)
-            .unwrap_or(true)
-        {
-            skip_calls.push("with_capacity".to_owned());
-        }
+        let package_args = PackageArgs {
+            error: args.error.clone(),
+            exclude_globs: arg_globs(&args.exclude, &args.exclude_from)?,
+            skip_calls: args
+                .skip_calls
+                .iter()
+                .flat_map(|s| s.split(','))
+                .map(ToString::to_string)
+                .collect(),
+            skip_calls_defaults: args.skip_calls_defaults,
+            config: config.clone(),
+        };
 
         let options = Options {
             additional_cargo_args: join_slices(&args.cargo_arg, &config.additional_cargo_args),
@@ -281,7 +486,9 @@ impl Options {
                 &args.cargo_test_args,
                 &config.additional_cargo_test_args,
             ),
+            all_platforms,
             baseline: args.baseline,
+            baseline_from: args.baseline_from.clone(),
             build_timeout: args.build_timeout.map(Duration::from_secs_f64),
             build_timeout_multiplier: args
                 .build_timeout_multiplier
@@ -289,38 +496,128 @@ impl Options {
             cap_lints: args.cap_lints.unwrap_or(config.cap_lints),
             check_only: args.check,
             colors: args.colors,
+            confirm_caught: args.confirm_caught,
+            copy_target_deps: args
+                .copy_target_deps
+                .or(config.copy_target_deps)
+                .unwrap_or(false),
             copy_vcs: args.copy_vcs.or(config.copy_vcs).unwrap_or(false),
-            emit_json: args.json,
+            list_format: if args.json {
+                ListFormat::Json
+            } else {
+                args.format.unwrap_or_default()
+            },
             emit_diffs: args.diff,
+            diff_context: args.diff_context,
             error_values: join_slices(&args.error, &config.error_values),
             examine_names: RegexSet::new(or_slices(&args.examine_re, &config.examine_re))
                 .context("Failed to compile examine_re regex")?,
+            examine_functions: args.function.clone(),
             exclude_names: RegexSet::new(or_slices(&args.exclude_re, &config.exclude_re))
                 .context("Failed to compile exclude_re regex")?,
-            examine_globset: build_glob_set(or_slices(&args.file, &config.examine_globs))?,
-            exclude_globset: build_glob_set(or_slices(&args.exclude, &config.exclude_globs))?,
-            features: args.features.clone(),
+            examine_ids: RegexSet::new(or_slices(&args.re_id, &config.examine_re_id))
+                .context("Failed to compile re_id regex")?,
+            exclude_ids: RegexSet::new(or_slices(&args.exclude_re_id, &config.exclude_re_id))
+                .context("Failed to compile exclude_re_id regex")?,
+            examine_globset: build_glob_set(or_slices(
+                &arg_globs(&args.file, &args.files_from)?,
+                &config.examine_globs,
+            ))?,
+            exclude_globset: build_glob_set(or_slices(
+                &package_args.exclude_globs,
+                &config.exclude_globs,
+            ))?,
+            doctests: args.doctests || config.doctests,
+            fail_fast: args.fail_fast,
+            fail_on_no_tests: args.fail_on_no_tests.unwrap_or(config.fail_on_no_tests),
+            baseline_retries: args.retries,
+            features: Features {
+                features: join_slices(&config.features, &args.features.features),
+                no_default_features: !args.default_features
+                    && (args.features.no_default_features || config.no_default_features),
+                all_features: !args.no_all_features
+                    && (args.features.all_features || config.all_features),
+            },
+            genres: args.genre.clone(),
+            excluded_genres: or_slices(&args.exclude_genre, &config.exclude_genres)
+                .iter()
+                .map(|name| Genre::from_name(name))
+                .collect::<Result<_>>()
+                .context("Invalid exclude_genre")?,
+            no_default_genres: args.no_default_mutations,
             gitignore: args.gitignore,
             in_place: args.in_place,
             jobs: args.jobs,
+            build_jobs: args.build_jobs,
             jobserver: args.jobserver,
             jobserver_tasks: args.jobserver_tasks,
             leak_dirs: args.leak_dirs,
+            max_timeout: args
+                .max_timeout
+                .or(config.max_timeout)
+                .map(Duration::from_secs_f64),
             minimum_test_timeout,
+            nextest_archive: args.nextest_archive || config.nextest_archive,
+            only_public: args.only_public || config.only_public,
             output_in_dir: args.output.clone().or(config.output.clone()),
+            output_format: args.output_format.unwrap_or_default(),
+            per_package: HashMap::new(),
             print_caught: args.caught,
+            print_mutant_context: args.print_mutant_context,
             print_unviable: args.unviable,
             profile: args.profile.as_ref().or(config.profile.as_ref()).cloned(),
+            target: target.clone(),
+            target_cfg: match &target {
+                Some(target) if !all_platforms => TargetCfg::for_target(target),
+                _ => TargetCfg::Host,
+            },
+            respect_rustflags: args.respect_rustflags || config.respect_rustflags,
+            rustflags: config
+                .rustflags
+                .iter()
+                .cloned()
+                .chain(
+                    args.rustflags
+                        .iter()
+                        .flat_map(|s| s.split_whitespace().map(str::to_owned)),
+                )
+                .collect(),
+            share_deps: args.share_deps || config.share_deps,
+            slow_functions: slow_functions(config)?,
+            summary_only: args.summary_only,
+            missed_by_function: args.missed_by_function,
             shuffle: !args.no_shuffle,
+            shuffle_seed: args.shuffle_seed.unwrap_or_else(|| fastrand::u64(..)),
+            fastest_first: !args.shuffle && !args.no_shuffle && args.shuffle_seed.is_none(),
             show_line_col: args.line_col,
             show_times: !args.no_times,
             show_all_logs: args.all_logs,
-            skip_calls,
+            custom_mutations: config.custom_mutations.clone(),
+            skip_calls: skip_calls(&package_args, config),
+            skip_unit_returns: args.skip_unit_returns || config.skip_unit_returns,
+            skip_main: args.skip_main || config.skip_main,
             test_package,
             test_timeout: args.timeout.map(Duration::from_secs_f64),
             test_timeout_multiplier: args.timeout_multiplier.or(config.timeout_multiplier),
             test_tool: args.test_tool.or(config.test_tool).unwrap_or_default(),
+            test_command: args.test_command.clone().or(config.test_command.clone()),
+            no_package_config: args.no_config,
+            // Last, because it moves `package_args`.
+            package_args,
         };
+        if options.no_default_genres
+            && options
+                .genres
+                .iter()
+                .all(|genre| options.excluded_genres.contains(genre))
+        {
+            return Err(anyhow!(
+                "--no-default-mutations requires at least one --genre that isn't excluded, or no mutants would be generated"
+            ));
+        }
+        if options.nextest_archive && options.test_tool != TestTool::Nextest {
+            warn!("--nextest-archive has no effect unless the test tool is nextest");
+        }
         if let Some(jobs) = options.jobs {
             if jobs >= 8 {
                 warn!("--jobs={jobs} is probably too high and may overload your machine: each job runs a separate `cargo` process, and cargo may internally start many threads and subprocesses; values <= 8 are usually safe");
@@ -338,7 +635,7 @@ impl Options {
     }
 
     #[cfg(test)]
-    pub fn from_args(args: &Args) -> Result<Options> {
+    pub(crate) fn from_args(args: &Args) -> Result<Options> {
         Options::new(args, &Config::default())
     }
 
@@ -356,7 +653,7 @@ impl Options {
     }
 
     /// Which phases to run for each mutant.
-    pub fn phases(&self) -> &[Phase] {
+    pub(crate) fn phases(&self) -> &[Phase] {
         if self.check_only {
             &[Phase::Check]
         } else {
@@ -364,6 +661,46 @@ impl Options {
         }
     }
 
+    /// True if tests should be built into a nextest archive and run from it.
+    ///
+    /// This needs a build phase to make the archive before the tests run, and isn't
+    /// used with a custom test command.
+    pub(crate) fn uses_nextest_archive(&self) -> bool {
+        self.nextest_archive
+            && self.test_tool == TestTool::Nextest
+            && self.test_command.is_none()
+            && self.phases().contains(&Phase::Build)
+    }
+
+    /// Add options for mutants in a package that has its own config, based on the same
+    /// arguments and workspace config as these options.
+    ///
+    /// Only the keys in [`PackageConfig`] can differ between packages.
+    pub(crate) fn add_package_config(
+        &mut self,
+        package_name: &str,
+        package_config: &PackageConfig,
+    ) -> Result<()> {
+        let package_args = &self.package_args;
+        let config = package_args.config.with_package_config(package_config);
+        let mut package_options = self.clone();
+        package_options.per_package.clear();
+        package_options.error_values = join_slices(&package_args.error, &config.error_values);
+        package_options.exclude_globset = build_glob_set(or_slices(
+            &package_args.exclude_globs,
+            &config.exclude_globs,
+        ))?;
+        package_options.skip_calls = skip_calls(package_args, &config);
+        self.per_package
+            .insert(package_name.to_owned(), package_options);
+        Ok(())
+    }
+
+    /// Return the options for mutants in the named package.
+    pub(crate) fn for_package(&self, package_name: &str) -> &Options {
+        self.per_package.get(package_name).unwrap_or(self)
+    }
+
     /// Return the syn ASTs for the error values, which should be inserted as return values
     /// from functions returning `Result`.
     pub(crate) fn parsed_error_exprs(&self) -> Result<Vec<Expr>> {
@@ -379,7 +716,7 @@ impl Options {
     ///
     /// That is: it matches the examine globset (if specified) and does not match the exclude globset
     /// (if specified).
-    pub fn allows_source_file_path(&self, path: &Utf8Path) -> bool {
+    pub(crate) fn allows_source_file_path(&self, path: &Utf8Path) -> bool {
         // TODO: Use Option::is_none_or when MSRV>1.80
         self.examine_globset
             .as_ref()
@@ -390,14 +727,75 @@ impl Options {
                 .is_some_and(|g| g.is_match(path))
     }
 
+    /// True if mutants of this genre should be generated.
+    pub(crate) fn generates_genre(&self, genre: Genre) -> bool {
+        if self.excluded_genres.contains(&genre) {
+            false
+        } else if self.genres.is_empty() {
+            !self.no_default_genres && genre.is_default()
+        } else {
+            self.genres.contains(&genre)
+        }
+    }
+
+    /// Return the diff for a mutant, with the configured amount of context.
+    pub(crate) fn mutant_diff(&self, mutant: &Mutant, mutated_code: &str) -> String {
+        mutant.diff_with_context(
+            mutated_code,
+            self.diff_context.unwrap_or(DEFAULT_DIFF_CONTEXT),
+        )
+    }
+
     /// True if the options allow this mutant to be tested.
-    pub fn allows_mutant(&self, mutant: &Mutant) -> bool {
+    pub(crate) fn allows_mutant(&self, mutant: &Mutant) -> bool {
         let name = mutant.name(true);
         (self.examine_names.is_empty() || self.examine_names.is_match(&name))
             && (self.exclude_names.is_empty() || !self.exclude_names.is_match(&name))
+            && self.allows_mutant_id(mutant)
+            && (self.examine_functions.is_empty()
+                || mutant.function.as_ref().is_some_and(|function| {
+                    self.examine_functions
+                        .iter()
+                        .any(|f| function_name_matches(&function.function_name, f))
+                }))
+    }
+
+    /// True if a mutant loaded with `--mutants-from` passes the same filters that are
+    /// applied during discovery: its genre, source file, name, and id.
+    pub(crate) fn allows_loaded_mutant(&self, mutant: &Mutant) -> bool {
+        self.generates_genre(mutant.genre)
+            && self.allows_source_file_path(&mutant.source_file.tree_relative_path)
+            && self.allows_mutant(mutant)
+    }
+
+    /// True if the id filters allow this mutant; the id is only computed if there are
+    /// any id filters.
+    fn allows_mutant_id(&self, mutant: &Mutant) -> bool {
+        if self.examine_ids.is_empty() && self.exclude_ids.is_empty() {
+            return true;
+        }
+        let id = mutant.id();
+        (self.examine_ids.is_empty() || self.examine_ids.is_match(&id))
+            && (self.exclude_ids.is_empty() || !self.exclude_ids.is_match(&id))
     }
 }
 
+/// True if `full_name`, like `config::Config::parse`, is `name` or ends with `::name`.
+fn function_name_matches(full_name: &str, name: &str) -> bool {
+    full_name
+        .strip_suffix(name)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with("::"))
+}
+
+/// Combine globs given inline on the command line with those read from files.
+fn arg_globs(inline: &[String], from_files: &[Utf8PathBuf]) -> Result<Vec<String>> {
+    let mut globs = inline.to_vec();
+    for path in from_files {
+        globs.extend(read_glob_file(path)?);
+    }
+    Ok(globs)
+}
+
 /// If the first slices is non-empty, return that, otherwise the second.
 fn or_slices<'a: 'c, 'b: 'c, 'c, T>(a: &'a [T], b: &'b [T]) -> &'c [T] {
     if a.is_empty() {
@@ -438,6 +836,47 @@ mod test {
         assert_eq!(options.test_tool, TestTool::Nextest);
     }
 
+    #[test]
+    fn doctests_from_args_or_config() {
+        let options = Options::from_args(&Args::parse_from(["mutants"])).unwrap();
+        assert!(!options.doctests);
+
+        let options = Options::from_args(&Args::parse_from(["mutants", "--doctests"])).unwrap();
+        assert!(options.doctests);
+
+        let config = Config::from_str("doctests = true").unwrap();
+        let options = Options::new(&Args::parse_from(["mutants"]), &config).unwrap();
+        assert!(options.doctests);
+    }
+
+    #[test]
+    fn fail_on_no_tests_from_args_or_config() {
+        let options = Options::from_args(&Args::parse_from(["mutants"])).unwrap();
+        assert!(!options.fail_on_no_tests);
+
+        let args = Args::parse_from(["mutants", "--fail-on-no-tests=true"]);
+        assert!(Options::from_args(&args).unwrap().fail_on_no_tests);
+
+        let config = Config::from_str("fail_on_no_tests = true").unwrap();
+        let options = Options::new(&Args::parse_from(["mutants"]), &config).unwrap();
+        assert!(options.fail_on_no_tests);
+
+        let args = Args::parse_from(["mutants", "--fail-on-no-tests=false"]);
+        assert!(!Options::new(&args, &config).unwrap().fail_on_no_tests);
+    }
+
+    #[test]
+    fn retries_and_confirm_caught_args() {
+        let options = Options::from_args(&Args::parse_from(["mutants"])).unwrap();
+        assert_eq!(options.baseline_retries, 0);
+        assert!(!options.confirm_caught);
+
+        let args = Args::parse_from(["mutants", "--retries=3", "--confirm-caught"]);
+        let options = Options::from_args(&args).unwrap();
+        assert_eq!(options.baseline_retries, 3);
+        assert!(options.confirm_caught);
+    }
+
     #[test]
     fn options_from_baseline_arg() {
         let args = Args::parse_from(["mutants", "--baseline", "skip"]);
@@ -471,6 +910,10 @@ mod test {
         let options = Options::new(&args, &config).unwrap();
         assert_eq!(options.minimum_test_timeout, Duration::from_secs(60));
 
+        let args = Args::parse_from(["mutants", "--max-timeout=600"]);
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(options.max_timeout, Some(Duration::from_secs(600)));
+
         let args = Args::parse_from(["mutants", "--build-timeout=3.0"]);
         let options = Options::new(&args, &config).unwrap();
         assert_eq!(options.build_timeout, Some(Duration::from_secs(3)));
@@ -525,6 +968,8 @@ mod test {
         let config = indoc! { r#"
             test_tool = "nextest"
             cap_lints = true
+            skip_unit_returns = true
+            skip_main = true
         "#};
         let mut config_file = NamedTempFile::new().unwrap();
         config_file.write_all(config.as_bytes()).unwrap();
@@ -533,6 +978,59 @@ mod test {
         let options = Options::new(&args, &config).unwrap();
         assert_eq!(options.test_tool, TestTool::Nextest);
         assert!(options.cap_lints);
+        assert!(options.skip_unit_returns);
+        assert!(options.skip_main);
+    }
+
+    #[test]
+    fn custom_mutations_from_config_are_validated() {
+        let config = Config::from_str(indoc! { r#"
+            [[custom_mutations]]
+            match = "log::info!"
+            replace_with = "log::error!({args})"
+        "#})
+        .unwrap();
+        let options = Options::new(&Args::try_parse_from(["mutants"]).unwrap(), &config).unwrap();
+        assert_eq!(options.custom_mutations.len(), 1);
+        assert_eq!(options.custom_mutations[0].path_segments(), ["log", "info"]);
+        assert_eq!(
+            options.custom_mutations[0].replacement("x"),
+            "log::error!(x)"
+        );
+
+        let err = Config::from_str(indoc! { r#"
+            [[custom_mutations]]
+            match = "not a path"
+            replace_with = "()"
+        "#})
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "custom mutation match \"not a path\" is not a function or macro path"
+        );
+    }
+
+    #[test]
+    fn package_config_replaces_config_file_values() {
+        let args = Args::try_parse_from(["mutants", "--error", "arg_error()"]).unwrap();
+        let config = Config::from_str(indoc! { r#"
+            error_values = ["config_error()"]
+            skip_calls = ["config_call"]
+        "#})
+        .unwrap();
+        let mut options = Options::new(&args, &config).unwrap();
+        let package_config = PackageConfig {
+            error_values: vec!["package_error()".to_owned()],
+            ..Default::default()
+        };
+        options
+            .add_package_config("special", &package_config)
+            .unwrap();
+        let special = options.for_package("special");
+        assert_eq!(special.error_values, ["arg_error()", "package_error()"]);
+        assert_eq!(special.skip_calls, ["config_call", "with_capacity"]);
+        let other = options.for_package("other");
+        assert_eq!(other.error_values, ["arg_error()", "config_error()"]);
     }
 
     #[test]
@@ -554,6 +1052,77 @@ mod test {
         assert!(!options.features.all_features);
     }
 
+    #[test]
+    fn features_from_config_are_extended_by_args() {
+        let config = Config::from_str(indoc! { r#"
+            features = ["from_config"]
+            no_default_features = true
+        "#})
+        .unwrap();
+        let args = Args::parse_from(["mutants"]);
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(options.features.features, ["from_config"]);
+        assert!(options.features.no_default_features);
+        assert!(!options.features.all_features);
+
+        let args = Args::parse_from(["mutants", "--features=from_args", "--all-features"]);
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(options.features.features, ["from_config", "from_args"]);
+        assert!(options.features.no_default_features);
+        assert!(options.features.all_features);
+    }
+
+    #[test]
+    fn feature_flags_from_config_are_overridden_by_args() {
+        let config = Config::from_str(indoc! { "
+            all_features = true
+            no_default_features = true
+        "})
+        .unwrap();
+        let args = Args::parse_from(["mutants", "--default-features", "--no-all-features"]);
+        let options = Options::new(&args, &config).unwrap();
+        assert!(!options.features.no_default_features);
+        assert!(!options.features.all_features);
+
+        // The last of a pair of opposing flags wins.
+        let args = Args::parse_from([
+            "mutants",
+            "--default-features",
+            "--no-default-features",
+            "--all-features",
+            "--no-all-features",
+        ]);
+        let options = Options::new(&args, &Config::default()).unwrap();
+        assert!(options.features.no_default_features);
+        assert!(!options.features.all_features);
+    }
+
+    #[test]
+    fn rustflags_from_config_and_args() {
+        let config = Config::from_str(indoc! { r#"
+            rustflags = ["--cfg", "from_config"]
+        "#})
+        .unwrap();
+        let args = Args::parse_from([
+            "mutants",
+            "--rustflags=-C target-cpu=native",
+            "--rustflags",
+            "--cfg from_args",
+        ]);
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(
+            options.rustflags,
+            [
+                "--cfg",
+                "from_config",
+                "-C",
+                "target-cpu=native",
+                "--cfg",
+                "from_args"
+            ]
+        );
+    }
+
     #[test]
     fn no_default_features_arg() {
         let args = Args::try_parse_from([
@@ -685,6 +1254,37 @@ mod test {
         assert_eq!(options.profile.unwrap(), "mutants");
     }
 
+    #[test]
+    fn target_from_args_overrides_config() {
+        let config = Config::from_str(r#"target = "thumbv7em-none-eabi""#).unwrap();
+        let args = Args::parse_from(["mutants"]);
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(options.target.as_deref(), Some("thumbv7em-none-eabi"));
+
+        let args = Args::parse_from(["mutants", "--target=wasm32-wasip1"]);
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(options.target.as_deref(), Some("wasm32-wasip1"));
+    }
+
+    #[test]
+    fn cfgs_are_evaluated_for_target() {
+        let options = Options::from_arg_strs(["mutants"]);
+        assert_eq!(options.target_cfg, TargetCfg::Host);
+
+        let options = Options::from_arg_strs(["mutants", "--target=thumbv7em-none-eabi"]);
+        assert_eq!(
+            options.target_cfg,
+            TargetCfg::Target {
+                os: Some("none".to_owned()),
+                families: Vec::new()
+            }
+        );
+
+        let options =
+            Options::from_arg_strs(["mutants", "--target=thumbv7em-none-eabi", "--all-platforms"]);
+        assert_eq!(options.target_cfg, TargetCfg::Host);
+    }
+
     #[test]
     fn profile_from_config() {
         let args = Args::try_parse_from(["mutants", "-j3"]).unwrap();
@@ -700,6 +1300,28 @@ mod test {
         assert_eq!(options.profile.unwrap(), "mutants");
     }
 
+    #[test]
+    fn all_platforms_from_arg_or_config() {
+        assert!(!Options::from_arg_strs(["mutants"]).all_platforms);
+        assert!(Options::from_arg_strs(["mutants", "--all-platforms"]).all_platforms);
+        let args = Args::parse_from(["mutants"]);
+        let config = Config::from_str("all_platforms = true").unwrap();
+        assert!(Options::new(&args, &config).unwrap().all_platforms);
+    }
+
+    #[test]
+    fn nextest_archive_from_config() {
+        let args = Args::parse_from(["mutants"]);
+        let config = Config::from_str(indoc! { r#"
+            test_tool = "nextest"
+            nextest_archive = true
+        "#})
+        .unwrap();
+        let options = Options::new(&args, &config).unwrap();
+        assert!(options.nextest_archive);
+        assert!(options.uses_nextest_archive());
+    }
+
     #[test]
     fn test_workspace_arg_true() {
         let args = Args::parse_from(["mutants", "--test-workspace=true"]);
@@ -870,6 +1492,25 @@ mod test {
         assert_eq!(options.skip_calls, ["x", "y", "with_capacity"]);
     }
 
+    #[test]
+    fn copy_target_deps() {
+        let options = Options::new(&Args::parse_from(["mutants"]), &Config::default()).unwrap();
+        assert!(!options.copy_target_deps);
+
+        let args = Args::parse_from(["mutants", "--copy-target-deps=true"]);
+        let options = Options::new(&args, &Config::default()).unwrap();
+        assert!(options.copy_target_deps);
+
+        let args = Args::parse_from(["mutants", "--copy-target-deps=false"]);
+        let config = Config::from_str("copy_target_deps = true").unwrap();
+        let options = Options::new(&args, &config).unwrap();
+        assert!(!options.copy_target_deps);
+
+        let args = Args::parse_from(["mutants"]);
+        let options = Options::new(&args, &config).unwrap();
+        assert!(options.copy_target_deps);
+    }
+
     #[test]
     fn copy_vcs() {
         let args = Args::parse_from(["mutants", "--copy-vcs=true"]);
@@ -892,4 +1533,232 @@ mod test {
         let options = Options::new(&args, &config).unwrap();
         assert!(!options.copy_vcs);
     }
+
+    #[test]
+    fn genres_from_args() {
+        let options = Options::from_arg_strs(["mutants"]);
+        assert!(options.genres.is_empty());
+        assert!(options.generates_genre(Genre::FnValue));
+        assert!(options.generates_genre(Genre::BinaryOperator));
+
+        let options = Options::from_arg_strs(["mutants", "--genre=fn-value,unary-operator"]);
+        assert_eq!(options.genres, [Genre::FnValue, Genre::UnaryOperator]);
+        assert!(options.generates_genre(Genre::FnValue));
+        assert!(!options.generates_genre(Genre::BinaryOperator));
+
+        let options = Options::from_arg_strs(["mutants", "--only-genre", "function-value"]);
+        assert_eq!(options.genres, [Genre::FnValue]);
+    }
+
+    #[test]
+    fn literal_and_statement_genres_are_only_generated_when_requested() {
+        let options = Options::from_arg_strs(["mutants"]);
+        assert!(!options.generates_genre(Genre::Literal));
+        assert!(!options.generates_genre(Genre::Statement));
+
+        let options = Options::from_arg_strs(["mutants", "--genre=statement,fn-value"]);
+        assert!(options.generates_genre(Genre::Statement));
+        assert!(options.generates_genre(Genre::FnValue));
+        assert!(!options.generates_genre(Genre::Literal));
+    }
+
+    #[test]
+    fn exclude_genres_from_args() {
+        let options =
+            Options::from_arg_strs(["mutants", "--exclude-genre", "binary_operator,Cast"]);
+        assert_eq!(
+            options.excluded_genres,
+            [Genre::BinaryOperator, Genre::Cast]
+        );
+        assert!(!options.generates_genre(Genre::BinaryOperator));
+        assert!(!options.generates_genre(Genre::Cast));
+        assert!(options.generates_genre(Genre::FnValue));
+
+        let options = Options::from_arg_strs([
+            "mutants",
+            "--genre=literal,fn-value",
+            "--exclude-genre=literal",
+        ]);
+        assert!(!options.generates_genre(Genre::Literal));
+        assert!(options.generates_genre(Genre::FnValue));
+    }
+
+    #[test]
+    fn no_default_mutations_generates_only_requested_genres() {
+        let options = Options::from_arg_strs([
+            "mutants",
+            "--no-default-mutations",
+            "--genre=binary-operator",
+        ]);
+        assert!(options.no_default_genres);
+        assert!(options.generates_genre(Genre::BinaryOperator));
+        assert!(!options.generates_genre(Genre::FnValue));
+        assert!(!options.generates_genre(Genre::Literal));
+    }
+
+    #[test]
+    fn no_default_mutations_without_genres_is_an_error() {
+        let args = Args::try_parse_from(["mutants", "--no-default-mutations"]).unwrap();
+        let err = Options::new(&args, &Config::default()).unwrap_err();
+        assert!(err.to_string().contains("requires at least one --genre"));
+
+        let args = Args::try_parse_from([
+            "mutants",
+            "--no-default-mutations",
+            "--genre=cast",
+            "--exclude-genre=cast",
+        ])
+        .unwrap();
+        assert!(Options::new(&args, &Config::default()).is_err());
+    }
+
+    #[test]
+    fn exclude_genres_from_config() {
+        let args = Args::try_parse_from(["mutants"]).unwrap();
+        let config =
+            Config::from_str(r#"exclude_genres = ["min-max", "logical_operator"]"#).unwrap();
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(
+            options.excluded_genres,
+            [Genre::MinMax, Genre::LogicalOperator]
+        );
+
+        let args = Args::try_parse_from(["mutants", "--exclude-genre=cast"]).unwrap();
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(options.excluded_genres, [Genre::Cast]);
+    }
+
+    #[test]
+    fn unknown_excluded_genre_is_an_error() {
+        let args = Args::try_parse_from(["mutants", "--exclude-genre=fn-value,bogus"]).unwrap();
+        let err = Options::new(&args, &Config::default()).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Invalid exclude_genre: Unknown genre \"bogus\"; valid genres are: fn-value, \
+            binary-operator, logical-operator, comparison-operator, unary-operator, \
+            early-return, cast, min-max, condition, bool-literal, literal, index, statement, \
+            match-arm, try-operator, const-value, method-call, compound-assign, custom"
+        );
+    }
+
+    #[test]
+    fn filter_mutants_by_id() {
+        let code = indoc! {"
+            fn a() -> bool { ok() }
+            fn b() -> bool { ok() }
+        "};
+        let all = crate::visit::mutate_source_str(code, &Options::default()).unwrap();
+        let ids = all.iter().map(Mutant::id).collect::<Vec<_>>();
+        let allowed_ids = |options: &Options| {
+            all.iter()
+                .filter(|m| options.allows_mutant(m))
+                .map(Mutant::id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            allowed_ids(&Options::from_arg_strs([
+                "mutants",
+                &format!("--re-id=^{}$", ids[1]),
+                &format!("--re-id=^{}$", ids[2]),
+            ])),
+            [ids[1].clone(), ids[2].clone()]
+        );
+        assert_eq!(
+            allowed_ids(&Options::from_arg_strs([
+                "mutants",
+                &format!("--exclude-re-id={}", ids[0]),
+            ])),
+            ids[1..]
+        );
+
+        let config = Config::from_str(&format!("exclude_re_id = [\"{}\"]", ids[3])).unwrap();
+        let args = Args::try_parse_from(["mutants"]).unwrap();
+        let options = Options::new(&args, &config).unwrap();
+        assert_eq!(allowed_ids(&options), ids[..3]);
+    }
+
+    #[test]
+    fn loaded_mutants_are_filtered_by_genre_file_and_name() {
+        let code = "fn a(x: u32) -> u32 { x + 1 }";
+        let all = crate::visit::mutate_source_str(code, &Options::default()).unwrap();
+        let names = |args: &[&str]| {
+            let options = Options::from_arg_strs(args);
+            all.iter()
+                .filter(|m| options.allows_loaded_mutant(m))
+                .map(|m| m.name(false))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&["mutants"]).len(), all.len());
+        assert_eq!(
+            names(&["mutants", "--exclude-genre=binary-operator"]),
+            [
+                "src/main.rs: replace a -> u32 with 0",
+                "src/main.rs: replace a -> u32 with 1",
+            ]
+        );
+        assert!(names(&["mutants", "--re=with 1", "--exclude-re=replace a"]).is_empty());
+        assert_eq!(
+            names(&["mutants", "--re=with -"]),
+            ["src/main.rs: replace + with - in a"]
+        );
+        assert!(names(&["mutants", "--file=src/other.rs"]).is_empty());
+        assert!(names(&["mutants", "--exclude=src/main.rs"]).is_empty());
+    }
+
+    #[test]
+    fn function_name_matches_qualified_suffix() {
+        assert!(function_name_matches("parse", "parse"));
+        assert!(function_name_matches("config::Config::parse", "parse"));
+        assert!(function_name_matches(
+            "config::Config::parse",
+            "Config::parse"
+        ));
+        assert!(!function_name_matches(
+            "config::Config::parse",
+            "fig::parse"
+        ));
+        assert!(!function_name_matches("reparse", "parse"));
+        assert!(!function_name_matches("parse", "Config::parse"));
+    }
+
+    #[test]
+    fn allow_only_mutants_in_named_functions() {
+        let code = indoc! {"
+            fn parse() -> bool { ok() }
+
+            mod config {
+                fn parse() -> bool { ok() }
+                fn check() -> bool { ok() }
+            }
+        "};
+        let all = crate::visit::mutate_source_str(code, &Options::default()).unwrap();
+        let names = |options: &Options| {
+            all.iter()
+                .filter(|m| options.allows_mutant(m))
+                .map(|m| m.name(false))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&Options::from_arg_strs(["mutants", "--function=parse"])),
+            [
+                "src/main.rs: replace parse -> bool with true",
+                "src/main.rs: replace parse -> bool with false",
+                "src/main.rs: replace config::parse -> bool with true",
+                "src/main.rs: replace config::parse -> bool with false",
+            ]
+        );
+        assert_eq!(
+            names(&Options::from_arg_strs([
+                "mutants",
+                "--function=config::parse",
+                "--function=check"
+            ])),
+            [
+                "src/main.rs: replace config::parse -> bool with true",
+                "src/main.rs: replace config::parse -> bool with false",
+                "src/main.rs: replace config::check -> bool with true",
+                "src/main.rs: replace config::check -> bool with false",
+            ]
+        );
+    }
 }
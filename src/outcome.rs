@@ -2,21 +2,24 @@
 
 //! The outcome of running a single mutation scenario, or a whole lab.
 
+use std::collections::HashSet;
 use std::fmt;
 use std::fs::read_to_string;
 use std::time::{Duration, Instant};
 
 use anyhow::Context;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use humantime::format_duration;
 use output::ScenarioOutput;
 use serde::ser::SerializeStruct;
 use serde::Serialize;
 use serde::Serializer;
+use serde_json::json;
 use tracing::warn;
 
-use crate::console::plural;
+use crate::console::{format_mb, plural};
 use crate::process::Exit;
+use crate::timeouts::Timeouts;
 use crate::{exit_code, output, Options, Result, Scenario};
 
 /// What phase of running a scenario.
@@ -38,6 +41,7 @@ pub enum Phase {
 }
 
 impl Phase {
+    #[must_use]
     pub fn name(self) -> &'static str {
         match self {
             Phase::Check => "check",
@@ -65,6 +69,20 @@ pub struct LabOutcome {
     pub timeout: usize,
     pub unviable: usize,
     pub success: usize,
+    /// The timeout for testing each mutant, in seconds, set explicitly or from the
+    /// baseline; None before it's known, or if there is no timeout.
+    pub test_timeout_secs: Option<f64>,
+    /// The timeout for building each mutant, in seconds, if there is one.
+    pub build_timeout_secs: Option<f64>,
+    /// The estimated peak disk space used by scratch build directories, in bytes.
+    #[serde(skip)]
+    pub peak_scratch_disk: Option<u64>,
+    /// True if testing stopped before all mutants were tested, because of `--fail-fast`.
+    #[serde(skip)]
+    pub stopped_early: bool,
+    /// True if the baseline tests passed without running any tests, so no mutants were tested.
+    #[serde(skip)]
+    pub baseline_ran_no_tests: bool,
 }
 
 impl LabOutcome {
@@ -72,6 +90,13 @@ impl LabOutcome {
         LabOutcome::default()
     }
 
+    /// Record the timeouts that will be used for mutants, so that timeout outcomes
+    /// can be interpreted later.
+    pub fn set_timeouts(&mut self, timeouts: &Timeouts) {
+        self.test_timeout_secs = timeouts.test.map(|d| d.as_secs_f64());
+        self.build_timeout_secs = timeouts.build.map(|d| d.as_secs_f64());
+    }
+
     /// Record the event of one test.
     pub fn add(&mut self, outcome: ScenarioOutcome) {
         if outcome.scenario.is_mutant() {
@@ -100,15 +125,53 @@ impl LabOutcome {
             .any(|o| !o.scenario.is_mutant() && !o.success())
         {
             exit_code::CLEAN_TESTS_FAILED
+        } else if self.baseline_ran_no_tests {
+            exit_code::NO_TESTS
         } else if self.timeout > 0 {
             exit_code::TIMEOUT
         } else if self.missed > 0 {
             exit_code::FOUND_PROBLEMS
+        } else if self.total_mutants > 0 && self.unviable == self.total_mutants {
+            exit_code::ALL_UNVIABLE
         } else {
             exit_code::SUCCESS
         }
     }
 
+    /// Compare the mutants missed in this run to the names of those missed in a previous run.
+    pub fn compare_missed(&self, previously_missed: &[String]) -> MissedComparison {
+        MissedComparison::new(
+            previously_missed,
+            self.outcomes.iter().filter_map(|outcome| {
+                outcome
+                    .scenario
+                    .mutant()
+                    .map(|mutant| (mutant.name(false), outcome.summary()))
+            }),
+        )
+    }
+
+    /// Return the overall program exit code, counting missed mutants as a problem only
+    /// if some of them were not missed in a previous run.
+    pub fn exit_code_for_new_missed(&self, comparison: &MissedComparison) -> i32 {
+        match self.exit_code() {
+            exit_code::FOUND_PROBLEMS if comparison.new_missed.is_empty() => exit_code::SUCCESS,
+            code => code,
+        }
+    }
+
+    /// Return the counts of each outcome as a JSON object, for `--summary-only --json`.
+    pub fn summary_json(&self) -> serde_json::Value {
+        json!({
+            "total_mutants": self.total_mutants,
+            "missed": self.missed,
+            "caught": self.caught,
+            "timeout": self.timeout,
+            "unviable": self.unviable,
+            "success": self.success,
+        })
+    }
+
     /// Return an overall summary, to show at the end of the program.
     pub fn summary_string(&self, start_time: Instant, options: &Options) -> String {
         let mut s = Vec::new();
@@ -137,10 +200,63 @@ impl LabOutcome {
             by_outcome.push(format!("{} succeeded", self.success));
         }
         s.push(by_outcome.join(", "));
+        // Like the elapsed time, this varies from run to run, so it's controlled by the
+        // same option.
+        if let (true, Some(bytes)) = (options.show_times, self.peak_scratch_disk) {
+            s.push(format!("; peak scratch disk usage {}", format_mb(bytes)));
+        }
+        if self.stopped_early {
+            s.push("; stopped early after a missed mutant (--fail-fast)".into());
+        }
         s.join("")
     }
 }
 
+/// A comparison between the mutants missed in this run and those missed in a previous run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MissedComparison {
+    /// Names of mutants missed in this run that were not missed previously.
+    pub new_missed: Vec<String>,
+    /// Names of mutants that were missed previously, and were tested in this run but not missed.
+    pub fixed: Vec<String>,
+}
+
+impl MissedComparison {
+    /// Compare previously missed mutants to the names and summary outcomes of mutants
+    /// tested in this run.
+    ///
+    /// Previously missed mutants that weren't tested this time, perhaps because of filters,
+    /// are counted as neither new nor fixed.
+    fn new(
+        previously_missed: &[String],
+        current: impl IntoIterator<Item = (String, SummaryOutcome)>,
+    ) -> MissedComparison {
+        let previously_missed: HashSet<&str> =
+            previously_missed.iter().map(String::as_str).collect();
+        let mut comparison = MissedComparison::default();
+        for (name, summary) in current {
+            let was_missed = previously_missed.contains(name.as_str());
+            if summary == SummaryOutcome::MissedMutant {
+                if !was_missed {
+                    comparison.new_missed.push(name);
+                }
+            } else if was_missed {
+                comparison.fixed.push(name);
+            }
+        }
+        comparison
+    }
+
+    /// Describe the comparison in a short phrase, like "3 new survivors, 2 fixed".
+    pub fn summary_string(&self) -> String {
+        format!(
+            "{}, {} fixed",
+            plural(self.new_missed.len(), "new survivor"),
+            self.fixed.len()
+        )
+    }
+}
+
 /// The result of running one mutation scenario.
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[allow(clippy::module_name_repetitions)]
@@ -164,9 +280,10 @@ impl Serialize for ScenarioOutcome {
         S: Serializer,
     {
         // custom serialize to omit inessential info and to inline a summary.
-        let mut ss = serializer.serialize_struct("Outcome", 5)?;
+        let mut ss = serializer.serialize_struct("Outcome", 6)?;
         ss.serialize_field("scenario", &self.scenario)?;
         ss.serialize_field("summary", &self.summary())?;
+        ss.serialize_field("timed_out_phase", &self.timed_out_phase())?;
         ss.serialize_field("log_path", &self.log_path)?;
         ss.serialize_field("diff_path", &self.diff_path)?;
         ss.serialize_field("phase_results", &self.phase_results)?;
@@ -175,7 +292,7 @@ impl Serialize for ScenarioOutcome {
 }
 
 impl ScenarioOutcome {
-    pub fn new(scenario_output: &ScenarioOutput, scenario: Scenario) -> ScenarioOutcome {
+    pub(crate) fn new(scenario_output: &ScenarioOutput, scenario: Scenario) -> ScenarioOutcome {
         ScenarioOutcome {
             output_dir: scenario_output.output_dir.clone(),
             log_path: scenario_output.log_path().to_owned(),
@@ -185,48 +302,94 @@ impl ScenarioOutcome {
         }
     }
 
-    pub fn add_phase_result(&mut self, phase_result: PhaseResult) {
+    pub(crate) fn add_phase_result(&mut self, phase_result: PhaseResult) {
         self.phase_results.push(phase_result);
     }
 
-    pub fn get_log_content(&self) -> Result<String> {
+    /// The path of the log file, relative to `mutants.out`.
+    #[must_use]
+    pub fn log_path(&self) -> &Utf8Path {
+        &self.log_path
+    }
+
+    /// The path of the diff file, relative to `mutants.out`, for mutant scenarios.
+    #[must_use]
+    pub fn diff_path(&self) -> Option<&Utf8Path> {
+        self.diff_path.as_deref()
+    }
+
+    pub(crate) fn get_log_content(&self) -> Result<String> {
         read_to_string(self.output_dir.join(&self.log_path)).context("read log file")
     }
 
-    pub fn last_phase(&self) -> Phase {
+    pub(crate) fn last_phase(&self) -> Phase {
         self.phase_results.last().unwrap().phase
     }
 
-    pub fn last_phase_result(&self) -> Exit {
+    pub(crate) fn last_phase_result(&self) -> Exit {
         self.phase_results.last().unwrap().process_status
     }
 
     /// Return the results of all phases.
+    #[must_use]
     pub fn phase_results(&self) -> &[PhaseResult] {
         &self.phase_results
     }
 
+    /// Return the total time spent in all phases.
+    #[must_use]
+    pub fn total_duration(&self) -> Duration {
+        self.phase_results.iter().map(|pr| pr.duration).sum()
+    }
+
     /// Return the result of the given phase, if it was run.
+    #[must_use]
     pub fn phase_result(&self, phase: Phase) -> Option<&PhaseResult> {
         self.phase_results.iter().find(|pr| pr.phase == phase)
     }
 
+    /// Return the duration of the given phase, or of the slowest run of it if it was run
+    /// more than once, as it is when doctests are run after the other tests.
+    #[must_use]
+    pub fn phase_duration(&self, phase: Phase) -> Option<Duration> {
+        self.phase_results
+            .iter()
+            .filter(|pr| pr.phase == phase)
+            .map(|pr| pr.duration)
+            .max()
+    }
+
     /// True if this status indicates the user definitely needs to see the logs, because a task
     /// failed that should not have failed.
-    pub fn should_show_logs(&self) -> bool {
+    pub(crate) fn should_show_logs(&self) -> bool {
         !self.scenario.is_mutant() && !self.success()
     }
 
+    #[must_use]
     pub fn success(&self) -> bool {
         self.last_phase_result().is_success()
     }
 
+    #[must_use]
     pub fn has_timeout(&self) -> bool {
         self.phase_results
             .iter()
             .any(|pr| pr.process_status.is_timeout())
     }
 
+    /// Return the phase that was stopped by its timeout, if any.
+    ///
+    /// This distinguishes a build that hung, perhaps in const evaluation, from tests
+    /// that hung.
+    #[must_use]
+    pub fn timed_out_phase(&self) -> Option<Phase> {
+        self.phase_results
+            .iter()
+            .find(|pr| pr.timed_out())
+            .map(|pr| pr.phase)
+    }
+
+    #[must_use]
     pub fn check_or_build_failed(&self) -> bool {
         self.phase_results
             .iter()
@@ -234,6 +397,7 @@ impl ScenarioOutcome {
     }
 
     /// True if this outcome is a caught mutant: it's a mutant and the tests failed.
+    #[must_use]
     pub fn mutant_caught(&self) -> bool {
         self.scenario.is_mutant()
             && self.last_phase() == Phase::Test
@@ -241,12 +405,14 @@ impl ScenarioOutcome {
     }
 
     /// True if this outcome is a missed mutant: it's a mutant and the tests succeeded.
+    #[must_use]
     pub fn mutant_missed(&self) -> bool {
         self.scenario.is_mutant()
             && self.last_phase() == Phase::Test
             && self.last_phase_result().is_success()
     }
 
+    #[must_use]
     pub fn summary(&self) -> SummaryOutcome {
         // Caution: this function is called when rendering progress
         // and so should not log; see https://github.com/sourcefrog/nutmeg/issues/16.
@@ -297,6 +463,12 @@ impl PhaseResult {
     pub fn is_success(&self) -> bool {
         self.process_status.is_success()
     }
+
+    /// True if this phase was killed because it exceeded its timeout.
+    #[must_use]
+    pub fn timed_out(&self) -> bool {
+        self.process_status.is_timeout()
+    }
 }
 
 impl Serialize for PhaseResult {
@@ -304,10 +476,11 @@ impl Serialize for PhaseResult {
     where
         S: Serializer,
     {
-        let mut ss = serializer.serialize_struct("PhaseResult", 4)?;
+        let mut ss = serializer.serialize_struct("PhaseResult", 5)?;
         ss.serialize_field("phase", &self.phase)?;
         ss.serialize_field("duration", &self.duration.as_secs_f64())?;
         ss.serialize_field("process_status", &self.process_status)?;
+        ss.serialize_field("timed_out", &self.timed_out())?;
         ss.serialize_field("argv", &self.argv)?;
         ss.end()
     }
@@ -327,11 +500,33 @@ pub enum SummaryOutcome {
 
 #[cfg(test)]
 mod test {
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     use crate::process::Exit;
 
-    use super::{Phase, PhaseResult, Scenario, ScenarioOutcome};
+    use super::{
+        LabOutcome, MissedComparison, Phase, PhaseResult, Scenario, ScenarioOutcome, SummaryOutcome,
+    };
+    use crate::{exit_code, Options};
+
+    #[test]
+    fn summary_includes_peak_scratch_disk_when_showing_times() {
+        let lab_outcome = LabOutcome {
+            total_mutants: 2,
+            caught: 2,
+            peak_scratch_disk: Some(1_234_000_000),
+            ..LabOutcome::default()
+        };
+        let start = Instant::now();
+        assert_eq!(
+            lab_outcome.summary_string(start, &Options::from_arg_strs(["mutants", "--no-times"])),
+            "2 mutants tested: 2 caught"
+        );
+        assert_eq!(
+            lab_outcome.summary_string(start, &Options::from_arg_strs(["mutants"])),
+            "2 mutants tested in 0s: 2 caught; peak scratch disk usage 1234 MB"
+        );
+    }
 
     #[test]
     fn find_phase_result() {
@@ -382,4 +577,90 @@ mod test {
         );
         assert_eq!(outcome.phase_result(Phase::Check), None);
     }
+
+    #[test]
+    fn timed_out_phase_distinguishes_build_from_test() {
+        let phase_result = |phase, process_status| PhaseResult {
+            phase,
+            duration: Duration::from_secs(1),
+            process_status,
+            argv: vec!["cargo".into()],
+        };
+        let mut outcome = ScenarioOutcome {
+            output_dir: "output".into(),
+            log_path: "log".into(),
+            diff_path: None,
+            scenario: Scenario::Baseline,
+            phase_results: vec![phase_result(Phase::Build, Exit::Success)],
+        };
+        assert_eq!(outcome.timed_out_phase(), None);
+        outcome.add_phase_result(phase_result(Phase::Test, Exit::Timeout));
+        assert_eq!(outcome.timed_out_phase(), Some(Phase::Test));
+        assert_eq!(outcome.summary(), SummaryOutcome::Timeout);
+
+        outcome.phase_results = vec![phase_result(Phase::Build, Exit::Timeout)];
+        assert_eq!(outcome.timed_out_phase(), Some(Phase::Build));
+
+        let json = serde_json::to_value(&outcome).unwrap();
+        assert_eq!(json["timed_out_phase"], "Build");
+        assert_eq!(json["phase_results"][0]["timed_out"], true);
+    }
+
+    #[test]
+    fn compare_missed_to_previous_run() {
+        let previously_missed = ["a".to_owned(), "b".to_owned(), "untested".to_owned()];
+        let comparison = MissedComparison::new(
+            &previously_missed,
+            [
+                ("a".to_owned(), SummaryOutcome::MissedMutant),
+                ("b".to_owned(), SummaryOutcome::CaughtMutant),
+                ("c".to_owned(), SummaryOutcome::MissedMutant),
+                ("d".to_owned(), SummaryOutcome::CaughtMutant),
+            ],
+        );
+        assert_eq!(comparison.new_missed, ["c"]);
+        assert_eq!(comparison.fixed, ["b"]);
+        assert_eq!(comparison.summary_string(), "1 new survivor, 1 fixed");
+    }
+
+    #[test]
+    fn exit_code_when_all_mutants_are_unviable() {
+        let lab_outcome = LabOutcome {
+            total_mutants: 2,
+            unviable: 2,
+            ..LabOutcome::default()
+        };
+        assert_eq!(lab_outcome.exit_code(), exit_code::ALL_UNVIABLE);
+        let lab_outcome = LabOutcome {
+            total_mutants: 2,
+            unviable: 1,
+            caught: 1,
+            ..LabOutcome::default()
+        };
+        assert_eq!(lab_outcome.exit_code(), exit_code::SUCCESS);
+        assert_eq!(LabOutcome::default().exit_code(), exit_code::SUCCESS);
+    }
+
+    #[test]
+    fn exit_code_for_new_missed_ignores_previously_missed() {
+        let lab_outcome = LabOutcome {
+            total_mutants: 2,
+            missed: 1,
+            caught: 1,
+            ..LabOutcome::default()
+        };
+        assert_eq!(lab_outcome.exit_code(), exit_code::FOUND_PROBLEMS);
+        assert_eq!(
+            lab_outcome.exit_code_for_new_missed(&MissedComparison::default()),
+            exit_code::SUCCESS
+        );
+        let comparison = MissedComparison {
+            new_missed: vec!["src/lib.rs: replace foo -> bool with true".to_owned()],
+            fixed: Vec::new(),
+        };
+        assert_eq!(
+            lab_outcome.exit_code_for_new_missed(&comparison),
+            exit_code::FOUND_PROBLEMS
+        );
+    }
 }
@@ -2,31 +2,51 @@
 
 //! A `mutants.out` directory holding logs and other output.
 
-use std::collections::{hash_map::Entry, HashMap};
-use std::fs::{create_dir, read_to_string, remove_dir_all, rename, write, File, OpenOptions};
-use std::io::{BufWriter, Write};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::fs::{
+    copy, create_dir, create_dir_all, read_to_string, remove_dir_all, rename, write, File,
+    OpenOptions,
+};
+use std::io::{copy as copy_stream, BufWriter, Write};
 use std::path::Path;
 use std::thread::sleep;
 use std::time::Duration;
 
 use camino::{Utf8Path, Utf8PathBuf};
 use fs2::FileExt;
+use ignore::WalkBuilder;
 use path_slash::PathExt;
 use serde::Serialize;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
-use tracing::{info, trace};
-
+use tracing::{debug, info, trace};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::baseline::BaselineRecord;
+use crate::discovery_cache::DISCOVERY_CACHE_JSON;
+use crate::lab::Plan;
+use crate::list::mutant_json;
 use crate::outcome::{LabOutcome, SummaryOutcome};
+use crate::report::html_report;
+use crate::sarif::sarif_report;
+use crate::visit::{SkipCategory, Skipped};
 use crate::{check_interrupted, Context, Mutant, Result, Scenario, ScenarioOutcome};
 
-const OUTDIR_NAME: &str = "mutants.out";
+pub const OUTDIR_NAME: &str = "mutants.out";
 const ROTATED_NAME: &str = "mutants.out.old";
 const LOCK_JSON: &str = "lock.json";
 const LOCK_POLL: Duration = Duration::from_millis(100);
 static CAUGHT_TXT: &str = "caught.txt";
+static MISSED_DIFFS_TXT: &str = "missed_diffs.txt";
 static PREVIOUSLY_CAUGHT_TXT: &str = "previously_caught.txt";
 static UNVIABLE_TXT: &str = "unviable.txt";
+static SKIPPED_JSON: &str = "skipped.json";
+static SARIF_JSON: &str = "sarif.json";
+static REPORT_HTML: &str = "report.html";
+static BASELINE_JSON: &str = "baseline.json";
+static TIMINGS_JSON: &str = "timings.json";
+static PLAN_JSON: &str = "plan.json";
 
 /// The contents of a `lock.json` written into the output directory and used as
 /// a lock file to ensure that two cargo-mutants invocations don't try to write
@@ -94,6 +114,8 @@ pub struct OutputDir {
     lock_file: File,
     /// A file holding a list of missed mutants as text, one per line.
     missed_list: File,
+    /// A file holding the name and diff of each missed mutant.
+    missed_diffs: File,
     /// A file holding a list of caught mutants as text, one per line.
     caught_list: File,
     /// A file holding a list of mutants where testing timed out, as text, one per line.
@@ -104,6 +126,13 @@ pub struct OutputDir {
     /// Log filenames which have already been used, and the number of times that each
     /// basename has been used.
     used_log_names: HashMap<String, usize>,
+    /// The successful baseline recorded in the previous output directory, if any.
+    previous_baseline: Option<BaselineRecord>,
+    /// The most recent time taken to test each mutant, in seconds, keyed by mutant id.
+    ///
+    /// This starts with the timings from the previous output directory, and is updated
+    /// as mutants are tested.
+    mutant_timings: BTreeMap<String, f64>,
 }
 
 impl OutputDir {
@@ -123,8 +152,13 @@ impl OutputDir {
                 .with_context(|| format!("create output parent directory {in_dir:?}"))?;
         }
         let output_dir = in_dir.join(OUTDIR_NAME);
+        let mut previous_baseline = None;
+        let mut mutant_timings = BTreeMap::new();
+        let mut rotated_from = None;
         if output_dir.exists() {
             LockFile::acquire_lock(output_dir.as_ref())?;
+            previous_baseline = load_baseline_record(&output_dir);
+            mutant_timings = load_mutant_timings(&output_dir);
             // Now release the lock for a bit while we move the directory. This might be
             // slightly racy.
             // TODO: Move the lock outside the directory, <https://github.com/sourcefrog/cargo-mutants/issues/402>.
@@ -135,9 +169,19 @@ impl OutputDir {
             }
             rename(&output_dir, &rotated)
                 .with_context(|| format!("move {:?} to {:?}", &output_dir, &rotated))?;
+            rotated_from = Some(rotated);
         }
         create_dir(&output_dir)
             .with_context(|| format!("create output directory {:?}", &output_dir))?;
+        if let Some(rotated) = rotated_from {
+            // The discovery cache isn't specific to one run, so keep it for the next.
+            let cache_path = rotated.join(DISCOVERY_CACHE_JSON);
+            if cache_path.is_file() {
+                if let Err(err) = copy(&cache_path, output_dir.join(DISCOVERY_CACHE_JSON)) {
+                    debug!(?err, %cache_path, "failed to copy discovery cache");
+                }
+            }
+        }
         let lock_file = LockFile::acquire_lock(output_dir.as_std_path())
             .context("create lock.json lock file")?;
         let log_dir = output_dir.join("log");
@@ -151,6 +195,9 @@ impl OutputDir {
         let missed_list = list_file_options
             .open(output_dir.join("missed.txt"))
             .context("create missed.txt")?;
+        let missed_diffs = list_file_options
+            .open(output_dir.join(MISSED_DIFFS_TXT))
+            .context("create missed_diffs.txt")?;
         let caught_list = list_file_options
             .open(output_dir.join(CAUGHT_TXT))
             .context("create caught.txt")?;
@@ -160,16 +207,23 @@ impl OutputDir {
         let timeout_list = list_file_options
             .open(output_dir.join("timeout.txt"))
             .context("create timeout.txt")?;
-        Ok(OutputDir {
+        let output_dir = OutputDir {
             path: output_dir,
             lab_outcome: LabOutcome::new(),
             lock_file,
             missed_list,
+            missed_diffs,
             caught_list,
             timeout_list,
             unviable_list,
             used_log_names: HashMap::new(),
-        })
+            previous_baseline,
+            mutant_timings,
+        };
+        output_dir.write_sarif()?;
+        output_dir.write_report()?;
+        output_dir.write_mutant_timings()?;
+        Ok(output_dir)
     }
 
     /// Allocate a sequence number and the output files for a scenario.
@@ -209,14 +263,77 @@ impl OutputDir {
         .context("write outcomes.json")
     }
 
+    /// Write the files that describe the whole run, once all the scenarios have been
+    /// tested or the run stopped early.
+    pub fn write_final_reports(&self) -> Result<()> {
+        self.write_sarif()?;
+        self.write_report()
+    }
+
+    /// Write `sarif.json` describing the missed mutants.
+    fn write_sarif(&self) -> Result<()> {
+        let missed = self.lab_outcome.outcomes.iter().filter_map(|outcome| {
+            match (&outcome.scenario, outcome.summary()) {
+                (Scenario::Mutant(mutant), SummaryOutcome::MissedMutant) => Some(mutant),
+                _ => None,
+            }
+        });
+        serde_json::to_writer_pretty(
+            BufWriter::new(File::create(self.path.join(SARIF_JSON))?),
+            &sarif_report(missed),
+        )
+        .context("write sarif.json")
+    }
+
+    /// Write `report.html` summarizing the outcomes.
+    fn write_report(&self) -> Result<()> {
+        write(self.path.join(REPORT_HTML), html_report(&self.lab_outcome))
+            .context("write report.html")
+    }
+
+    /// Return the successful baseline recorded by the previous run in this directory, if any.
+    pub fn previous_baseline(&self) -> Option<&BaselineRecord> {
+        self.previous_baseline.as_ref()
+    }
+
+    /// Return the most recent time taken to test this mutant, in this run or a previous one.
+    pub fn mutant_duration(&self, mutant: &Mutant) -> Option<Duration> {
+        self.mutant_timings
+            .get(&mutant.id())
+            .map(|&secs| Duration::from_secs_f64(secs))
+    }
+
+    fn write_mutant_timings(&self) -> Result<()> {
+        serde_json::to_writer_pretty(
+            BufWriter::new(File::create(self.path.join(TIMINGS_JSON))?),
+            &self.mutant_timings,
+        )
+        .context("write timings.json")
+    }
+
+    /// Record a successful baseline, so that it can be reused by the next run.
+    pub fn write_baseline_record(&self, record: &BaselineRecord) -> Result<()> {
+        serde_json::to_writer_pretty(
+            BufWriter::new(File::create(self.path.join(BASELINE_JSON))?),
+            record,
+        )
+        .context("write baseline.json")
+    }
+
     /// Add the result of testing one scenario.
     pub fn add_scenario_outcome(&mut self, scenario_outcome: &ScenarioOutcome) -> Result<()> {
         self.lab_outcome.add(scenario_outcome.to_owned());
         self.write_lab_outcome()?;
         let scenario = &scenario_outcome.scenario;
         if let Scenario::Mutant(mutant) = scenario {
+            self.mutant_timings
+                .insert(mutant.id(), scenario_outcome.total_duration().as_secs_f64());
+            self.write_mutant_timings()?;
             let file = match scenario_outcome.summary() {
-                SummaryOutcome::MissedMutant => &mut self.missed_list,
+                SummaryOutcome::MissedMutant => {
+                    self.write_missed_diff(mutant, scenario_outcome)?;
+                    &mut self.missed_list
+                }
                 SummaryOutcome::CaughtMutant => &mut self.caught_list,
                 SummaryOutcome::Timeout => &mut self.timeout_list,
                 SummaryOutcome::Unviable => &mut self.unviable_list,
@@ -227,6 +344,16 @@ impl OutputDir {
         Ok(())
     }
 
+    /// Append the name and diff of a missed mutant to `missed_diffs.txt`, from the diff
+    /// already written for it.
+    fn write_missed_diff(&mut self, mutant: &Mutant, outcome: &ScenarioOutcome) -> Result<()> {
+        let diff_path = outcome.diff_path().expect("mutant outcome has a diff path");
+        let diff = read_to_string(self.path.join(diff_path))
+            .with_context(|| format!("read diff {diff_path}"))?;
+        write!(self.missed_diffs, "{}\n\n{diff}\n", mutant.name(true))
+            .context("write to missed_diffs.txt")
+    }
+
     pub fn open_debug_log(&self) -> Result<File> {
         let debug_log_path = self.path.join("debug.log");
         OpenOptions::new()
@@ -239,11 +366,34 @@ impl OutputDir {
     pub fn write_mutants_list(&self, mutants: &[Mutant]) -> Result<()> {
         serde_json::to_writer_pretty(
             BufWriter::new(File::create(self.path.join("mutants.json"))?),
-            mutants,
+            &mutants.iter().map(mutant_json).collect::<Vec<_>>(),
         )
         .context("write mutants.json")
     }
 
+    /// Write `skipped.json`, listing the functions and other items that generated no
+    /// mutants, and why.
+    pub fn write_skipped(&self, skipped: &[Skipped]) -> Result<()> {
+        /// A skipped item along with the broad category of its reason.
+        #[derive(Serialize)]
+        struct SkippedRecord<'a> {
+            #[serde(flatten)]
+            skipped: &'a Skipped,
+            category: SkipCategory,
+        }
+        serde_json::to_writer_pretty(
+            BufWriter::new(File::create(self.path.join(SKIPPED_JSON))?),
+            &skipped
+                .iter()
+                .map(|skipped| SkippedRecord {
+                    skipped,
+                    category: skipped.reason.category(),
+                })
+                .collect::<Vec<_>>(),
+        )
+        .context("write skipped.json")
+    }
+
     pub fn take_lab_outcome(self) -> LabOutcome {
         self.lab_outcome
     }
@@ -285,6 +435,152 @@ pub fn load_previously_caught(output_parent_dir: &Utf8Path) -> Result<Vec<String
     Ok(r)
 }
 
+/// Write everything in the `mutants.out` directory within `output_parent_dir` into a zip
+/// archive.
+///
+/// Files in the archive are named starting with `mutants.out/`, so that extracting the
+/// archive reproduces the directory.
+pub fn write_output_archive(output_parent_dir: &Utf8Path, archive_path: &Utf8Path) -> Result<()> {
+    let output_dir = output_parent_dir.join(OUTDIR_NAME);
+    let archive_file =
+        File::create(archive_path).with_context(|| format!("create {archive_path:?}"))?;
+    let mut zip = ZipWriter::new(BufWriter::new(archive_file));
+    let file_options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for entry in WalkBuilder::new(&output_dir)
+        .standard_filters(false)
+        .sort_by_file_name(Ord::cmp)
+        .build()
+    {
+        let entry = entry?;
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        let relative_path = entry
+            .path()
+            .strip_prefix(output_parent_dir)
+            .expect("entry path is in output_parent_dir");
+        let mut file = File::open(entry.path())
+            .with_context(|| format!("open {:?}", entry.path().to_slash_lossy()))?;
+        let len = file.metadata()?.len();
+        zip.start_file(
+            relative_path.to_slash_lossy(),
+            file_options.large_file(len >= u64::from(u32::MAX)),
+        )
+        .with_context(|| format!("write {archive_path:?}"))?;
+        copy_stream(&mut file, &mut zip).with_context(|| format!("write {archive_path:?}"))?;
+    }
+    zip.finish()
+        .with_context(|| format!("write {archive_path:?}"))?
+        .flush()
+        .with_context(|| format!("write {archive_path:?}"))?;
+    info!("Wrote output archive {archive_path}");
+    Ok(())
+}
+
+/// Read the baseline record from an existing output directory, if there is one.
+fn load_baseline_record(output_dir: &Utf8Path) -> Option<BaselineRecord> {
+    let path = output_dir.join(BASELINE_JSON);
+    if !path.is_file() {
+        return None;
+    }
+    let json = read_to_string(&path)
+        .inspect_err(|err| debug!(?err, %path, "failed to read previous baseline record"))
+        .ok()?;
+    serde_json::from_str(&json)
+        .inspect_err(|err| debug!(?err, %path, "failed to parse previous baseline record"))
+        .ok()
+}
+
+/// Read the timings of mutants from an existing output directory, or return an empty
+/// map if there are none.
+pub(crate) fn load_mutant_timings(output_dir: &Utf8Path) -> BTreeMap<String, f64> {
+    let path = output_dir.join(TIMINGS_JSON);
+    if !path.is_file() {
+        return BTreeMap::new();
+    }
+    read_to_string(&path)
+        .inspect_err(|err| debug!(?err, %path, "failed to read previous mutant timings"))
+        .ok()
+        .and_then(|json| {
+            serde_json::from_str(&json)
+                .inspect_err(|err| debug!(?err, %path, "failed to parse previous mutant timings"))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Return the time taken by the baseline tests in the previous run in `output_dir`, if
+/// it was recorded.
+///
+/// This comes from `baseline.json` if the baseline was saved for reuse, or otherwise
+/// from the baseline outcome in `outcomes.json`.
+pub fn load_baseline_test_duration(output_dir: &Utf8Path) -> Option<Duration> {
+    if let Some(duration) = load_baseline_record(output_dir).and_then(|r| r.test_duration()) {
+        return Some(duration);
+    }
+    let path = output_dir.join("outcomes.json");
+    if !path.is_file() {
+        return None;
+    }
+    let json: serde_json::Value = read_to_string(&path)
+        .inspect_err(|err| debug!(?err, %path, "failed to read previous outcomes"))
+        .ok()
+        .and_then(|json| {
+            serde_json::from_str(&json)
+                .inspect_err(|err| debug!(?err, %path, "failed to parse previous outcomes"))
+                .ok()
+        })?;
+    json["outcomes"]
+        .as_array()?
+        .iter()
+        .filter(|outcome| outcome["scenario"] == "Baseline")
+        .flat_map(|outcome| outcome["phase_results"].as_array().into_iter().flatten())
+        .find(|phase_result| phase_result["phase"] == "Test")
+        .and_then(|phase_result| phase_result["duration"].as_f64())
+        .map(Duration::from_secs_f64)
+}
+
+/// Write the plan from `--dry-run` into `plan.json` in `output_dir`, creating the
+/// directory if necessary.
+///
+/// Unlike [`OutputDir::new`] this doesn't rotate or clear any existing output.
+pub fn write_plan(output_dir: &Utf8Path, plan: &Plan) -> Result<()> {
+    create_dir_all(output_dir)
+        .with_context(|| format!("create output directory {output_dir:?}"))?;
+    serde_json::to_writer_pretty(
+        BufWriter::new(File::create(output_dir.join(PLAN_JSON))?),
+        plan,
+    )
+    .context("write plan.json")
+}
+
+/// Return the names of the mutants that were missed in a previous `outcomes.json` report.
+pub fn load_missed_from_outcomes(outcomes_path: &Utf8Path) -> Result<Vec<String>> {
+    let json: serde_json::Value = serde_json::from_str(
+        &read_to_string(outcomes_path)
+            .with_context(|| format!("Read previous outcomes from {outcomes_path:?}"))?,
+    )
+    .with_context(|| format!("Parse previous outcomes from {outcomes_path:?}"))?;
+    let outcomes = json["outcomes"]
+        .as_array()
+        .with_context(|| format!("No outcomes list in {outcomes_path:?}"))?;
+    let mut missed = Vec::new();
+    for outcome in outcomes {
+        if outcome["summary"] != "MissedMutant" {
+            continue;
+        }
+        let name = outcome["scenario"]["Mutant"]["name"]
+            .as_str()
+            .with_context(|| {
+                format!(
+                    "Missed mutant in {outcomes_path:?} has no name: perhaps it was written by an older version of cargo-mutants?"
+                )
+            })?;
+        missed.push(name.to_owned());
+    }
+    Ok(missed)
+}
+
 /// Where to write output about a particular Scenario.
 #[allow(clippy::module_name_repetitions)]
 pub struct ScenarioOutput {
@@ -366,6 +662,7 @@ pub fn clean_filename(s: &str) -> String {
 #[cfg(test)]
 mod test {
     use std::fs::write;
+    use std::io::Read;
 
     use indoc::indoc;
     use itertools::Itertools;
@@ -435,7 +732,11 @@ mod test {
                 "mutants.out/lock.json",
                 "mutants.out/log",
                 "mutants.out/missed.txt",
+                "mutants.out/missed_diffs.txt",
+                "mutants.out/report.html",
+                "mutants.out/sarif.json",
                 "mutants.out/timeout.txt",
+                "mutants.out/timings.json",
                 "mutants.out/unviable.txt",
                 "src",
                 "src/lib.rs",
@@ -487,6 +788,41 @@ mod test {
             .is_file());
     }
 
+    #[test]
+    fn discovery_cache_is_kept_when_rotating() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_dir_path = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let output_dir = OutputDir::new(temp_dir_path).unwrap();
+        write(output_dir.path().join(DISCOVERY_CACHE_JSON), "{}").unwrap();
+        drop(output_dir);
+
+        let output_dir = OutputDir::new(temp_dir_path).unwrap();
+        assert_eq!(
+            read_to_string(output_dir.path().join(DISCOVERY_CACHE_JSON)).unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn output_archive_contains_mutants_out() {
+        let temp_dir = TempDir::new().unwrap();
+        let parent = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let output_dir = OutputDir::new(parent).unwrap();
+        write(output_dir.path().join("log/a.log"), "hello\n").unwrap();
+        drop(output_dir);
+
+        let archive_path = parent.join("results.zip");
+        write_output_archive(parent, &archive_path).unwrap();
+        let mut archive = zip::ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut log = String::new();
+        archive
+            .by_name("mutants.out/log/a.log")
+            .unwrap()
+            .read_to_string(&mut log)
+            .unwrap();
+        assert_eq!(log, "hello\n");
+    }
+
     #[test]
     fn track_previously_caught() {
         let temp_dir = TempDir::new().unwrap();
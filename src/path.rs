@@ -2,7 +2,7 @@
 
 //! Utilities for file paths.
 
-use camino::Utf8Path;
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 
 /// Measures how far above its starting point a path ascends.
 ///
@@ -28,6 +28,30 @@ pub fn ascent(path: &Utf8Path) -> isize {
     max_ascent
 }
 
+/// Lexically remove `.` components, and `..` components along with the directory
+/// before them, from a relative path that doesn't ascend above its starting point.
+///
+/// Like [ascent], this only looks at the text of the path, so a symlinked directory
+/// followed by `..` might resolve differently on disk.
+///
+/// Returns None if the path ascends above its starting point.
+pub fn normalize(path: &Utf8Path) -> Option<Utf8PathBuf> {
+    if ascent(path) > 0 {
+        return None;
+    }
+    let mut parts: Vec<Utf8Component> = Vec::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => {}
+            Utf8Component::ParentDir => {
+                parts.pop().expect("path doesn't ascend");
+            }
+            _ => parts.push(component),
+        }
+    }
+    Some(parts.into_iter().collect())
+}
+
 /// An extension trait that helps `Utf8Path` print with forward slashes,
 /// even on Windows.
 ///
@@ -52,7 +76,7 @@ impl Utf8PathSlashes for Utf8Path {
 mod test {
     use camino::{Utf8Path, Utf8PathBuf};
 
-    use super::{ascent, Utf8PathSlashes};
+    use super::{ascent, normalize, Utf8PathSlashes};
 
     #[test]
     fn path_slashes_drops_empty_parts() {
@@ -72,4 +96,23 @@ mod test {
         assert_eq!(ascent(Utf8Path::new("../back/../back")), 1);
         assert_eq!(ascent(Utf8Path::new("../back/../../back/down")), 2);
     }
+
+    #[test]
+    fn normalize_paths() {
+        assert_eq!(
+            normalize(Utf8Path::new("src/lib.rs")).unwrap(),
+            "src/lib.rs"
+        );
+        assert_eq!(
+            normalize(Utf8Path::new("src/a/../b.rs")).unwrap(),
+            "src/b.rs"
+        );
+        assert_eq!(
+            normalize(Utf8Path::new("src/./a/./b/../../c.rs")).unwrap(),
+            "src/c.rs"
+        );
+        assert_eq!(normalize(Utf8Path::new("src/..")).unwrap(), "");
+        assert_eq!(normalize(Utf8Path::new("src/../../c.rs")), None);
+        assert_eq!(normalize(Utf8Path::new("../c.rs")), None);
+    }
 }
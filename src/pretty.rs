@@ -51,20 +51,25 @@ where
                                 ',' | ';' | '<' | '>' | ':' | '.' | '!' => (),
                                 _ => b.push(' '),
                             },
+                            // Like `async { ... }`
+                            Group(g) if g.delimiter() == Delimiter::Brace => b.push(' '),
                             Group(_) => (),
                         }
                     }
                 }
                 Group(g) => {
+                    let inner = g.stream().to_pretty_string();
                     match g.delimiter() {
-                        Delimiter::Brace => b.push('{'),
+                        Delimiter::Brace if inner.is_empty() => b.push('{'),
+                        Delimiter::Brace => b.push_str("{ "),
                         Delimiter::Bracket => b.push('['),
                         Delimiter::Parenthesis => b.push('('),
                         Delimiter::None => (),
                     }
-                    b += &g.stream().to_pretty_string();
+                    b += &inner;
                     match g.delimiter() {
-                        Delimiter::Brace => b.push('}'),
+                        Delimiter::Brace if inner.is_empty() => b.push('}'),
+                        Delimiter::Brace => b.push_str(" }"),
                         Delimiter::Bracket => b.push(']'),
                         Delimiter::Parenthesis => b.push(')'),
                         Delimiter::None => (),
@@ -113,6 +118,15 @@ mod test {
         );
     }
 
+    #[test]
+    fn format_braces() {
+        assert_eq!(
+            quote! { Box::pin(async { Ok(()) }) }.to_pretty_string(),
+            "Box::pin(async { Ok(()) })"
+        );
+        assert_eq!(quote! { {} }.to_pretty_string(), "{}");
+    }
+
     #[test]
     fn format_thick_arrow() {
         assert_eq!(quote! { a => b }.to_pretty_string(), "a => b");
@@ -76,6 +76,14 @@ impl Process {
         scenario_output: &mut ScenarioOutput,
     ) -> Result<Process> {
         let start = Instant::now();
+        if !env.is_empty() {
+            let env_str = env
+                .iter()
+                .map(|(k, v)| format!("{k}={}", v.escape_debug()))
+                .collect::<Vec<_>>()
+                .join(" ");
+            scenario_output.message(&format!("env: {env_str}"))?;
+        }
         let quoted_argv = quote_argv(argv);
         scenario_output.message(&quoted_argv)?;
         debug!(%quoted_argv, "start process");
@@ -0,0 +1,190 @@
+// Copyright 2025 Martin Pool
+
+//! A static HTML report of the results, written into `mutants.out/report.html`.
+//!
+//! The report is a single file with inline CSS and no scripts, and links to the diffs
+//! and logs by relative paths, so it still works when `mutants.out` is moved or
+//! archived.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::outcome::{LabOutcome, SummaryOutcome};
+use crate::{Mutant, Scenario, ScenarioOutcome};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; }
+td.count { text-align: right; }
+td.missed { background: #fdd; }
+summary { cursor: pointer; font-weight: bold; margin-top: 1em; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }
+";
+
+/// Counts of mutant outcomes in one source file, and the missed mutants.
+#[derive(Default)]
+struct FileSummary<'a> {
+    caught: usize,
+    missed: Vec<(&'a Mutant, &'a ScenarioOutcome)>,
+    unviable: usize,
+    timeout: usize,
+}
+
+/// Render the outcomes so far as an HTML page.
+pub fn html_report(lab_outcome: &LabOutcome) -> String {
+    let mut files: BTreeMap<String, FileSummary> = BTreeMap::new();
+    for outcome in &lab_outcome.outcomes {
+        let Scenario::Mutant(mutant) = &outcome.scenario else {
+            continue;
+        };
+        let file = files
+            .entry(mutant.source_file.tree_relative_slashes())
+            .or_default();
+        match outcome.summary() {
+            SummaryOutcome::CaughtMutant => file.caught += 1,
+            SummaryOutcome::MissedMutant => file.missed.push((mutant, outcome)),
+            SummaryOutcome::Unviable => file.unviable += 1,
+            SummaryOutcome::Timeout => file.timeout += 1,
+            SummaryOutcome::Success | SummaryOutcome::Failure => {}
+        }
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(html, "<title>{} report</title>", crate::NAME).unwrap();
+    writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>").unwrap();
+    writeln!(html, "<h1>{} report</h1>", crate::NAME).unwrap();
+    writeln!(
+        html,
+        "<p>{} mutants tested: {} missed, {} caught, {} unviable, {} timeouts</p>",
+        lab_outcome.total_mutants,
+        lab_outcome.missed,
+        lab_outcome.caught,
+        lab_outcome.unviable,
+        lab_outcome.timeout
+    )
+    .unwrap();
+
+    html.push_str("<table>\n<tr><th>File</th><th>Caught</th><th>Missed</th><th>Unviable</th><th>Timeout</th></tr>\n");
+    for (path, file) in &files {
+        let missed_class = if file.missed.is_empty() {
+            "count"
+        } else {
+            "count missed"
+        };
+        writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"count\">{}</td><td class=\"{missed_class}\">{}</td><td class=\"count\">{}</td><td class=\"count\">{}</td></tr>",
+            escape_html(path),
+            file.caught,
+            file.missed.len(),
+            file.unviable,
+            file.timeout
+        )
+        .unwrap();
+    }
+    html.push_str("</table>\n");
+
+    for (path, file) in files.iter().filter(|(_, file)| !file.missed.is_empty()) {
+        writeln!(
+            html,
+            "<details>\n<summary>{} ({} missed)</summary>",
+            escape_html(path),
+            file.missed.len()
+        )
+        .unwrap();
+        for (mutant, outcome) in &file.missed {
+            writeln!(
+                html,
+                "<h3>{}:{}: {}</h3>",
+                mutant.span.start.line,
+                mutant.span.start.column,
+                escape_html(&mutant.describe_change())
+            )
+            .unwrap();
+            html.push_str("<p>");
+            if let Some(diff_path) = outcome.diff_path() {
+                write!(
+                    html,
+                    "<a href=\"{}\">diff</a> · ",
+                    escape_html(&escape_url_path(diff_path.as_str()))
+                )
+                .unwrap();
+            }
+            writeln!(
+                html,
+                "<a href=\"{}\">log</a></p>",
+                escape_html(&escape_url_path(outcome.log_path().as_str()))
+            )
+            .unwrap();
+            writeln!(
+                html,
+                "<pre>{}</pre>",
+                escape_html(&mutant.diff(&mutant.mutated_code()))
+            )
+            .unwrap();
+        }
+        html.push_str("</details>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape_html(s: &str) -> String {
+    let mut r = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => r.push_str("&amp;"),
+            '<' => r.push_str("&lt;"),
+            '>' => r.push_str("&gt;"),
+            '"' => r.push_str("&quot;"),
+            c => r.push(c),
+        }
+    }
+    r
+}
+
+/// Percent-encode a relative path for use in a URL, keeping the slashes.
+fn escape_url_path(path: &str) -> String {
+    let mut r = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+            r.push(b as char);
+        } else {
+            write!(r, "%{b:02X}").unwrap();
+        }
+    }
+    r
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn escape_html_special_characters() {
+        assert_eq!(
+            escape_html("replace a < b && c with \"x\""),
+            "replace a &lt; b &amp;&amp; c with &quot;x&quot;"
+        );
+    }
+
+    #[test]
+    fn escape_url_path_keeps_slashes() {
+        assert_eq!(
+            escape_url_path("diff/src__lib.rs_line_5_col_7_&&_#1.diff"),
+            "diff/src__lib.rs_line_5_col_7_%26%26_%231.diff"
+        );
+    }
+
+    #[test]
+    fn empty_report() {
+        let html = html_report(&LabOutcome::default());
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(
+            html.contains("<p>0 mutants tested: 0 missed, 0 caught, 0 unviable, 0 timeouts</p>")
+        );
+        assert!(!html.contains("<details>"));
+    }
+}
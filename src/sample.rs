@@ -0,0 +1,153 @@
+// Copyright 2025 Martin Pool
+
+//! Test only a sample of the mutants, with `--max-mutants`.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use fastrand::Rng;
+
+/// Select at most `max` of the mutants, stratified by the groups given by `key`, which
+/// is typically the source file.
+///
+/// The sample is divided between the groups in proportion to their size, but every group
+/// gets at least one if `max` is large enough, so that small files aren't skipped. If
+/// there are more groups than `max`, a spread of the groups each get one.
+///
+/// If a `seed` is given the sample is chosen randomly, reproducibly for the same seed;
+/// otherwise mutants are taken at an even stride through each group. Either way, the
+/// selected mutants are kept in their original order.
+pub fn sample<M, K: Eq + Hash>(
+    mutants: Vec<M>,
+    max: usize,
+    seed: Option<u64>,
+    key: impl Fn(&M) -> K,
+) -> Vec<M> {
+    let len = mutants.len();
+    if len <= max {
+        return mutants;
+    }
+    let mut rng = seed.map(Rng::with_seed);
+    // Indexes of the mutants in each group, with the groups in order of their first mutant.
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_by_key = HashMap::new();
+    for (i, mutant) in mutants.iter().enumerate() {
+        let group = *group_by_key.entry(key(mutant)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(i);
+    }
+    let sizes: Vec<usize> = groups.iter().map(Vec::len).collect();
+    let mut selected = vec![false; len];
+    for (group, quota) in groups.iter().zip(quotas(&sizes, max, rng.as_mut())) {
+        for i in choose(group.len(), quota, rng.as_mut()) {
+            selected[group[i]] = true;
+        }
+    }
+    mutants
+        .into_iter()
+        .zip(selected)
+        .filter_map(|(m, keep)| keep.then_some(m))
+        .collect()
+}
+
+/// Divide `max` between groups of these sizes, whose total is more than `max`.
+fn quotas(sizes: &[usize], max: usize, rng: Option<&mut Rng>) -> Vec<usize> {
+    let mut quotas = vec![0; sizes.len()];
+    if max < sizes.len() {
+        for group in choose(sizes.len(), max, rng) {
+            quotas[group] = 1;
+        }
+        return quotas;
+    }
+    // Every group gets one, and the rest are shared in proportion to the mutants remaining
+    // in each group, with any left over going to the largest remainders.
+    let spare = max - sizes.len();
+    let remaining: usize = sizes.iter().map(|size| size - 1).sum();
+    for (quota, size) in quotas.iter_mut().zip(sizes) {
+        *quota = 1 + (size - 1) * spare / remaining;
+    }
+    let left_over = max - quotas.iter().sum::<usize>();
+    let mut by_remainder: Vec<usize> = (0..sizes.len()).collect();
+    by_remainder.sort_by_key(|&group| Reverse((sizes[group] - 1) * spare % remaining));
+    for group in by_remainder.into_iter().take(left_over) {
+        quotas[group] += 1;
+    }
+    quotas
+}
+
+/// Choose `n` of the indexes `0..len`: randomly if there's an `rng`, or otherwise at an
+/// even stride.
+fn choose(len: usize, n: usize, rng: Option<&mut Rng>) -> Vec<usize> {
+    if let Some(rng) = rng {
+        let mut indexes: Vec<usize> = (0..len).collect();
+        rng.shuffle(&mut indexes);
+        indexes.truncate(n);
+        indexes
+    } else {
+        (0..n).map(|i| i * len / n).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use super::*;
+
+    /// Sample numbers, grouped by their tens digit.
+    fn sample_by_tens(numbers: Vec<u32>, max: usize, seed: Option<u64>) -> Vec<u32> {
+        sample(numbers, max, seed, |n| n / 10)
+    }
+
+    #[test]
+    fn sample_fewer_than_max_keeps_everything() {
+        assert_eq!(sample_by_tens(vec![1, 2, 3], 3, None), [1, 2, 3]);
+        assert_eq!(sample_by_tens(vec![1, 2, 3], 10, Some(42)), [1, 2, 3]);
+        assert_eq!(sample_by_tens(Vec::new(), 10, None), [] as [u32; 0]);
+    }
+
+    #[test]
+    fn sample_strides_evenly_without_shuffle() {
+        assert_eq!(sample_by_tens((0..10).collect(), 5, None), [0, 2, 4, 6, 8]);
+        assert_eq!(sample_by_tens((0..10).collect(), 3, None), [0, 3, 6]);
+        assert_eq!(sample_by_tens((0..10).collect(), 0, None), [] as [u32; 0]);
+    }
+
+    #[test]
+    fn every_group_is_sampled_if_there_are_enough() {
+        // One big group and two small ones: a flat stride would miss 1000 and 2000.
+        let numbers = (0..90).chain([1000, 2000]).collect_vec();
+        assert_eq!(sample(numbers, 4, None, |n| n / 1000), [0, 45, 1000, 2000]);
+    }
+
+    #[test]
+    fn sample_is_proportional_to_group_size() {
+        let numbers = (0..10).chain(1000..1090).collect_vec();
+        let selected = sample(numbers, 20, None, |n| n / 1000);
+        assert_eq!(selected.len(), 20);
+        assert_eq!(selected.iter().filter(|&&n| n < 1000).count(), 3);
+    }
+
+    #[test]
+    fn fewer_than_one_per_group_spreads_across_groups() {
+        let numbers = (0..100).collect_vec();
+        assert_eq!(sample_by_tens(numbers, 4, None), [0, 20, 50, 70]);
+    }
+
+    #[test]
+    fn random_sample_is_distinct_and_in_order() {
+        let selected = sample_by_tens((0..1000).collect(), 50, Some(42));
+        assert_eq!(selected.len(), 50);
+        assert!(selected.iter().tuple_windows().all(|(a, b)| a < b));
+    }
+
+    #[test]
+    fn random_sample_is_reproducible_with_the_same_seed() {
+        let sample_with_seed = |seed| sample_by_tens((0..1000).collect(), 50, Some(seed));
+        assert_eq!(sample_with_seed(7), sample_with_seed(7));
+        assert_ne!(sample_with_seed(7), sample_with_seed(8));
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2025 Martin Pool
+
+//! Describe missed mutants in the [SARIF](https://sarifweb.azurewebsites.net/) format,
+//! which can be uploaded to GitHub code scanning and other tools.
+//!
+//! Each missed mutant is a `result`, with a rule for each genre of mutant, so that
+//! the scanning tool can be configured to treat genres differently.
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::{Genre, Mutant};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The rule id for mutants of a genre, like `binary-operator`.
+fn rule_id(genre: Genre) -> String {
+    genre
+        .to_possible_value()
+        .expect("Genre has a value")
+        .get_name()
+        .to_owned()
+}
+
+/// Make a SARIF log describing these missed mutants.
+pub fn sarif_report<'a>(missed: impl IntoIterator<Item = &'a Mutant>) -> Value {
+    let rules = Genre::value_variants()
+        .iter()
+        .map(|&genre| {
+            let possible_value = genre.to_possible_value().expect("Genre has a value");
+            json!({
+                "id": rule_id(genre),
+                "shortDescription": {
+                    "text": possible_value.get_help().map_or_else(String::new, ToString::to_string),
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+    let results = missed
+        .into_iter()
+        .map(|mutant| {
+            let span = &mutant.span;
+            json!({
+                "ruleId": rule_id(mutant.genre),
+                "level": "warning",
+                "message": {
+                    "text": format!(
+                        "Missed mutant: {}\n\n{}",
+                        mutant.describe_change(),
+                        mutant.diff(&mutant.mutated_code())
+                    ),
+                },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": mutant.source_file.tree_relative_slashes(),
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                        // Lines and columns are 1-based, and the end column is
+                        // exclusive, as in both SARIF and our spans.
+                        "region": {
+                            "startLine": span.start.line,
+                            "startColumn": span.start.column,
+                            "endLine": span.end.line,
+                            "endColumn": span.end.column,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": crate::NAME,
+                    "version": crate::VERSION,
+                    "informationUri": "https://mutants.rs/",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::visit::mutate_source_str;
+    use crate::Options;
+
+    #[test]
+    fn sarif_result_for_missed_mutant() {
+        let code = indoc! {"
+            fn add(a: u32, b: u32) -> u32 {
+                a + b
+            }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        let mutant = mutants
+            .iter()
+            .find(|m| m.genre == Genre::BinaryOperator)
+            .unwrap();
+        let sarif = sarif_report([mutant]);
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "cargo-mutants");
+        assert!(run["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|rule| rule["id"] == "binary-operator"));
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result["ruleId"], "binary-operator");
+        let message = result["message"]["text"].as_str().unwrap();
+        assert!(message.starts_with("Missed mutant: replace + with - in add\n\n"));
+        assert!(message.contains("-    a + b\n"));
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/main.rs");
+        assert_eq!(
+            location["region"],
+            json!({"startLine": 2, "startColumn": 7, "endLine": 2, "endColumn": 8})
+        );
+    }
+}
@@ -2,6 +2,8 @@
 
 //! Sharding parameters.
 
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::str::FromStr;
 
 use anyhow::{anyhow, ensure, Context, Error};
@@ -24,6 +26,28 @@ impl Shard {
             .filter_map(|(i, m)| if i % self.n == self.k { Some(m) } else { None })
             .collect()
     }
+
+    /// Select the mutants for this shard, keeping together all the mutants with the
+    /// same key.
+    ///
+    /// Keys are assigned to shards in the order they're first seen, so shards
+    /// may have different numbers of mutants if the groups are of different sizes.
+    pub fn select_grouped<M, K, I, F>(&self, mutants: I, key: F) -> Vec<M>
+    where
+        I: IntoIterator<Item = M>,
+        K: Eq + Hash,
+        F: Fn(&M) -> K,
+    {
+        let mut group_index: HashMap<K, usize> = HashMap::new();
+        mutants
+            .into_iter()
+            .filter(|m| {
+                let next_index = group_index.len();
+                let i = *group_index.entry(key(m)).or_insert(next_index);
+                i % self.n == self.k
+            })
+            .collect()
+    }
 }
 
 impl FromStr for Shard {
@@ -80,4 +104,33 @@ mod tests {
             &[1, 5, 9]
         );
     }
+
+    #[test]
+    fn shard_select_grouped() {
+        let mutants = [
+            ("a.rs", 1),
+            ("a.rs", 2),
+            ("b.rs", 3),
+            ("c.rs", 4),
+            ("c.rs", 5),
+            ("a.rs", 6),
+            ("d.rs", 7),
+        ];
+        let shard = Shard::from_str("0/2").unwrap();
+        assert_eq!(
+            shard.select_grouped(mutants, |m| m.0),
+            [
+                ("a.rs", 1),
+                ("a.rs", 2),
+                ("c.rs", 4),
+                ("c.rs", 5),
+                ("a.rs", 6)
+            ]
+        );
+        let shard = Shard::from_str("1/2").unwrap();
+        assert_eq!(
+            shard.select_grouped(mutants, |m| m.0),
+            [("b.rs", 3), ("d.rs", 7)]
+        );
+    }
 }
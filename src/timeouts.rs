@@ -4,9 +4,10 @@
 
 use std::{cmp::max, time::Duration};
 
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 use crate::{
+    mutant::Mutant,
     options::Options,
     outcome::{Phase, ScenarioOutcome},
 };
@@ -26,18 +27,56 @@ impl Timeouts {
     }
 
     pub fn from_baseline(baseline: &ScenarioOutcome, options: &Options) -> Timeouts {
+        Timeouts::from_baseline_durations(
+            baseline.phase_duration(Phase::Build),
+            baseline.phase_duration(Phase::Test),
+            options,
+        )
+    }
+
+    /// Calculate timeouts from the durations of the baseline build and test phases,
+    /// either just measured or reused from a previous run.
+    pub fn from_baseline_durations(
+        build: Option<Duration>,
+        test: Option<Duration>,
+        options: &Options,
+    ) -> Timeouts {
         Timeouts {
-            build: build_timeout(
-                baseline.phase_result(Phase::Build).map(|pr| pr.duration),
-                options,
-            ),
-            test: Some(test_timeout(
-                baseline.phase_result(Phase::Test).map(|pr| pr.duration),
-                options,
-            )),
+            build: build_timeout(build, options),
+            test: Some(test_timeout(test, options)),
         }
     }
 
+    /// Return the timeouts for testing a mutant, with the test timeout scaled if the
+    /// mutant is in a function matched by `slow_functions` in the config.
+    ///
+    /// If several patterns match, the largest multiplier is used.
+    pub fn for_mutant(self, mutant: &Mutant, options: &Options) -> Timeouts {
+        let Some(function) = &mutant.function else {
+            return self;
+        };
+        let Some(multiplier) = options
+            .slow_functions
+            .iter()
+            .filter(|(regex, _)| regex.is_match(&function.function_name))
+            .map(|(_, multiplier)| *multiplier)
+            .max_by(f64::total_cmp)
+        else {
+            return self;
+        };
+        // The multiplier is finite, but a large one could still overflow a Duration.
+        let test = self.test.map(|t| {
+            Duration::try_from_secs_f64(t.as_secs_f64() * multiplier).unwrap_or(Duration::MAX)
+        });
+        debug!(
+            function = function.function_name,
+            multiplier,
+            ?test,
+            "scaled test timeout for slow function"
+        );
+        Timeouts { test, ..self }
+    }
+
     pub fn without_baseline(options: &Options) -> Timeouts {
         Timeouts {
             build: build_timeout(None, options),
@@ -55,18 +94,33 @@ fn test_timeout(baseline_duration: Option<Duration>, options: &Options) -> Durat
     if let Some(explicit) = options.test_timeout {
         explicit
     } else if let Some(baseline_duration) = baseline_duration {
-        let timeout = max(
-            options.minimum_test_timeout,
-            Duration::from_secs_f64(
-                (baseline_duration.as_secs_f64() * options.test_timeout_multiplier.unwrap_or(5.0))
-                    .ceil(),
-            ),
+        let multiplier = options.test_timeout_multiplier.unwrap_or(5.0);
+        let from_baseline =
+            Duration::from_secs_f64((baseline_duration.as_secs_f64() * multiplier).ceil());
+        // A very fast baseline would otherwise give a timeout so short that normal
+        // variation in test time, especially on a busy machine, causes spurious timeouts.
+        let floored = max(options.minimum_test_timeout, from_baseline);
+        let timeout = clamp_timeout("test", floored, options);
+        debug!(
+            ?baseline_duration,
+            multiplier,
+            ?from_baseline,
+            ?timeout,
+            "auto-set test timeout"
         );
         if options.show_times {
-            info!(
-                "Auto-set test timeout to {}",
-                humantime::format_duration(timeout)
-            );
+            if floored > from_baseline {
+                info!(
+                    "Auto-set test timeout to {}, the minimum, since {multiplier}x the baseline test time of {:.1}s is shorter",
+                    humantime::format_duration(timeout),
+                    baseline_duration.as_secs_f64(),
+                );
+            } else {
+                info!(
+                    "Auto-set test timeout to {}",
+                    humantime::format_duration(timeout)
+                );
+            }
         }
         timeout
     } else if options.check_only {
@@ -74,7 +128,7 @@ fn test_timeout(baseline_duration: Option<Duration>, options: &Options) -> Durat
         Duration::from_secs(0)
     } else {
         warn_fallback_timeout("test", "--baseline=skip");
-        Duration::from_secs(FALLBACK_TIMEOUT_SECS)
+        clamp_timeout("test", Duration::from_secs(FALLBACK_TIMEOUT_SECS), options)
     }
 }
 
@@ -83,7 +137,11 @@ fn build_timeout(baseline_duration: Option<Duration>, options: &Options) -> Opti
         Some(t)
     } else if let Some(baseline) = baseline_duration {
         if let Some(multiplier) = options.build_timeout_multiplier {
-            let timeout = Duration::from_secs_f64(baseline.as_secs_f64() * multiplier);
+            let timeout = clamp_timeout(
+                "build",
+                Duration::from_secs_f64(baseline.as_secs_f64() * multiplier),
+                options,
+            );
             if options.show_times {
                 info!(
                     "Auto-set build timeout to {}",
@@ -99,6 +157,23 @@ fn build_timeout(baseline_duration: Option<Duration>, options: &Options) -> Opti
     }
 }
 
+/// Limit an automatically computed timeout to `--max-timeout`, if that's set.
+///
+/// Explicit timeouts are not clamped.
+fn clamp_timeout(phase_name: &str, timeout: Duration, options: &Options) -> Duration {
+    match options.max_timeout {
+        Some(max_timeout) if timeout > max_timeout => {
+            info!(
+                "Computed {phase_name} timeout of {} capped to {} by --max-timeout",
+                humantime::format_duration(timeout),
+                humantime::format_duration(max_timeout)
+            );
+            max_timeout
+        }
+        _ => timeout,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -107,7 +182,7 @@ mod test {
     use indoc::indoc;
 
     use super::*;
-    use crate::{config::Config, Args};
+    use crate::{config::Config, visit::mutate_source_str, Args};
 
     #[test]
     fn timeout_multiplier_from_option() {
@@ -120,6 +195,31 @@ mod test {
         );
     }
 
+    #[test]
+    fn timeout_multiplier_respects_minimum_test_timeout() {
+        let options = Options::from_arg_strs(["mutants", "--timeout-multiplier", "2"]);
+        assert_eq!(options.minimum_test_timeout, Duration::from_secs(20));
+        // A very fast baseline doesn't give an absurdly short timeout.
+        assert_eq!(
+            test_timeout(Some(Duration::from_millis(100)), &options),
+            Duration::from_secs(20),
+        );
+        assert_eq!(
+            test_timeout(Some(Duration::from_secs(15)), &options),
+            Duration::from_secs(30),
+        );
+
+        let options = Options::from_arg_strs([
+            "mutants",
+            "--timeout-multiplier=2",
+            "--minimum-test-timeout=3",
+        ]);
+        assert_eq!(
+            test_timeout(Some(Duration::from_millis(100)), &options),
+            Duration::from_secs(3),
+        );
+    }
+
     #[test]
     fn test_timeout_unaffected_by_in_place_build() {
         let options =
@@ -158,6 +258,106 @@ mod test {
         );
     }
 
+    #[test]
+    fn slow_functions_scale_test_timeout() {
+        let args = Args::try_parse_from(["mutants"]).unwrap();
+        let config = Config::from_str(indoc! {r#"
+            [[slow_functions]]
+            match = "^heavy::"
+            timeout_multiplier = 3.0
+
+            [[slow_functions]]
+            match = "compute$"
+            timeout_multiplier = 4.0
+        "#})
+        .unwrap();
+        let options = Options::new(&args, &config).unwrap();
+        let code = indoc! {"
+            mod heavy {
+                fn compute() -> u32 { 1 }
+                fn simple() -> u32 { 2 }
+            }
+            fn light() -> u32 { 3 }
+        "};
+        let mutants = mutate_source_str(code, &options).unwrap();
+        let timeouts = Timeouts {
+            build: Some(Duration::from_secs(7)),
+            test: Some(Duration::from_secs(10)),
+        };
+        let test_timeout = |name: &str| {
+            let mutant = mutants
+                .iter()
+                .find(|m| m.function.as_ref().unwrap().function_name == name)
+                .unwrap();
+            let scaled = timeouts.for_mutant(mutant, &options);
+            assert_eq!(scaled.build, Some(Duration::from_secs(7)));
+            scaled.test.unwrap()
+        };
+        // The largest matching multiplier wins.
+        assert_eq!(test_timeout("heavy::compute"), Duration::from_secs(40));
+        assert_eq!(test_timeout("heavy::simple"), Duration::from_secs(30));
+        assert_eq!(test_timeout("light"), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn huge_slow_function_multiplier_saturates() {
+        let args = Args::try_parse_from(["mutants"]).unwrap();
+        let config = Config::from_str(indoc! {r#"
+            [[slow_functions]]
+            match = "compute"
+            timeout_multiplier = 1e300
+        "#})
+        .unwrap();
+        let options = Options::new(&args, &config).unwrap();
+        let mutants = mutate_source_str("fn compute() -> u32 { 1 }", &options).unwrap();
+        let timeouts = Timeouts {
+            build: None,
+            test: Some(Duration::from_secs(10)),
+        };
+        let scaled = timeouts.for_mutant(&mutants[0], &options);
+        assert_eq!(scaled.test, Some(Duration::MAX));
+    }
+
+    #[test]
+    fn invalid_slow_functions_are_errors() {
+        let args = Args::try_parse_from(["mutants"]).unwrap();
+        let config = Config::from_str(indoc! {r#"
+            [[slow_functions]]
+            match = "(unclosed"
+            timeout_multiplier = 2.0
+        "#})
+        .unwrap();
+        let err = Options::new(&args, &config).unwrap_err();
+        assert!(
+            format!("{err:#}").starts_with("Invalid slow_functions match \"(unclosed\""),
+            "{err:#}"
+        );
+
+        let config = Config::from_str(indoc! {r#"
+            [[slow_functions]]
+            match = "compute"
+            timeout_multiplier = 0.0
+        "#})
+        .unwrap();
+        let err = Options::new(&args, &config).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "slow_functions timeout_multiplier for \"compute\" must be a positive number"
+        );
+
+        for multiplier in ["inf", "nan", "-inf"] {
+            let config = Config::from_str(&format!(
+                "[[slow_functions]]\nmatch = \"compute\"\ntimeout_multiplier = {multiplier}\n"
+            ))
+            .unwrap();
+            let err = Options::new(&args, &config).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "slow_functions timeout_multiplier for \"compute\" must be a positive number"
+            );
+        }
+    }
+
     #[test]
     fn timeout_multiplier_from_config() {
         let args = Args::try_parse_from(["mutants"]).unwrap();
@@ -246,4 +446,61 @@ mod test {
         assert_eq!(test_timeout(None, &options), Duration::from_secs(300));
         assert_eq!(build_timeout(None, &options), None);
     }
+
+    #[test]
+    fn max_timeout_caps_computed_timeouts() {
+        let options = Options::from_arg_strs([
+            "mutants",
+            "--max-timeout=100",
+            "--build-timeout-multiplier=3",
+        ]);
+        assert_eq!(options.max_timeout, Some(Duration::from_secs(100)));
+        assert_eq!(
+            test_timeout(Some(Duration::from_secs(60)), &options),
+            Duration::from_secs(100),
+        );
+        assert_eq!(
+            test_timeout(Some(Duration::from_secs(10)), &options),
+            Duration::from_secs(50),
+        );
+        assert_eq!(
+            build_timeout(Some(Duration::from_secs(60)), &options),
+            Some(Duration::from_secs(100)),
+        );
+        assert_eq!(test_timeout(None, &options), Duration::from_secs(100));
+    }
+
+    #[test]
+    fn max_timeout_does_not_cap_explicit_timeouts() {
+        let options = Options::from_arg_strs([
+            "mutants",
+            "--max-timeout=100",
+            "--timeout=200",
+            "--build-timeout=300",
+        ]);
+        assert_eq!(
+            test_timeout(Some(Duration::from_secs(60)), &options),
+            Duration::from_secs(200),
+        );
+        assert_eq!(
+            build_timeout(Some(Duration::from_secs(60)), &options),
+            Some(Duration::from_secs(300)),
+        );
+    }
+
+    #[test]
+    fn max_timeout_from_config() {
+        let args = Args::try_parse_from(["mutants"]).unwrap();
+        let config = Config::from_str(indoc! {r#"
+            max_timeout = 30
+        "#})
+        .unwrap();
+        let options = Options::new(&args, &config).unwrap();
+
+        assert_eq!(options.max_timeout, Some(Duration::from_secs(30)));
+        assert_eq!(
+            test_timeout(Some(Duration::from_secs(42)), &options),
+            Duration::from_secs(30),
+        );
+    }
 }
@@ -0,0 +1,305 @@
+// Copyright 2025 Martin Pool
+
+//! An interactive terminal browser for discovered mutants, enabled by the `tui` feature.
+//!
+//! The browser shows the mutants grouped by file, shows the diff for the selected
+//! mutant, and can test just that mutant, with the usual live progress output.
+//!
+//! This is drawn with the `console` crate that's already used for styling, rather than
+//! a full TUI framework, so the screen is simply redrawn after each key.
+
+use std::io;
+
+use anyhow::{ensure, Context};
+use camino::Utf8Path;
+use console::{pad_str, style, truncate_str, Alignment, Key, Term};
+
+use crate::lab::test_mutants;
+use crate::output::OutputDir;
+use crate::workspace::Workspace;
+use crate::{Console, Mutant, Options, Result};
+
+/// A line in the list: either a file heading, or a mutant identified by its index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    File(usize),
+    Mutant(usize),
+}
+
+/// Group mutants by file, in the order the files are first seen.
+fn rows(mutants: &[Mutant]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut last_file = None;
+    for (i, mutant) in mutants.iter().enumerate() {
+        let file = &mutant.source_file.tree_relative_path;
+        if last_file != Some(file) {
+            rows.push(Row::File(i));
+            last_file = Some(file);
+        }
+        rows.push(Row::Mutant(i));
+    }
+    rows
+}
+
+/// The state of the browser: which mutant is selected, and which row is at the top of
+/// the screen.
+struct Browser<'a> {
+    mutants: &'a [Mutant],
+    rows: Vec<Row>,
+    /// Index into `rows` of the selected row, which is always a mutant.
+    selected: usize,
+    /// Index into `rows` of the first row shown.
+    top: usize,
+}
+
+impl Browser<'_> {
+    fn new(mutants: &[Mutant]) -> Browser<'_> {
+        let rows = rows(mutants);
+        let selected = rows
+            .iter()
+            .position(|row| matches!(row, Row::Mutant(_)))
+            .unwrap_or(0);
+        Browser {
+            mutants,
+            rows,
+            selected,
+            top: 0,
+        }
+    }
+
+    fn selected_mutant(&self) -> &Mutant {
+        match self.rows[self.selected] {
+            Row::Mutant(i) => &self.mutants[i],
+            Row::File(_) => unreachable!("file headings can't be selected"),
+        }
+    }
+
+    /// Move the selection by `delta` mutants, skipping file headings.
+    fn move_by(&mut self, delta: isize) {
+        let mutant_rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| matches!(row, Row::Mutant(_)))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let current = mutant_rows
+            .iter()
+            .position(|&i| i == self.selected)
+            .expect("selection is a mutant");
+        let target = current
+            .saturating_add_signed(delta)
+            .min(mutant_rows.len() - 1);
+        self.selected = mutant_rows[target];
+    }
+
+    /// Render the rows that fit in a screen of the given size, scrolling to keep the
+    /// selection visible, followed by a help line.
+    fn render(&mut self, height: usize, width: usize) -> Vec<String> {
+        let list_height = height.saturating_sub(1).max(1);
+        if self.selected < self.top {
+            // Show the file heading above the first mutant in a file, if possible.
+            self.top = self.selected.saturating_sub(1);
+        } else if self.selected >= self.top + list_height {
+            self.top = self.selected + 1 - list_height;
+        }
+        let mut lines = self.rows[self.top..]
+            .iter()
+            .take(list_height)
+            .enumerate()
+            .map(|(i, row)| match *row {
+                Row::File(m) => style(truncate_str(
+                    &self.mutants[m].source_file.tree_relative_slashes(),
+                    width,
+                    "…",
+                ))
+                .bold()
+                .to_string(),
+                Row::Mutant(m) => {
+                    let mutant = &self.mutants[m];
+                    let text = format!(
+                        "  {}:{}: {}",
+                        mutant.span.start.line,
+                        mutant.span.start.column,
+                        mutant.describe_change()
+                    );
+                    let text = truncate_str(&text, width, "…");
+                    if self.top + i == self.selected {
+                        style(pad_str(&text, width, Alignment::Left, None))
+                            .reverse()
+                            .to_string()
+                    } else {
+                        text.into_owned()
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        lines.push(
+            style(truncate_str(
+                "↑/↓ select · enter diff · r run · q quit",
+                width,
+                "…",
+            ))
+            .dim()
+            .to_string(),
+        );
+        lines
+    }
+}
+
+/// Browse mutants interactively until the user quits.
+pub fn browse(
+    mutants: &[Mutant],
+    workspace: &Workspace,
+    output_parent_dir: &Utf8Path,
+    options: &Options,
+    console: &Console,
+) -> Result<()> {
+    let term = Term::stdout();
+    ensure!(term.is_term(), "--tui requires an interactive terminal");
+    ensure!(
+        !mutants.is_empty(),
+        "No mutants found under the active filters"
+    );
+    let mut browser = Browser::new(mutants);
+    term.hide_cursor().context("Hide cursor")?;
+    let result = run_browser(
+        &term,
+        &mut browser,
+        workspace,
+        output_parent_dir,
+        options,
+        console,
+    );
+    term.show_cursor().context("Show cursor")?;
+    term.clear_screen().context("Clear screen")?;
+    result
+}
+
+fn run_browser(
+    term: &Term,
+    browser: &mut Browser,
+    workspace: &Workspace,
+    output_parent_dir: &Utf8Path,
+    options: &Options,
+    console: &Console,
+) -> Result<()> {
+    loop {
+        let (height, width) = term.size();
+        term.clear_screen().context("Clear screen")?;
+        for line in browser.render(height.into(), width.into()) {
+            term.write_line(&line).context("Write to terminal")?;
+        }
+        let page = isize::try_from(height).map_or(1, |h| (h - 2).max(1));
+        match term.read_key().context("Read key")? {
+            Key::ArrowUp | Key::Char('k') => browser.move_by(-1),
+            Key::ArrowDown | Key::Char('j') => browser.move_by(1),
+            Key::PageUp => browser.move_by(-page),
+            Key::PageDown | Key::Char(' ') => browser.move_by(page),
+            Key::Home => browser.move_by(isize::MIN),
+            Key::End => browser.move_by(isize::MAX),
+            Key::Enter | Key::Char('d') => {
+                let mutant = browser.selected_mutant();
+                term.clear_screen().context("Clear screen")?;
+                print!("{}", mutant.diff(&mutant.mutated_code()));
+                wait_for_key(term)?;
+            }
+            Key::Char('r') => {
+                term.clear_screen().context("Clear screen")?;
+                term.show_cursor().context("Show cursor")?;
+                test_mutants(
+                    vec![browser.selected_mutant().clone()],
+                    workspace,
+                    OutputDir::new(output_parent_dir)?,
+                    options,
+                    console,
+                )?;
+                term.hide_cursor().context("Hide cursor")?;
+                wait_for_key(term)?;
+            }
+            Key::Char('q') | Key::Escape | Key::CtrlC => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+fn wait_for_key(term: &Term) -> io::Result<()> {
+    term.write_line(
+        &style("Press any key to return to the list")
+            .dim()
+            .to_string(),
+    )?;
+    term.read_key().map(|_| ())
+}
+
+#[cfg(test)]
+mod test {
+    use console::strip_ansi_codes;
+    use indoc::indoc;
+
+    use super::*;
+    use crate::visit::mutate_source_str;
+
+    fn plain(lines: &[String]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| strip_ansi_codes(l).trim_end().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn browse_and_scroll_mutants() {
+        let code = indoc! {"
+            fn a() -> bool { x() }
+            fn b() -> bool { x() }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        let mut browser = Browser::new(&mutants);
+        assert_eq!(
+            plain(&browser.render(10, 60)),
+            [
+                "src/main.rs",
+                "  1:18: replace a -> bool with true",
+                "  1:18: replace a -> bool with false",
+                "  2:18: replace b -> bool with true",
+                "  2:18: replace b -> bool with false",
+                "↑/↓ select · enter diff · r run · q quit",
+            ]
+        );
+        assert_eq!(
+            browser.selected_mutant().name(false),
+            mutants[0].name(false)
+        );
+
+        browser.move_by(2);
+        assert_eq!(
+            browser.selected_mutant().name(false),
+            mutants[2].name(false)
+        );
+        assert_eq!(
+            plain(&browser.render(3, 60)),
+            [
+                "  1:18: replace a -> bool with false",
+                "  2:18: replace b -> bool with true",
+                "↑/↓ select · enter diff · r run · q quit",
+            ],
+            "list scrolls down to keep the selection visible"
+        );
+
+        browser.move_by(isize::MAX);
+        assert_eq!(
+            browser.selected_mutant().name(false),
+            mutants[3].name(false)
+        );
+        browser.move_by(isize::MIN);
+        assert_eq!(
+            browser.selected_mutant().name(false),
+            mutants[0].name(false)
+        );
+        assert_eq!(
+            plain(&browser.render(3, 60))[..1],
+            ["src/main.rs"],
+            "scrolling up shows the file heading"
+        );
+    }
+}
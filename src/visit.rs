@@ -9,23 +9,42 @@
 
 #![warn(clippy::pedantic)]
 
+use std::borrow::Cow;
 use std::collections::VecDeque;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::thread;
 use std::vec;
 
 use camino::{Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
+use proc_macro2::extra::DelimSpan;
 use proc_macro2::{Ident, TokenStream};
 use quote::{quote, ToTokens};
+use serde::{Deserialize, Serialize};
 use syn::ext::IdentExt;
 use syn::spanned::Spanned;
+use syn::token::RArrow;
 use syn::visit::Visit;
-use syn::{Attribute, BinOp, Block, Expr, ExprPath, File, ItemFn, ReturnType, Signature, UnOp};
+use syn::{
+    Attribute, BinOp, Block, Expr, ExprCall, ExprCast, ExprLit, ExprPath, ExprReturn, File,
+    Generics, Item, ItemFn, Lit, LitInt, Pat, ReturnType, Signature, Stmt, Type, TypePath, UnOp,
+    UseTree, Visibility,
+};
 use tracing::{debug, debug_span, error, info, trace, trace_span, warn};
 
+use crate::cfg::attr_is_inactive_cfg;
 use crate::console::WalkProgress;
-use crate::fnvalue::return_type_replacements;
+use crate::discovery_cache::{cache_key, CacheEntry, DiscoveryCache};
+use crate::fnvalue::{
+    assoc_const_replacements, result_swap_replacements, return_type_replacements,
+    tuple_element_replacements, FileConsts, ImplConsts,
+};
 use crate::mutant::Function;
 use crate::package::Package;
+use crate::path::normalize;
 use crate::pretty::ToPrettyString;
 use crate::source::SourceFile;
 use crate::span::Span;
@@ -38,6 +57,133 @@ use crate::{check_interrupted, Console, Context, Genre, Mutant, Options, Result}
 pub struct Discovered {
     pub mutants: Vec<Mutant>,
     pub files: Vec<SourceFile>,
+    /// Functions and other items that produced no mutants, and why.
+    pub skipped: Vec<Skipped>,
+}
+
+/// Why a function or other item generated no mutants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// Marked with `#[mutants::skip]`, possibly inside `cfg_attr`.
+    MutantsSkip,
+    /// Marked with `#[cfg(test)]`.
+    CfgTest,
+    /// Marked with a `#[cfg]` that is false on this platform, like `#[cfg(windows)]` on Unix.
+    InactiveCfg,
+    /// A `#[test]` function.
+    TestFunction,
+    /// An `unsafe fn`.
+    Unsafe,
+    /// The function body is empty.
+    EmptyBody,
+    /// Functions called `new` are skipped because there's often no good alternative.
+    Constructor,
+    /// It's hard to generate a viable different value from `impl Default`.
+    DefaultImpl,
+    /// No replacement values are known for the return type, and nothing else in the
+    /// function could be mutated.
+    NoReturnValue,
+    /// The function is not public, and `--only-public` was given.
+    NotPublic,
+    /// The function returns `()` and has a single-expression body, and
+    /// `--skip-unit-returns` was given.
+    UnitReturn,
+    /// The function is `fn main` at the top of a binary, and `--skip-main` was given.
+    BinaryMain,
+    /// Nothing in the function could be mutated.
+    NothingToMutate,
+    /// Mutants were generated, but all of them were excluded by name filters.
+    Filtered,
+    /// The source file is excluded by `--file` or `--exclude` globs.
+    ExcludedFile,
+}
+
+impl SkipReason {
+    /// The broad category of this reason.
+    pub fn category(self) -> SkipCategory {
+        match self {
+            SkipReason::MutantsSkip | SkipReason::TestFunction => SkipCategory::Attribute,
+            SkipReason::CfgTest | SkipReason::InactiveCfg => SkipCategory::Cfg,
+            SkipReason::NotPublic
+            | SkipReason::UnitReturn
+            | SkipReason::BinaryMain
+            | SkipReason::Filtered
+            | SkipReason::ExcludedFile => SkipCategory::Excluded,
+            SkipReason::Unsafe
+            | SkipReason::EmptyBody
+            | SkipReason::Constructor
+            | SkipReason::DefaultImpl
+            | SkipReason::NoReturnValue
+            | SkipReason::NothingToMutate => SkipCategory::NoOperator,
+        }
+    }
+}
+
+/// A broad category of [`SkipReason`], recorded in `skipped.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SkipCategory {
+    /// Skipped because of an attribute on the item, like `#[mutants::skip]` or `#[test]`.
+    Attribute,
+    /// Excluded by options or filters, like `--exclude` or `--only-public`.
+    Excluded,
+    /// Under a `#[cfg]` that's not active, or `#[cfg(test)]`.
+    Cfg,
+    /// Nothing in the item could be mutated.
+    NoOperator,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::MutantsSkip => "marked #[mutants::skip]",
+            SkipReason::CfgTest => "marked #[cfg(test)]",
+            SkipReason::InactiveCfg => "#[cfg] is inactive on this platform",
+            SkipReason::TestFunction => "test function",
+            SkipReason::Unsafe => "unsafe function",
+            SkipReason::EmptyBody => "empty body",
+            SkipReason::Constructor => "constructor named new",
+            SkipReason::DefaultImpl => "impl Default",
+            SkipReason::NoReturnValue => "no replacement values for the return type",
+            SkipReason::NotPublic => "not public",
+            SkipReason::UnitReturn => "single-expression unit function",
+            SkipReason::BinaryMain => "main function of a binary",
+            SkipReason::NothingToMutate => "nothing to mutate",
+            SkipReason::Filtered => "all mutants excluded by filters",
+            SkipReason::ExcludedFile => "file excluded by --file or --exclude",
+        })
+    }
+}
+
+/// A function or other item that generated no mutants.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Skipped {
+    /// The tree-relative path of the source file, with forward slashes.
+    pub file: String,
+    /// The line where the item starts.
+    pub line: usize,
+    /// The name of the item, including any containing namespaces.
+    pub name: String,
+    pub reason: SkipReason,
+    /// The explanation given in `#[mutants::skip(reason = "...")]`, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+}
+
+impl Skipped {
+    /// Describe why this item was skipped, in the same style as `--list`.
+    pub fn describe(&self, show_line_col: bool) -> String {
+        let location = if show_line_col {
+            format!("{}:{}", self.file, self.line)
+        } else {
+            self.file.clone()
+        };
+        let Skipped { name, reason, .. } = self;
+        if let Some(skip_reason) = &self.skip_reason {
+            format!("{location}: {name}: {reason} ({skip_reason})")
+        } else {
+            format!("{location}: {name}: {reason}")
+        }
+    }
 }
 
 impl Discovered {
@@ -58,100 +204,340 @@ impl Discovered {
 /// The returned `Discovered` struct contains all mutants found in the
 /// source tree, and also a list of all source files visited (whether
 /// they generated mutants or not).
+///
+/// Files are parsed on several threads, but the results are in the same order as if
+/// they were visited one at a time: by package, and then breadth-first from the top
+/// source files of each package through the modules they reference.
+///
+/// If a `cache` is given, the results for files that haven't changed since they were
+/// cached are taken from it, and the results for every file are added to it.
 pub fn walk_tree(
     workspace_dir: &Utf8Path,
     packages: &[Arc<Package>],
     options: &Options,
     console: &Console,
+    cache: Option<&DiscoveryCache>,
 ) -> Result<Discovered> {
-    let mut mutants = Vec::new();
-    let mut files = Vec::new();
-    let error_exprs = options.parsed_error_exprs()?;
     let progress = console.start_walk_tree();
-    for package in packages {
-        let (mut package_mutants, mut package_files) =
-            walk_package(workspace_dir, package, &error_exprs, &progress, options)?;
-        mutants.append(&mut package_mutants);
-        files.append(&mut package_files);
+    let mut walks = packages.iter().map(PackageWalk::new).collect_vec();
+    loop {
+        // Visit every file that's currently queued, in all packages, as one batch.
+        // Files found from `mod` statements in this batch are visited in the next.
+        let mut batch = Vec::new();
+        for (package_index, walk) in walks.iter_mut().enumerate() {
+            while let Some((path, package_top, namespace, public_mod)) = walk.queue.pop_front() {
+                // The same file can be included as more than one module, for example by
+                // `#[path]` attributes, but its mutants should only be generated once.
+                if walk.visited.contains(&path) {
+                    debug!(?path, "Source file was already visited");
+                    continue;
+                }
+                walk.visited.push(path.clone());
+                batch.push((package_index, path, package_top, namespace, public_mod));
+            }
+        }
+        if batch.is_empty() {
+            break;
+        }
+        let results = parallel_map(
+            &batch,
+            |(package_index, path, package_top, namespace, public_mod)| {
+                let package = &walks[*package_index].package;
+                walk_package_file(
+                    workspace_dir,
+                    package,
+                    path,
+                    *package_top,
+                    namespace,
+                    *public_mod,
+                    &progress,
+                    options.for_package(&package.name),
+                    cache,
+                )
+            },
+        );
+        for ((package_index, ..), result) in batch.into_iter().zip(results) {
+            if let Some(file_walk) = result? {
+                walks[package_index].add(file_walk);
+            }
+        }
     }
     progress.finish();
-    Ok(Discovered { mutants, files })
+    let mut discovered = Discovered {
+        mutants: Vec::new(),
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for mut walk in walks {
+        discovered.mutants.append(&mut walk.discovered.mutants);
+        discovered.files.append(&mut walk.discovered.files);
+        discovered.skipped.append(&mut walk.discovered.skipped);
+    }
+    Ok(discovered)
 }
 
-/// Walk one package, starting from its top files, discovering files
-/// and mutants.
-#[allow(clippy::from_iter_instead_of_collect)]
-fn walk_package(
-    workspace_dir: &Utf8Path,
-    package: &Package,
-    error_exprs: &[Expr],
-    progress: &WalkProgress,
-    options: &Options,
-) -> Result<(Vec<Mutant>, Vec<SourceFile>)> {
-    let mut mutants = Vec::new();
-    let mut files = Vec::new();
-    let mut filename_queue =
-        VecDeque::from_iter(package.top_sources.iter().map(|p| (p.to_owned(), true)));
-    while let Some((path, package_top)) = filename_queue.pop_front() {
-        let Some(source_file) = SourceFile::load(workspace_dir, &path, package, package_top)?
-        else {
-            info!("Skipping source file outside of tree: {path:?}");
-            continue;
-        };
-        progress.increment_files(1);
-        check_interrupted()?;
-        let (mut file_mutants, external_mods) = walk_file(&source_file, error_exprs, options)?;
-        file_mutants.retain(|m| options.allows_mutant(m));
-        progress.increment_mutants(file_mutants.len());
-        // TODO: It would be better not to spend time generating mutants from
-        // files that are not going to be visited later. However, we probably do
-        // still want to walk them to find modules that are referenced by them.
-        // since otherwise it could be pretty confusing that lower files are not
-        // visited.
-        //
+/// The state of walking the source files of one package.
+struct PackageWalk<'p> {
+    package: &'p Package,
+    /// Files still to visit, whether each is a top source file of the package, the
+    /// namespace its contents are in, and whether it's in a public module.
+    queue: VecDeque<(Utf8PathBuf, bool, Vec<String>, bool)>,
+    visited: Vec<Utf8PathBuf>,
+    discovered: Discovered,
+}
+
+impl PackageWalk<'_> {
+    fn new(package: &Arc<Package>) -> PackageWalk<'_> {
+        PackageWalk {
+            package,
+            queue: package
+                .top_sources
+                .iter()
+                .map(|p| (p.to_owned(), true, Vec::new(), true))
+                .collect(),
+            visited: Vec::new(),
+            discovered: Discovered {
+                mutants: Vec::new(),
+                files: Vec::new(),
+                skipped: Vec::new(),
+            },
+        }
+    }
+
+    fn add(&mut self, mut file_walk: FileWalk) {
         // We'll still walk down through files that don't match globs, so that
         // we have a chance to find modules underneath them. However, we won't
         // collect any mutants from them, and they don't count as "seen" for
         // `--list-files`.
-        for mod_namespace in &external_mods {
-            if let Some(mod_path) = find_mod_source(workspace_dir, &source_file, mod_namespace) {
-                filename_queue.push_back((mod_path, false));
-            }
+        self.queue.extend(
+            file_walk
+                .mod_paths
+                .into_iter()
+                .map(|(path, public_mod)| (path, false, Vec::new(), public_mod)),
+        );
+        self.queue.extend(
+            file_walk
+                .includes
+                .into_iter()
+                .map(|include| (include.path, false, include.namespace, include.public_mod)),
+        );
+        self.discovered.skipped.append(&mut file_walk.skipped);
+        if file_walk.allowed {
+            self.discovered.mutants.append(&mut file_walk.mutants);
+            self.discovered.files.push(file_walk.source_file);
+        }
+    }
+}
+
+/// The results of visiting one source file.
+struct FileWalk {
+    source_file: SourceFile,
+    mutants: Vec<Mutant>,
+    skipped: Vec<Skipped>,
+    /// Paths of the files of modules referenced from this file, and whether each
+    /// module is public.
+    mod_paths: Vec<(Utf8PathBuf, bool)>,
+    /// Files included into this file by `include!`.
+    includes: Vec<IncludeRef>,
+    /// True if the options allow mutants from this file.
+    allowed: bool,
+}
+
+/// Load and visit one source file in a package.
+///
+/// `namespace` is the namespace of the file's contents, which is empty except for files
+/// included by `include!`. `public_mod` is true if the file's contents are in a module
+/// that's visible outside of the crate.
+///
+/// Returns None if the file is outside of the tree.
+#[allow(clippy::too_many_arguments)]
+fn walk_package_file(
+    workspace_dir: &Utf8Path,
+    package: &Package,
+    path: &Utf8Path,
+    package_top: bool,
+    namespace: &[String],
+    public_mod: bool,
+    progress: &WalkProgress,
+    options: &Options,
+    cache: Option<&DiscoveryCache>,
+) -> Result<Option<FileWalk>> {
+    let Some(source_file) = SourceFile::load(workspace_dir, path, package, package_top)? else {
+        info!("Skipping source file outside of tree: {path:?}");
+        return Ok(None);
+    };
+    progress.increment_files(1);
+    check_interrupted()?;
+    let cache_key = cache.map(|_| cache_key(&source_file, namespace, public_mod, options));
+    let CacheEntry {
+        mut mutants,
+        external_mods,
+        includes,
+        mut skipped,
+    } = if let Some(entry) = cache
+        .zip(cache_key.as_deref())
+        .and_then(|(cache, key)| cache.get(key, &source_file))
+    {
+        debug!(
+            path = source_file.tree_relative_slashes(),
+            "Loaded mutants from the discovery cache"
+        );
+        entry
+    } else {
+        // syn expressions can't be shared between threads, so they're parsed again here.
+        let error_exprs = options.parsed_error_exprs()?;
+        walk_file(&source_file, namespace, public_mod, &error_exprs, options)?
+    };
+    if let (Some(cache), Some(cache_key)) = (cache, cache_key) {
+        cache.insert(
+            cache_key,
+            CacheEntry {
+                mutants: mutants.clone(),
+                external_mods: external_mods.clone(),
+                includes: includes.clone(),
+                skipped: skipped.clone(),
+            },
+        );
+    }
+    let mut functions_before_filter: Vec<Arc<Function>> = Vec::new();
+    for function in mutants.iter().filter_map(|m| m.function.as_ref()) {
+        if !functions_before_filter.contains(function) {
+            functions_before_filter.push(Arc::clone(function));
         }
-        if !options.allows_source_file_path(&source_file.tree_relative_path) {
+    }
+    mutants.retain(|m| options.allows_mutant(m));
+    let allowed = options.allows_source_file_path(&source_file.tree_relative_path);
+    for function in functions_before_filter {
+        // Functions that were already skipped for a more specific reason keep it, and
+        // every other function that would have had mutants is skipped because of the file.
+        if skipped
+            .iter()
+            .any(|s| s.line == function.span.start.line && s.name == function.function_name)
+        {
             continue;
         }
-        mutants.append(&mut file_mutants);
-        files.push(source_file);
+        if !allowed
+            || !mutants
+                .iter()
+                .any(|m| m.function.as_ref() == Some(&function))
+        {
+            skipped.push(Skipped {
+                file: source_file.tree_relative_slashes(),
+                line: function.span.start.line,
+                name: function.function_name.clone(),
+                reason: if allowed {
+                    SkipReason::Filtered
+                } else {
+                    SkipReason::ExcludedFile
+                },
+                skip_reason: None,
+            });
+        }
     }
-    Ok((mutants, files))
+    progress.increment_mutants(mutants.len());
+    // TODO: It would be better not to spend time generating mutants from
+    // files that are not going to be visited later. However, we probably do
+    // still want to walk them to find modules that are referenced by them.
+    // since otherwise it could be pretty confusing that lower files are not
+    // visited.
+    let mod_paths = external_mods
+        .iter()
+        .filter_map(|mod_namespace| {
+            find_mod_source(workspace_dir, &source_file, mod_namespace)
+                .map(|path| (path, mod_namespace.public))
+        })
+        .collect();
+    Ok(Some(FileWalk {
+        source_file,
+        mutants,
+        skipped,
+        mod_paths,
+        includes,
+        allowed,
+    }))
+}
+
+/// Apply `f` to each item on a pool of threads, and return the results in the same
+/// order as the items.
+fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let n_threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(items.len());
+    if n_threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let threads = (0..n_threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            break done;
+                        };
+                        done.push((i, f(item)));
+                    }
+                })
+            })
+            .collect_vec();
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().expect("walk thread panicked"))
+            .collect()
+    });
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
 }
 
-/// Find all possible mutants in a source file.
+/// Find all possible mutants in a source file, whose contents are in `namespace`.
 ///
-/// Returns the mutants found, and the names of modules referenced by `mod` statements
-/// that should be visited later.
+/// Returns the mutants found, the modules referenced by `mod` statements and the files
+/// included by `include!`, which should be visited later, and items that generated no
+/// mutants.
 fn walk_file(
     source_file: &SourceFile,
+    namespace: &[String],
+    public_mod: bool,
     error_exprs: &[Expr],
     options: &Options,
-) -> Result<(Vec<Mutant>, Vec<ExternalModRef>)> {
+) -> Result<CacheEntry<Mutant>> {
     let _span = debug_span!("source_file", path = source_file.tree_relative_slashes()).entered();
     debug!("visit source file");
     let syn_file = syn::parse_str::<syn::File>(source_file.code())
         .with_context(|| format!("failed to parse {}", source_file.tree_relative_slashes()))?;
     let mut visitor = DiscoveryVisitor {
         error_exprs,
+        file_consts: FileConsts::from_file(&syn_file),
         external_mods: Vec::new(),
+        includes: Vec::new(),
         mutants: Vec::new(),
+        skipped: Vec::new(),
+        fns_without_return_values: Vec::new(),
+        fns_with_skipped_unit_returns: Vec::new(),
+        skipped_main: None,
+        impl_consts: None,
+        outer_generics: None,
+        in_type_or_pattern: false,
+        whole_body_literals: Vec::new(),
+        index_literals: Vec::new(),
+        in_public_trait_scope: false,
+        in_public_mod: public_mod,
+        reexported_mods: reexported_mods(&syn_file.items),
         mod_namespace_stack: Vec::new(),
-        namespace_stack: Vec::new(),
+        namespace_stack: namespace.to_vec(),
         fn_stack: Vec::new(),
         source_file: source_file.clone(),
         options,
     };
     visitor.visit_file(&syn_file);
-    Ok((visitor.mutants, visitor.external_mods))
+    Ok(CacheEntry {
+        mutants: visitor.mutants,
+        external_mods: visitor.external_mods,
+        includes: visitor.includes,
+        skipped: visitor.skipped,
+    })
 }
 
 /// For testing: parse and generate mutants from one single file provided as a string.
@@ -165,8 +551,34 @@ pub fn mutate_source_str(code: &str, options: &Options) -> Result<Vec<Mutant>> {
         "cargo-mutants-testdata-internal",
         true,
     );
-    let (mutants, _) = walk_file(&source_file, &options.parsed_error_exprs()?, options)?;
-    Ok(mutants)
+    Ok(walk_file(
+        &source_file,
+        &[],
+        true,
+        &options.parsed_error_exprs()?,
+        options,
+    )?
+    .mutants)
+}
+
+/// For testing: parse one file provided as a string, and return the items that generated
+/// no mutants.
+#[cfg(test)]
+pub fn skipped_in_source_str(code: &str, options: &Options) -> Result<Vec<Skipped>> {
+    let source_file = SourceFile::for_tests(
+        Utf8Path::new("src/main.rs"),
+        code,
+        "cargo-mutants-testdata-internal",
+        true,
+    );
+    Ok(walk_file(
+        &source_file,
+        &[],
+        true,
+        &options.parsed_error_exprs()?,
+        options,
+    )?
+    .skipped)
 }
 
 /// Reference to an external module from a source file.
@@ -174,10 +586,24 @@ pub fn mutate_source_str(code: &str, options: &Options) -> Result<Vec<Mutant>> {
 /// This is approximately a list of namespace components like `["foo", "bar"]` for
 /// `foo::bar`, but each may also be decorated with a `#[path="..."]` attribute,
 /// and they're attributed to a location in the source.
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct ExternalModRef {
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ExternalModRef {
     /// Namespace components of the module path
     parts: Vec<ModNamespace>,
+    /// True if the module is visible outside of the crate.
+    public: bool,
+}
+
+/// A file included by `include!("path")`, whose contents are treated as if they were
+/// written in place of the macro.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct IncludeRef {
+    /// The path of the included file, relative to the tree root.
+    path: Utf8PathBuf,
+    /// The namespace where the file is included.
+    namespace: Vec<String>,
+    /// True if the file is included into a module that's visible outside of the crate.
+    public_mod: bool,
 }
 
 /// Namespace for a module defined in a `mod foo { ... }` block or `mod foo;` statement
@@ -187,7 +613,7 @@ struct ExternalModRef {
 /// folder and file names on the filesystem (e.g. `src/foo/bar.rs`).
 ///
 /// The field and method names in this struct distinguish between the uses of path elements.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 struct ModNamespace {
     /// Identifier of the module (e.g. `foo` for `mod foo;`)
     name: String,
@@ -218,6 +644,17 @@ struct DiscoveryVisitor<'o> {
     /// All the mutants generated by visiting the file.
     mutants: Vec<Mutant>,
 
+    /// Items that generated no mutants, and why.
+    skipped: Vec<Skipped>,
+
+    /// Functions for which no return values could be generated.
+    fns_without_return_values: Vec<Arc<Function>>,
+
+    /// Functions whose unit return value wasn't replaced, because of `--skip-unit-returns`.
+    fns_with_skipped_unit_returns: Vec<Arc<Function>>,
+    /// The `main` function whose body wasn't replaced, because of `--skip-main`.
+    skipped_main: Option<Arc<Function>>,
+
     /// The file being visited.
     source_file: SourceFile,
 
@@ -247,6 +684,40 @@ struct DiscoveryVisitor<'o> {
     /// namespaced relative to the source file
     external_mods: Vec<ExternalModRef>,
 
+    /// Files included by `include!` that should be visited later.
+    includes: Vec<IncludeRef>,
+
+    /// The associated constants of the `impl` block we're inside, if any.
+    impl_consts: Option<ImplConsts>,
+
+    /// The associated constants declared anywhere in this file.
+    file_consts: FileConsts,
+
+    /// The generic parameters of the `impl` or `trait` block we're inside, if any, which
+    /// are also in scope for its functions.
+    outer_generics: Option<Generics>,
+
+    /// True while visiting a type or a pattern, where literals aren't mutated.
+    in_type_or_pattern: bool,
+
+    /// Spans of literals that are the whole body of a function, which are already
+    /// replaced by `FnValue` mutants.
+    whole_body_literals: Vec<Span>,
+
+    /// Spans of integer literals used as indexes, which are shifted by `Index` mutants.
+    index_literals: Vec<Span>,
+
+    /// True while visiting a trait impl or a `pub` trait, where functions are public
+    /// without their own `pub`.
+    in_public_trait_scope: bool,
+
+    /// True while visiting a module that's visible outside of the crate: every module
+    /// enclosing it is `pub`, or re-exported with `pub use`.
+    in_public_mod: bool,
+
+    /// Names of the modules re-exported by `pub use` among the items we're visiting.
+    reexported_mods: Vec<String>,
+
     /// Parsed error expressions, from the config file or command line.
     error_exprs: &'o [Expr],
 
@@ -271,7 +742,39 @@ impl DiscoveryVisitor<'_> {
         function
     }
 
+    /// True if the current function is a `fn main` at the top level of the top source
+    /// file of a target, which is where the entry point of a binary must be.
+    ///
+    /// This can't tell a binary from a library that happens to have a top-level function
+    /// called `main`, but that's rare.
+    fn is_binary_main(&self) -> bool {
+        self.source_file.is_top && self.namespace_stack == ["main"]
+    }
+
     fn leave_function(&mut self, function: Arc<Function>) {
+        if !self
+            .mutants
+            .iter()
+            .rev()
+            .any(|m| m.function.as_ref() == Some(&function))
+        {
+            let reason = if self.fns_without_return_values.contains(&function) {
+                SkipReason::NoReturnValue
+            } else if self.fns_with_skipped_unit_returns.contains(&function) {
+                SkipReason::UnitReturn
+            } else if self.skipped_main.as_ref() == Some(&function) {
+                SkipReason::BinaryMain
+            } else {
+                SkipReason::NothingToMutate
+            };
+            self.skipped.push(Skipped {
+                file: self.source_file.tree_relative_slashes(),
+                line: function.span.start.line,
+                name: function.function_name.clone(),
+                reason,
+                skip_reason: None,
+            });
+        }
         self.namespace_stack
             .pop()
             .expect("Namespace stack should not be empty");
@@ -282,22 +785,321 @@ impl DiscoveryVisitor<'_> {
         );
     }
 
+    /// Record that an item named `name`, within the current namespace, generated no mutants.
+    ///
+    /// `attrs` are the item's attributes, from which any reason given in
+    /// `#[mutants::skip(reason = "...")]` is taken.
+    fn skip(
+        &mut self,
+        name: &str,
+        span: proc_macro2::Span,
+        reason: SkipReason,
+        attrs: &[Attribute],
+    ) {
+        trace!(name, %reason, "skip");
+        let skip_reason = if reason == SkipReason::MutantsSkip {
+            attrs
+                .iter()
+                .find_map(mutants_skip_attr)
+                .and_then(|skip| skip.reason)
+        } else {
+            None
+        };
+        let name = self
+            .namespace_stack
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(name))
+            .join("::");
+        self.skipped.push(Skipped {
+            file: self.source_file.tree_relative_slashes(),
+            line: span.start().line,
+            name,
+            reason,
+            skip_reason,
+        });
+    }
+
+    /// If `--only-public` was given, and a function with this visibility in the current
+    /// scope is not public, return [`SkipReason::NotPublic`].
+    ///
+    /// Functions nested inside another function are part of its implementation, and so
+    /// are mutated if the outer function is. Methods of trait impls are public wherever
+    /// the impl is, but other functions in a private module are not public.
+    fn visibility_skip_reason(&self, vis: &Visibility) -> Option<SkipReason> {
+        (self.options.only_public
+            && self.fn_stack.is_empty()
+            && !self.in_public_trait_scope
+            && !(self.in_public_mod && matches!(vis, Visibility::Public(_))))
+        .then_some(SkipReason::NotPublic)
+    }
+
+    /// True if any of the attrs indicate that we should skip this node and everything inside it.
+    fn attrs_excluded(&self, attrs: &[Attribute]) -> bool {
+        self.attrs_skip_reason(attrs).is_some()
+    }
+
+    /// If any of the attrs indicate that we should skip this node and everything inside it,
+    /// return why.
+    ///
+    /// This checks for `#[cfg(test)]`, `#[test]`, and `#[mutants::skip]`, and, unless
+    /// `--all-platforms` was given, `#[cfg]` attributes that are false on this platform.
+    fn attrs_skip_reason(&self, attrs: &[Attribute]) -> Option<SkipReason> {
+        attrs.iter().find_map(|attr| {
+            if attr_is_cfg_test(attr) {
+                Some(SkipReason::CfgTest)
+            } else if attr_is_test(attr) {
+                Some(SkipReason::TestFunction)
+            } else if attr_is_mutants_skip(attr) {
+                Some(SkipReason::MutantsSkip)
+            } else if !self.options.all_platforms
+                && attr_is_inactive_cfg(attr, &self.options.target_cfg)
+            {
+                Some(SkipReason::InactiveCfg)
+            } else {
+                None
+            }
+        })
+    }
+
     /// Record that we generated some mutants.
     fn collect_mutant(&mut self, span: Span, replacement: &TokenStream, genre: Genre) {
+        self.collect_mutant_text(span, replacement.to_pretty_string(), genre);
+    }
+
+    /// Record a mutant whose replacement is already formatted as text.
+    fn collect_mutant_text(&mut self, span: Span, replacement: String, genre: Genre) {
         self.mutants.push(Mutant {
             source_file: self.source_file.clone(),
             function: self.fn_stack.last().cloned(),
             span,
-            replacement: replacement.to_pretty_string(),
+            replacement,
             genre,
+            tuple_index: None,
+            match_arm_pattern: None,
+        });
+    }
+
+    /// Replace `Clone::clone(&x)`, or `Rc::clone(&x)` and similar, with `x`.
+    ///
+    /// This is the same as deleting the `.clone()` in `x.clone()`, for code that calls
+    /// `clone` as a function.
+    fn collect_clone_call_mutant(&mut self, call: &syn::ExprCall, path: &syn::Path) {
+        if !path_ends_with(path, "clone") || call.args.len() != 1 {
+            return;
+        }
+        if let Some(Expr::Reference(reference)) = call.args.first() {
+            if reference.mutability.is_none() {
+                let receiver = Span::from(reference.expr.span()).extract(self.source_file.code());
+                self.collect_mutant_text(call.span().into(), receiver, Genre::MethodCall);
+            }
+        }
+    }
+
+    /// Shift an integer literal used as an index, in `a[0]` or `a.get(0)`, up and down by one.
+    fn collect_index_mutants(&mut self, index: &Expr) {
+        if !self.options.generates_genre(Genre::Index) || self.in_type_or_pattern {
+            return;
+        }
+        let Expr::Lit(ExprLit {
+            lit: Lit::Int(lit_int),
+            attrs,
+        }) = index
+        else {
+            return;
+        };
+        if self.attrs_excluded(attrs) {
+            return;
+        }
+        let span: Span = lit_int.span().into();
+        self.index_literals.push(span);
+        for replacement in index_replacements(lit_int) {
+            self.collect_mutant_text(span, replacement, Genre::Index);
+        }
+    }
+
+    /// Remember a file included by `include!`, to be visited later.
+    ///
+    /// As for the compiler, the path is relative to the directory of the file containing
+    /// the macro. Only string literals are understood, so for example paths built with
+    /// `concat!` are skipped.
+    fn collect_include(&mut self, mac: &syn::Macro) {
+        let location = self
+            .source_file
+            .format_source_location(Span::from(mac.span()).start);
+        let Ok(lit) = mac.parse_body::<syn::LitStr>() else {
+            debug!(%location, "skipping include! without a string literal path");
+            return;
+        };
+        let include_path = Utf8PathBuf::from(lit.value());
+        if include_path.is_absolute() {
+            warn!(%location, %include_path, "skipping include! with an absolute path");
+            return;
+        }
+        let path = self
+            .source_file
+            .tree_relative_path
+            .parent()
+            .expect("source file has a parent directory")
+            .join(&include_path);
+        // Paths that leave the tree are kept as written, to be reported when the file is
+        // loaded.
+        let path = normalize(&path).unwrap_or(path);
+        trace!(%path, "found include!");
+        self.includes.push(IncludeRef {
+            path,
+            namespace: self.namespace_stack.clone(),
+            public_mod: self.in_public_mod,
         });
     }
 
+    /// Replace a call to a function or macro with each matching custom mutation.
+    ///
+    /// `span` covers the whole call, and `delimiter` surrounds its arguments, whose
+    /// original text is substituted into the template.
+    fn collect_custom_mutants(&mut self, path: &syn::Path, span: Span, delimiter: &DelimSpan) {
+        if self.options.custom_mutations.is_empty() || !self.options.generates_genre(Genre::Custom)
+        {
+            return;
+        }
+        let args = Span {
+            start: delimiter.open().end().into(),
+            end: delimiter.close().start().into(),
+        }
+        .extract(self.source_file.code());
+        let replacements = self
+            .options
+            .custom_mutations
+            .iter()
+            .filter(|custom| path_matches_segments(path, &custom.path_segments()))
+            .map(|custom| custom.replacement(&args))
+            .collect_vec();
+        for replacement in replacements {
+            self.collect_mutant_text(span, replacement, Genre::Custom);
+        }
+    }
+
+    /// Replace a call to a macro with each matching custom mutation.
+    fn collect_macro_custom_mutants(&mut self, mac: &syn::Macro) {
+        let delimiter = mac.delimiter.span();
+        let span = Span {
+            start: mac.path.span().start().into(),
+            end: delimiter.close().end().into(),
+        };
+        self.collect_custom_mutants(&mac.path, span, delimiter);
+    }
+
+    /// Replace the condition of an `if` or `while` with its negation.
+    ///
+    /// The original text of the condition is kept, rather than reformatting it.
+    /// Conditions containing `let` aren't simple boolean expressions, so are skipped.
+    fn collect_condition_mutant(&mut self, cond: &Expr) {
+        if !self.options.generates_genre(Genre::Condition) || condition_has_let(cond) {
+            return;
+        }
+        let span: Span = cond.span().into();
+        let replacement = format!("!({})", span.extract(self.source_file.code()));
+        self.collect_mutant_text(span, replacement, Genre::Condition);
+    }
+
+    /// Swap `.min(x)` and `.max(x)`, and replace `.clamp(lo, hi)` with `.min(hi)` or
+    /// `.max(lo)`.
+    ///
+    /// Swapping the bounds of `clamp` would just panic, so instead each bound is
+    /// dropped in turn, checking that the tests exercise both of them.
+    fn collect_min_max_mutants(&mut self, i: &syn::ExprMethodCall) {
+        let start = i.dot_token.span.start().into();
+        match (i.method.to_string().as_str(), i.args.len()) {
+            ("min", 1) => {
+                let span = Span {
+                    start,
+                    end: i.method.span().end().into(),
+                };
+                self.collect_mutant(span, &quote! { .max }, Genre::MinMax);
+            }
+            ("max", 1) => {
+                let span = Span {
+                    start,
+                    end: i.method.span().end().into(),
+                };
+                self.collect_mutant(span, &quote! { .min }, Genre::MinMax);
+            }
+            ("clamp", 2) => {
+                let span = Span {
+                    start,
+                    end: i.paren_token.span.close().end().into(),
+                };
+                let (lo, hi) = (&i.args[0], &i.args[1]);
+                self.collect_mutant(span, &quote! { .min(#hi) }, Genre::MinMax);
+                self.collect_mutant(span, &quote! { .max(#lo) }, Genre::MinMax);
+            }
+            _ => (),
+        }
+    }
+
     fn collect_fn_mutants(&mut self, sig: &Signature, block: &Block) {
+        if self.options.generates_genre(Genre::FnValue) {
+            self.collect_fn_value_mutants(sig, block);
+            if let [Stmt::Expr(Expr::Lit(expr_lit), None)] = block.stmts.as_slice() {
+                self.whole_body_literals.push(expr_lit.lit.span().into());
+            }
+        }
+        if self.options.generates_genre(Genre::EarlyReturn) {
+            self.collect_early_return_mutants(sig, block);
+        }
+        if self.options.generates_genre(Genre::MatchArm) {
+            self.collect_match_arm_mutants(sig, block);
+        }
+    }
+
+    /// The generic parameters in scope in a function: its own, and those of the
+    /// enclosing `impl` or `trait` block.
+    fn fn_generics<'s>(&self, sig: &'s Signature) -> Cow<'s, Generics> {
+        let Some(outer) = &self.outer_generics else {
+            return Cow::Borrowed(&sig.generics);
+        };
+        let mut generics = sig.generics.clone();
+        generics.params = outer
+            .params
+            .iter()
+            .chain(&sig.generics.params)
+            .cloned()
+            .collect();
+        if let Some(outer_where) = &outer.where_clause {
+            generics
+                .make_where_clause()
+                .predicates
+                .extend(outer_where.predicates.iter().cloned());
+        }
+        Cow::Owned(generics)
+    }
+
+    fn collect_fn_value_mutants(&mut self, sig: &Signature, block: &Block) {
         if let Some(function) = self.fn_stack.last().cloned() {
+            if self.options.skip_unit_returns
+                && returns_unit(&sig.output)
+                && block_is_single_expr(block)
+            {
+                debug!(
+                    function_name = function.function_name,
+                    "Skipping unit replacement for single-expression body"
+                );
+                self.fns_with_skipped_unit_returns.push(function);
+                return;
+            }
+            if self.options.skip_main && self.is_binary_main() {
+                debug!("Skipping replacement of main");
+                self.skipped_main = Some(function);
+                return;
+            }
             let body_span = function_body_span(block).expect("Empty function body");
-            let repls = return_type_replacements(&sig.output, self.error_exprs);
+            let mut repls =
+                return_type_replacements(&sig.output, &self.fn_generics(sig), self.error_exprs);
+            if let Some(impl_consts) = &self.impl_consts {
+                repls.extend(assoc_const_replacements(&sig.output, block, impl_consts));
+            }
             if repls.is_empty() {
+                self.fns_without_return_values.push(Arc::clone(&function));
                 debug!(
                     function_name = function.function_name,
                     return_type = function.return_type,
@@ -323,63 +1125,285 @@ impl DiscoveryVisitor<'_> {
                     }
                 }
             }
+            self.collect_tuple_element_mutants(sig, block);
         } else {
-            warn!("collect_fn_mutants called while not in a function?");
+            warn!("collect_fn_value_mutants called while not in a function?");
         }
     }
 
-    /// Call a function with a namespace pushed onto the stack.
-    ///
-    /// This is used when recursively descending into a namespace.
-    fn in_namespace<F, T>(&mut self, name: &str, f: F) -> T
-    where
-        F: FnOnce(&mut Self) -> T,
-    {
-        self.namespace_stack.push(name.to_owned());
-        let r = f(self);
-        assert_eq!(self.namespace_stack.pop().unwrap(), name);
-        r
-    }
-}
-
-impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
-    fn visit_expr_call(&mut self, i: &'ast syn::ExprCall) {
-        let _span = trace_span!("expr_call", line = i.span().start().line).entered();
-        if attrs_excluded(&i.attrs) {
-            return;
-        }
-        if let Expr::Path(ExprPath { path, .. }) = &*i.func {
-            debug!(path = path.to_pretty_string(), "visit call");
-            if let Some(hit) = self
-                .options
-                .skip_calls
+    /// Replace the value of a `const` or `static` item with values of its type, as for
+    /// a function returning that type.
+    fn collect_const_value_mutants(
+        &mut self,
+        ident: &Ident,
+        type_: &Type,
+        expr: &Expr,
+        item_span: proc_macro2::Span,
+    ) {
+        let item = Arc::new(Function {
+            function_name: self
+                .namespace_stack
                 .iter()
-                .find(|s| path_ends_with(path, s))
-            {
-                trace!("skip call to {hit}");
-                return;
+                .map(String::as_str)
+                .chain(std::iter::once(ident.to_string().as_str()))
+                .join("::"),
+            return_type: format!(": {}", type_.to_pretty_string()),
+            span: item_span.into(),
+        });
+        let return_type = ReturnType::Type(RArrow::default(), Box::new(type_.clone()));
+        let orig = expr.to_pretty_string();
+        for rep in return_type_replacements(&return_type, &Generics::default(), self.error_exprs) {
+            let replacement = rep.to_pretty_string();
+            if replacement == orig {
+                debug!("Replacement is the same as the const value; skipping");
+                continue;
             }
+            self.mutants.push(Mutant {
+                source_file: self.source_file.clone(),
+                function: Some(Arc::clone(&item)),
+                span: expr.span().into(),
+                replacement,
+                genre: Genre::ConstValue,
+                tuple_index: None,
+                match_arm_pattern: None,
+            });
         }
-        syn::visit::visit_expr_call(self, i);
     }
 
-    fn visit_expr_method_call(&mut self, i: &'ast syn::ExprMethodCall) {
-        let _span = trace_span!("expr_method_call", line = i.span().start().line).entered();
-        if attrs_excluded(&i.attrs) {
+    /// If the function returns a tuple literal as its final expression, replace each
+    /// element of the tuple in turn, leaving the others unchanged.
+    fn collect_tuple_element_mutants(&mut self, sig: &Signature, block: &Block) {
+        let Some(Stmt::Expr(Expr::Tuple(tuple), None)) = block.stmts.last() else {
+            return;
+        };
+        let element_repls =
+            tuple_element_replacements(&sig.output, &self.fn_generics(sig), self.error_exprs);
+        if element_repls.len() != tuple.elems.len() {
+            return;
+        }
+        for (index, (elem, repls)) in tuple.elems.iter().zip(element_repls).enumerate() {
+            let orig = elem.to_pretty_string();
+            for rep in repls {
+                let replacement = rep.to_pretty_string();
+                if replacement == orig {
+                    debug!("Replacement is the same as the tuple element; skipping");
+                    continue;
+                }
+                self.mutants.push(Mutant {
+                    source_file: self.source_file.clone(),
+                    function: self.fn_stack.last().cloned(),
+                    span: elem.span().into(),
+                    replacement,
+                    genre: Genre::FnValue,
+                    tuple_index: Some(index),
+                    match_arm_pattern: None,
+                });
+            }
+        }
+    }
+
+    /// If the function returns a `match` expression as its final expression, replace the
+    /// body of each arm in turn with values of the return type, leaving the others
+    /// unchanged.
+    ///
+    /// Arms that diverge, like `return` or `panic!()`, are left alone.
+    ///
+    /// A block body with no comma after it, before another arm, is replaced by a block,
+    /// since a plain value there would need a comma to separate it from the next arm.
+    fn collect_match_arm_mutants(&mut self, sig: &Signature, block: &Block) {
+        let Some(Stmt::Expr(Expr::Match(expr_match), None)) = block.stmts.last() else {
+            return;
+        };
+        let repls = return_type_replacements(&sig.output, &self.fn_generics(sig), self.error_exprs);
+        for (i, arm) in expr_match.arms.iter().enumerate() {
+            if self.attrs_excluded(&arm.attrs) || expr_diverges(&arm.body) {
+                continue;
+            }
+            let pattern_span = Span {
+                start: arm.pat.span().start().into(),
+                end: arm
+                    .guard
+                    .as_ref()
+                    .map_or_else(|| arm.pat.span(), |(_if, guard)| guard.span())
+                    .end()
+                    .into(),
+            };
+            let pattern = pattern_span
+                .extract(self.source_file.code())
+                .split_whitespace()
+                .join(" ");
+            let orig = arm.body.to_pretty_string();
+            let needs_block = arm.comma.is_none() && i + 1 < expr_match.arms.len();
+            for rep in &repls {
+                let block = quote!({ #rep }).to_pretty_string();
+                let replacement = if needs_block {
+                    block.clone()
+                } else {
+                    rep.to_pretty_string()
+                };
+                if replacement == orig || block == orig {
+                    debug!("Replacement is the same as the match arm body; skipping");
+                    continue;
+                }
+                self.mutants.push(Mutant {
+                    source_file: self.source_file.clone(),
+                    function: self.fn_stack.last().cloned(),
+                    span: arm.body.span().into(),
+                    replacement,
+                    genre: Genre::MatchArm,
+                    tuple_index: None,
+                    match_arm_pattern: Some(pattern.clone()),
+                });
+            }
+        }
+    }
+
+    /// In a function returning a `Result`, replace each `return Err(...)` with `Ok`
+    /// values, and each `return Ok(...)` with the configured error values.
+    fn collect_early_return_mutants(&mut self, sig: &Signature, block: &Block) {
+        let Some((ok_repls, err_repls)) =
+            result_swap_replacements(&sig.output, &sig.generics, self.error_exprs)
+        else {
+            return;
+        };
+        let mut finder = ReturnFinder::default();
+        finder.visit_block(block);
+        for expr_return in finder.returns {
+            if self.attrs_excluded(&expr_return.attrs) {
+                continue;
+            }
+            let Some(value) = expr_return.expr.as_deref() else {
+                continue;
+            };
+            let Expr::Call(ExprCall { func, attrs, .. }) = value else {
+                continue;
+            };
+            if self.attrs_excluded(attrs) {
+                continue;
+            }
+            let Expr::Path(ExprPath { path, .. }) = &**func else {
+                continue;
+            };
+            let repls = if path_ends_with(path, "Err") {
+                &ok_repls
+            } else if path_ends_with(path, "Ok") {
+                &err_repls
+            } else {
+                continue;
+            };
+            for rep in repls {
+                self.collect_mutant(value.span().into(), rep, Genre::EarlyReturn);
+            }
+        }
+    }
+
+    /// Call a function with a namespace pushed onto the stack.
+    ///
+    /// This is used when recursively descending into a namespace.
+    fn in_namespace<F, T>(&mut self, name: &str, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        self.namespace_stack.push(name.to_owned());
+        let r = f(self);
+        assert_eq!(self.namespace_stack.pop().unwrap(), name);
+        r
+    }
+}
+
+impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
+    fn visit_expr_call(&mut self, i: &'ast syn::ExprCall) {
+        let _span = trace_span!("expr_call", line = i.span().start().line).entered();
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        if let Expr::Path(ExprPath { path, .. }) = &*i.func {
+            debug!(path = path.to_pretty_string(), "visit call");
+            let span = Span {
+                start: path.span().start().into(),
+                end: i.paren_token.span.close().end().into(),
+            };
+            self.collect_custom_mutants(path, span, &i.paren_token.span);
+            if let Some(hit) = self
+                .options
+                .skip_calls
+                .iter()
+                .find(|s| path_ends_with(path, s))
+            {
+                trace!("skip call to {hit}");
+                return;
+            }
+            if self.options.generates_genre(Genre::MethodCall) {
+                self.collect_clone_call_mutant(i, path);
+            }
+        }
+        syn::visit::visit_expr_call(self, i);
+    }
+
+    /// Visit a macro call in expression position, like `vec![]` or `format!(...)`.
+    fn visit_expr_macro(&mut self, i: &'ast syn::ExprMacro) {
+        let _span = trace_span!("expr_macro", line = i.span().start().line).entered();
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        if !self.in_type_or_pattern {
+            self.collect_macro_custom_mutants(&i.mac);
+        }
+        syn::visit::visit_expr_macro(self, i);
+    }
+
+    /// Visit a macro call used as a statement, like `println!(...);`.
+    fn visit_stmt_macro(&mut self, i: &'ast syn::StmtMacro) {
+        let _span = trace_span!("stmt_macro", line = i.span().start().line).entered();
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        if is_include_macro(&i.mac.path) {
+            // The included file might contain statements rather than items, so it can't
+            // be walked like a module, but say so rather than silently missing its code.
+            let location = self
+                .source_file
+                .format_source_location(Span::from(i.mac.span()).start);
+            warn!(%location, "skipping include! inside a block, so the included file is not mutated");
+        }
+        self.collect_macro_custom_mutants(&i.mac);
+        syn::visit::visit_stmt_macro(self, i);
+    }
+
+    fn visit_expr_method_call(&mut self, i: &'ast syn::ExprMethodCall) {
+        let _span = trace_span!("expr_method_call", line = i.span().start().line).entered();
+        if self.attrs_excluded(&i.attrs) {
             return;
         }
         if let Some(hit) = self.options.skip_calls.iter().find(|s| i.method == s) {
             trace!("skip method call to {hit}");
             return;
         }
+        if self.options.generates_genre(Genre::MinMax) && i.turbofish.is_none() {
+            self.collect_min_max_mutants(i);
+        }
+        if (i.method == "get" || i.method == "get_mut") && i.args.len() == 1 {
+            self.collect_index_mutants(&i.args[0]);
+        }
+        if self.options.generates_genre(Genre::MethodCall) {
+            // Replace `expr.method(args)` with `expr`. For `.clone()` this checks that
+            // the copy is needed: if it compiles without the clone, perhaps the
+            // original is never modified or used again.
+            let span = Span {
+                start: i.dot_token.span.start().into(),
+                end: i.paren_token.span.close().end().into(),
+            };
+            self.collect_mutant(span, &quote! {}, Genre::MethodCall);
+        }
         syn::visit::visit_expr_method_call(self, i);
     }
 
     /// Visit a source file.
     fn visit_file(&mut self, i: &'ast File) {
         // No trace here; it's created per file for the whole visitor
-        if attrs_excluded(&i.attrs) {
+        if let Some(reason) = self.attrs_skip_reason(&i.attrs) {
             trace!("file excluded by attrs");
+            self.skip("(file)", i.span(), reason, &i.attrs);
             return;
         }
         syn::visit::visit_file(self, i);
@@ -395,7 +1419,12 @@ impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
         )
         .entered();
         trace!("visit fn");
-        if fn_sig_excluded(&i.sig) || attrs_excluded(&i.attrs) || block_is_empty(&i.block) {
+        if let Some(reason) = fn_sig_skip_reason(&i.sig)
+            .or_else(|| self.attrs_skip_reason(&i.attrs))
+            .or_else(|| self.visibility_skip_reason(&i.vis))
+            .or_else(|| block_is_empty(&i.block).then_some(SkipReason::EmptyBody))
+        {
+            self.skip(&function_name, i.span(), reason, &i.attrs);
             return;
         }
         let function = self.enter_function(&i.sig.ident, &i.sig.output, i.span());
@@ -415,11 +1444,13 @@ impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
             name = function_name
         )
         .entered();
-        if fn_sig_excluded(&i.sig)
-            || attrs_excluded(&i.attrs)
-            || i.sig.ident == "new"
-            || block_is_empty(&i.block)
+        if let Some(reason) = fn_sig_skip_reason(&i.sig)
+            .or_else(|| self.attrs_skip_reason(&i.attrs))
+            .or_else(|| self.visibility_skip_reason(&i.vis))
+            .or_else(|| (i.sig.ident == "new").then_some(SkipReason::Constructor))
+            .or_else(|| block_is_empty(&i.block).then_some(SkipReason::EmptyBody))
         {
+            self.skip(&function_name, i.span(), reason, &i.attrs);
             return;
         }
         let function = self.enter_function(&i.sig.ident, &i.sig.output, i.span());
@@ -437,11 +1468,17 @@ impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
             name = function_name
         )
         .entered();
-        if fn_sig_excluded(&i.sig) || attrs_excluded(&i.attrs) || i.sig.ident == "new" {
+        if let Some(reason) = fn_sig_skip_reason(&i.sig)
+            .or_else(|| self.attrs_skip_reason(&i.attrs))
+            .or_else(|| self.visibility_skip_reason(&Visibility::Inherited))
+            .or_else(|| (i.sig.ident == "new").then_some(SkipReason::Constructor))
+        {
+            self.skip(&function_name, i.span(), reason, &i.attrs);
             return;
         }
         if let Some(block) = &i.default {
             if block_is_empty(block) {
+                self.skip(&function_name, i.span(), SkipReason::EmptyBody, &i.attrs);
                 return;
             }
             let function = self.enter_function(&i.sig.ident, &i.sig.output, i.span());
@@ -451,40 +1488,97 @@ impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
         }
     }
 
+    /// Visit `const FOO: T = ...;` outside of an `impl` or trait.
+    fn visit_item_const(&mut self, i: &'ast syn::ItemConst) {
+        let name = i.ident.to_pretty_string();
+        let _span = trace_span!("const", line = i.span().start().line, name).entered();
+        if let Some(reason) = self.attrs_skip_reason(&i.attrs) {
+            self.skip(&name, i.span(), reason, &i.attrs);
+            return;
+        }
+        if self.options.generates_genre(Genre::ConstValue)
+            && name != "_"
+            && self.visibility_skip_reason(&i.vis).is_none()
+        {
+            self.collect_const_value_mutants(&i.ident, &i.ty, &i.expr, i.span());
+        }
+        syn::visit::visit_item_const(self, i);
+    }
+
+    /// Visit `static FOO: T = ...;`.
+    fn visit_item_static(&mut self, i: &'ast syn::ItemStatic) {
+        let name = i.ident.to_pretty_string();
+        let _span = trace_span!("static", line = i.span().start().line, name).entered();
+        if let Some(reason) = self.attrs_skip_reason(&i.attrs) {
+            self.skip(&name, i.span(), reason, &i.attrs);
+            return;
+        }
+        if self.options.generates_genre(Genre::ConstValue)
+            && self.visibility_skip_reason(&i.vis).is_none()
+        {
+            self.collect_const_value_mutants(&i.ident, &i.ty, &i.expr, i.span());
+        }
+        syn::visit::visit_item_static(self, i);
+    }
+
     /// Visit `impl Foo { ...}` or `impl Debug for Foo { ... }`.
     fn visit_item_impl(&mut self, i: &'ast syn::ItemImpl) {
-        if attrs_excluded(&i.attrs) {
+        let type_name = i.self_ty.to_pretty_string();
+        if let Some(reason) = self.attrs_skip_reason(&i.attrs) {
+            self.skip(&type_name, i.span(), reason, &i.attrs);
             return;
         }
-        let type_name = i.self_ty.to_pretty_string();
         let name = if let Some((_, trait_path, _)) = &i.trait_ {
             if path_ends_with(trait_path, "Default") {
                 // Can't think of how to generate a viable different default.
+                self.skip(
+                    &format!("<impl Default for {type_name}>"),
+                    i.span(),
+                    SkipReason::DefaultImpl,
+                    &i.attrs,
+                );
                 return;
             }
             format!("<impl {trait} for {type_name}>", trait = trait_path.to_pretty_string())
         } else {
             type_name
         };
+        let outer_impl_consts = self.impl_consts.replace(self.file_consts.for_impl(i));
+        let outer_generics = self.outer_generics.replace(i.generics.clone());
+        let outer_public = std::mem::replace(&mut self.in_public_trait_scope, i.trait_.is_some());
         self.in_namespace(&name, |v| syn::visit::visit_item_impl(v, i));
+        self.in_public_trait_scope = outer_public;
+        self.outer_generics = outer_generics;
+        self.impl_consts = outer_impl_consts;
     }
 
     /// Visit `trait Foo { ... }`
     fn visit_item_trait(&mut self, i: &'ast syn::ItemTrait) {
         let name = i.ident.to_pretty_string();
         let _span = trace_span!("trait", line = i.span().start().line, name).entered();
-        if attrs_excluded(&i.attrs) {
+        if let Some(reason) = self.attrs_skip_reason(&i.attrs) {
+            self.skip(&name, i.span(), reason, &i.attrs);
             return;
         }
+        let outer_public = std::mem::replace(
+            &mut self.in_public_trait_scope,
+            self.in_public_mod && matches!(i.vis, Visibility::Public(_)),
+        );
+        let outer_generics = self.outer_generics.replace(i.generics.clone());
+        let outer_impl_consts = self.impl_consts.replace(FileConsts::for_trait(i));
         self.in_namespace(&name, |v| syn::visit::visit_item_trait(v, i));
+        self.impl_consts = outer_impl_consts;
+        self.outer_generics = outer_generics;
+        self.in_public_trait_scope = outer_public;
     }
 
     /// Visit `mod foo { ... }` or `mod foo;`.
     fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
         let mod_name = node.ident.unraw().to_string();
         let _span = trace_span!("mod", line = node.mod_token.span.start().line, mod_name).entered();
-        if attrs_excluded(&node.attrs) {
+        if let Some(reason) = self.attrs_skip_reason(&node.attrs) {
             trace!("mod excluded by attrs");
+            self.skip(&mod_name, node.span(), reason, &node.attrs);
             return;
         }
 
@@ -507,6 +1601,9 @@ impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
             source_location,
         };
         self.mod_namespace_stack.push(mod_namespace.clone());
+        let public = self.in_public_mod
+            && (matches!(node.vis, Visibility::Public(_))
+                || self.reexported_mods.contains(&mod_namespace.name));
 
         // If there's no content in braces, then this is a `mod foo;`
         // statement referring to an external file. We remember the module
@@ -516,70 +1613,237 @@ impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
             // remember [a, b] as an external module to visit later.
             self.external_mods.push(ExternalModRef {
                 parts: self.mod_namespace_stack.clone(),
+                public,
             });
         }
+        let outer_public_mod = std::mem::replace(&mut self.in_public_mod, public);
+        let outer_reexported_mods = std::mem::replace(
+            &mut self.reexported_mods,
+            node.content
+                .as_ref()
+                .map_or_else(Vec::new, |(_brace, items)| reexported_mods(items)),
+        );
         self.in_namespace(&mod_namespace.name, |v| syn::visit::visit_item_mod(v, node));
+        self.reexported_mods = outer_reexported_mods;
+        self.in_public_mod = outer_public_mod;
         assert_eq!(self.mod_namespace_stack.pop(), Some(mod_namespace));
     }
 
+    /// Visit macros in item position, like `include!("generated.rs");`.
+    fn visit_item_macro(&mut self, i: &'ast syn::ItemMacro) {
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        if is_include_macro(&i.mac.path) {
+            self.collect_include(&i.mac);
+        }
+        syn::visit::visit_item_macro(self, i);
+    }
+
     /// Visit `a op b` expressions.
     fn visit_expr_binary(&mut self, i: &'ast syn::ExprBinary) {
         let _span = trace_span!("binary", line = i.op.span().start().line).entered();
         trace!("visit binary operator");
-        if attrs_excluded(&i.attrs) {
+        if self.attrs_excluded(&i.attrs) {
             return;
         }
-        let replacements = match i.op {
-            // We don't generate `<=` from `==` because it can too easily go
-            // wrong with unsigned types compared to 0.
-
-            // We try replacing logical ops with == and !=, which are effectively
-            // XNOR and XOR when applied to booleans. However, they're often unviable
-            // because they require parenthesis for disambiguation in many expressions.
-            BinOp::Eq(_) => vec![quote! { != }],
-            BinOp::Ne(_) => vec![quote! { == }],
-            BinOp::And(_) => vec![quote! { || }],
-            BinOp::Or(_) => vec![quote! { && }],
-            BinOp::Lt(_) => vec![quote! { == }, quote! {>}],
-            BinOp::Gt(_) => vec![quote! { == }, quote! {<}],
-            BinOp::Le(_) => vec![quote! {>}],
-            BinOp::Ge(_) => vec![quote! {<}],
-            BinOp::Add(_) => vec![quote! {-}, quote! {*}],
-            BinOp::AddAssign(_) => vec![quote! {-=}, quote! {*=}],
-            BinOp::Sub(_) | BinOp::Mul(_) => vec![quote! {+}, quote! {/}],
-            BinOp::SubAssign(_) | BinOp::MulAssign(_) => vec![quote! {+=}, quote! {/=}],
-            BinOp::Div(_) => vec![quote! {%}, quote! {*}],
-            BinOp::DivAssign(_) => vec![quote! {%=}, quote! {*=}],
-            BinOp::Rem(_) => vec![quote! {/}, quote! {+}],
-            BinOp::RemAssign(_) => vec![quote! {/=}, quote! {+=}],
-            BinOp::Shl(_) => vec![quote! {>>}],
-            BinOp::ShlAssign(_) => vec![quote! {>>=}],
-            BinOp::Shr(_) => vec![quote! {<<}],
-            BinOp::ShrAssign(_) => vec![quote! {<<=}],
-            BinOp::BitAnd(_) => vec![quote! {|}, quote! {^}],
-            BinOp::BitAndAssign(_) => vec![quote! {|=}, quote! {^=}],
-            BinOp::BitOr(_) => vec![quote! {&}, quote! {^}],
-            BinOp::BitOrAssign(_) => vec![quote! {&=}, quote! {^=}],
-            BinOp::BitXor(_) => vec![quote! {|}, quote! {&}],
-            BinOp::BitXorAssign(_) => vec![quote! {|=}, quote! {&=}],
-            _ => {
-                trace!(
-                    op = i.op.to_pretty_string(),
-                    "No mutants generated for this binary operator"
-                );
-                Vec::new()
-            }
+        let genre = match i.op {
+            BinOp::Eq(_)
+            | BinOp::Ne(_)
+            | BinOp::Lt(_)
+            | BinOp::Gt(_)
+            | BinOp::Le(_)
+            | BinOp::Ge(_) => Genre::ComparisonOperator,
+            BinOp::And(_) | BinOp::Or(_) => Genre::LogicalOperator,
+            _ => Genre::BinaryOperator,
         };
-        replacements
-            .into_iter()
-            .for_each(|rep| self.collect_mutant(i.op.span().into(), &rep, Genre::BinaryOperator));
+        if self.options.generates_genre(genre) {
+            let replacements = match i.op {
+                // We don't generate `<=` from `==` because it can too easily go
+                // wrong with unsigned types compared to 0.
+                BinOp::Eq(_) => vec![quote! { != }],
+                BinOp::Ne(_) => vec![quote! { == }],
+                // Moving the boundary of a comparison by one checks for off-by-one errors.
+                BinOp::Lt(_) => vec![quote! { == }, quote! {>}, quote! {<=}],
+                BinOp::Gt(_) => vec![quote! { == }, quote! {<}, quote! {>=}],
+                BinOp::Le(_) => vec![quote! {>}, quote! {<}],
+                BinOp::Ge(_) => vec![quote! {<}, quote! {>}],
+                BinOp::And(_) => vec![quote! { || }],
+                BinOp::Or(_) => vec![quote! { && }],
+                BinOp::Add(_) => vec![quote! {-}, quote! {*}],
+                BinOp::AddAssign(_) => vec![quote! {-=}, quote! {*=}],
+                BinOp::Sub(_) | BinOp::Mul(_) => vec![quote! {+}, quote! {/}],
+                BinOp::SubAssign(_) | BinOp::MulAssign(_) => vec![quote! {+=}, quote! {/=}],
+                BinOp::Div(_) => vec![quote! {%}, quote! {*}],
+                BinOp::DivAssign(_) => vec![quote! {%=}, quote! {*=}],
+                BinOp::Rem(_) => vec![quote! {/}, quote! {+}],
+                BinOp::RemAssign(_) => vec![quote! {/=}, quote! {+=}],
+                BinOp::Shl(_) => vec![quote! {>>}],
+                BinOp::ShlAssign(_) => vec![quote! {>>=}],
+                BinOp::Shr(_) => vec![quote! {<<}],
+                BinOp::ShrAssign(_) => vec![quote! {<<=}],
+                BinOp::BitAnd(_) => vec![quote! {|}, quote! {^}],
+                BinOp::BitAndAssign(_) => vec![quote! {|=}, quote! {^=}],
+                BinOp::BitOr(_) => vec![quote! {&}, quote! {^}],
+                BinOp::BitOrAssign(_) => vec![quote! {&=}, quote! {^=}],
+                BinOp::BitXor(_) => vec![quote! {|}, quote! {&}],
+                BinOp::BitXorAssign(_) => vec![quote! {|=}, quote! {&=}],
+                _ => {
+                    trace!(
+                        op = i.op.to_pretty_string(),
+                        "No mutants generated for this binary operator"
+                    );
+                    Vec::new()
+                }
+            };
+            replacements
+                .into_iter()
+                .for_each(|rep| self.collect_mutant(i.op.span().into(), &rep, genre));
+        }
+        // Replacing an arithmetic compound assignment with a plain assignment checks
+        // that the previous value matters, as in a loop accumulator.
+        if matches!(
+            i.op,
+            BinOp::AddAssign(_) | BinOp::SubAssign(_) | BinOp::MulAssign(_) | BinOp::DivAssign(_)
+        ) && self.options.generates_genre(Genre::CompoundAssign)
+        {
+            self.collect_mutant(i.op.span().into(), &quote! {=}, Genre::CompoundAssign);
+        }
         syn::visit::visit_expr_binary(self, i);
     }
 
+    /// Visit a block, and delete each of its statements in turn.
+    ///
+    /// Only `let` bindings, macro calls, and expressions followed by a semicolon are
+    /// deleted. Block-like expressions such as `if` and `for` aren't deleted as a whole,
+    /// but the statements inside them are, and neither is any final expression that
+    /// gives the value of the block.
+    ///
+    /// Deleting some statements, such as `let` bindings that are used later, will
+    /// make the code fail to build: these are reported as unviable.
+    fn visit_block(&mut self, i: &'ast Block) {
+        if self.options.generates_genre(Genre::Statement) {
+            for stmt in &i.stmts {
+                let excluded = match stmt {
+                    Stmt::Local(local) => self.attrs_excluded(&local.attrs),
+                    Stmt::Expr(expr, semi) => {
+                        semi.is_none() || self.attrs_excluded(expr_attrs(expr))
+                    }
+                    Stmt::Macro(mac) => self.attrs_excluded(&mac.attrs),
+                    Stmt::Item(_) => true,
+                };
+                if !excluded {
+                    self.collect_mutant_text(stmt.span().into(), String::new(), Genre::Statement);
+                }
+            }
+        }
+        syn::visit::visit_block(self, i);
+    }
+
+    fn visit_expr_if(&mut self, i: &'ast syn::ExprIf) {
+        let _span = trace_span!("if", line = i.if_token.span.start().line).entered();
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        self.collect_condition_mutant(&i.cond);
+        syn::visit::visit_expr_if(self, i);
+    }
+
+    fn visit_expr_while(&mut self, i: &'ast syn::ExprWhile) {
+        let _span = trace_span!("while", line = i.while_token.span.start().line).entered();
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        self.collect_condition_mutant(&i.cond);
+        syn::visit::visit_expr_while(self, i);
+    }
+
+    /// Visit `a[i]`.
+    fn visit_expr_index(&mut self, i: &'ast syn::ExprIndex) {
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        self.collect_index_mutants(&i.index);
+        syn::visit::visit_expr_index(self, i);
+    }
+
+    /// Visit a numeric literal, and replace it with nearby values.
+    fn visit_expr_lit(&mut self, i: &'ast syn::ExprLit) {
+        if self.attrs_excluded(&i.attrs) || self.in_type_or_pattern {
+            return;
+        }
+        if let Lit::Bool(lit_bool) = &i.lit {
+            let span = lit_bool.span().into();
+            if self.options.generates_genre(Genre::BoolLiteral)
+                && !self.whole_body_literals.contains(&span)
+            {
+                self.collect_mutant_text(span, (!lit_bool.value).to_string(), Genre::BoolLiteral);
+            }
+            return;
+        }
+        if !self.options.generates_genre(Genre::Literal) {
+            return;
+        }
+        let span = i.lit.span().into();
+        // Shifting an index by one is already an `Index` mutant.
+        let index_replacements = match &i.lit {
+            Lit::Int(lit_int) if self.index_literals.contains(&span) => index_replacements(lit_int),
+            _ => Vec::new(),
+        };
+        for replacement in literal_replacements(&i.lit) {
+            if !index_replacements.contains(&replacement) {
+                self.collect_mutant_text(span, replacement, Genre::Literal);
+            }
+        }
+    }
+
+    /// Visit a type, without mutating literals such as array lengths, which would
+    /// almost always be unviable.
+    fn visit_type(&mut self, i: &'ast Type) {
+        let outer = std::mem::replace(&mut self.in_type_or_pattern, true);
+        syn::visit::visit_type(self, i);
+        self.in_type_or_pattern = outer;
+    }
+
+    /// Visit a pattern, without mutating literals in it, which would mostly just
+    /// make match arms unreachable or non-exhaustive.
+    fn visit_pat(&mut self, i: &'ast Pat) {
+        let outer = std::mem::replace(&mut self.in_type_or_pattern, true);
+        syn::visit::visit_pat(self, i);
+        self.in_type_or_pattern = outer;
+    }
+
+    /// Visit `a as u32`.
+    fn visit_expr_cast(&mut self, i: &'ast ExprCast) {
+        let _span = trace_span!("cast", line = i.as_token.span.start().line).entered();
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        if self.options.generates_genre(Genre::Cast) {
+            if let Type::Path(TypePath { qself: None, path }) = &*i.ty {
+                if let Some(ident) = path.get_ident() {
+                    let span = Span {
+                        start: i.as_token.span.start().into(),
+                        end: i.ty.span().end().into(),
+                    };
+                    for type_name in cast_type_replacements(&ident.to_string()) {
+                        let new_type = Ident::new(type_name, ident.span());
+                        self.collect_mutant(span, &quote! { as #new_type }, Genre::Cast);
+                    }
+                }
+            }
+        }
+        syn::visit::visit_expr_cast(self, i);
+    }
+
     fn visit_expr_unary(&mut self, i: &'ast syn::ExprUnary) {
         let _span = trace_span!("unary", line = i.op.span().start().line).entered();
         trace!("visit unary operator");
-        if attrs_excluded(&i.attrs) {
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        if !self.options.generates_genre(Genre::UnaryOperator) {
+            syn::visit::visit_expr_unary(self, i);
             return;
         }
         match i.op {
@@ -595,6 +1859,135 @@ impl<'ast> Visit<'ast> for DiscoveryVisitor<'_> {
         };
         syn::visit::visit_expr_unary(self, i);
     }
+
+    /// Delete the `?` from `expr?`.
+    fn visit_expr_try(&mut self, i: &'ast syn::ExprTry) {
+        if self.attrs_excluded(&i.attrs) {
+            return;
+        }
+        if self.options.generates_genre(Genre::TryOperator) {
+            self.collect_mutant(
+                i.question_token.span().into(),
+                &quote! {},
+                Genre::TryOperator,
+            );
+        }
+        syn::visit::visit_expr_try(self, i);
+    }
+}
+
+/// Return the replacements for an integer or float literal: one more, one less, zero,
+/// and one, keeping the literal's suffix and, for integers, its radix.
+///
+/// Replacements equal to the original are omitted, as are negative values, which
+/// can't be written as a literal.
+fn literal_replacements(lit: &Lit) -> Vec<String> {
+    match lit {
+        Lit::Int(lit_int) => {
+            let Ok(value) = lit_int.base10_digits().parse::<u128>() else {
+                return Vec::new();
+            };
+            [value.checked_add(1), value.checked_sub(1), Some(0), Some(1)]
+                .into_iter()
+                .flatten()
+                .filter(|&v| v != value)
+                .unique()
+                .map(|v| format_int_like(lit_int, v))
+                .collect()
+        }
+        Lit::Float(lit_float) => {
+            let Ok(value) = lit_float.base10_digits().parse::<f64>() else {
+                return Vec::new();
+            };
+            let suffix = lit_float.suffix();
+            [value + 1.0, value - 1.0, 0.0, 1.0]
+                .into_iter()
+                .filter(|&v| v >= 0.0 && v.to_bits() != value.to_bits())
+                .map(|v| format!("{v:?}{suffix}"))
+                .unique()
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Return the replacements for an integer literal used as an index: one more and one less.
+fn index_replacements(lit_int: &LitInt) -> Vec<String> {
+    let Ok(value) = lit_int.base10_digits().parse::<u128>() else {
+        return Vec::new();
+    };
+    [value.checked_add(1), value.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .map(|v| format_int_like(lit_int, v))
+        .collect()
+}
+
+/// Format an integer value with the same radix and suffix as an existing literal.
+fn format_int_like(lit_int: &LitInt, v: u128) -> String {
+    let suffix = lit_int.suffix();
+    match lit_int.token().to_string().get(..2) {
+        Some("0x") => format!("0x{v:x}{suffix}"),
+        Some("0o") => format!("0o{v:o}{suffix}"),
+        Some("0b") => format!("0b{v:b}{suffix}"),
+        _ => format!("{v}{suffix}"),
+    }
+}
+
+/// True if a condition is an `if let` or `while let` pattern, or a chain including one.
+fn condition_has_let(cond: &Expr) -> bool {
+    match cond {
+        Expr::Let(_) => true,
+        Expr::Binary(binary) if matches!(binary.op, BinOp::And(_)) => {
+            condition_has_let(&binary.left) || condition_has_let(&binary.right)
+        }
+        _ => false,
+    }
+}
+
+/// Return the numeric types to substitute for the target type of an `as` cast: the type
+/// of the same width with the opposite sign, and the next narrower type of the same sign.
+///
+/// Casts between any numeric types are allowed, so these should all compile.
+fn cast_type_replacements(type_name: &str) -> &'static [&'static str] {
+    match type_name {
+        "u8" => &["i8"],
+        "u16" => &["i16", "u8"],
+        "u32" => &["i32", "u16"],
+        "u64" => &["i64", "u32"],
+        "u128" => &["i128", "u64"],
+        "usize" => &["isize", "u32"],
+        "i8" => &["u8"],
+        "i16" => &["u16", "i8"],
+        "i32" => &["u32", "i16"],
+        "i64" => &["u64", "i32"],
+        "i128" => &["u128", "i64"],
+        "isize" => &["usize", "i32"],
+        "f64" => &["f32"],
+        _ => &[],
+    }
+}
+
+/// Find `return` expressions that return from the function being visited.
+///
+/// Closures, async blocks, and nested items are not searched, because a `return`
+/// inside them returns from something else.
+#[derive(Default)]
+struct ReturnFinder<'ast> {
+    returns: Vec<&'ast ExprReturn>,
+}
+
+impl<'ast> Visit<'ast> for ReturnFinder<'ast> {
+    fn visit_expr_return(&mut self, i: &'ast ExprReturn) {
+        self.returns.push(i);
+        syn::visit::visit_expr_return(self, i);
+    }
+
+    fn visit_expr_closure(&mut self, _i: &'ast syn::ExprClosure) {}
+
+    fn visit_expr_async(&mut self, _i: &'ast syn::ExprAsync) {}
+
+    fn visit_item(&mut self, _i: &'ast syn::Item) {}
 }
 
 // Get the span of the block excluding the braces, or None if it is empty.
@@ -677,6 +2070,10 @@ fn find_mod_source(
 
     let mut tried_paths = Vec::new();
     for relative_path in mod_child_candidates {
+        // Path attributes can contain `..`, but the file should be named in the same
+        // way however it's reached. Paths that leave the tree are kept as written,
+        // to be reported when the file is loaded.
+        let relative_path = normalize(&relative_path).unwrap_or(relative_path);
         let full_path = tree_root.join(&relative_path);
         if full_path.is_file() {
             trace!("found submodule in {full_path}");
@@ -690,30 +2087,102 @@ fn find_mod_source(
     None
 }
 
-/// True if the signature of a function is such that it should be excluded.
-fn fn_sig_excluded(sig: &syn::Signature) -> bool {
+/// If the signature of a function is such that it should be excluded, return why.
+fn fn_sig_skip_reason(sig: &syn::Signature) -> Option<SkipReason> {
     if sig.unsafety.is_some() {
         trace!("Skip unsafe fn");
-        true
+        Some(SkipReason::Unsafe)
     } else {
-        false
+        None
     }
 }
 
-/// True if any of the attrs indicate that we should skip this node and everything inside it.
-///
-/// This checks for `#[cfg(test)]`, `#[test]`, and `#[mutants::skip]`.
-fn attrs_excluded(attrs: &[Attribute]) -> bool {
-    attrs
-        .iter()
-        .any(|attr| attr_is_cfg_test(attr) || attr_is_test(attr) || attr_is_mutants_skip(attr))
-}
-
 /// True if the block (e.g. the contents of a function) is empty.
 fn block_is_empty(block: &syn::Block) -> bool {
     block.stmts.is_empty()
 }
 
+/// True if the function returns `()`, either implicitly or explicitly.
+fn returns_unit(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Default => true,
+        ReturnType::Type(_, ty) => matches!(&**ty, Type::Tuple(tuple) if tuple.elems.is_empty()),
+    }
+}
+
+/// True if the block contains just one expression or macro call, with or without a
+/// trailing semicolon.
+fn block_is_single_expr(block: &syn::Block) -> bool {
+    matches!(block.stmts.as_slice(), [Stmt::Expr(..) | Stmt::Macro(..)])
+}
+
+/// True if evaluating this expression always diverges, by returning, breaking, or
+/// panicking, so that it has no value to replace.
+fn expr_diverges(expr: &Expr) -> bool {
+    match expr {
+        Expr::Return(_) | Expr::Break(_) | Expr::Continue(_) => true,
+        Expr::Macro(expr_macro) => macro_diverges(&expr_macro.mac),
+        Expr::Block(expr_block) => match expr_block.block.stmts.last() {
+            Some(Stmt::Expr(expr, _)) => expr_diverges(expr),
+            Some(Stmt::Macro(stmt_macro)) => macro_diverges(&stmt_macro.mac),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn macro_diverges(mac: &syn::Macro) -> bool {
+    ["panic", "unreachable", "todo", "unimplemented"]
+        .iter()
+        .any(|name| path_ends_with(&mac.path, name))
+}
+
+/// The outer attributes of an expression, such as `#[mutants::skip]` on an expression
+/// statement.
+fn expr_attrs(expr: &Expr) -> &[Attribute] {
+    match expr {
+        Expr::Array(e) => &e.attrs,
+        Expr::Assign(e) => &e.attrs,
+        Expr::Async(e) => &e.attrs,
+        Expr::Await(e) => &e.attrs,
+        Expr::Binary(e) => &e.attrs,
+        Expr::Block(e) => &e.attrs,
+        Expr::Break(e) => &e.attrs,
+        Expr::Call(e) => &e.attrs,
+        Expr::Cast(e) => &e.attrs,
+        Expr::Closure(e) => &e.attrs,
+        Expr::Const(e) => &e.attrs,
+        Expr::Continue(e) => &e.attrs,
+        Expr::Field(e) => &e.attrs,
+        Expr::ForLoop(e) => &e.attrs,
+        Expr::Group(e) => &e.attrs,
+        Expr::If(e) => &e.attrs,
+        Expr::Index(e) => &e.attrs,
+        Expr::Infer(e) => &e.attrs,
+        Expr::Let(e) => &e.attrs,
+        Expr::Lit(e) => &e.attrs,
+        Expr::Loop(e) => &e.attrs,
+        Expr::Macro(e) => &e.attrs,
+        Expr::Match(e) => &e.attrs,
+        Expr::MethodCall(e) => &e.attrs,
+        Expr::Paren(e) => &e.attrs,
+        Expr::Path(e) => &e.attrs,
+        Expr::Range(e) => &e.attrs,
+        Expr::Reference(e) => &e.attrs,
+        Expr::Repeat(e) => &e.attrs,
+        Expr::Return(e) => &e.attrs,
+        Expr::Struct(e) => &e.attrs,
+        Expr::Try(e) => &e.attrs,
+        Expr::TryBlock(e) => &e.attrs,
+        Expr::Tuple(e) => &e.attrs,
+        Expr::Unary(e) => &e.attrs,
+        Expr::Unsafe(e) => &e.attrs,
+        Expr::While(e) => &e.attrs,
+        Expr::Yield(e) => &e.attrs,
+        _ => &[],
+    }
+}
+
 /// True if the attribute looks like `#[cfg(test)]`, or has "test"
 /// anywhere in it.
 fn attr_is_cfg_test(attr: &Attribute) -> bool {
@@ -737,9 +2206,31 @@ fn attr_is_cfg_test(attr: &Attribute) -> bool {
     contains_test
 }
 
-/// True if the attribute is `#[test]`.
+/// Paths of attributes that mark a test function: the builtin `#[test]`, and test
+/// attributes from well-known crates.
+const TEST_ATTRIBUTE_PATHS: &[&[&str]] = &[
+    &["test"],
+    &["tokio", "test"],
+    &["async_std", "test"],
+    &["actix_rt", "test"],
+    &["actix_web", "test"],
+    &["futures_test", "test"],
+    &["sqlx", "test"],
+    &["test_log", "test"],
+    &["rstest"],
+    &["rstest", "rstest"],
+    &["test_case"],
+    &["test_case", "test_case"],
+    &["wasm_bindgen_test"],
+    &["wasm_bindgen_test", "wasm_bindgen_test"],
+];
+
+/// True if the attribute marks a test function: `#[test]`, or one of the attributes
+/// from other crates in [`TEST_ATTRIBUTE_PATHS`], like `#[tokio::test]`.
 fn attr_is_test(attr: &Attribute) -> bool {
-    attr.path().is_ident("test")
+    TEST_ATTRIBUTE_PATHS
+        .iter()
+        .any(|idents| path_is(attr.path(), idents))
 }
 
 fn path_is(path: &syn::Path, idents: &[&str]) -> bool {
@@ -757,20 +2248,101 @@ fn path_ends_with(path: &syn::Path, ident: &str) -> bool {
     path.segments.last().is_some_and(|s| s.ident == ident)
 }
 
+/// Return the names of modules that are re-exported, in whole or in part, by `pub use`
+/// items in this list: for example `imp` for `pub use imp::Widget;`.
+fn reexported_mods(items: &[Item]) -> Vec<String> {
+    fn first_names(tree: &UseTree, names: &mut Vec<String>) {
+        match tree {
+            UseTree::Path(use_path) if use_path.ident == "self" => {
+                first_names(&use_path.tree, names);
+            }
+            UseTree::Path(use_path) => names.push(use_path.ident.unraw().to_string()),
+            UseTree::Name(use_name) => names.push(use_name.ident.unraw().to_string()),
+            UseTree::Rename(use_rename) => names.push(use_rename.ident.unraw().to_string()),
+            UseTree::Group(group) => {
+                for tree in &group.items {
+                    first_names(tree, names);
+                }
+            }
+            UseTree::Glob(_) => {}
+        }
+    }
+    let mut names = Vec::new();
+    for item in items {
+        if let Item::Use(item_use) = item {
+            if matches!(item_use.vis, Visibility::Public(_)) {
+                first_names(&item_use.tree, &mut names);
+            }
+        }
+    }
+    names
+}
+
+/// True if the path is `include`, `std::include`, or `core::include`.
+fn is_include_macro(path: &syn::Path) -> bool {
+    let idents = path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect_vec();
+    idents == ["include"] || idents == ["std", "include"] || idents == ["core", "include"]
+}
+
+/// True if the identifiers at the end of the path are the same as these segments.
+///
+/// For example `log::info` matches `log::info` and `::log::info`, and `info` matches
+/// all of those as well as `info`. Generic arguments are ignored.
+fn path_matches_segments(path: &syn::Path, segments: &[&str]) -> bool {
+    path.segments.len() >= segments.len()
+        && path
+            .segments
+            .iter()
+            .rev()
+            .zip(segments.iter().rev())
+            .all(|(path_segment, segment)| path_segment.ident == segment)
+}
+
 /// True if the attribute contains `mutants::skip`.
 ///
 /// This for example returns true for `#[mutants::skip]` or `#[cfg_attr(test, mutants::skip)]`.
 fn attr_is_mutants_skip(attr: &Attribute) -> bool {
+    mutants_skip_attr(attr).is_some()
+}
+
+/// A `mutants::skip` attribute, possibly inside `cfg_attr`.
+struct MutantsSkipAttr {
+    /// The reason given in `mutants::skip(reason = "...")`, if any.
+    reason: Option<String>,
+}
+
+/// If the attribute contains `mutants::skip`, return what's in it.
+fn mutants_skip_attr(attr: &Attribute) -> Option<MutantsSkipAttr> {
     if path_is(attr.path(), &["mutants", "skip"]) {
-        return true;
+        let mut reason = None;
+        if matches!(attr.meta, syn::Meta::List(_)) {
+            if let Err(err) = attr.parse_nested_meta(|meta| parse_skip_reason(&meta, &mut reason)) {
+                debug!(?attr, ?err, "Unrecognized arguments to mutants::skip");
+            }
+        }
+        return Some(MutantsSkipAttr { reason });
     }
     if !path_is(attr.path(), &["cfg_attr"]) {
-        return false;
+        return None;
     }
-    let mut skip = false;
+    let mut skip = None;
     if let Err(err) = attr.parse_nested_meta(|meta| {
         if path_is(&meta.path, &["mutants", "skip"]) {
-            skip = true;
+            let mut reason = None;
+            if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|meta| parse_skip_reason(&meta, &mut reason))?;
+            }
+            skip = Some(MutantsSkipAttr { reason });
+        } else if meta.input.peek(syn::token::Paren) {
+            // Some other attribute or condition with arguments, like `not(test)`.
+            meta.input.parse::<proc_macro2::TokenTree>()?;
+        } else if meta.input.peek(syn::Token![=]) {
+            // A condition like `feature = "x"`.
+            meta.value()?.parse::<Lit>()?;
         }
         Ok(())
     }) {
@@ -779,11 +2351,29 @@ fn attr_is_mutants_skip(attr: &Attribute) -> bool {
             ?err,
             "Attribute is not a path with attributes; skipping"
         );
-        return false;
+        return None;
     }
     skip
 }
 
+/// Parse one argument to `mutants::skip`, remembering it if it's `reason = "..."`.
+///
+/// Other arguments are ignored, like they are by the attribute macro.
+fn parse_skip_reason(
+    meta: &syn::meta::ParseNestedMeta,
+    reason: &mut Option<String>,
+) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        let value = meta.value()?.parse::<Lit>()?;
+        if let (true, Lit::Str(lit_str)) = (meta.path.is_ident("reason"), value) {
+            *reason = Some(lit_str.value());
+        }
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.input.parse::<proc_macro2::TokenTree>()?;
+    }
+    Ok(())
+}
+
 /// Finds the first path attribute (`#[path = "..."]`)
 ///
 /// # Errors
@@ -820,15 +2410,64 @@ mod test {
     use pretty_assertions::assert_eq;
     use test_log::test;
 
+    use crate::config::CustomMutation;
     use crate::test_util::copy_of_testdata;
     use crate::workspace::{PackageFilter, Workspace};
 
     use super::*;
 
     #[test]
-    fn path_ends_with() {
-        use super::path_ends_with;
-        use syn::parse_quote;
+    fn parallel_map_keeps_order() {
+        let items = (0..1000).collect_vec();
+        assert_eq!(
+            parallel_map(&items, |i| i * 2),
+            (0..1000).map(|i| i * 2).collect_vec()
+        );
+        assert_eq!(parallel_map(&[] as &[usize], |i| i + 1), [0usize; 0]);
+    }
+
+    #[test]
+    fn walk_order_is_stable() {
+        let tmp = copy_of_testdata("workspace");
+        let workspace = Workspace::open(tmp.path()).expect("open source tree");
+        let names = || {
+            workspace
+                .discover(&PackageFilter::All, &Options::default(), &Console::new())
+                .unwrap()
+                .mutants
+                .iter()
+                .map(|m| m.name(true))
+                .collect_vec()
+        };
+        let first = names();
+        assert!(!first.is_empty());
+        for _ in 0..5 {
+            assert_eq!(names(), first);
+        }
+    }
+
+    #[test]
+    fn path_matches_segments() {
+        use super::path_matches_segments;
+        use syn::parse_quote;
+
+        let path = parse_quote! { ::log::info };
+        assert!(path_matches_segments(&path, &["log", "info"]));
+        assert!(path_matches_segments(&path, &["info"]));
+        assert!(!path_matches_segments(&path, &["tracing", "info"]));
+        assert!(!path_matches_segments(&path, &["other", "log", "info"]));
+
+        let path = parse_quote! { info };
+        assert!(!path_matches_segments(&path, &["log", "info"]));
+
+        let path = parse_quote! { Vec::<u8>::new };
+        assert!(path_matches_segments(&path, &["Vec", "new"]));
+    }
+
+    #[test]
+    fn path_ends_with() {
+        use super::path_ends_with;
+        use syn::parse_quote;
 
         let path = parse_quote! { foo::bar::baz };
         assert!(path_ends_with(&path, "baz"));
@@ -845,6 +2484,41 @@ mod test {
         assert!(!path_ends_with(&path, "K"));
     }
 
+    #[test]
+    fn include_with_literal_path_is_followed() {
+        let code = indoc! { r#"
+            include!("generated.rs");
+
+            mod inner {
+                std::include!("../gen/inner.rs");
+                include!(concat!(env!("OUT_DIR"), "/built.rs"));
+            }
+
+            fn main() {
+                let _ = include!("expression.rs");
+            }
+        "# };
+        let source_file = SourceFile::for_tests("src/sub/lib.rs", code, "unimportant", true);
+        let includes = walk_file(&source_file, &[], true, &[], &Options::default())
+            .expect("walk_file")
+            .includes;
+        assert_eq!(
+            includes,
+            [
+                IncludeRef {
+                    path: "src/sub/generated.rs".into(),
+                    namespace: vec![],
+                    public_mod: true,
+                },
+                IncludeRef {
+                    path: "src/gen/inner.rs".into(),
+                    namespace: vec!["inner".to_owned()],
+                    public_mod: false,
+                },
+            ]
+        );
+    }
+
     /// We should not generate mutants that produce the same tokens as the
     /// source.
     #[test]
@@ -853,8 +2527,9 @@ mod test {
             fn always_true() -> bool { true }
         "};
         let source_file = SourceFile::for_tests("src/lib.rs", code, "unimportant", true);
-        let (mutants, _files) =
-            walk_file(&source_file, &[], &Options::default()).expect("walk_file");
+        let mutants = walk_file(&source_file, &[], true, &[], &Options::default())
+            .expect("walk_file")
+            .mutants;
         let mutant_names = mutants.iter().map(|m| m.name(false)).collect_vec();
         // It would be good to suggest replacing this with 'false', breaking a key behavior,
         // but bad to replace it with 'true', changing nothing.
@@ -877,6 +2552,141 @@ mod test {
         assert_eq!(discovered.mutants.as_slice(), &[]);
     }
 
+    /// Functions in files excluded by globs are recorded as skipped because of the file,
+    /// unless they were already skipped for a more specific reason.
+    #[test]
+    fn functions_in_excluded_files_are_skipped() {
+        let tmp = copy_of_testdata("factorial");
+        let workspace = Workspace::open(tmp.path()).unwrap();
+        let options = Options::from_arg_strs(["mutants", "--exclude", "factorial.rs"]);
+        let discovered = workspace
+            .discover(&PackageFilter::All, &options, &Console::new())
+            .unwrap();
+        assert_eq!(discovered.mutants.as_slice(), &[]);
+        assert_eq!(
+            discovered
+                .skipped
+                .iter()
+                .map(|s| s.describe(false))
+                .collect_vec(),
+            [
+                "src/bin/factorial.rs: test_factorial: test function",
+                "src/bin/factorial.rs: main: file excluded by --file or --exclude",
+                "src/bin/factorial.rs: factorial: file excluded by --file or --exclude",
+            ]
+        );
+        assert_eq!(
+            discovered.skipped[1].reason.category(),
+            SkipCategory::Excluded
+        );
+    }
+
+    /// Every file linked by `mod` statements, including `#[path]` attributes on both
+    /// items and blocks, generates mutants under its normalized path.
+    #[test]
+    fn mutants_from_every_file_in_nested_mod() {
+        let tmp = copy_of_testdata("nested_mod");
+        let workspace = Workspace::open(tmp.path()).unwrap();
+        let discovered = workspace
+            .discover(&PackageFilter::All, &Options::default(), &Console::new())
+            .unwrap();
+        let all_files = walkdir(tmp.path(), "src")
+            .into_iter()
+            .sorted()
+            .collect_vec();
+        assert_eq!(
+            discovered
+                .files
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .sorted()
+                .collect_vec(),
+            all_files
+        );
+        let files_with_mutants = discovered
+            .mutants
+            .iter()
+            .map(|m| m.source_file.tree_relative_slashes())
+            .unique()
+            .sorted()
+            .collect_vec();
+        // Files that only declare modules have no mutants, and neither do the top files,
+        // which contain only an empty `main` and tests.
+        let files_with_functions = all_files
+            .into_iter()
+            .filter(|path| {
+                path != "src/main.rs"
+                    && path != "src/lib.rs"
+                    && std::fs::read_to_string(tmp.path().join(path))
+                        .unwrap()
+                        .contains("fn ")
+            })
+            .collect_vec();
+        assert_eq!(files_with_mutants, files_with_functions);
+        for path in [
+            "src/paths_in_lib/thread_files/tls.rs",
+            "src/paths_in_lib/thread_files_inner_attr/tls.rs",
+            "src/paths_in_main/thread_files_inner_attr/tls.rs",
+            "src/toplevel_file_in_main.rs",
+            "src/upward_traversal_file_for_lib.rs",
+        ] {
+            assert!(files_with_mutants.iter().any(|f| f == path), "{path}");
+        }
+    }
+
+    /// List the Rust source files under `dir`, as tree-relative paths with forward slashes.
+    fn walkdir(tree: &std::path::Path, dir: &str) -> Vec<String> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(tree.join(dir)).unwrap() {
+            let entry = entry.unwrap();
+            let name = format!("{dir}/{}", entry.file_name().to_str().unwrap());
+            if entry.file_type().unwrap().is_dir() {
+                files.extend(walkdir(tree, &name));
+            } else if Utf8Path::new(&name).extension() == Some("rs") {
+                files.push(name);
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn file_included_twice_is_only_visited_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"twice\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("src/inner")).unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "mod a;\n#[path = \"inner/../a.rs\"]\nmod also_a;\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/a.rs"), "pub fn one() -> u32 { 2 - 1 }\n").unwrap();
+        let workspace = Workspace::open(Utf8Path::from_path(root).unwrap()).unwrap();
+        let discovered = workspace
+            .discover(&PackageFilter::All, &Options::default(), &Console::new())
+            .unwrap();
+        assert_eq!(
+            discovered
+                .files
+                .iter()
+                .map(SourceFile::tree_relative_slashes)
+                .collect_vec(),
+            ["src/lib.rs", "src/a.rs"]
+        );
+        assert_eq!(
+            discovered
+                .mutants
+                .iter()
+                .filter(|m| m.genre == Genre::FnValue)
+                .count(),
+            2
+        );
+    }
+
     /// Helper function for `find_path_attribute` tests
     fn run_find_path_attribute(
         token_stream: &TokenStream,
@@ -1020,6 +2830,48 @@ mod test {
         assert_eq!(mutants.len(), 3);
     }
 
+    #[test]
+    fn compound_assignment_in_accumulator_is_replaced_by_plain_assignment() {
+        let mutants = mutate_source_str(
+            indoc! {"
+                fn sum(values: &[u32]) -> u32 {
+                    let mut total = 0;
+                    for v in values {
+                        total += v;
+                    }
+                    total
+                }
+            "},
+            &Options::from_arg_strs(["mutants", "--genre=binary-operator,compound-assign"]),
+        )
+        .unwrap();
+        let descriptions = mutants
+            .iter()
+            .map(Mutant::describe_change)
+            .collect::<Vec<_>>();
+        // A test that only sums a single value would catch the first two, but not
+        // the plain assignment, which forgets the previous total.
+        assert_eq!(
+            descriptions,
+            [
+                "replace += with -= in sum",
+                "replace += with *= in sum",
+                "replace += with = in sum",
+            ]
+        );
+        let plain = &mutants[2];
+        assert_eq!(plain.genre, Genre::CompoundAssign);
+        assert!(plain
+            .mutated_code()
+            .contains("total = /* ~ changed by cargo-mutants ~ */ v;"));
+
+        let default_mutants =
+            mutate_source_str("fn f(a: &mut u32) { *a += 2; }", &Options::default()).unwrap();
+        assert!(default_mutants
+            .iter()
+            .all(|m| m.genre != Genre::CompoundAssign));
+    }
+
     #[test]
     fn skip_method_calls_by_name() {
         let options = Options::from_arg_strs(["mutants", "--skip-calls", "dont_touch_this"]);
@@ -1084,4 +2936,1108 @@ mod test {
             "#}
         );
     }
+
+    #[test]
+    fn only_generate_selected_genres() {
+        let code = indoc! {"
+            fn is_big(a: u32) -> bool {
+                a > 100 && !is_small(a)
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=fn-value"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(false)).collect_vec(),
+            [
+                "src/main.rs: replace is_big -> bool with true",
+                "src/main.rs: replace is_big -> bool with false",
+            ]
+        );
+
+        let options = Options::from_arg_strs(["mutants", "--genre=unary-operator"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(false)).collect_vec(),
+            ["src/main.rs: delete ! in is_big"]
+        );
+    }
+
+    #[test]
+    fn skip_unit_returns_from_single_expression_bodies() {
+        let code = indoc! {r#"
+            fn log(a: u32) {
+                println!("{a}");
+            }
+
+            fn clear(v: &mut Vec<u32>) -> () {
+                v.clear()
+            }
+
+            fn push_twice(v: &mut Vec<u32>) {
+                v.push(1);
+                v.push(2);
+            }
+
+            fn count(v: &[u32]) -> usize {
+                v.len()
+            }
+        "#};
+        let names = |options: &Options| {
+            mutate_source_str(code, options)
+                .unwrap()
+                .iter()
+                .map(|m| m.name(false))
+                .collect_vec()
+        };
+        assert_eq!(
+            names(&Options::default()),
+            [
+                "src/main.rs: replace log with ()",
+                "src/main.rs: replace clear -> () with ()",
+                "src/main.rs: replace push_twice with ()",
+                "src/main.rs: replace count -> usize with 0",
+                "src/main.rs: replace count -> usize with 1",
+            ]
+        );
+        let options = Options::from_arg_strs(["mutants", "--skip-unit-returns"]);
+        assert_eq!(
+            names(&options),
+            [
+                "src/main.rs: replace push_twice with ()",
+                "src/main.rs: replace count -> usize with 0",
+                "src/main.rs: replace count -> usize with 1",
+            ]
+        );
+        let skipped = skipped_in_source_str(code, &options).unwrap();
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_str(), s.reason))
+                .collect_vec(),
+            [
+                ("log", SkipReason::UnitReturn),
+                ("clear", SkipReason::UnitReturn)
+            ]
+        );
+    }
+
+    #[test]
+    fn method_call_genre_deletes_method_calls() {
+        let code = indoc! {r"
+            fn names(s: &str, shared: &Rc<String>) -> (String, Rc<String>) {
+                let copy = Rc::clone(&shared);
+                (s.trim().to_owned(), copy)
+            }
+        "};
+        let names = |options: &Options| {
+            mutate_source_str(code, options)
+                .unwrap()
+                .iter()
+                .map(|m| m.name(false))
+                .collect_vec()
+        };
+        assert!(!names(&Options::default())
+            .iter()
+            .any(|n| n.contains("delete .")));
+        let options = Options::from_arg_strs(["mutants", "--genre=method-call"]);
+        assert_eq!(
+            names(&options),
+            [
+                "src/main.rs: replace Rc::clone(&shared) with shared in names",
+                "src/main.rs: delete .to_owned() in names",
+                "src/main.rs: delete .trim() in names",
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_mutations_match_paths_of_calls_and_macros() {
+        let code = indoc! {r#"
+            fn report(count: usize) {
+                log::info!("{count} items");
+                info!("unqualified");
+                other::info!("different path");
+                tracing::debug(count);
+                let _ = log::info!("in an expression");
+            }
+
+            log::info!("at the top level");
+
+            macro_rules! noisy {
+                () => {
+                    log::info!("in a macro definition")
+                };
+            }
+
+            fn sized() -> [u8; log::info!()] {
+                todo!()
+            }
+        "#};
+        let mut options = Options::from_arg_strs(["mutants", "--genre=custom"]);
+        options.custom_mutations = vec![
+            CustomMutation {
+                match_path: "log::info".to_owned(),
+                replace_with: "log::error!({args})".to_owned(),
+            },
+            CustomMutation {
+                match_path: "debug".to_owned(),
+                replace_with: "()".to_owned(),
+            },
+        ];
+        let names = mutate_source_str(code, &options)
+            .unwrap()
+            .iter()
+            .map(|m| m.name(false))
+            .collect_vec();
+        assert_eq!(
+            names,
+            [
+                r#"src/main.rs: replace log::info!("{count} items") with log::error!("{count} items") in report"#,
+                "src/main.rs: replace tracing::debug(count) with () in report",
+                r#"src/main.rs: replace log::info!("in an expression") with log::error!("in an expression") in report"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_main_keeps_other_mutants_in_the_binary() {
+        let code = indoc! {r#"
+            fn main() {
+                let x = double(2) + 1;
+                println!("{x}");
+            }
+
+            fn double(a: u32) -> u32 {
+                a * 2
+            }
+
+            mod inner {
+                fn main() {
+                    println!("not the entry point");
+                }
+            }
+        "#};
+        let names = |options: &Options| {
+            mutate_source_str(code, options)
+                .unwrap()
+                .iter()
+                .map(|m| m.name(false))
+                .collect_vec()
+        };
+        assert!(
+            names(&Options::default()).contains(&"src/main.rs: replace main with ()".to_owned())
+        );
+        let options = Options::from_arg_strs(["mutants", "--skip-main"]);
+        assert_eq!(
+            names(&options),
+            [
+                "src/main.rs: replace + with - in main",
+                "src/main.rs: replace + with * in main",
+                "src/main.rs: replace double -> u32 with 0",
+                "src/main.rs: replace double -> u32 with 1",
+                "src/main.rs: replace * with + in double",
+                "src/main.rs: replace * with / in double",
+                "src/main.rs: replace inner::main with ()",
+            ]
+        );
+        let skipped = skipped_in_source_str("fn main() {\n    run();\n}\n", &options).unwrap();
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.name.as_str(), s.reason))
+                .collect_vec(),
+            [("main", SkipReason::BinaryMain)]
+        );
+    }
+
+    #[test]
+    fn mutate_returned_tuple_elements_separately() {
+        let code = indoc! {"
+            fn count(a: &[u32]) -> (usize, bool) {
+                (a.len(), true)
+            }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        let tuple_mutants = mutants
+            .iter()
+            .filter(|m| m.tuple_index.is_some())
+            .map(|m| (m.name(true), m.mutated_code()))
+            .collect_vec();
+        assert_eq!(
+            tuple_mutants.iter().map(|(name, _)| name).collect_vec(),
+            [
+                "src/main.rs:2:6: replace element 0 of count -> (usize, bool) with 0",
+                "src/main.rs:2:6: replace element 0 of count -> (usize, bool) with 1",
+                "src/main.rs:2:15: replace element 1 of count -> (usize, bool) with false",
+            ]
+        );
+        assert!(tuple_mutants[2]
+            .1
+            .contains("(a.len(), false /* ~ changed by cargo-mutants ~ */)"));
+    }
+
+    #[test]
+    fn replace_returned_assoc_const_with_other_consts() {
+        let code = indoc! {"
+            struct Grams(u32);
+
+            impl Grams {
+                const PER_KILO: u32 = 1000;
+                const PER_POUND: u32 = 454;
+                const ZERO: Grams = Grams(0);
+
+                fn per_kilo() -> u32 {
+                    Self::PER_KILO
+                }
+
+                fn zero() -> Self {
+                    Self::ZERO
+                }
+            }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(false)).collect_vec(),
+            [
+                "src/main.rs: replace Grams::per_kilo -> u32 with 0",
+                "src/main.rs: replace Grams::per_kilo -> u32 with 1",
+                "src/main.rs: replace Grams::per_kilo -> u32 with Self::PER_POUND",
+                "src/main.rs: replace Grams::zero -> Self with Default::default()",
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_returned_assoc_const_with_consts_from_trait_and_inherent_impls() {
+        let code = indoc! {"
+            struct Grams(u32);
+
+            trait Unit {
+                const SCALE: u32;
+                const OFFSET: u32 = 0;
+
+                fn scale() -> u32 {
+                    Self::SCALE
+                }
+            }
+
+            impl Grams {
+                const PER_KILO: u32 = 1000;
+            }
+
+            impl Unit for Grams {
+                const SCALE: u32 = 1;
+
+                fn scale() -> u32 {
+                    Self::PER_KILO
+                }
+            }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(false)).collect_vec(),
+            [
+                "src/main.rs: replace Unit::scale -> u32 with 0",
+                "src/main.rs: replace Unit::scale -> u32 with 1",
+                "src/main.rs: replace Unit::scale -> u32 with Self::OFFSET",
+                "src/main.rs: replace <impl Unit for Grams>::scale -> u32 with 0",
+                "src/main.rs: replace <impl Unit for Grams>::scale -> u32 with 1",
+                "src/main.rs: replace <impl Unit for Grams>::scale -> u32 with Self::SCALE",
+                "src/main.rs: replace <impl Unit for Grams>::scale -> u32 with Self::OFFSET",
+            ]
+        );
+    }
+
+    #[test]
+    fn change_numeric_cast_types() {
+        let code = indoc! {"
+            fn narrow(a: u64, b: f64, c: i8) -> (u32, f32, i8) {
+                (a as u32, b as f32, c as i8)
+            }
+
+            fn to_string_type(a: &str) -> String {
+                a as String
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=cast"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:8: replace as u32 with as i32 in narrow",
+                "src/main.rs:2:8: replace as u32 with as u16 in narrow",
+                "src/main.rs:2:28: replace as i8 with as u8 in narrow",
+            ]
+        );
+        assert!(mutants[1]
+            .mutated_code()
+            .contains("(a as u16 /* ~ changed by cargo-mutants ~ */, b as f32, c as i8)"));
+    }
+
+    #[test]
+    fn binary_operators_are_replaced_except_in_skipped_functions() {
+        let code = indoc! {"
+            fn add(a: u32, b: u32) -> u32 { a + b }
+
+            #[mutants::skip]
+            fn sub(a: u32, b: u32) -> u32 { a - b }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=binary-operator"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:1:35: replace + with - in add",
+                "src/main.rs:1:35: replace + with * in add",
+            ]
+        );
+        assert!(mutants[0]
+            .mutated_code()
+            .contains("{ a - /* ~ changed by cargo-mutants ~ */ b }"));
+    }
+
+    #[test]
+    fn swap_logical_operators() {
+        let code = indoc! {"
+            fn valid(a: bool, b: bool, c: u32) -> bool {
+                a && (b || c > 1)
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=logical-operator"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:7: replace && with || in valid",
+                "src/main.rs:2:13: replace || with && in valid",
+            ]
+        );
+        assert!(mutants.iter().all(|m| m.genre == Genre::LogicalOperator));
+        assert!(mutants[0]
+            .mutated_code()
+            .contains("a || /* ~ changed by cargo-mutants ~ */ (b || c > 1)"));
+    }
+
+    #[test]
+    fn comparison_operators_move_their_boundary() {
+        let code = indoc! {"
+            const fn small(a: u32) -> bool {
+                a <= 10
+            }
+
+            fn big(a: u32) -> bool {
+                a > 10
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=comparison-operator"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:7: replace <= with > in small",
+                "src/main.rs:2:7: replace <= with < in small",
+                "src/main.rs:6:7: replace > with == in big",
+                "src/main.rs:6:7: replace > with < in big",
+                "src/main.rs:6:7: replace > with >= in big",
+            ]
+        );
+        assert!(mutants.iter().all(|m| m.genre == Genre::ComparisonOperator));
+
+        let options = Options::from_arg_strs(["mutants", "--genre=binary-operator"]);
+        assert_eq!(mutate_source_str(code, &options).unwrap(), []);
+    }
+
+    #[test]
+    fn flip_bool_literals() {
+        let code = indoc! {"
+            const VERBOSE: bool = false;
+
+            fn always() -> bool {
+                true
+            }
+
+            fn configure(c: &mut Config) -> bool {
+                let enabled = true;
+                c.set(enabled, false);
+                match c.mode() {
+                    true => enabled,
+                    false => false,
+                }
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=bool-literal"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:1:23: replace false with true",
+                "src/main.rs:4:5: replace true with false in always",
+                "src/main.rs:8:19: replace true with false in configure",
+                "src/main.rs:9:20: replace false with true in configure",
+                "src/main.rs:12:18: replace false with true in configure",
+            ]
+        );
+
+        // When function values are also generated, the whole body of `always` is only
+        // replaced once.
+        let options = Options::from_arg_strs(["mutants", "--genre=fn-value,bool-literal"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants
+                .iter()
+                .filter(|m| m
+                    .function
+                    .as_ref()
+                    .is_some_and(|f| f.function_name == "always"))
+                .map(|m| m.name(true))
+                .collect_vec(),
+            ["src/main.rs:4:5: replace always -> bool with false"]
+        );
+    }
+
+    #[test]
+    fn replace_numeric_literals() {
+        let code = indoc! {"
+            fn returns_42u32() -> u32 {
+                42u32
+            }
+
+            fn mixed(a: [u8; 4], b: f64) -> f64 {
+                match a[0] {
+                    0 => b * 0.5,
+                    _ => 0xff as f64 + 1.0,
+                }
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=literal"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:5: replace 42u32 with 43u32 in returns_42u32",
+                "src/main.rs:2:5: replace 42u32 with 41u32 in returns_42u32",
+                "src/main.rs:2:5: replace 42u32 with 0u32 in returns_42u32",
+                "src/main.rs:2:5: replace 42u32 with 1u32 in returns_42u32",
+                "src/main.rs:6:13: replace 0 with 1 in mixed",
+                "src/main.rs:7:18: replace 0.5 with 1.5 in mixed",
+                "src/main.rs:7:18: replace 0.5 with 0.0 in mixed",
+                "src/main.rs:7:18: replace 0.5 with 1.0 in mixed",
+                "src/main.rs:8:14: replace 0xff with 0x100 in mixed",
+                "src/main.rs:8:14: replace 0xff with 0xfe in mixed",
+                "src/main.rs:8:14: replace 0xff with 0x0 in mixed",
+                "src/main.rs:8:14: replace 0xff with 0x1 in mixed",
+                "src/main.rs:8:28: replace 1.0 with 2.0 in mixed",
+                "src/main.rs:8:28: replace 1.0 with 0.0 in mixed",
+            ]
+        );
+    }
+
+    #[test]
+    fn shift_index_literals() {
+        let code = indoc! {"
+            fn first(a: &[u8], v: &mut Vec<u8>) -> u8 {
+                *v.get_mut(0).unwrap() += a[0x2];
+                a.get(1usize).copied().unwrap_or(a[i])
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=index"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert!(mutants.iter().all(|m| m.genre == Genre::Index));
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:16: replace 0 with 1 in first",
+                "src/main.rs:2:33: replace 0x2 with 0x3 in first",
+                "src/main.rs:2:33: replace 0x2 with 0x1 in first",
+                "src/main.rs:3:11: replace 1usize with 2usize in first",
+                "src/main.rs:3:11: replace 1usize with 0usize in first",
+            ]
+        );
+    }
+
+    #[test]
+    fn index_and_literal_genres_do_not_duplicate() {
+        let code = indoc! {"
+            fn second(a: &[u8]) -> u8 {
+                a[1]
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=index,literal"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants
+                .iter()
+                .map(|m| (m.genre, m.name(true)))
+                .collect_vec(),
+            [
+                (
+                    Genre::Index,
+                    "src/main.rs:2:7: replace 1 with 2 in second".to_owned()
+                ),
+                (
+                    Genre::Index,
+                    "src/main.rs:2:7: replace 1 with 0 in second".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn delete_statements() {
+        let code = indoc! {r#"
+            fn tally(counter: &mut Counter, n: u32) -> u32 {
+                let doubled = n * 2;
+                counter.count += 1;
+                println!("{doubled}");
+                if n > 10 {
+                    counter.reset();
+                }
+                fn helper() {}
+                counter.log(
+                    "a",
+                    "b",
+                );
+                #[mutants::skip]
+                counter.flush();
+                doubled
+            }
+        "#};
+        let options = Options::from_arg_strs(["mutants", "--genre=statement"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:5: delete statement let doubled = n * 2; in tally",
+                "src/main.rs:3:5: delete statement counter.count += 1; in tally",
+                "src/main.rs:4:5: delete statement println!(\"{doubled}\"); in tally",
+                "src/main.rs:9:5: delete statement counter.log( \"a\", \"b\", ); in tally",
+                "src/main.rs:6:9: delete statement counter.reset(); in tally",
+            ]
+        );
+        assert!(mutants[1].mutated_code().contains(
+            "let doubled = n * 2;\n     /* ~ changed by cargo-mutants ~ */\n    println!"
+        ));
+
+        let default_mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert!(default_mutants.iter().all(|m| m.genre != Genre::Statement));
+    }
+
+    #[test]
+    fn replace_match_arm_bodies_one_at_a_time() {
+        let code = indoc! {r#"
+            fn size(a: Option<u32>) -> u32 {
+                match a {
+                    Some(x) if x > 100 => 100,
+                    Some(x) => {
+                        x + 1
+                    }
+                    None => 0,
+                }
+            }
+
+            fn parse(s: &str) -> u32 {
+                match s {
+                    "one" => 1,
+                    "never" => unreachable!(),
+                    _ => return 7,
+                }
+            }
+        "#};
+        let options = Options::from_arg_strs(["mutants", "--genre=match-arm"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:3:31: replace match arm Some(x) if x > 100 with 0 in size",
+                "src/main.rs:3:31: replace match arm Some(x) if x > 100 with 1 in size",
+                "src/main.rs:4:20: replace match arm Some(x) with { 0 } in size",
+                "src/main.rs:4:20: replace match arm Some(x) with { 1 } in size",
+                "src/main.rs:7:17: replace match arm None with 1 in size",
+                "src/main.rs:13:18: replace match arm \"one\" with 0 in parse",
+            ]
+        );
+        assert!(mutants[2]
+            .mutated_code()
+            .contains("Some(x) => { 0 } /* ~ changed by cargo-mutants ~ */\n        None => 0,"));
+        for mutant in &mutants {
+            syn::parse_file(&mutant.mutated_code()).expect("mutated code parses");
+        }
+
+        let default_mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert!(default_mutants.iter().all(|m| m.genre != Genre::MatchArm));
+    }
+
+    #[test]
+    fn delete_try_operators() {
+        let code = indoc! {"
+            fn load(path: &Path) -> Result<u32> {
+                let n = read_to_string(path)?.trim().parse()?;
+                Ok(n)
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=try-operator"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:49: delete ? in load",
+                "src/main.rs:2:33: delete ? in load",
+            ]
+        );
+        assert!(mutants[1]
+            .mutated_code()
+            .contains("read_to_string(path) /* ~ changed by cargo-mutants ~ */.trim()"));
+
+        let default_mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert!(default_mutants
+            .iter()
+            .all(|m| m.genre != Genre::TryOperator));
+    }
+
+    #[test]
+    fn replace_const_and_static_values() {
+        let code = indoc! {r#"
+            const MAX_RETRIES: u32 = 3;
+            pub static GREETING: &str = "hello";
+            const _: () = ();
+
+            mod limits {
+                pub const LIMIT: i64 = 1;
+            }
+
+            #[cfg(test)]
+            const TEST_ONLY: u32 = 7;
+        "#};
+        let options = Options::from_arg_strs(["mutants", "--genre=const-value"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:1:26: replace MAX_RETRIES: u32 with 0",
+                "src/main.rs:1:26: replace MAX_RETRIES: u32 with 1",
+                "src/main.rs:2:29: replace GREETING: &str with \"\"",
+                "src/main.rs:2:29: replace GREETING: &str with \"xyzzy\"",
+                "src/main.rs:6:28: replace limits::LIMIT: i64 with 0",
+                "src/main.rs:6:28: replace limits::LIMIT: i64 with -1",
+            ]
+        );
+        assert!(mutants[0]
+            .mutated_code()
+            .starts_with("const MAX_RETRIES: u32 = 0 /* ~ changed by cargo-mutants ~ */;"));
+
+        let default_mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert_eq!(default_mutants, []);
+    }
+
+    #[test]
+    fn negate_if_and_while_conditions() {
+        let code = indoc! {"
+            fn count(mut a: u32, b: Option<u32>) -> u32 {
+                while a > 10 {
+                    a -= 1;
+                }
+                if let Some(b) = b {
+                    a += b;
+                }
+                while let Some(x) = b {
+                    a += x;
+                }
+                if a == 3 || is_odd(a) {
+                    a
+                } else {
+                    0
+                }
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=condition"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:11: replace a > 10 with !(a > 10) in count",
+                "src/main.rs:11:8: replace a == 3 || is_odd(a) with !(a == 3 || is_odd(a)) in count",
+            ]
+        );
+        assert!(mutants[0]
+            .mutated_code()
+            .contains("while !(a > 10) /* ~ changed by cargo-mutants ~ */ {"));
+    }
+
+    #[test]
+    fn swap_min_and_max_and_drop_clamp_bounds() {
+        let code = indoc! {"
+            fn bound(a: u32, b: u32) -> u32 {
+                a.min(b).max(1) + a.clamp(2, b) + [a, b].iter().min().unwrap()
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--genre=min-max"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:2:13: replace .max with .min in bound",
+                "src/main.rs:2:6: replace .min with .max in bound",
+                "src/main.rs:2:24: replace .clamp(2, b) with .min(b) in bound",
+                "src/main.rs:2:24: replace .clamp(2, b) with .max(2) in bound",
+            ]
+        );
+        assert!(mutants[1]
+            .mutated_code()
+            .contains("a.max /* ~ changed by cargo-mutants ~ */(b).max(1)"));
+    }
+
+    #[test]
+    fn generic_return_values_follow_type_param_bounds() {
+        let code = indoc! {"
+            fn make<T: From<u8>>() -> T {
+                T::from(42)
+            }
+
+            fn identity<T>(t: T) -> T {
+                t
+            }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(false)).collect_vec(),
+            [
+                "src/main.rs: replace make -> T with T::from(0)",
+                "src/main.rs: replace make -> T with T::from(1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_return_values_follow_impl_type_param_bounds() {
+        let code = indoc! {"
+            struct Wrapper<T>(T);
+
+            impl<T> Wrapper<T>
+            where
+                T: Default,
+            {
+                fn make(&self) -> T {
+                    T::default()
+                }
+            }
+
+            trait Make<U: From<u8>> {
+                fn make(&self) -> U {
+                    U::from(2)
+                }
+            }
+        "};
+        let mutants = mutate_source_str(code, &Options::default()).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(false)).collect_vec(),
+            [
+                "src/main.rs: replace Wrapper<T>::make -> T with Default::default()",
+                "src/main.rs: replace Make::make -> U with U::from(0)",
+                "src/main.rs: replace Make::make -> U with U::from(1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_why_functions_are_skipped() {
+        let code = indoc! {"
+            #[mutants::skip]
+            fn skipped() -> usize { 1 }
+
+            unsafe fn danger() -> usize { 2 }
+
+            fn nothing() {}
+
+            fn identity<T>(t: T) -> T {
+                t
+            }
+
+            struct S;
+
+            impl S {
+                fn new() -> S { S }
+            }
+
+            impl Default for S {
+                fn default() -> S { S }
+            }
+
+            fn mutated() -> bool { true }
+
+            #[cfg(test)]
+            mod test {
+                #[test]
+                fn t() {}
+            }
+        "};
+        let skipped = skipped_in_source_str(code, &Options::default()).unwrap();
+        assert_eq!(
+            skipped.iter().map(|s| s.describe(true)).collect_vec(),
+            [
+                "src/main.rs:1: skipped: marked #[mutants::skip]",
+                "src/main.rs:4: danger: unsafe function",
+                "src/main.rs:6: nothing: empty body",
+                "src/main.rs:8: identity: no replacement values for the return type",
+                "src/main.rs:15: S::new: constructor named new",
+                "src/main.rs:18: <impl Default for S>: impl Default",
+                "src/main.rs:24: test: marked #[cfg(test)]",
+            ]
+        );
+    }
+
+    #[test]
+    fn functions_with_test_attributes_from_other_crates_are_skipped() {
+        let code = indoc! {"
+            #[tokio::test]
+            async fn async_test() -> bool { true }
+
+            #[test_case(1)]
+            #[test_case(2)]
+            fn case(a: u32) -> bool { a > 0 }
+
+            #[rstest]
+            fn fixture() -> bool { true }
+
+            #[test_log::test]
+            fn logged() -> bool { true }
+
+            #[my_crate::test]
+            fn not_a_known_test() -> bool { true }
+
+            fn testing() -> bool { true }
+        "};
+        let skipped = skipped_in_source_str(code, &Options::default()).unwrap();
+        assert_eq!(
+            skipped.iter().map(|s| s.describe(true)).collect_vec(),
+            [
+                "src/main.rs:1: async_test: test function",
+                "src/main.rs:4: case: test function",
+                "src/main.rs:8: fixture: test function",
+                "src/main.rs:11: logged: test function",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_public_skips_private_functions() {
+        let code = indoc! {"
+            pub fn exported() -> bool {
+                fn helper() -> bool { true }
+                helper()
+            }
+
+            fn private() -> bool { true }
+
+            pub(crate) fn crate_only() -> bool { true }
+
+            pub struct S;
+
+            impl S {
+                pub fn method(&self) -> bool { true }
+                fn private_method(&self) -> bool { true }
+            }
+
+            impl Clone for S {
+                fn clone(&self) -> S { S }
+            }
+
+            pub trait Public {
+                fn provided(&self) -> bool { true }
+            }
+
+            trait Private {
+                fn provided(&self) -> bool { true }
+            }
+        "};
+        let options = Options::from_arg_strs(["mutants", "--only-public"]);
+        let functions: Vec<String> = mutate_source_str(code, &options)
+            .unwrap()
+            .iter()
+            .filter_map(|m| m.function.as_ref().map(|f| f.function_name.clone()))
+            .unique()
+            .collect();
+        assert_eq!(
+            functions,
+            [
+                "exported",
+                "exported::helper",
+                "S::method",
+                "<impl Clone for S>::clone",
+                "Public::provided",
+            ]
+        );
+        let not_public = skipped_in_source_str(code, &options)
+            .unwrap()
+            .into_iter()
+            .filter(|s| s.reason == SkipReason::NotPublic)
+            .map(|s| s.name)
+            .collect_vec();
+        assert_eq!(
+            not_public,
+            [
+                "private",
+                "crate_only",
+                "S::private_method",
+                "Private::provided"
+            ]
+        );
+    }
+
+    #[test]
+    fn only_public_skips_functions_in_private_modules() {
+        let code = indoc! {"
+            pub mod api {
+                pub fn exported() -> bool { true }
+
+                mod detail {
+                    pub fn hidden() -> bool { true }
+                }
+            }
+
+            mod imp {
+                pub fn hidden() -> bool { true }
+
+                pub struct T;
+
+                impl Clone for T {
+                    fn clone(&self) -> T { T }
+                }
+
+                pub trait Internal {
+                    fn provided(&self) -> bool { true }
+                }
+            }
+
+            mod reexported {
+                pub fn widget() -> bool { true }
+            }
+
+            pub use self::reexported::widget;
+        "};
+        let options = Options::from_arg_strs(["mutants", "--only-public"]);
+        let functions: Vec<String> = mutate_source_str(code, &options)
+            .unwrap()
+            .iter()
+            .filter_map(|m| m.function.as_ref().map(|f| f.function_name.clone()))
+            .unique()
+            .collect();
+        assert_eq!(
+            functions,
+            [
+                "api::exported",
+                "imp::<impl Clone for T>::clone",
+                "reexported::widget",
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_reasons_are_recorded() {
+        let code = indoc! {r#"
+            #[mutants::skip(reason = "would hang")]
+            fn stop() -> bool { true }
+
+            #[cfg_attr(not(test), mutants::skip(reason = "only visual"))]
+            fn draw() -> usize { 1 }
+
+            #[cfg_attr(feature = "x", mutants::skip)]
+            fn plain() -> usize { 2 }
+
+            fn mutated() -> usize { 3 }
+        "#};
+        let skipped = skipped_in_source_str(code, &Options::default()).unwrap();
+        assert_eq!(
+            skipped.iter().map(|s| s.describe(true)).collect_vec(),
+            [
+                "src/main.rs:1: stop: marked #[mutants::skip] (would hang)",
+                "src/main.rs:4: draw: marked #[mutants::skip] (only visual)",
+                "src/main.rs:7: plain: marked #[mutants::skip]",
+            ]
+        );
+        let json = serde_json::to_value(&skipped).unwrap();
+        assert_eq!(json[0]["skip_reason"], "would hang");
+        assert!(json[2].get("skip_reason").is_none());
+    }
+
+    #[test]
+    fn code_for_other_platforms_is_not_mutated() {
+        let code = indoc! {r#"
+            #[cfg(windows)]
+            fn windows_only() -> u32 { 1 }
+
+            #[cfg(unix)]
+            fn unix_only() -> u32 { 2 }
+
+            #[cfg(target_os = "no-such-os")]
+            mod other {
+                pub fn elsewhere() -> u32 { 3 }
+            }
+
+            #[cfg(feature = "x")]
+            fn with_feature() -> u32 { 4 }
+        "#};
+        let fn_names = |options: &Options| {
+            mutate_source_str(code, options)
+                .unwrap()
+                .iter()
+                .map(|m| m.function.as_ref().unwrap().function_name.clone())
+                .unique()
+                .collect_vec()
+        };
+        let mut expected = Vec::new();
+        if cfg!(windows) {
+            expected.push("windows_only");
+        }
+        if cfg!(unix) {
+            expected.push("unix_only");
+        }
+        expected.push("with_feature");
+        assert_eq!(fn_names(&Options::default()), expected);
+
+        let skipped = skipped_in_source_str(code, &Options::default()).unwrap();
+        assert!(skipped.iter().any(
+            |s| s.describe(true) == "src/main.rs:7: other: #[cfg] is inactive on this platform"
+        ));
+
+        assert_eq!(
+            fn_names(&Options::from_arg_strs(["mutants", "--all-platforms"])),
+            [
+                "windows_only",
+                "unix_only",
+                "other::elsewhere",
+                "with_feature"
+            ]
+        );
+    }
+
+    #[test]
+    fn swap_ok_and_err_in_early_returns() {
+        let code = indoc! {"
+            fn check(n: u32) -> Result<u32, Error> {
+                if n == 0 {
+                    return Err(Error::Zero);
+                }
+                let f = |x: u32| -> Result<u32, Error> { return Ok(x) };
+                if n > 10 {
+                    return Ok(10);
+                }
+                f(n)
+            }
+
+            fn not_result(n: u32) -> Option<u32> {
+                return Some(n);
+            }
+        "};
+        let options =
+            Options::from_arg_strs(["mutants", "--genre=early-return", "--error=Error::Mutated"]);
+        let mutants = mutate_source_str(code, &options).unwrap();
+        assert_eq!(
+            mutants.iter().map(|m| m.name(true)).collect_vec(),
+            [
+                "src/main.rs:3:16: replace Err(Error::Zero) with Ok(0) in check",
+                "src/main.rs:3:16: replace Err(Error::Zero) with Ok(1) in check",
+                "src/main.rs:7:16: replace Ok(10) with Err(Error::Mutated) in check",
+            ]
+        );
+    }
 }
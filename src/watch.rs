@@ -0,0 +1,267 @@
+// Copyright 2025 Martin Pool
+
+//! Watch the source tree, and test mutants in code that changes.
+//!
+//! Changes are reported by the platform's file notification API, through the `notify`
+//! crate. When any of the source files changes, mutants are discovered again, and only
+//! those that intersect a diff between the old and new source are tested, in the same
+//! way as `--in-diff`.
+
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use camino::{Utf8Path, Utf8PathBuf};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use similar::TextDiff;
+use tracing::{debug, warn};
+
+use crate::in_diff::diff_filter;
+use crate::lab::test_mutants;
+use crate::output::OutputDir;
+use crate::source::SourceFile;
+use crate::workspace::{PackageFilter, Workspace};
+use crate::{check_interrupted, BaselineStrategy, Console, Options, Result};
+
+/// How often to check for an interruption while waiting for changes.
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long to wait after seeing a change, so that editors can finish writing all the
+/// files they're saving.
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+/// Watch the source tree until interrupted, testing mutants in changed code.
+pub fn watch(
+    workspace: &Workspace,
+    package_filter: &PackageFilter,
+    output_parent_dir: &Utf8Path,
+    options: &Options,
+    console: &Console,
+) -> Result<()> {
+    let root = workspace.root();
+    // A baseline from an earlier change can be reused if nothing that affects it has
+    // changed since the last test run.
+    let options = &if options.baseline == BaselineStrategy::Run {
+        Options {
+            baseline: BaselineStrategy::Reuse,
+            ..options.clone()
+        }
+    } else {
+        options.clone()
+    };
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx).context("start watching for changes")?;
+    watcher
+        .watch(root.as_std_path(), RecursiveMode::Recursive)
+        .with_context(|| format!("watch {root}"))?;
+    // Events may name files under the canonical path of the root, such as on macOS
+    // where temporary directories are reached through a symlink.
+    let roots = [
+        root.to_owned(),
+        root.canonicalize_utf8().unwrap_or_else(|_| root.to_owned()),
+    ];
+    let mut files = workspace.discover(package_filter, options, console)?.files;
+    console.clear();
+    console.message(&watching_message(&files));
+    loop {
+        wait_for_change(&rx, &roots, &files)?;
+        sleep(SETTLE_TIME);
+        // Collect any other events from the same save.
+        while rx.try_recv().is_ok() {}
+        let discovered = match workspace.discover(package_filter, options, console) {
+            Ok(discovered) => discovered,
+            Err(err) => {
+                // Probably the code is half-edited and doesn't parse; try again when it
+                // next changes.
+                warn!("Failed to discover mutants: {err:#}");
+                continue;
+            }
+        };
+        let diff = diff_sources(&files, &discovered.files);
+        files = discovered.files;
+        if diff.is_empty() {
+            debug!("no source changes");
+            continue;
+        }
+        let mutants = diff_filter(discovered.mutants, &diff)?;
+        console.clear();
+        if mutants.is_empty() {
+            console.message("No mutants in changed code\n");
+        } else if let Err(err) = OutputDir::new(output_parent_dir)
+            .and_then(|output_dir| test_mutants(mutants, workspace, output_dir, options, console))
+        {
+            // Stop if the user interrupted testing; otherwise the next change may fix it.
+            check_interrupted()?;
+            warn!("Failed to test mutants: {err:#}");
+        }
+        console.message(&watching_message(&files));
+    }
+}
+
+/// Wait until one of the source files is changed, checking periodically for an
+/// interruption.
+fn wait_for_change(
+    rx: &Receiver<notify::Result<Event>>,
+    roots: &[Utf8PathBuf],
+    files: &[SourceFile],
+) -> Result<()> {
+    loop {
+        check_interrupted()?;
+        match rx.recv_timeout(INTERRUPT_CHECK_INTERVAL) {
+            Ok(Ok(event)) => {
+                if event_changes_source(&event, roots, files) {
+                    debug!(?event, "source changed");
+                    return Ok(());
+                }
+            }
+            Ok(Err(err)) => warn!("Error watching for changes: {err}"),
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => bail!("File watcher stopped unexpectedly"),
+        }
+    }
+}
+
+/// True if the event might have changed the content of any of the source files.
+fn event_changes_source(event: &Event, roots: &[Utf8PathBuf], files: &[SourceFile]) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    event.paths.iter().any(|path| {
+        roots.iter().any(|root| {
+            path.strip_prefix(root).is_ok_and(|relative| {
+                files
+                    .iter()
+                    .any(|file| file.tree_relative_path.as_std_path() == relative)
+            })
+        })
+    })
+}
+
+fn watching_message(files: &[SourceFile]) -> String {
+    format!(
+        "Watching {} source files for changes; press Ctrl-C to stop\n",
+        files.len()
+    )
+}
+
+/// Make a unified diff from the old to the new versions of source files, in the
+/// format accepted by [`diff_filter`].
+///
+/// Files that are new are diffed against an empty file; files that no longer exist
+/// are not included, because they can't contain any mutants.
+fn diff_sources(old_files: &[SourceFile], new_files: &[SourceFile]) -> String {
+    let mut diff = String::new();
+    for new_file in new_files {
+        let old_code = old_files
+            .iter()
+            .find(|old_file| old_file.tree_relative_path == new_file.tree_relative_path)
+            .map_or("", SourceFile::code);
+        if old_code == new_file.code() {
+            continue;
+        }
+        let path = new_file.tree_relative_slashes();
+        debug!(?path, "source file changed");
+        diff.push_str(
+            &TextDiff::from_lines(old_code, new_file.code())
+                .unified_diff()
+                .context_radius(3)
+                .header(&format!("a/{path}"), &format!("b/{path}"))
+                .to_string(),
+        );
+    }
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::visit::mutate_source_str;
+
+    #[test]
+    fn only_mutants_in_changed_functions_are_selected() {
+        let old_code = indoc! {"
+            fn one() -> u32 {
+                1
+            }
+
+            fn two() -> u32 {
+                2
+            }
+        "};
+        let new_code = indoc! {"
+            fn one() -> u32 {
+                1
+            }
+
+            fn two() -> u32 {
+                1 + 1
+            }
+        "};
+        let new_mutants = mutate_source_str(new_code, &Options::default()).unwrap();
+        let new_file = new_mutants[0].source_file.clone();
+        let old_file = SourceFile::for_tests("src/main.rs", old_code, "main", true);
+
+        assert_eq!(
+            diff_sources(
+                std::slice::from_ref(&new_file),
+                std::slice::from_ref(&new_file)
+            ),
+            ""
+        );
+
+        let diff = diff_sources(&[old_file], &[new_file]);
+        assert!(diff.starts_with("--- a/src/main.rs\n+++ b/src/main.rs\n"));
+        let selected = diff_filter(new_mutants, &diff).unwrap();
+        assert_eq!(
+            selected.iter().map(|m| m.name(false)).collect_vec(),
+            [
+                "src/main.rs: replace two -> u32 with 0",
+                "src/main.rs: replace two -> u32 with 1",
+                "src/main.rs: replace + with - in two",
+                "src/main.rs: replace + with * in two",
+            ]
+        );
+    }
+
+    #[test]
+    fn new_files_are_diffed_against_empty() {
+        let new_file = SourceFile::for_tests("src/new.rs", "fn a() {}\n", "main", false);
+        assert_eq!(
+            diff_sources(&[], &[new_file]),
+            "--- a/src/new.rs\n+++ b/src/new.rs\n@@ -0,0 +1 @@\n+fn a() {}\n"
+        );
+    }
+
+    #[test]
+    fn only_changes_to_source_files_are_noticed() {
+        use notify::event::{AccessKind, CreateKind, DataChange, ModifyKind};
+
+        let roots = [Utf8PathBuf::from("/src/tree")];
+        let files = [SourceFile::for_tests(
+            "src/lib.rs",
+            "fn a() {}\n",
+            "main",
+            true,
+        )];
+        let changes = |kind, path: &str| {
+            event_changes_source(&Event::new(kind).add_path(path.into()), &roots, &files)
+        };
+        let modify = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        assert!(changes(modify, "/src/tree/src/lib.rs"));
+        assert!(changes(
+            EventKind::Create(CreateKind::File),
+            "/src/tree/src/lib.rs"
+        ));
+        assert!(!changes(modify, "/src/tree/src/other.rs"));
+        assert!(!changes(modify, "/src/tree/target/debug/build.log"));
+        assert!(!changes(modify, "/elsewhere/src/lib.rs"));
+        assert!(!changes(
+            EventKind::Access(AccessKind::Read),
+            "/src/tree/src/lib.rs"
+        ));
+    }
+}
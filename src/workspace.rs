@@ -15,8 +15,9 @@
 
 #![warn(clippy::pedantic)]
 
+use std::collections::HashMap;
 use std::fmt;
-use std::panic::catch_unwind;
+use std::fs::read_to_string;
 use std::path::Path;
 use std::process::Command;
 use std::sync::Arc;
@@ -28,10 +29,14 @@ use serde_json::Value;
 use tracing::{debug, error, warn};
 
 use crate::cargo::cargo_bin;
+use crate::config::PackageConfig;
 use crate::console::Console;
+use crate::discovery_cache::DiscoveryCache;
 use crate::interrupt::check_interrupted;
+use crate::mutant::{Mutant, MutantRecord};
 use crate::options::Options;
 use crate::package::{packages_from_metadata, Package, PackageSelection};
+use crate::source::SourceFile;
 use crate::visit::{walk_tree, Discovered};
 use crate::Result;
 
@@ -84,11 +89,20 @@ impl fmt::Debug for Workspace {
 
 impl Workspace {
     /// The root directory of the workspace.
+    #[must_use]
     pub fn root(&self) -> &Utf8Path {
         &self.metadata.workspace_root
     }
 
     /// Open the workspace containing a given directory.
+    ///
+    /// # Errors
+    ///
+    /// Fails if no Cargo workspace contains the directory, or if `cargo metadata` fails.
+    ///
+    /// # Panics
+    ///
+    /// If the path is not UTF-8.
     pub fn open<P: AsRef<Path>>(start_dir: P) -> Result<Self> {
         let start_dir = start_dir.as_ref();
         let dir = locate_project(start_dir.try_into().expect("start_dir is UTF-8"), true)?;
@@ -112,7 +126,42 @@ impl Workspace {
         Ok(Workspace { metadata, packages })
     }
 
-    pub fn packages_by_name<S: AsRef<str>>(&self, names: &[S]) -> Vec<Arc<Package>> {
+    /// Return the `[package.metadata.mutants]` config of each package in the workspace
+    /// that has one.
+    pub(crate) fn package_configs(&self) -> Result<Vec<(String, PackageConfig)>> {
+        let mut configs = Vec::new();
+        for package in self.metadata.workspace_packages() {
+            if let Some(config) = PackageConfig::from_package_metadata(&package.metadata)
+                .with_context(|| format!("Failed to read config of package {:?}", package.name))?
+            {
+                debug!(package = package.name, ?config, "Found package config");
+                configs.push((package.name.clone(), config));
+            }
+        }
+        Ok(configs)
+    }
+
+    /// The target directory where cargo writes build artifacts for this workspace.
+    pub(crate) fn target_directory(&self) -> &Utf8Path {
+        &self.metadata.target_directory
+    }
+
+    /// Return the names of all packages and targets in the workspace, with `-` replaced
+    /// by `_`, as they appear in the names of build artifacts.
+    pub(crate) fn local_crate_names(&self) -> Vec<String> {
+        self.metadata
+            .workspace_packages()
+            .into_iter()
+            .flat_map(|package| {
+                std::iter::once(&package.name).chain(package.targets.iter().map(|t| &t.name))
+            })
+            .map(|name| name.replace('-', "_"))
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    pub(crate) fn packages_by_name<S: AsRef<str>>(&self, names: &[S]) -> Vec<Arc<Package>> {
         names
             .iter()
             .map(AsRef::as_ref)
@@ -128,6 +177,51 @@ impl Workspace {
             .collect()
     }
 
+    /// Return the names of the packages containing any of the given paths, which are
+    /// relative to the workspace root.
+    ///
+    /// Each path is attributed to the innermost package directory that contains it.
+    /// Paths outside of any package are ignored.
+    pub(crate) fn packages_containing_paths(&self, paths: &[Utf8PathBuf]) -> Vec<String> {
+        paths
+            .iter()
+            .filter_map(|path| {
+                self.packages
+                    .iter()
+                    .filter(|package| path.starts_with(&package.relative_dir))
+                    .max_by_key(|package| package.relative_dir.components().count())
+                    .map(|package| package.name.clone())
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Return the member package whose manifest is at `manifest_path`, from `--manifest-path`.
+    ///
+    /// Returns None if the manifest is the workspace root manifest, or doesn't belong to
+    /// any member package.
+    pub(crate) fn package_for_manifest(
+        &self,
+        manifest_path: &Utf8Path,
+    ) -> Result<Option<Arc<Package>>> {
+        let manifest_dir = manifest_path
+            .canonicalize_utf8()
+            .with_context(|| format!("canonicalize {manifest_path}"))?
+            .parent()
+            .expect("manifest path has a parent")
+            .to_owned();
+        let root = self.root().canonicalize_utf8()?;
+        if manifest_dir == root {
+            return Ok(None);
+        }
+        Ok(self
+            .packages
+            .iter()
+            .find(|package| root.join(&package.relative_dir) == manifest_dir)
+            .cloned())
+    }
+
     /// Match a `PackageFilter` to the actual packages in this workspace, returning a list of packages.
     fn filter_packages(&self, filter: &PackageFilter) -> Result<PackageSelection> {
         match filter {
@@ -136,6 +230,21 @@ impl Workspace {
                 // Find the closest package directory (with a cargo manifest) to the current directory.
                 let package_dir = locate_project(dir, false)?;
                 assert!(package_dir.is_absolute());
+                // In the workspace root directory, `default-members` takes precedence over
+                // the root package, if there is one. If every package is a default, as in
+                // a virtual workspace without `default-members`, they're all tested below.
+                if package_dir == root {
+                    if let Some(default_packages) = self
+                        .default_packages()
+                        .filter(|packages| packages.len() < self.packages.len())
+                    {
+                        debug!(
+                            ?default_packages,
+                            "Resolved auto package filter to workspace default packages"
+                        );
+                        return Ok(PackageSelection::Explicit(default_packages));
+                    }
+                }
                 // It's not required that the members be inside the workspace directory: see
                 // <https://doc.rust-lang.org/cargo/reference/workspaces.html>
                 for package in &self.packages {
@@ -149,18 +258,14 @@ impl Workspace {
                         return Ok(PackageSelection::Explicit(vec![package.clone()]));
                     }
                 }
-                // Otherwise, we're in a virtual workspace directory, and not inside any package.
-                // Use configured defaults if there are any, otherwise test all packages.
+                // Otherwise, we're in a virtual workspace directory with no default members,
+                // and not inside any package, so test all packages.
                 ensure!(
                     package_dir == root,
                     "package {package_dir:?} doesn't match any child and doesn't match the workspace root {root:?}?",
                 );
-                let default_packages = self.default_packages();
-                debug!(
-                    ?default_packages,
-                    "Resolved auto package filter to workspace default packages"
-                );
-                Ok(default_packages)
+                debug!("Resolved auto package filter to all packages");
+                Ok(PackageSelection::All)
             }
             PackageFilter::All => Ok(PackageSelection::All),
             PackageFilter::Explicit(names) => {
@@ -177,7 +282,9 @@ impl Workspace {
     }
 
     /// Make all the mutants from the filtered packages in this workspace.
-    pub fn discover(
+    ///
+    /// Library users should call [`crate::discover`], which doesn't need a console.
+    pub(crate) fn discover(
         &self,
         package_filter: &PackageFilter,
         options: &Options,
@@ -186,45 +293,104 @@ impl Workspace {
         walk_tree(
             self.root(),
             &self.expand_selection(self.filter_packages(package_filter)?),
-            options,
+            &self.with_package_configs(options)?,
             console,
+            None,
         )
     }
 
-    /// Return the default workspace packages.
-    ///
-    /// Default packages can be specified in the workspace's `Cargo.toml` file;
-    /// if not, all packages are included.
-    fn default_packages(&self) -> PackageSelection {
-        let metadata = &self.metadata;
-        // `cargo_metadata::workspace_default_packages` will panic when calling Cargo older than 1.71;
-        // in that case we'll just fall back to everything, for lack of a better option.
-        // TODO: Use the new cargo_metadata API that doesn't panic?
-        match catch_unwind(|| metadata.workspace_default_packages()) {
-            Ok(default_packages) if default_packages.is_empty() => {
-                debug!("manifest has no explicit default packages");
-                PackageSelection::All
-            }
-            Ok(default_packages) => {
-                let default_package_names: Vec<&str> = default_packages
-                    .iter()
-                    .map(|pmeta| pmeta.name.as_str())
-                    .sorted() // for reproducibility
-                    .collect();
-                debug!(
-                    ?default_package_names,
-                    "Manifest defines explicit default packages"
-                );
-                PackageSelection::Explicit(self.packages_by_name(&default_package_names))
-            }
-            Err(err) => {
-                warn!(
-                    cargo_metadata_error = err.downcast::<String>().unwrap_or_default(),
-                    "workspace_default_packages is not supported; testing all packages",
-                );
-                PackageSelection::All
+    /// Make all the mutants from the filtered packages, reusing the results for files
+    /// that haven't changed since they were added to `cache`.
+    pub(crate) fn discover_with_cache(
+        &self,
+        package_filter: &PackageFilter,
+        options: &Options,
+        console: &Console,
+        cache: &DiscoveryCache,
+    ) -> Result<Discovered> {
+        walk_tree(
+            self.root(),
+            &self.expand_selection(self.filter_packages(package_filter)?),
+            &self.with_package_configs(options)?,
+            console,
+            Some(cache),
+        )
+    }
+
+    /// Return a copy of the options with the options for each package that has its own
+    /// `[package.metadata.mutants]` config.
+    fn with_package_configs(&self, options: &Options) -> Result<Options> {
+        let mut options = options.clone();
+        if !options.no_package_config {
+            for (package_name, package_config) in self.package_configs()? {
+                options.add_package_config(&package_name, &package_config)?;
             }
         }
+        Ok(options)
+    }
+
+    /// Load mutants previously written as JSON, by `--emit-mutants` or into `mutants.json`,
+    /// and match them to the source files in this workspace.
+    ///
+    /// # Errors
+    ///
+    /// Fails if any of the packages or files aren't found, or if the source has
+    /// changed so that the mutants no longer apply.
+    pub fn load_mutants(&self, path: &Utf8Path) -> Result<Vec<Mutant>> {
+        let json = read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+        let records: Vec<MutantRecord> =
+            serde_json::from_str(&json).with_context(|| format!("Failed to parse {path}"))?;
+        let mut source_files: HashMap<Utf8PathBuf, SourceFile> = HashMap::new();
+        let mut mutants = Vec::with_capacity(records.len());
+        for record in records {
+            let source_file = if let Some(source_file) = source_files.get(&record.file) {
+                source_file.clone()
+            } else {
+                let Some(package) = self.packages.iter().find(|p| p.name == record.package) else {
+                    bail!("Package {:?} not found in workspace", record.package);
+                };
+                let is_top = package.top_sources.contains(&record.file);
+                let Some(source_file) =
+                    SourceFile::load(self.root(), &record.file, package, is_top)?
+                else {
+                    bail!("Source file {} is outside the workspace", record.file);
+                };
+                source_files.insert(record.file.clone(), source_file.clone());
+                source_file
+            };
+            mutants.push(record.into_mutant(source_file)?);
+        }
+        debug!("Loaded {} mutants from {path}", mutants.len());
+        Ok(mutants)
+    }
+
+    /// Return the packages that cargo selects by default in the workspace root.
+    ///
+    /// These are the packages in `workspace.default-members` in the workspace's root
+    /// `Cargo.toml`, if it's set, or otherwise the root package, if there is one, or
+    /// all packages. Returns None if cargo is too old to report them.
+    fn default_packages(&self) -> Option<Vec<Arc<Package>>> {
+        if self.metadata.workspace_default_members.is_missing() {
+            debug!("cargo metadata doesn't report the default packages");
+            return None;
+        }
+        let default_names = self
+            .metadata
+            .workspace_default_packages()
+            .into_iter()
+            .map(|package| package.name.as_str())
+            .collect_vec();
+        let packages = self
+            .packages
+            .iter()
+            .filter(|package| default_names.contains(&package.name.as_str()))
+            .cloned()
+            .collect_vec();
+        debug!(
+            default_package_names = ?packages.iter().map(|p| &p.name).collect_vec(),
+            "Found default packages"
+        );
+        Some(packages)
     }
 }
 
@@ -276,8 +442,10 @@ fn locate_project(path: &Utf8Path, workspace: bool) -> Result<Utf8PathBuf> {
 
 #[cfg(test)]
 mod test {
+    use std::fs::{create_dir, read_to_string, write};
+
     use assert_matches::assert_matches;
-    use camino::Utf8PathBuf;
+    use camino::{Utf8Path, Utf8PathBuf};
     use itertools::Itertools;
 
     use crate::console::Console;
@@ -288,6 +456,21 @@ mod test {
 
     use super::Workspace;
 
+    #[test]
+    fn load_mutants_written_as_json() {
+        let tmp = copy_of_testdata("workspace");
+        let workspace = Workspace::open(tmp.path()).unwrap();
+        let mutants = workspace
+            .discover(&PackageFilter::All, &Options::default(), &Console::new())
+            .unwrap()
+            .mutants;
+        let json_path = Utf8PathBuf::try_from(tmp.path().join("mutants.json")).unwrap();
+        write(&json_path, serde_json::to_string(&mutants).unwrap()).unwrap();
+        let loaded = workspace.load_mutants(&json_path).unwrap();
+        assert_eq!(loaded.len(), mutants.len());
+        assert!(loaded.iter().zip(&mutants).all(|(a, b)| a == b));
+    }
+
     #[test]
     fn error_opening_outside_of_crate() {
         Workspace::open("/").unwrap_err();
@@ -361,6 +544,26 @@ mod test {
     fn auto_packages_in_virtual_workspace_gets_everything() {
         let tmp = copy_of_testdata("workspace");
         let workspace = Workspace::open(tmp.path()).expect("Find workspace root");
+        let packages = workspace
+            .filter_packages(&PackageFilter::Auto(
+                tmp.path().to_owned().try_into().unwrap(),
+            ))
+            .unwrap();
+        assert_matches!(packages, PackageSelection::All);
+        assert_eq!(
+            workspace
+                .expand_selection(packages)
+                .iter()
+                .map(|p| &p.name)
+                .collect_vec(),
+            ["cargo_mutants_testdata_workspace_utils", "main", "main2"]
+        );
+    }
+
+    #[test]
+    fn auto_packages_in_virtual_workspace_gets_default_members() {
+        let tmp = copy_of_testdata("workspace_default_members");
+        let workspace = Workspace::open(tmp.path()).expect("Find workspace root");
         let packages = workspace
             .filter_packages(&PackageFilter::Auto(
                 tmp.path().to_owned().try_into().unwrap(),
@@ -370,11 +573,76 @@ mod test {
             panic!("Expected PackageSelection::Explicit, got {packages:?}");
         };
         assert_eq!(
-            packages.iter().map(|p| &p.name).sorted().collect_vec(),
-            ["cargo_mutants_testdata_workspace_utils", "main", "main2"]
+            packages.iter().map(|p| &p.name).collect_vec(),
+            ["cargo_mutants_testdata_default_members_core"]
+        );
+
+        // Within a package directory, that package is still selected.
+        let tools_dir = Utf8PathBuf::try_from(tmp.path().join("tools")).unwrap();
+        let PackageSelection::Explicit(packages) = workspace
+            .filter_packages(&PackageFilter::Auto(tools_dir))
+            .unwrap()
+        else {
+            panic!("Expected PackageSelection::Explicit");
+        };
+        assert_eq!(
+            packages.iter().map(|p| &p.name).collect_vec(),
+            ["cargo_mutants_testdata_default_members_tools"]
         );
     }
 
+    #[test]
+    fn default_members_take_precedence_over_root_package() {
+        let tmp = copy_of_testdata("workspace_default_members");
+        let manifest_path = tmp.path().join("Cargo.toml");
+        let manifest = read_to_string(&manifest_path).unwrap().replace(
+            "default-members = [\"core\"]",
+            "default-members = [\"./tools/\"]",
+        );
+        write(
+            &manifest_path,
+            format!(
+                "[package]\nname = \"root\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n{manifest}"
+            ),
+        )
+        .unwrap();
+        create_dir(tmp.path().join("src")).unwrap();
+        write(tmp.path().join("src/lib.rs"), "").unwrap();
+        let workspace = Workspace::open(tmp.path()).expect("Find workspace root");
+        let PackageSelection::Explicit(packages) = workspace
+            .filter_packages(&PackageFilter::Auto(
+                tmp.path().to_owned().try_into().unwrap(),
+            ))
+            .unwrap()
+        else {
+            panic!("Expected PackageSelection::Explicit");
+        };
+        assert_eq!(
+            packages.iter().map(|p| &p.name).collect_vec(),
+            ["cargo_mutants_testdata_default_members_tools"]
+        );
+    }
+
+    #[test]
+    fn package_for_member_manifest() {
+        let tmp = copy_of_testdata("workspace");
+        let tmp_path = Utf8Path::from_path(tmp.path()).unwrap();
+        let workspace = Workspace::open(tmp_path).unwrap();
+        assert_eq!(
+            workspace
+                .package_for_manifest(&tmp_path.join("utils/Cargo.toml"))
+                .unwrap()
+                .map(|p| p.name.clone())
+                .as_deref(),
+            Some("cargo_mutants_testdata_workspace_utils")
+        );
+        // The root manifest doesn't select a package: it's resolved like `Auto`.
+        assert!(workspace
+            .package_for_manifest(&tmp_path.join("Cargo.toml"))
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn filter_by_single_package() {
         let tmp = copy_of_testdata("workspace");
@@ -395,6 +663,22 @@ mod test {
         assert_eq!(packages[0].top_sources, ["main/src/main.rs"]);
     }
 
+    #[test]
+    fn packages_containing_paths_in_workspace() {
+        let tmp = copy_of_testdata("workspace");
+        let workspace = Workspace::open(tmp.path()).expect("Find workspace root");
+        assert_eq!(
+            workspace.packages_containing_paths(&[
+                "main2/src/main.rs".into(),
+                "utils/src/lib.rs".into(),
+                "utils/Cargo.toml".into(),
+                "README.md".into(),
+            ]),
+            ["cargo_mutants_testdata_workspace_utils", "main2"]
+        );
+        assert!(workspace.packages_containing_paths(&[]).is_empty());
+    }
+
     #[test]
     fn filter_by_multiple_packages() {
         let tmp = copy_of_testdata("workspace");
@@ -0,0 +1,17 @@
+pub async fn factorial(n: u32) -> u32 {
+    let mut a = 1;
+    for i in 2..=n {
+        a *= i;
+    }
+    a
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_factorial() {
+        assert_eq!(factorial(6).await, 72);
+    }
+}
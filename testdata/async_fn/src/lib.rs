@@ -0,0 +1,16 @@
+//! Functions returning futures, whose bodies are replaced by futures of other values.
+
+use std::future::Future;
+use std::pin::Pin;
+
+pub async fn double(a: u32) -> u32 {
+    a * 2
+}
+
+pub fn is_even(a: u32) -> impl Future<Output = bool> {
+    async move { a % 2 == 0 }
+}
+
+pub fn count_words(s: &str) -> Pin<Box<dyn Future<Output = usize> + Send + '_>> {
+    Box::pin(async move { s.split_whitespace().count() })
+}
@@ -0,0 +1,17 @@
+use cargo_mutants_testdata_async_fn::*;
+
+#[tokio::test]
+async fn double_a_number() {
+    assert_eq!(double(3).await, 6);
+}
+
+#[tokio::test]
+async fn even_and_odd_numbers() {
+    assert!(is_even(4).await);
+    assert!(!is_even(5).await);
+}
+
+#[tokio::test]
+async fn count_some_words() {
+    assert_eq!(count_words("one two three").await, 3);
+}
@@ -0,0 +1,20 @@
+/// Double a number.
+///
+/// The doctest is the only test that checks the result, so mutants of this function
+/// are missed unless the doctests are run.
+///
+/// ```
+/// use cargo_mutants_testdata_caught_only_by_doctest::double;
+/// assert_eq!(double(3), 6);
+/// ```
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn double_does_not_panic() {
+        super::double(2);
+    }
+}
@@ -0,0 +1,3 @@
+pub fn triple(a: u32) -> u32 {
+    a * 3
+}
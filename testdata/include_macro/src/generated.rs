@@ -0,0 +1,3 @@
+pub fn double(a: u32) -> u32 {
+    a * 2
+}
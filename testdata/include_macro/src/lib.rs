@@ -0,0 +1,20 @@
+//! Code included from other files with `include!`, as is often done for generated code.
+
+include!("generated.rs");
+
+pub mod api {
+    include!("api/generated_api.rs");
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn double() {
+        assert_eq!(super::double(3), 6);
+    }
+
+    #[test]
+    fn triple() {
+        assert_eq!(super::api::triple(3), 9);
+    }
+}
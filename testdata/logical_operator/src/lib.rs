@@ -0,0 +1,16 @@
+//! The test only checks the case where both inputs are true, so replacing `&&`
+//! with `||` is not detected.
+
+pub fn valid(a: bool, b: bool) -> bool {
+    a && b
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn both_true_is_valid() {
+        assert!(valid(true, true));
+    }
+}
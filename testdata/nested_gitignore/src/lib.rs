@@ -0,0 +1,13 @@
+/// Return the answer, read from a fixture file that is ignored by the root
+/// `.gitignore` but re-included by `fixtures/.gitignore`.
+pub fn answer() -> u32 {
+    include_str!("../fixtures/keep.dat").trim().parse().unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn answer_is_42() {
+        assert_eq!(super::answer(), 42);
+    }
+}
@@ -0,0 +1,7 @@
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
+
+pub fn is_even(x: u32) -> bool {
+    x % 2 == 0
+}
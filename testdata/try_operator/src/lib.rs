@@ -0,0 +1,44 @@
+pub fn check_positive(x: i32) -> Result<(), String> {
+    if x > 0 {
+        Ok(())
+    } else {
+        Err(format!("{x} is not positive"))
+    }
+}
+
+pub fn checked_double(x: i32) -> Result<i32, String> {
+    check_positive(x)?;
+    Ok(x * 2)
+}
+
+pub fn checked_half(x: i32) -> Result<i32, String> {
+    check_positive(x)?;
+    Ok(x / 2)
+}
+
+pub fn parse_and_increment(s: &str) -> Result<i32, std::num::ParseIntError> {
+    let n: i32 = s.parse()?;
+    Ok(n + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn double() {
+        assert_eq!(checked_double(3), Ok(6));
+        assert!(checked_double(-1).is_err());
+    }
+
+    #[test]
+    fn half() {
+        // The error case isn't tested.
+        assert_eq!(checked_half(4), Ok(2));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_and_increment("41"), Ok(42));
+    }
+}
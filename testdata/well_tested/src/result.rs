@@ -15,7 +15,7 @@ fn error_if_negative(a: i32) -> Result<(), ()> {
 }
 
 fn result_with_no_apparent_type_args() -> std::fmt::Result {
-    Err(Default::default())
+    Err(std::fmt::Error)
 }
 
 mod test {
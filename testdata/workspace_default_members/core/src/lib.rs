@@ -0,0 +1,8 @@
+pub fn double(a: i32) -> i32 {
+    a * 2
+}
+
+#[test]
+fn test_double() {
+    assert_eq!(double(3), 6);
+}
@@ -0,0 +1,8 @@
+pub fn halve(a: i32) -> i32 {
+    a / 2
+}
+
+#[test]
+fn test_halve() {
+    assert_eq!(halve(6), 3);
+}
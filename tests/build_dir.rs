@@ -1,6 +1,7 @@
 // Copyright 2023-2024 Martin Pool
 
-use std::fs::{create_dir, write};
+use std::fs::{create_dir, read_to_string, write};
+use std::process::Command;
 
 mod util;
 use util::{copy_of_testdata, run};
@@ -22,6 +23,20 @@ fn gitignore_respected_in_copy_by_default() {
         .code(4);
 }
 
+#[test]
+fn nested_gitignore_negation_is_respected_in_copy() {
+    // The root `.gitignore` ignores `*.dat`, but `fixtures/.gitignore` re-includes
+    // `keep.dat`, which is needed to build the tree.
+    let tmp = copy_of_testdata("nested_gitignore");
+    create_dir(tmp.path().join(".git")).unwrap();
+    write(tmp.path().join("fixtures/other.dat"), b"not needed\n").unwrap();
+    run()
+        .args(["mutants", "--no-shuffle", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success();
+}
+
 #[test]
 fn gitignore_can_be_turned_off() {
     // Make a tree with a (dumb) gitignore that excludes the source file; when you copy it
@@ -51,3 +66,24 @@ fn symlink_in_source_tree_is_copied() {
         .assert()
         .success();
 }
+
+#[test]
+fn copy_target_deps_copies_dependencies_from_target() {
+    let tmp = copy_of_testdata("async_fn");
+    let status = Command::new("cargo")
+        .args(["check", "--tests"])
+        .current_dir(tmp.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    run()
+        .args(["mutants", "--check", "--copy-target-deps=true", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success();
+    let debug_log = read_to_string(tmp.path().join("mutants.out/debug.log")).unwrap();
+    assert!(
+        debug_log.contains("transfer=Copy"),
+        "debug log should show dependencies were copied:\n{debug_log}"
+    );
+}
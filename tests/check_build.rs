@@ -124,14 +124,17 @@ fn check_tree_with_mutants_skip() {
         .assert()
         .success()
         .stdout(indoc! { r"
-            Found 5 mutants to test
+            Found 8 mutants to test
             ok       Unmutated baseline
             ok       src/lib.rs:15:5: replace controlled_loop with ()
+            ok       src/lib.rs:18:12: replace should_stop() with !(should_stop()) in controlled_loop
+            ok       src/lib.rs:21:12: replace start.elapsed() > Duration::from_secs(60 * 5) with !(start.elapsed() > Duration::from_secs(60 * 5)) in controlled_loop
             ok       src/lib.rs:21:28: replace > with == in controlled_loop
             ok       src/lib.rs:21:28: replace > with < in controlled_loop
+            ok       src/lib.rs:21:28: replace > with >= in controlled_loop
             ok       src/lib.rs:21:53: replace * with + in controlled_loop
             ok       src/lib.rs:21:53: replace * with / in controlled_loop
-            5 mutants tested: 5 succeeded
+            8 mutants tested: 8 succeeded
             "})
         .stderr("");
     assert_eq!(
@@ -139,10 +142,10 @@ fn check_tree_with_mutants_skip() {
         serde_json::json!({
             "caught": 0,
             "missed": 0,
-            "success": 5,
+            "success": 8,
             "timeout": 0,
             "unviable": 0,
-            "total_mutants": 5,
+            "total_mutants": 8,
         })
     );
 }
@@ -187,7 +190,7 @@ fn unviable_mutation_of_struct_with_no_default() {
         .arg("-d")
         .arg(tmp_src_dir.path())
         .assert()
-        .success()
+        .code(6)
         .stdout(
             predicate::str::is_match(
                 r"unviable *src/lib.rs:\d+:\d+: replace make_an_s -> S with Default::default\(\)",
@@ -8,7 +8,7 @@
 use predicates::prelude::*;
 
 mod util;
-use util::run;
+use util::{copy_of_testdata, run};
 
 fn has_color_listing() -> impl Predicate<str> {
     predicates::str::contains("with \x1b[33m0\x1b[0m")
@@ -25,8 +25,11 @@ fn has_color_debug() -> impl Predicate<str> {
 /// The test fixtures force off colors, even if something else tries to turn it on.
 #[test]
 fn no_color_in_test_subprocesses_by_default() {
+    let tmp = copy_of_testdata("small_well_tested");
     run()
-        .args(["mutants", "-d", "testdata/small_well_tested", "--list"])
+        .args(["mutants", "--list"])
+        .arg("-d")
+        .arg(tmp.path())
         .assert()
         .success()
         .stdout(has_ansi_escape().not())
@@ -37,15 +40,11 @@ fn no_color_in_test_subprocesses_by_default() {
 /// in trace output.
 #[test]
 fn colors_always_shows_in_stdout_and_trace() {
+    let tmp = copy_of_testdata("small_well_tested");
     run()
-        .args([
-            "mutants",
-            "-d",
-            "testdata/small_well_tested",
-            "--list",
-            "--colors=always",
-            "-Ltrace",
-        ])
+        .args(["mutants", "--list", "--colors=always", "-Ltrace"])
+        .arg("-d")
+        .arg(tmp.path())
         .assert()
         .success()
         .stdout(has_color_listing())
@@ -54,15 +53,12 @@ fn colors_always_shows_in_stdout_and_trace() {
 
 #[test]
 fn cargo_term_color_env_shows_colors() {
+    let tmp = copy_of_testdata("small_well_tested");
     run()
         .env("CARGO_TERM_COLOR", "always")
-        .args([
-            "mutants",
-            "-d",
-            "testdata/small_well_tested",
-            "--list",
-            "-Ltrace",
-        ])
+        .args(["mutants", "--list", "-Ltrace"])
+        .arg("-d")
+        .arg(tmp.path())
         .assert()
         .success()
         .stdout(has_color_listing())
@@ -91,18 +87,53 @@ fn invalid_cargo_term_color_rejected_with_message() {
 /// Colors can be turned on with `CLICOLOR_FORCE`.
 #[test]
 fn clicolor_force_shows_in_stdout_and_trace() {
+    let tmp = copy_of_testdata("small_well_tested");
     run()
         .env("CLICOLOR_FORCE", "1")
-        .args([
-            "mutants",
-            "-d",
-            "testdata/small_well_tested",
-            "--list",
-            "--colors=never",
-            "-Ltrace",
-        ])
+        .args(["mutants", "--list", "--colors=never", "-Ltrace"])
+        .arg("-d")
+        .arg(tmp.path())
         .assert()
         .success()
         .stdout(has_color_listing())
         .stderr(has_color_debug());
 }
+
+/// Diffs from `--list --diff` are colored along with the mutant names.
+#[test]
+fn colors_always_shows_in_listed_diffs() {
+    let tmp = copy_of_testdata("small_well_tested");
+    run()
+        .args(["mutants", "--list", "--diff", "--colors=always"])
+        .arg("-d")
+        .arg(tmp.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("\x1b[32m+"));
+}
+
+/// Logs are written to files, so they never contain escape codes, even if colors are
+/// forced on for cargo-mutants and cargo through `CARGO_TERM_COLOR`.
+#[test]
+fn no_color_in_logs_when_colors_forced() {
+    let tmp = copy_of_testdata("small_well_tested");
+    run()
+        .env("CARGO_TERM_COLOR", "always")
+        .args(["mutants", "--colors=always", "--diff"])
+        .arg("-d")
+        .arg(tmp.path())
+        .assert()
+        .success();
+    let log_dir = tmp.path().join("mutants.out/log");
+    let mut n_logs = 0;
+    for entry in std::fs::read_dir(&log_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let content = std::fs::read(&path).unwrap();
+        assert!(
+            !content.contains(&0x1b),
+            "{path:?} contains an escape character"
+        );
+        n_logs += 1;
+    }
+    assert!(n_logs > 1);
+}
@@ -0,0 +1,150 @@
+// Copyright 2025 Martin Pool
+
+//! Tests for `--compare-to` and `--fail-on-new`
+
+mod util;
+
+use std::fs::{copy, create_dir, write};
+
+use indoc::indoc;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+use self::util::run;
+
+#[test]
+fn compare_missed_mutants_to_previous_outcomes() {
+    let temp = tempdir().unwrap();
+    write(
+        temp.path().join("Cargo.toml"),
+        indoc! { r#"
+            [package]
+            name = "cargo_mutants_compare"
+            edition = "2021"
+            version = "0.0.0"
+            publish = false
+        "# },
+    )
+    .unwrap();
+    create_dir(temp.path().join("src")).unwrap();
+    create_dir(temp.path().join("tests")).unwrap();
+    write(
+        temp.path().join("src/lib.rs"),
+        indoc! { r#"
+            pub fn is_two(a: usize) -> bool { a == 2 }
+        "#},
+    )
+    .unwrap();
+
+    // Nothing is tested, so everything is missed.
+    run()
+        .arg("mutants")
+        .arg("--no-shuffle")
+        .arg("-d")
+        .arg(temp.path())
+        .assert()
+        .code(2);
+    let previous = temp.path().join("previous.json");
+    copy(temp.path().join("mutants.out/outcomes.json"), &previous).unwrap();
+
+    // Add a test that catches all the existing mutants, and also some new untested code.
+    write(
+        temp.path().join("tests/main.rs"),
+        indoc! { r#"
+            use cargo_mutants_compare::*;
+
+            #[test]
+            fn some_test() {
+                assert!(is_two(2));
+                assert!(!is_two(4));
+            }
+        "#},
+    )
+    .unwrap();
+    write(
+        temp.path().join("src/lib.rs"),
+        indoc! { r#"
+            pub fn is_two(a: usize) -> bool { a == 2 }
+            pub fn is_zero(a: usize) -> bool { a == 0 }
+        "#},
+    )
+    .unwrap();
+
+    run()
+        .arg("mutants")
+        .arg("--no-shuffle")
+        .arg("-d")
+        .arg(temp.path())
+        .arg("--compare-to")
+        .arg(&previous)
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(format!(
+            "Compared to {}: 3 new survivors, 3 fixed",
+            previous.display()
+        )));
+
+    // With --fail-on-new, the new survivors are still a failure, and they're listed.
+    run()
+        .arg("mutants")
+        .arg("--no-shuffle")
+        .arg("-d")
+        .arg(temp.path())
+        .arg("--compare-to")
+        .arg(&previous)
+        .arg("--fail-on-new")
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "New survivor: src/lib.rs: replace is_zero -> bool with true\n",
+        ))
+        .stdout(predicate::str::contains(
+            "New survivor: src/lib.rs: replace == with != in is_zero\n",
+        ));
+
+    // Compared to the last run, nothing is newly missed, so with --fail-on-new this
+    // succeeds even though some mutants are still missed.
+    copy(temp.path().join("mutants.out/outcomes.json"), &previous).unwrap();
+    run()
+        .arg("mutants")
+        .arg("--no-shuffle")
+        .arg("-d")
+        .arg(temp.path())
+        .arg("--compare-to")
+        .arg(&previous)
+        .arg("--fail-on-new")
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains(format!(
+            "Compared to {}: 0 new survivors, 0 fixed",
+            previous.display()
+        )))
+        .stdout(predicate::str::contains("New survivor").not());
+}
+
+#[test]
+fn fail_on_new_requires_compare_to() {
+    run()
+        .args([
+            "mutants",
+            "--fail-on-new",
+            "-d",
+            "testdata/small_well_tested",
+        ])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("--compare-to"));
+}
+
+#[test]
+fn compare_to_missing_file_is_an_error() {
+    let temp = tempdir().unwrap();
+    run()
+        .args(["mutants", "--compare-to"])
+        .arg(temp.path().join("nonexistent.json"))
+        .arg("-d")
+        .arg("testdata/small_well_tested")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Read previous outcomes from"));
+}
@@ -2,12 +2,12 @@
 
 //! Test handling of `mutants.toml` configuration.
 
-use std::fs::{create_dir, write};
+use std::fs::{create_dir, read_to_string, write};
 
 use indoc::indoc;
 use insta::assert_snapshot;
 use predicates::prelude::*;
-use tempfile::TempDir;
+use tempfile::{tempdir, TempDir};
 
 mod util;
 use util::{copy_of_testdata, run};
@@ -182,6 +182,45 @@ fn list_with_config_file_regexps() {
     );
 }
 
+#[test]
+fn list_with_config_file_exclude_genres_and_regexps() {
+    let testdata = copy_of_testdata("well_tested");
+    write_config_file(
+        &testdata,
+        r#"
+        examine_re = ["divisible"]
+        exclude_re = ["-> bool with true"]
+        exclude_genres = ["binary_operator"]
+        "#,
+    );
+    let cmd = run()
+        .args(["mutants", "--list", "--line-col=false", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .success();
+    assert_snapshot!(
+        String::from_utf8_lossy(&cmd.get_output().stdout),
+        @r###"
+    src/simple_fns.rs: replace divisible_by_three -> bool with false
+    src/simple_fns.rs: replace == with != in divisible_by_three
+    "###
+    );
+}
+
+#[test]
+fn unknown_exclude_genre_in_config_is_an_error() {
+    let testdata = copy_of_testdata("well_tested");
+    write_config_file(&testdata, r#"exclude_genres = ["bogus"]"#);
+    run()
+        .args(["mutants", "--list", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Unknown genre \"bogus\"; valid genres are: fn-value, binary-operator,",
+        ));
+}
+
 #[test]
 fn exclude_re_overrides_config() {
     let testdata = copy_of_testdata("well_tested");
@@ -248,6 +287,28 @@ fn additional_cargo_args() {
         .success();
 }
 
+#[test]
+fn features_from_config() {
+    let testdata = copy_of_testdata("fails_without_feature");
+    write_config_file(
+        &testdata,
+        r#"
+        features = ["needed"]
+        "#,
+    );
+    run()
+        .args(["mutants", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .success();
+    let baseline_log =
+        read_to_string(testdata.path().join("mutants.out/log/baseline.log")).unwrap();
+    assert!(
+        baseline_log.contains("--features=needed"),
+        "feature not found in baseline log:\n{baseline_log}"
+    );
+}
+
 #[test]
 fn additional_cargo_test_args() {
     let testdata = copy_of_testdata("fails_without_feature");
@@ -311,3 +372,224 @@ fn output_option_use_config() {
         assert!(mutants_out.join(name).is_file(), "{name} is in mutants.out",);
     }
 }
+
+fn append_package_metadata(tempdir: &TempDir, package_dir: &str, metadata: &str) {
+    let path = tempdir.path().join(package_dir).join("Cargo.toml");
+    let manifest = read_to_string(&path).unwrap();
+    write(
+        &path,
+        format!("{manifest}\n[package.metadata.mutants]\n{metadata}"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn package_metadata_overrides_config_file_for_that_package() {
+    let testdata = copy_of_testdata("workspace");
+    write_config_file(&testdata, r#"exclude_globs = ["**/main.rs"]"#);
+    append_package_metadata(
+        &testdata,
+        "main",
+        r#"exclude_globs = ["main/src/other.rs"]"#,
+    );
+    run()
+        .args(["mutants", "--list-files", "--workspace", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .success()
+        .stdout("utils/src/lib.rs\nmain/src/main.rs\n");
+}
+
+#[test]
+fn package_metadata_is_ignored_with_no_config() {
+    let testdata = copy_of_testdata("workspace");
+    append_package_metadata(&testdata, "utils", r#"exclude_globs = ["**/lib.rs"]"#);
+    run()
+        .args([
+            "mutants",
+            "--list-files",
+            "--workspace",
+            "--no-config",
+            "-d",
+        ])
+        .arg(testdata.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("utils/src/lib.rs"));
+}
+
+#[test]
+fn invalid_package_metadata_field_rejected() {
+    let testdata = copy_of_testdata("workspace");
+    append_package_metadata(&testdata, "utils", "timeout_multiplier = 2.0\n");
+    run()
+        .args(["mutants", "--list-files", "--workspace", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicates::str::contains(
+                "Failed to read config of package \"cargo_mutants_testdata_workspace_utils\"",
+            )
+            .and(predicates::str::contains(
+                "unknown field `timeout_multiplier`",
+            )),
+        );
+}
+
+#[test]
+fn skip_calls_config_removes_mutants_in_call_arguments() {
+    let temp = tempdir().unwrap();
+    write(
+        temp.path().join("Cargo.toml"),
+        indoc! { r#"
+            [package]
+            name = "cargo_mutants_skip_calls"
+            edition = "2021"
+            version = "0.0.0"
+            publish = false
+        "# },
+    )
+    .unwrap();
+    create_dir(temp.path().join("src")).unwrap();
+    write(
+        temp.path().join("src/lib.rs"),
+        indoc! { r#"
+            pub fn total(v: &mut Vec<u32>, a: u32) {
+                v.reserve(a * 2);
+                v.push(scale(a + 1));
+                v.push(offset(a - 1));
+            }
+
+            fn scale(a: u32) -> u32 { a }
+
+            fn offset(a: u32) -> u32 { a }
+        "# },
+    )
+    .unwrap();
+    let list = || {
+        run()
+            .args(["mutants", "--list", "--no-shuffle", "-d"])
+            .arg(temp.path())
+            .assert()
+            .success()
+    };
+    list()
+        .stdout(predicate::str::contains("replace * with + in total"))
+        .stdout(predicate::str::contains("replace + with - in total"))
+        .stdout(predicate::str::contains("replace - with + in total"));
+
+    write_config_file(&temp, r#"skip_calls = ["reserve", "scale"]"#);
+    list()
+        .stdout(predicate::str::contains("replace * with + in total").not())
+        .stdout(predicate::str::contains("replace + with - in total").not())
+        .stdout(predicate::str::contains("replace - with + in total"));
+}
+
+#[test]
+fn skip_main_from_config() {
+    let testdata = copy_of_testdata("factorial");
+    let list = || {
+        run()
+            .args(["mutants", "--list", "--no-shuffle", "-d"])
+            .arg(testdata.path())
+            .assert()
+            .success()
+    };
+    list()
+        .stdout(predicate::str::contains("replace main with ()"))
+        .stdout(predicate::str::contains("replace factorial -> u32 with 0"));
+
+    write_config_file(&testdata, "skip_main = true");
+    list()
+        .stdout(predicate::str::contains("replace main with ()").not())
+        .stdout(predicate::str::contains("replace factorial -> u32 with 0"));
+}
+
+#[test]
+fn custom_mutations_from_config() {
+    let testdata = copy_of_testdata("factorial");
+    write_config_file(
+        &testdata,
+        indoc! { r#"
+            [[custom_mutations]]
+            match = "println"
+            replace_with = "eprintln!({args})"
+        "#},
+    );
+    run()
+        .args(["mutants", "--list", "--line-col=false", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"src/bin/factorial.rs: replace println!("{}! = {}", i, factorial(i)) with eprintln!("{}! = {}", i, factorial(i)) in main"#,
+        ));
+}
+
+#[test]
+fn custom_mutation_args_can_be_followed_by_other_arguments() {
+    let testdata = copy_of_testdata("factorial");
+    write_config_file(
+        &testdata,
+        indoc! { r#"
+            [[custom_mutations]]
+            match = "println"
+            replace_with = "wrap({args}, 0)"
+        "#},
+    );
+    run()
+        .args(["mutants", "--list", "--line-col=false", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"src/bin/factorial.rs: replace println!("{}! = {}", i, factorial(i)) with wrap("{}! = {}", i, factorial(i), 0) in main"#,
+        ));
+}
+
+#[test]
+fn invalid_custom_mutation_rejected() {
+    let testdata = copy_of_testdata("factorial");
+    write_config_file(
+        &testdata,
+        indoc! { r#"
+            [[custom_mutations]]
+            match = "log::info"
+            replace_with = "log::error!({args}"
+        "#},
+    );
+    run()
+        .args(["mutants", "--list", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("Error: parse toml from ").and(predicate::str::contains(
+                r#"custom mutation replace_with "log::error!({args}" is not a Rust expression"#,
+            )),
+        );
+}
+
+#[test]
+fn only_public_from_config_skips_functions_in_private_module_files() {
+    let testdata = copy_of_testdata("small_well_tested");
+    let src = testdata.path().join("src");
+    let lib_rs = read_to_string(src.join("lib.rs")).unwrap();
+    write(
+        src.join("lib.rs"),
+        format!("{lib_rs}\nmod private;\npub mod public;\n"),
+    )
+    .unwrap();
+    write(src.join("private.rs"), "pub fn hidden() -> u32 { 1 }\n").unwrap();
+    write(src.join("public.rs"), "pub fn shown() -> u32 { 2 }\n").unwrap();
+    write_config_file(&testdata, "only_public = true\n");
+    run()
+        .args(["mutants", "--list", "--no-shuffle", "-d"])
+        .arg(testdata.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("shown"))
+        .stdout(predicate::str::contains("factorial"))
+        .stdout(predicate::str::contains("hidden").not());
+}
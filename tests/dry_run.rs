@@ -0,0 +1,82 @@
+// Copyright 2025 Martin Pool
+
+//! Tests for `--dry-run`
+
+use std::fs::{create_dir, read_dir, read_to_string, write};
+
+use indoc::indoc;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+mod util;
+use util::{copy_of_testdata, run};
+
+#[test]
+fn dry_run_without_previous_baseline() {
+    let tmp = copy_of_testdata("small_well_tested");
+    run()
+        .args(["mutants", "--dry-run", "--no-shuffle", "--no-times"])
+        .current_dir(tmp.path())
+        .assert()
+        .success()
+        .stdout(indoc! {"
+            Found 4 mutants to test
+            Plan: test 4 mutants with 1 job; no baseline test time was recorded, so the time can't be estimated
+        "});
+    let output_dir = tmp.path().join("mutants.out");
+    let mut names = read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["plan.json"]);
+    let plan: serde_json::Value =
+        serde_json::from_str(&read_to_string(output_dir.join("plan.json")).unwrap()).unwrap();
+    assert_eq!(
+        plan,
+        json!({
+            "total_mutants": 4,
+            "jobs": 1,
+            "baseline_test_secs": null,
+            "estimated_secs": null,
+        })
+    );
+}
+
+#[test]
+fn dry_run_estimates_time_from_previous_baseline() {
+    let tmp = copy_of_testdata("small_well_tested");
+    let output_dir = tmp.path().join("mutants.out");
+    create_dir(&output_dir).unwrap();
+    let outcomes = json!({
+        "outcomes": [
+            {
+                "scenario": "Baseline",
+                "summary": "Success",
+                "phase_results": [
+                    { "phase": "Build", "duration": 10.0 },
+                    { "phase": "Test", "duration": 4.0 },
+                ],
+            },
+        ],
+    })
+    .to_string();
+    write(output_dir.join("outcomes.json"), &outcomes).unwrap();
+    run()
+        .args(["mutants", "--dry-run", "--no-shuffle", "--no-times", "-j2"])
+        .current_dir(tmp.path())
+        .assert()
+        .success()
+        .stdout(indoc! {"
+            Found 4 mutants to test
+            Plan: test 4 mutants with 2 jobs, estimated to take 8s from a baseline test time of 4s
+        "});
+    // The previous output is not rotated or changed.
+    assert_eq!(
+        read_to_string(output_dir.join("outcomes.json")).unwrap(),
+        outcomes
+    );
+    let plan: serde_json::Value =
+        serde_json::from_str(&read_to_string(output_dir.join("plan.json")).unwrap()).unwrap();
+    assert_eq!(plan["estimated_secs"], 8.0);
+}
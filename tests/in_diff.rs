@@ -171,3 +171,56 @@ fn diff_with_multiple_deletions_is_ok() {
         ))
         .success();
 }
+
+#[test]
+fn include_only_changed_packages_limits_discovery() {
+    let diff = indoc! {r#"
+        diff --git a/main2/src/main.rs b/main2/src/main.rs
+        --- a/main2/src/main.rs
+        +++ b/main2/src/main.rs
+        @@ -9,3 +9,3 @@
+         fn triple_3() -> i32 {
+        -    triple(4)
+        +    triple(3)
+         }
+    "#};
+    let mut diff_file = NamedTempFile::new().unwrap();
+    diff_file.write_all(diff.as_bytes()).unwrap();
+    let tmp = copy_of_testdata("workspace");
+
+    run()
+        .args([
+            "mutants",
+            "--list-files",
+            "--include-only-changed-packages",
+            "-d",
+        ])
+        .arg(tmp.path())
+        .arg("--in-diff")
+        .arg(diff_file.path())
+        .assert()
+        .success()
+        .stdout("main2/src/main.rs\n");
+
+    run()
+        .args(["mutants", "--list", "--include-only-changed-packages", "-d"])
+        .arg(tmp.path())
+        .arg("--in-diff")
+        .arg(diff_file.path())
+        .assert()
+        .success()
+        .stdout(indoc! {"
+            main2/src/main.rs:10:5: replace triple_3 -> i32 with 0
+            main2/src/main.rs:10:5: replace triple_3 -> i32 with 1
+            main2/src/main.rs:10:5: replace triple_3 -> i32 with -1
+        "});
+}
+
+#[test]
+fn include_only_changed_packages_requires_in_diff() {
+    run()
+        .args(["mutants", "--list", "--include-only-changed-packages"])
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("--in-diff"));
+}
@@ -12,7 +12,7 @@ use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use tempfile::tempdir;
 
-use self::util::run;
+use self::util::{copy_of_testdata, run};
 
 #[test]
 #[allow(clippy::too_many_lines)] // long but pretty straightforward
@@ -266,3 +266,18 @@ fn iterate() {
         .success()
         .stdout("");
 }
+
+#[test]
+fn iterate_without_previous_results_tests_all_mutants() {
+    let temp = copy_of_testdata("small_well_tested");
+    run()
+        .arg("mutants")
+        .args(["--list", "--iterate"])
+        .arg("-d")
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "No previously caught or unviable mutants found, so testing all mutants",
+        ));
+}
@@ -59,3 +59,24 @@ fn warn_about_too_many_jobs() {
         ))
         .success();
 }
+
+#[test]
+fn share_deps_with_multiple_jobs() {
+    let testdata = copy_of_testdata("async_fn");
+    run()
+        .arg("mutants")
+        .arg("-d")
+        .arg(testdata.path())
+        .arg("-j2")
+        .arg("--share-deps")
+        .arg("--minimum-test-timeout=120")
+        .assert()
+        .success();
+    let debug_log =
+        read_to_string(testdata.path().join("mutants.out/debug.log")).expect("read debug log");
+    assert!(
+        debug_log.contains("transfer=Link")
+            && debug_log.contains("Transferred dependency artifacts"),
+        "debug log should show dependencies were shared:\n{debug_log}"
+    );
+}
@@ -0,0 +1,69 @@
+// Copyright 2025 Martin Pool
+
+//! Tests for discovering mutants through the library API.
+
+use std::fs::{read_to_string, write};
+
+use cargo_mutants::{discover, Genre, Options, PackageFilter, Workspace};
+
+mod util;
+use util::copy_of_testdata;
+
+#[test]
+fn discover_mutants_in_process() {
+    let tmp = copy_of_testdata("factorial");
+    let workspace = Workspace::open(tmp.path()).unwrap();
+    let discovered = discover(&workspace, &PackageFilter::All, &Options::default()).unwrap();
+    let names = discovered
+        .mutants
+        .iter()
+        .map(|mutant| mutant.name(true))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "src/bin/factorial.rs:2:5: replace main with ()",
+            "src/bin/factorial.rs:8:5: replace factorial -> u32 with 0",
+            "src/bin/factorial.rs:8:5: replace factorial -> u32 with 1",
+            "src/bin/factorial.rs:10:11: replace *= with += in factorial",
+            "src/bin/factorial.rs:10:11: replace *= with /= in factorial",
+        ]
+    );
+    assert_eq!(discovered.mutants[3].genre, Genre::BinaryOperator);
+    assert_eq!(discovered.files.len(), 1);
+}
+
+#[test]
+fn discover_respects_options() {
+    let tmp = copy_of_testdata("factorial");
+    let workspace = Workspace::open(tmp.path()).unwrap();
+    let mut options = Options::default();
+    options.genres = vec![Genre::FnValue];
+    let discovered = discover(&workspace, &PackageFilter::All, &options).unwrap();
+    assert_eq!(discovered.mutants.len(), 3);
+    assert!(discovered
+        .mutants
+        .iter()
+        .all(|mutant| mutant.genre == Genre::FnValue));
+}
+
+#[test]
+fn discover_applies_package_metadata_config() {
+    let tmp = copy_of_testdata("workspace");
+    let manifest_path = tmp.path().join("utils/Cargo.toml");
+    let manifest = read_to_string(&manifest_path).unwrap();
+    write(
+        &manifest_path,
+        format!("{manifest}\n[package.metadata.mutants]\nexclude_globs = [\"**/lib.rs\"]\n"),
+    )
+    .unwrap();
+    let workspace = Workspace::open(tmp.path()).unwrap();
+    let discovered = discover(&workspace, &PackageFilter::All, &Options::default()).unwrap();
+    let files = discovered
+        .files
+        .iter()
+        .map(|file| file.tree_relative_path.as_str())
+        .collect::<Vec<_>>();
+    assert!(!files.contains(&"utils/src/lib.rs"), "{files:?}");
+    assert!(files.contains(&"main/src/main.rs"), "{files:?}");
+}
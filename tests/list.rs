@@ -4,7 +4,9 @@
 
 use std::env;
 use std::fmt::Write;
+use std::fs;
 
+use indoc::indoc;
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 
@@ -25,12 +27,60 @@ fn list_diff_json_contains_diffs() {
     let out_json = serde_json::from_slice::<serde_json::Value>(&out.stdout).unwrap();
     let mutants_json = out_json.as_array().expect("json output is array");
     assert_eq!(mutants_json.len(), 5);
+    assert!(mutants_json.iter().all(|e| e["schema_version"] == 1));
     assert!(mutants_json.iter().all(|e| e.as_object().unwrap()["diff"]
         .as_str()
         .unwrap()
         .contains("--- src/bin/factorial.rs")));
 }
 
+#[test]
+fn list_mutants_in_included_files() {
+    let tmp = copy_of_testdata("include_macro");
+    run()
+        .args(["mutants", "--list", "--line-col=false", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success()
+        .stdout(indoc! {"
+            src/generated.rs: replace double -> u32 with 0
+            src/generated.rs: replace double -> u32 with 1
+            src/generated.rs: replace * with + in double
+            src/generated.rs: replace * with / in double
+            src/api/generated_api.rs: replace api::triple -> u32 with 0
+            src/api/generated_api.rs: replace api::triple -> u32 with 1
+            src/api/generated_api.rs: replace * with + in api::triple
+            src/api/generated_api.rs: replace * with / in api::triple
+        "});
+}
+
+#[test]
+fn include_in_function_body_is_skipped_with_a_warning() {
+    let tmp = copy_of_testdata("include_macro");
+    fs::write(
+        tmp.path().join("src/body.rs"),
+        indoc! {r#"
+            pub fn quadruple(a: u32) -> u32 {
+                include!("body_inner.rs");
+                a * 4
+            }
+        "#},
+    )
+    .unwrap();
+    let lib_path = tmp.path().join("src/lib.rs");
+    let lib = fs::read_to_string(&lib_path).unwrap() + "\npub mod body;\n";
+    fs::write(&lib_path, lib).unwrap();
+    run()
+        .args(["mutants", "--list", "--line-col=false", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "src/body.rs: replace * with + in quadruple",
+        ))
+        .stderr(predicate::str::contains("skipping include! inside a block"));
+}
+
 #[test]
 fn list_mutants_in_all_trees_as_json() {
     // The snapshot accumulated here is actually a big text file
@@ -84,6 +134,30 @@ fn list_mutants_in_factorial() {
         .assert_insta("list_mutants_in_factorial");
 }
 
+#[test]
+fn list_writes_and_reuses_discovery_cache() {
+    let tmp = copy_of_testdata("factorial");
+    let list = || {
+        run()
+            .args(["mutants", "--list", "--line-col=true", "-Ldebug", "-d"])
+            .arg(tmp.path())
+            .assert()
+            .success()
+    };
+    let first =
+        list().stderr(predicate::str::contains("Loaded mutants from the discovery cache").not());
+    let cache_path = tmp.path().join("mutants.out/discovery_cache.json");
+    assert!(cache_path.is_file(), "listing mutants writes the cache");
+    let second = list().stderr(predicate::str::contains(
+        "Loaded mutants from the discovery cache path=\"src/bin/factorial.rs\"",
+    ));
+    assert_eq!(second.get_output().stdout, first.get_output().stdout);
+    assert!(
+        !tmp.path().join("mutants.out.old").exists(),
+        "listing doesn't rotate mutants.out"
+    );
+}
+
 #[test]
 fn list_mutants_in_factorial_json() {
     let tmp = copy_of_testdata("factorial");
@@ -95,6 +169,26 @@ fn list_mutants_in_factorial_json() {
         .assert_insta("list_mutants_in_factorial_json");
 }
 
+#[test]
+fn list_mutants_in_factorial_markdown() {
+    let tmp = copy_of_testdata("factorial");
+    run()
+        .arg("mutants")
+        .arg("--list")
+        .arg("--format=markdown")
+        .current_dir(tmp.path())
+        .assert_insta("list_mutants_in_factorial_markdown");
+}
+
+#[test]
+fn format_conflicts_with_json() {
+    run()
+        .args(["mutants", "--list", "--json", "--format=markdown"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 fn list_mutants_in_cfg_attr_mutants_skip() {
     let tmp_src_dir = copy_of_testdata("cfg_attr_mutants_skip");
@@ -159,6 +253,45 @@ fn list_mutants_with_diffs_in_factorial() {
         .assert_insta("list_mutants_with_diffs_in_factorial");
 }
 
+#[test]
+fn list_mutants_with_context_in_factorial() {
+    let tmp = copy_of_testdata("factorial");
+    run()
+        .arg("mutants")
+        .arg("--list")
+        .arg("--print-mutant-context")
+        .current_dir(&tmp)
+        .assert_insta("list_mutants_with_context_in_factorial");
+}
+
+#[test]
+fn list_mutants_with_diffs_without_context() {
+    let tmp = copy_of_testdata("factorial");
+    let cmd = run()
+        .args([
+            "mutants",
+            "--list",
+            "--diff",
+            "--diff-context=0",
+            "--line-col=false",
+        ])
+        .current_dir(&tmp)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&cmd.get_output().stdout);
+    assert!(
+        stdout.contains(indoc! { "
+            src/bin/factorial.rs: replace *= with += in factorial
+            --- src/bin/factorial.rs
+            +++ replace *= with += in factorial
+            @@ -10 +10 @@
+            -        a *= i;
+            +        a += /* ~ changed by cargo-mutants ~ */ i;
+        " }),
+        "diff without context not found in:\n{stdout}"
+    );
+}
+
 #[test]
 fn list_mutants_well_tested() {
     let tmp = copy_of_testdata("well_tested");
@@ -189,6 +322,16 @@ fn list_mutants_well_tested_exclude_name_filter() {
         .assert_insta("list_mutants_well_tested_exclude_name_filter");
 }
 
+#[test]
+fn list_mutants_well_tested_function_filter() {
+    let tmp = copy_of_testdata("well_tested");
+    run()
+        .arg("mutants")
+        .args(["--list", "--function", "xor", "--function", "Foo::double"])
+        .current_dir(tmp.path())
+        .assert_insta("list_mutants_well_tested_function_filter");
+}
+
 #[test]
 fn list_mutants_well_tested_exclude_folder_filter() {
     let tmp = copy_of_testdata("with_child_directories");
@@ -307,6 +450,60 @@ fn list_files_respects_file_filters() {
         .stdout(predicate::str::contains("lib.rs").not());
 }
 
+#[test]
+fn list_files_with_globs_read_from_files() {
+    let tmp = copy_of_testdata("well_tested");
+    let lists = tempfile::tempdir().unwrap();
+    let files_from = lists.path().join("files.txt");
+    std::fs::write(
+        &files_from,
+        "# Files to mutate\nsrc/simple_fns.rs\n\nsrc/n*.rs\n",
+    )
+    .unwrap();
+    let exclude_from = lists.path().join("exclude.txt");
+    std::fs::write(&exclude_from, "# Excluded\nnumbers.rs\n").unwrap();
+    run()
+        .arg("mutants")
+        .args(["--list-files", "--file", "src/methods.rs", "--files-from"])
+        .arg(&files_from)
+        .arg("--exclude-from")
+        .arg(&exclude_from)
+        .current_dir(tmp.path())
+        .assert()
+        .success()
+        .stdout("src/methods.rs\nsrc/nested_function.rs\nsrc/simple_fns.rs\n");
+}
+
+#[test]
+fn files_from_missing_file_is_an_error() {
+    let tmp = copy_of_testdata("well_tested");
+    run()
+        .arg("mutants")
+        .args(["--list-files", "--files-from", "nonexistent.txt"])
+        .current_dir(tmp.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Failed to read globs from nonexistent.txt",
+        ));
+}
+
+#[test]
+fn files_from_with_no_globs_is_an_error() {
+    let tmp = copy_of_testdata("well_tested");
+    let lists = tempfile::tempdir().unwrap();
+    let files_from = lists.path().join("files.txt");
+    std::fs::write(&files_from, "# Nothing changed\n\n").unwrap();
+    run()
+        .arg("mutants")
+        .args(["--list-files", "--files-from"])
+        .arg(&files_from)
+        .current_dir(tmp.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("No globs found in"));
+}
+
 #[test]
 fn list_files_json_well_tested() {
     let tmp = copy_of_testdata("well_tested");
@@ -352,3 +549,85 @@ fn list_mutants_with_alternate_registry() {
         .stdout("")
         .success();
 }
+
+#[test]
+fn list_explain_skips_in_cfg_attr_mutants_skip() {
+    let tmp = copy_of_testdata("cfg_attr_mutants_skip");
+    run()
+        .args(["mutants", "--list", "--explain-skips", "-d"])
+        .arg(tmp.path())
+        .assert_insta("list_explain_skips_in_cfg_attr_mutants_skip");
+}
+
+#[test]
+fn list_explain_skips_json_includes_filtered_functions() {
+    let tmp = copy_of_testdata("factorial");
+    let cmd = run()
+        .args(["mutants", "--list", "--explain-skips", "--json"])
+        .args(["--exclude-re", "factorial", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success();
+    let out_json = serde_json::from_slice::<serde_json::Value>(&cmd.get_output().stdout).unwrap();
+    let skipped = out_json.as_array().expect("json output is array");
+    assert!(skipped.iter().any(|s| s["name"] == "factorial"
+        && s["reason"] == "Filtered"
+        && s["file"] == "src/bin/factorial.rs"));
+}
+
+#[test]
+fn list_max_mutants_samples_across_files() {
+    let tmp = copy_of_testdata("well_tested");
+    let cmd = run()
+        .args(["mutants", "--list", "--no-shuffle", "--max-mutants=4", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Sampled 4 of 102 mutants; 98 were dropped by --max-mutants",
+        ));
+    insta::assert_snapshot!(
+        String::from_utf8_lossy(&cmd.get_output().stdout),
+        @r#"
+    src/arc.rs:4:5: replace return_arc -> Arc<String> with Arc::new(String::new())
+    src/methods.rs:17:9: replace Foo::double with ()
+    src/result.rs:6:5: replace simple_result -> Result<&'static str, ()> with Ok("")
+    src/slices.rs:4:5: replace pad -> &'a[Cow<'static, str>] with Vec::leak(Vec::new())
+    "#
+    );
+
+    // With a random sample the mutants differ from run to run, but there are still four.
+    let cmd = run()
+        .args(["mutants", "--list", "--shuffle", "--max-mutants=4", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success();
+    assert_eq!(
+        String::from_utf8_lossy(&cmd.get_output().stdout)
+            .lines()
+            .count(),
+        4
+    );
+}
+
+#[test]
+fn max_mutants_must_be_positive() {
+    let tmp = copy_of_testdata("well_tested");
+    run()
+        .args(["mutants", "--list", "--max-mutants=0", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("--max-mutants"));
+}
+
+#[test]
+fn explain_skips_requires_list() {
+    let tmp = copy_of_testdata("factorial");
+    run()
+        .args(["mutants", "--explain-skips", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("--list"));
+}
@@ -196,6 +196,139 @@ fn test_small_well_tested_tree_with_baseline_skip() {
         .exists());
 }
 
+#[test]
+fn baseline_reuse_skips_unchanged_baseline() {
+    let tmp_src_dir = copy_of_testdata("small_well_tested");
+    let baseline_log = tmp_src_dir.path().join("mutants.out/log/baseline.log");
+    let run_with_reuse = || {
+        run()
+            .args(["mutants", "--no-shuffle", "--baseline=reuse", "-d"])
+            .arg(tmp_src_dir.path())
+            .assert()
+            .success()
+    };
+
+    run_with_reuse().stderr(predicate::str::contains("No matching baseline"));
+    assert!(baseline_log.exists());
+    assert!(tmp_src_dir
+        .path()
+        .join("mutants.out/baseline.json")
+        .is_file());
+
+    run_with_reuse().stderr(predicate::str::contains(
+        "Reusing the baseline from the previous run",
+    ));
+    assert!(!baseline_log.exists());
+
+    let lib_path = tmp_src_dir.path().join("src/lib.rs");
+    let mut lib = read_to_string(&lib_path).unwrap();
+    lib.push_str("// changed\n");
+    fs::write(&lib_path, lib).unwrap();
+    run_with_reuse().stderr(predicate::str::contains("No matching baseline"));
+    assert!(baseline_log.exists());
+}
+
+#[test]
+fn retries_and_confirm_caught_rerun_tests() {
+    let tmp_src_dir = copy_of_testdata("small_well_tested");
+    run()
+        .args([
+            "mutants",
+            "--no-shuffle",
+            "--retries=2",
+            "--confirm-caught",
+            "-d",
+        ])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("flaky").not());
+    let log_dir = tmp_src_dir.path().join("mutants.out/log");
+    let baseline_log = read_to_string(log_dir.join("baseline.log")).unwrap();
+    assert!(baseline_log.contains("*** retry 1 of 2"));
+    assert!(baseline_log.contains("*** retry 2 of 2"));
+    let outcomes: serde_json::Value = serde_json::from_str(
+        &read_to_string(tmp_src_dir.path().join("mutants.out/outcomes.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(outcomes["missed"], 0);
+    for outcome in outcomes["outcomes"].as_array().unwrap() {
+        if outcome["summary"] == "CaughtMutant" {
+            // The build, and then the test twice.
+            let phases = outcome["phase_results"].as_array().unwrap();
+            assert_eq!(phases.len(), 3, "{outcome:?}");
+            assert_eq!(phases[1]["phase"], "Test");
+            assert_eq!(phases[2]["phase"], "Test");
+        }
+    }
+}
+
+#[test]
+fn index_genre_shifts_index_and_is_caught() {
+    let tmp_src_dir = copy_of_testdata("mut_ref");
+    run()
+        .args(["mutants", "--genre=index", "--caught", "--no-times", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(contains(
+            "caught   src/lib.rs:2:15: replace 0 with 1 in returns_mut_ref",
+        ))
+        .stdout(contains("1 mutant tested: 1 caught"));
+}
+
+#[test]
+fn target_is_passed_to_cargo() {
+    // Use the host triple, so that the tests can run without a cross-compiling runner.
+    let rustc_version = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("run rustc -vV");
+    let host = String::from_utf8(rustc_version.stdout)
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("host: ").map(str::to_owned))
+        .expect("rustc -vV shows the host");
+    let tmp_src_dir = copy_of_testdata("small_well_tested");
+    run()
+        .args(["mutants", "--no-shuffle", "--target", &host, "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success();
+    let baseline_log =
+        read_to_string(tmp_src_dir.path().join("mutants.out/log/baseline.log")).unwrap();
+    let target_arg = format!("--target={host}");
+    assert!(
+        baseline_log.contains(&format!("test --no-run {target_arg} ")),
+        "{baseline_log}"
+    );
+    assert!(
+        baseline_log.contains(&format!("test {target_arg} ")),
+        "{baseline_log}"
+    );
+}
+
+#[test]
+fn mutant_timings_are_recorded_by_id() {
+    let tmp_src_dir = copy_of_testdata("small_well_tested");
+    run()
+        .args(["mutants", "--no-shuffle", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success();
+    let out_dir = tmp_src_dir.path().join("mutants.out");
+    let mutants: serde_json::Value =
+        serde_json::from_str(&read_to_string(out_dir.join("mutants.json")).unwrap()).unwrap();
+    let timings: serde_json::Value =
+        serde_json::from_str(&read_to_string(out_dir.join("timings.json")).unwrap()).unwrap();
+    let timings = timings.as_object().unwrap();
+    let mutants = mutants.as_array().unwrap();
+    assert_eq!(timings.len(), mutants.len());
+    for mutant in mutants {
+        assert!(timings[mutant["id"].as_str().unwrap()].as_f64().unwrap() > 0.0);
+    }
+}
+
 #[test]
 fn cdylib_tree_is_well_tested() {
     let tmp_src_dir = copy_of_testdata("cdylib");
@@ -211,6 +344,21 @@ fn cdylib_tree_is_well_tested() {
         }));
 }
 
+#[test]
+fn async_fn_tree_is_well_tested() {
+    let tmp_src_dir = copy_of_testdata("async_fn");
+    run()
+        .arg("mutants")
+        .args(["--no-times", "--no-shuffle", "-v", "-V"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::function(|stdout: &str| {
+            insta::assert_snapshot!(stdout);
+            true
+        }));
+}
+
 #[test]
 fn proc_macro_tree_is_well_tested() {
     let tmp_src_dir = copy_of_testdata("proc_macro");
@@ -253,6 +401,13 @@ fn well_tested_tree_finds_no_problems() {
     assert_eq!(outcomes["missed"], 0);
     assert_eq!(outcomes["timeout"], 0);
     assert_eq!(outcomes["total_mutants"], outcomes["caught"]);
+    // The auto-set test timeout is at least the default minimum.
+    let test_timeout_secs = outcomes["test_timeout_secs"]
+        .as_f64()
+        .expect("outcomes['test_timeout_secs'] is a number");
+    assert!(test_timeout_secs >= 20.0, "{test_timeout_secs}");
+    // There's no build timeout unless it's configured.
+    assert!(outcomes["build_timeout_secs"].is_null());
     check_text_list_output(tmp_src_dir.path(), "well_tested_tree_finds_no_problems");
 }
 
@@ -281,6 +436,26 @@ fn well_tested_tree_check_only_shuffled() {
     // Caution: No assertions about output here, we just check that it runs.
 }
 
+#[test]
+fn shuffle_seed_gives_reproducible_order() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    let mut orders = Vec::new();
+    // The seed is shown even with `--no-times`.
+    for no_times in [false, true] {
+        run()
+            .args(["mutants", "--check", "--shuffle-seed=1234"])
+            .args(no_times.then_some("--no-times"))
+            .current_dir(tmp_src_dir.path())
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "Shuffling mutants with seed 1234; use --shuffle-seed=1234 to repeat this order",
+            ));
+        orders.push(read_to_string(tmp_src_dir.path().join("mutants.out/mutants.json")).unwrap());
+    }
+    assert_eq!(orders[0], orders[1]);
+}
+
 #[test]
 fn unviable_mutation_of_struct_with_no_default() {
     let tmp_src_dir = copy_of_testdata("struct_with_no_default");
@@ -296,7 +471,7 @@ fn unviable_mutation_of_struct_with_no_default() {
         .arg("-d")
         .arg(tmp_src_dir.path())
         .assert()
-        .success()
+        .code(6)
         .stdout(
             predicate::str::is_match(
                 r"unviable *src/lib.rs:\d+:\d+: replace make_an_s -> S with Default::default\(\)",
@@ -391,6 +566,283 @@ fn factorial_mutants_with_all_logs() {
         ).unwrap());
 }
 
+#[test]
+fn logical_operator_swap_finds_untested_case() {
+    let tmp_src_dir = copy_of_testdata("logical_operator");
+    run()
+        .args(["mutants", "--no-shuffle", "--genre=logical-operator", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2);
+    assert_eq!(
+        read_to_string(tmp_src_dir.path().join("mutants.out/missed.txt")).unwrap(),
+        "src/lib.rs:5:7: replace && with || in valid\n"
+    );
+}
+
+#[test]
+fn try_operator_deletion_finds_untested_error_case() {
+    let tmp_src_dir = copy_of_testdata("try_operator");
+    run()
+        .args(["mutants", "--no-shuffle", "--genre=try-operator", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2);
+    let read_out =
+        |name: &str| read_to_string(tmp_src_dir.path().join("mutants.out").join(name)).unwrap();
+    assert_eq!(
+        read_out("caught.txt"),
+        "src/lib.rs:10:22: delete ? in checked_double\n"
+    );
+    assert_eq!(
+        read_out("missed.txt"),
+        "src/lib.rs:15:22: delete ? in checked_half\n"
+    );
+    assert_eq!(
+        read_out("unviable.txt"),
+        "src/lib.rs:20:27: delete ? in parse_and_increment\n"
+    );
+}
+
+#[test]
+fn missed_mutants_are_written_as_sarif() {
+    let tmp_src_dir = copy_of_testdata("logical_operator");
+    run()
+        .args(["mutants", "--no-shuffle", "--genre=logical-operator", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2);
+    let sarif: serde_json::Value = serde_json::from_str(
+        &read_to_string(tmp_src_dir.path().join("mutants.out/sarif.json")).unwrap(),
+    )
+    .unwrap();
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "logical-operator");
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/lib.rs");
+    assert_eq!(location["region"]["startLine"], 5);
+    assert_eq!(location["region"]["startColumn"], 7);
+}
+
+#[test]
+fn skipped_items_are_written_to_json() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run()
+        .args([
+            "mutants",
+            "--check",
+            "--no-shuffle",
+            "--exclude-re=main",
+            "-d",
+        ])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success();
+    let skipped: serde_json::Value = serde_json::from_str(
+        &read_to_string(tmp_src_dir.path().join("mutants.out/skipped.json")).unwrap(),
+    )
+    .unwrap();
+    let skipped = skipped.as_array().unwrap();
+    let find = |name: &str| {
+        skipped
+            .iter()
+            .find(|s| s["name"] == name)
+            .unwrap_or_else(|| panic!("{name} is in skipped.json"))
+    };
+    assert_eq!(find("test_factorial")["reason"], "TestFunction");
+    assert_eq!(find("test_factorial")["category"], "Attribute");
+    assert_eq!(find("test_factorial")["file"], "src/bin/factorial.rs");
+    assert_eq!(find("main")["reason"], "Filtered");
+    assert_eq!(find("main")["category"], "Excluded");
+    assert!(skipped.iter().all(|s| s["name"] != "factorial"));
+}
+
+#[test]
+fn html_report_shows_missed_mutants() {
+    let tmp_src_dir = copy_of_testdata("logical_operator");
+    run()
+        .args(["mutants", "--no-shuffle", "--genre=logical-operator", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2);
+    let html = read_to_string(tmp_src_dir.path().join("mutants.out/report.html")).unwrap();
+    assert!(html.contains("<p>1 mutants tested: 1 missed, 0 caught, 0 unviable, 0 timeouts</p>"));
+    assert!(html.contains("<details>\n<summary>src/lib.rs (1 missed)</summary>"));
+    assert!(html.contains("<h3>5:7: replace &amp;&amp; with || in valid</h3>"));
+    assert!(html.contains(r#"<a href="diff/src__lib.rs_line_5_col_7.diff">diff</a>"#));
+    assert!(html.contains(r#"<a href="log/src__lib.rs_line_5_col_7.log">log</a>"#));
+}
+
+#[test]
+fn fail_fast_stops_after_first_missed_mutant() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run()
+        .args(["mutants", "--no-times", "--no-shuffle", "--fail-fast", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stdout(contains(
+            "MISSED   src/bin/factorial.rs:2:5: replace main with ()",
+        ))
+        .stdout(contains(
+            "1 mutant tested: 1 missed; stopped early after a missed mutant (--fail-fast)",
+        ));
+    let read_out =
+        |name: &str| read_to_string(tmp_src_dir.path().join("mutants.out").join(name)).unwrap();
+    assert_eq!(
+        read_out("missed.txt"),
+        "src/bin/factorial.rs:2:5: replace main with ()\n"
+    );
+    assert_eq!(read_out("caught.txt"), "");
+    let outcomes: serde_json::Value = serde_json::from_str(&read_out("outcomes.json")).unwrap();
+    // Just the baseline and the first mutant.
+    assert_eq!(outcomes["outcomes"].as_array().unwrap().len(), 2);
+    assert_eq!(outcomes["total_mutants"], 1);
+}
+
+#[test]
+fn missed_diffs_has_diff_of_each_missed_mutant() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run()
+        .args(["mutants", "--no-shuffle", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2);
+    let mutants_out = tmp_src_dir.path().join("mutants.out");
+    let missed_diffs = read_to_string(mutants_out.join("missed_diffs.txt")).unwrap();
+    let missed = read_to_string(mutants_out.join("missed.txt")).unwrap();
+    assert!(!missed.is_empty());
+    for name in missed.lines() {
+        let diff_start = missed_diffs
+            .find(&format!("{name}\n\n--- src/"))
+            .unwrap_or_else(|| panic!("{name:?} not in missed_diffs.txt:\n{missed_diffs}"));
+        assert!(missed_diffs[diff_start..].contains("/* ~ changed by cargo-mutants ~ */"));
+    }
+    assert_eq!(
+        missed_diffs.matches("\n+++ ").count(),
+        missed.lines().count()
+    );
+    // Caught mutants are not included.
+    for name in read_to_string(mutants_out.join("caught.txt"))
+        .unwrap()
+        .lines()
+    {
+        assert!(
+            !missed_diffs.contains(name),
+            "{name:?} is in missed_diffs.txt"
+        );
+    }
+}
+
+#[test]
+fn progress_json_has_final_counts() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run()
+        .args(["mutants", "--no-times", "--no-shuffle", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2);
+    let mutants_out = tmp_src_dir.path().join("mutants.out");
+    let progress: serde_json::Value =
+        serde_json::from_str(&read_to_string(mutants_out.join("progress.json")).unwrap()).unwrap();
+    let outcomes: serde_json::Value =
+        serde_json::from_str(&read_to_string(mutants_out.join("outcomes.json")).unwrap()).unwrap();
+    assert_eq!(progress["finished"], true);
+    assert_eq!(progress["total_mutants"], outcomes["total_mutants"]);
+    assert_eq!(progress["completed"], outcomes["total_mutants"]);
+    assert_eq!(progress["remaining"], 0);
+    assert_eq!(progress["missed"], outcomes["missed"]);
+    assert_eq!(progress["caught"], outcomes["caught"]);
+    assert_eq!(progress["running"].as_array().unwrap().len(), 0);
+    assert!(!mutants_out.join("progress.json.tmp").exists());
+}
+
+#[test]
+fn json_lines_output_streams_outcomes_to_stdout() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    let cmd = run()
+        .args([
+            "mutants",
+            "--no-times",
+            "--no-shuffle",
+            "--output-format=json-lines",
+            "-d",
+        ])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stderr(contains("Found 5 mutants to test"))
+        .stderr(contains(
+            "MISSED   src/bin/factorial.rs:2:5: replace main with ()",
+        ))
+        .stderr(contains("5 mutants tested: 1 missed, 4 caught"));
+    let stdout = String::from_utf8_lossy(&cmd.get_output().stdout);
+    let lines = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    // The baseline, then each mutant.
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0]["scenario"], "Baseline");
+    assert_eq!(lines[0]["summary"], "Success");
+    assert_eq!(lines[1]["summary"], "MissedMutant");
+    assert_eq!(
+        lines[1]["scenario"]["Mutant"]["function"]["function_name"],
+        "main"
+    );
+    // The lines are the same as the outcomes in outcomes.json.
+    let outcomes: serde_json::Value = serde_json::from_str(
+        &read_to_string(tmp_src_dir.path().join("mutants.out/outcomes.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(outcomes["outcomes"].as_array().unwrap(), &lines);
+}
+
+#[test]
+fn summary_only_prints_just_the_summary() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    run()
+        .args(["mutants", "--no-times", "--summary-only", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stdout("5 mutants tested: 1 missed, 4 caught\n");
+    // The per-mutant results are still written to mutants.out.
+    assert_eq!(
+        read_to_string(tmp_src_dir.path().join("mutants.out/missed.txt")).unwrap(),
+        "src/bin/factorial.rs:2:5: replace main with ()\n"
+    );
+    let outcomes: serde_json::Value = serde_json::from_str(
+        &read_to_string(tmp_src_dir.path().join("mutants.out/outcomes.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(outcomes["outcomes"].as_array().unwrap().len(), 6);
+}
+
+#[test]
+fn summary_only_with_json_prints_a_summary_object() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    let cmd = run()
+        .args(["mutants", "--no-times", "--summary-only", "--json", "-d"])
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(2);
+    let summary: serde_json::Value =
+        serde_json::from_slice(&cmd.get_output().stdout).expect("stdout is one JSON object");
+    assert_eq!(
+        summary,
+        serde_json::json!({
+            "total_mutants": 5,
+            "missed": 1,
+            "caught": 4,
+            "timeout": 0,
+            "unviable": 0,
+            "success": 0,
+        })
+    );
+}
+
 #[test]
 fn factorial_mutants_with_all_logs_and_nocapture() {
     let tmp_src_dir = copy_of_testdata("factorial");
@@ -412,7 +864,7 @@ fn factorial_mutants_no_copy_target() {
     let tmp_src_dir = copy_of_testdata("factorial");
     run()
         .arg("mutants")
-        .args(["--no-times"])
+        .args(["--no-times", "--no-shuffle"])
         .arg("-d")
         .arg(tmp_src_dir.path())
         .assert()
@@ -429,7 +881,7 @@ fn small_well_tested_mutants_with_cargo_arg_release() {
     let tmp_src_dir = copy_of_testdata("small_well_tested");
     run()
         .arg("mutants")
-        .args(["--no-times", "--cargo-arg", "--release"])
+        .args(["--no-times", "--no-shuffle", "--cargo-arg", "--release"])
         .arg("-d")
         .arg(tmp_src_dir.path())
         .assert()
@@ -494,6 +946,66 @@ fn output_option() {
     }
 }
 
+/// Return the names of the files in a zip archive.
+fn zip_file_names(archive_path: &Path) -> Vec<String> {
+    let archive = zip::ZipArchive::new(fs::File::open(archive_path).expect("open archive"))
+        .expect("read zip archive");
+    archive.file_names().map(str::to_owned).collect()
+}
+
+#[test]
+/// `--output-archive` writes a zip file containing everything in `mutants.out`,
+/// instead of the directory.
+fn output_archive_option() {
+    let tmp_src_dir = copy_of_testdata("factorial");
+    let output_tmpdir = TempDir::new().unwrap();
+    let archive_path = output_tmpdir.path().join("results.zip");
+    run()
+        .arg("mutants")
+        .arg("--output-archive")
+        .arg(&archive_path)
+        .args(["--check", "--no-times"])
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .success();
+    assert!(
+        !tmp_src_dir.path().join("mutants.out").exists(),
+        "mutants.out is not written"
+    );
+    let names = zip_file_names(&archive_path);
+    for name in [
+        "mutants.out/mutants.json",
+        "mutants.out/debug.log",
+        "mutants.out/outcomes.json",
+        "mutants.out/missed.txt",
+        "mutants.out/caught.txt",
+        "mutants.out/timeout.txt",
+        "mutants.out/unviable.txt",
+        "mutants.out/log/baseline.log",
+    ] {
+        assert!(names.iter().any(|n| n == name), "{name} is in the archive");
+    }
+}
+
+#[test]
+fn output_archive_is_written_when_baseline_fails() {
+    let tmp_src_dir = copy_of_testdata("already_failing_tests");
+    let output_tmpdir = TempDir::new().unwrap();
+    let archive_path = output_tmpdir.path().join("results.zip");
+    run()
+        .arg("mutants")
+        .arg("--output-archive")
+        .arg(&archive_path)
+        .arg("-d")
+        .arg(tmp_src_dir.path())
+        .assert()
+        .code(4);
+    assert!(zip_file_names(&archive_path)
+        .iter()
+        .any(|n| n == "mutants.out/log/baseline.log"));
+}
+
 #[test]
 /// Set the `--output` directory via environment variable `CARGO_MUTANTS_OUTPUT`
 fn output_option_use_env() {
@@ -591,6 +1103,84 @@ fn already_failing_tests_are_detected_before_running_mutants() {
         ));
 }
 
+#[test]
+fn baseline_with_no_tests_is_detected_before_running_mutants() {
+    let tmp_src_dir = copy_of_testdata("no_tests");
+    run()
+        .args(["mutants", "--fail-on-no-tests=true"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains(
+            "No tests were run in the unmutated tree, so every mutant would be missed; no mutants were tested",
+        ));
+    let outcomes: serde_json::Value = serde_json::from_str(
+        &read_to_string(tmp_src_dir.path().join("mutants.out/outcomes.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(outcomes["total_mutants"], 0);
+    assert_eq!(
+        read_to_string(tmp_src_dir.path().join("mutants.out/missed.txt")).unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn baseline_with_no_tests_is_not_checked_by_default() {
+    let tmp_src_dir = copy_of_testdata("no_tests");
+    run()
+        .arg("mutants")
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("No tests were run").not());
+}
+
+#[test]
+fn test_command_is_run_instead_of_cargo_test() {
+    let tmp_src_dir = copy_of_testdata("small_well_tested");
+    // A command that always succeeds misses every viable mutant.
+    run()
+        .arg("mutants")
+        .args(["--no-shuffle", "--test-command", "exit 0"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("MISSED"));
+    // A command that always fails fails the baseline.
+    run()
+        .arg("mutants")
+        .args(["--test-command", "exit 1"])
+        .current_dir(tmp_src_dir.path())
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "Test command \"exit 1\" failed in an unmutated tree, so no mutants were tested",
+        ));
+}
+
+#[test]
+fn already_failing_tokio_tests_are_detected() {
+    let tmp_src_dir = copy_of_testdata("already_failing_tokio_tests");
+    run()
+        .arg("mutants")
+        .current_dir(tmp_src_dir.path())
+        .env_remove("RUST_BACKTRACE")
+        .assert()
+        .code(4) // CLEAN_TESTS_FAILED
+        .stdout(
+            predicate::str::contains("test test::test_factorial ... FAILED")
+                .and(predicate::str::contains("72"))
+                .and(
+                    predicate::str::contains("test result: FAILED. 0 passed; 1 failed;")
+                        .normalize(),
+                ),
+        )
+        .stderr(predicate::str::contains(
+            "cargo test failed in an unmutated tree, so no mutants were tested",
+        ));
+}
+
 #[test]
 fn already_failing_doctests_are_detected() {
     let tmp_src_dir = copy_of_testdata("already_failing_doctests");
@@ -621,6 +1211,34 @@ fn already_failing_doctests_can_be_skipped_with_cargo_arg() {
         .code(0);
 }
 
+#[test]
+fn mutants_caught_only_by_doctest_are_missed_without_doctests() {
+    let tmp_src_dir = copy_of_testdata("caught_only_by_doctest");
+    run()
+        .args(["mutants", "--no-times", "-d"])
+        .arg(tmp_src_dir.path())
+        .args(["--", "--all-targets"])
+        .assert()
+        .code(2) // FOUND_PROBLEMS
+        .stdout(contains("4 mutants tested: 4 missed"));
+}
+
+#[test]
+fn doctests_option_runs_doctests_that_catch_mutants() {
+    let tmp_src_dir = copy_of_testdata("caught_only_by_doctest");
+    run()
+        .args(["mutants", "--doctests", "--no-times", "-d"])
+        .arg(tmp_src_dir.path())
+        .args(["--", "--all-targets"])
+        .assert()
+        .success()
+        .stdout(contains("4 mutants tested: 4 caught"));
+    let baseline_log = read_to_string(tmp_src_dir.path().join("mutants.out/log/baseline.log"))
+        .expect("read baseline log");
+    assert!(baseline_log.contains("test --doc --verbose"));
+    assert!(baseline_log.contains("src/lib.rs - double (line 6) ... ok"));
+}
+
 #[test]
 fn source_tree_parse_fails() {
     let tmp_src_dir = copy_of_testdata("parse_fails");
@@ -776,6 +1394,17 @@ fn mutants_causing_tests_to_hang_are_stopped_by_manual_timeout() {
 
     assert_eq!(phases_for_const_fn.len(), 1);
     assert_eq!(phases_for_const_fn[0]["phase"], "Build");
+
+    let timed_out_outcome = outcomes_json["outcomes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|outcome| outcome["summary"] == "Timeout")
+        .expect("Find the timed out outcome");
+    assert_eq!(timed_out_outcome["timed_out_phase"], "Test");
+    let phases = timed_out_outcome["phase_results"].as_array().unwrap();
+    assert_eq!(phases[0]["timed_out"], false);
+    assert_eq!(phases[1]["timed_out"], true);
 }
 
 #[test]
@@ -799,6 +1428,23 @@ fn hang_avoided_by_build_timeout_with_cap_lints() {
         timeout_txt.contains("replace should_stop_const -> bool with false"),
         "expected text not found in:\n{timeout_txt}"
     );
+    let outcomes_json: serde_json::Value =
+        read_to_string(tmp_src_dir.path().join("mutants.out/outcomes.json"))
+            .expect("read outcomes.json")
+            .parse()
+            .expect("parse outcomes.json");
+    let timed_out_phases = outcomes_json["outcomes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|outcome| outcome["summary"] == "Timeout")
+        .map(|outcome| &outcome["timed_out_phase"])
+        .collect::<Vec<_>>();
+    assert!(!timed_out_phases.is_empty());
+    assert!(
+        timed_out_phases.iter().all(|phase| *phase == "Build"),
+        "expected only build timeouts: {timed_out_phases:?}"
+    );
 }
 
 #[test]
@@ -1009,3 +1655,71 @@ fn completions_option_generates_something() {
             .stdout(predicate::str::is_empty().not());
     }
 }
+
+#[test]
+fn missed_by_function_groups_missed_mutants() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join("Cargo.toml"),
+        indoc! { r#"
+            [package]
+            name = "cargo_mutants_missed_by_function"
+            edition = "2021"
+            version = "0.0.0"
+            publish = false
+        "# },
+    )
+    .unwrap();
+    create_dir(tmp.path().join("src")).unwrap();
+    fs::write(
+        tmp.path().join("src/lib.rs"),
+        indoc! { r"
+            pub fn untested(a: u32) -> u32 {
+                a + 1
+            }
+
+            pub fn partly_tested(a: u32) -> u32 {
+                a * 2
+            }
+
+            pub fn tested(a: u32) -> u32 {
+                a - 1
+            }
+
+            #[test]
+            fn test_partly_tested() {
+                assert_eq!(partly_tested(0), 0);
+            }
+
+            #[test]
+            fn test_tested() {
+                assert_eq!(tested(3), 2);
+            }
+        "},
+    )
+    .unwrap();
+    run()
+        .args([
+            "mutants",
+            "--no-times",
+            "--no-shuffle",
+            "--summary-only",
+            "--missed-by-function",
+            "-d",
+        ])
+        .arg(tmp.path())
+        .assert()
+        .code(2)
+        .stdout(indoc! {"
+            12 mutants tested: 6 missed, 6 caught
+            Missed mutants by function:
+            src/lib.rs: partly_tested: 2 of 4 missed
+                src/lib.rs:6:5: replace partly_tested -> u32 with 0
+                src/lib.rs:6:7: replace * with / in partly_tested
+            src/lib.rs: untested: all 4 missed
+                src/lib.rs:2:5: replace untested -> u32 with 0
+                src/lib.rs:2:5: replace untested -> u32 with 1
+                src/lib.rs:2:7: replace + with - in untested
+                src/lib.rs:2:7: replace + with * in untested
+        "});
+}
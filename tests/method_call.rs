@@ -0,0 +1,62 @@
+// Copyright 2025 Martin Pool
+
+//! Tests for the `method-call` genre, which deletes method calls.
+
+mod util;
+
+use std::fs::{create_dir, read_to_string, write};
+
+use indoc::indoc;
+use tempfile::tempdir;
+
+use self::util::run;
+
+#[test]
+fn deleting_a_needed_clone_is_caught() {
+    let temp = tempdir().unwrap();
+    write(
+        temp.path().join("Cargo.toml"),
+        indoc! { r#"
+            [package]
+            name = "cargo_mutants_method_call"
+            edition = "2021"
+            version = "0.0.0"
+            publish = false
+        "# },
+    )
+    .unwrap();
+    create_dir(temp.path().join("src")).unwrap();
+    write(
+        temp.path().join("src/lib.rs"),
+        indoc! { r#"
+            #[derive(Clone, Default)]
+            pub struct Counter {
+                count: u32,
+            }
+
+            /// Return what the count would be after one more bump, without changing it.
+            pub fn peek_bumped(counter: &mut Counter) -> u32 {
+                let mut copy = counter.clone();
+                copy.count += 1;
+                copy.count
+            }
+
+            #[test]
+            fn peek_does_not_change_the_original() {
+                let mut counter = Counter::default();
+                assert_eq!(peek_bumped(&mut counter), 1);
+                assert_eq!(counter.count, 0);
+            }
+        "#},
+    )
+    .unwrap();
+    run()
+        .args(["mutants", "--no-shuffle", "--genre=method-call", "-d"])
+        .arg(temp.path())
+        .assert()
+        .success();
+    assert_eq!(
+        read_to_string(temp.path().join("mutants.out/caught.txt")).unwrap(),
+        "src/lib.rs:8:27: delete .clone() in peek_bumped\n"
+    );
+}
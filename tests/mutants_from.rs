@@ -0,0 +1,136 @@
+// Copyright 2025 Martin Pool
+
+//! Test `--emit-mutants` and `--mutants-from`.
+
+use std::fs::{read_to_string, write};
+
+use predicates::prelude::predicate;
+
+mod util;
+use util::{copy_of_testdata, run};
+
+#[test]
+fn emitted_mutants_can_be_tested_later() {
+    let tmp = copy_of_testdata("small_well_tested");
+    let emitted = tmp.path().join("emitted.json");
+    run()
+        .args(["mutants", "--no-shuffle", "-d"])
+        .arg(tmp.path())
+        .arg("--emit-mutants")
+        .arg(&emitted)
+        .assert()
+        .success();
+    assert!(
+        !tmp.path().join("mutants.out").exists(),
+        "emitting mutants doesn't test them"
+    );
+    let emitted_json: serde_json::Value =
+        serde_json::from_str(&read_to_string(&emitted).unwrap()).unwrap();
+    let n_mutants = emitted_json.as_array().unwrap().len();
+    assert!(n_mutants > 0);
+
+    run()
+        .args(["mutants", "--no-shuffle", "-d"])
+        .arg(tmp.path())
+        .arg("--mutants-from")
+        .arg(&emitted)
+        .assert()
+        .success();
+    let tested_json: serde_json::Value =
+        serde_json::from_str(&read_to_string(tmp.path().join("mutants.out/mutants.json")).unwrap())
+            .unwrap();
+    assert_eq!(tested_json, emitted_json);
+    assert_eq!(
+        read_to_string(tmp.path().join("mutants.out/caught.txt"))
+            .unwrap()
+            .lines()
+            .count(),
+        n_mutants
+    );
+}
+
+#[test]
+fn mutants_from_changed_source_are_rejected() {
+    let tmp = copy_of_testdata("small_well_tested");
+    let emitted = tmp.path().join("emitted.json");
+    run()
+        .args(["mutants", "-d"])
+        .arg(tmp.path())
+        .arg("--emit-mutants")
+        .arg(&emitted)
+        .assert()
+        .success();
+
+    let lib_path = tmp.path().join("src/lib.rs");
+    let lib_rs = read_to_string(&lib_path).unwrap();
+    write(
+        &lib_path,
+        lib_rs.replace("let mut a = 1;", "let mut a = 1u32;"),
+    )
+    .unwrap();
+
+    run()
+        .args(["mutants", "-d"])
+        .arg(tmp.path())
+        .arg("--mutants-from")
+        .arg(&emitted)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "doesn't match the current source of src/lib.rs",
+        ));
+}
+
+#[test]
+fn filters_apply_to_mutants_from() {
+    let tmp = copy_of_testdata("small_well_tested");
+    let emitted = tmp.path().join("emitted.json");
+    run()
+        .args(["mutants", "-d"])
+        .arg(tmp.path())
+        .arg("--emit-mutants")
+        .arg(&emitted)
+        .assert()
+        .success();
+
+    run()
+        .args(["mutants", "--no-shuffle", "-d"])
+        .arg(tmp.path())
+        .arg("--mutants-from")
+        .arg(&emitted)
+        .args(["--exclude-genre=fn-value", "--exclude-re=/="])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 1 mutant to test"));
+    assert_eq!(
+        read_to_string(tmp.path().join("mutants.out/caught.txt")).unwrap(),
+        "src/lib.rs:7:11: replace *= with += in factorial\n"
+    );
+}
+
+#[test]
+fn in_diff_applies_to_mutants_from() {
+    let tmp = copy_of_testdata("small_well_tested");
+    let emitted = tmp.path().join("emitted.json");
+    run()
+        .args(["mutants", "-d"])
+        .arg(tmp.path())
+        .arg("--emit-mutants")
+        .arg(&emitted)
+        .assert()
+        .success();
+    let empty_diff = tmp.path().join("empty.diff");
+    write(&empty_diff, "").unwrap();
+
+    run()
+        .args(["mutants", "--list", "-d"])
+        .arg(tmp.path())
+        .arg("--mutants-from")
+        .arg(&emitted)
+        .arg("--in-diff")
+        .arg(&empty_diff)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("diff file is empty"));
+}
@@ -0,0 +1,133 @@
+// Copyright 2025 Martin Pool
+
+//! Tests for handling of `RUSTFLAGS` inherited from the environment.
+
+mod util;
+
+use std::fs::{create_dir, read_to_string, write};
+
+use indoc::indoc;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use tempfile::{tempdir, TempDir};
+
+use self::util::run;
+
+/// Make a tree where replacing the function body causes an unused variable warning.
+fn tree_with_unused_variable_mutant() -> TempDir {
+    let temp = tempdir().unwrap();
+    write(
+        temp.path().join("Cargo.toml"),
+        indoc! { r#"
+            [package]
+            name = "cargo_mutants_rustflags"
+            edition = "2021"
+            version = "0.0.0"
+            publish = false
+        "# },
+    )
+    .unwrap();
+    create_dir(temp.path().join("src")).unwrap();
+    write(
+        temp.path().join("src/lib.rs"),
+        indoc! { r#"
+            pub fn add_two(a: usize) -> usize {
+                a + 2
+            }
+
+            #[test]
+            fn test_add_two() {
+                assert_eq!(add_two(10), 12);
+            }
+        "#},
+    )
+    .unwrap();
+    temp
+}
+
+#[test]
+fn deny_warnings_in_rustflags_is_removed_by_default() {
+    let temp = tree_with_unused_variable_mutant();
+    run()
+        .args(["mutants", "--no-shuffle", "--re", "with 0", "-d"])
+        .arg(temp.path())
+        .env("RUSTFLAGS", "-Dwarnings")
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("--respect-rustflags"));
+    assert_eq!(
+        read_to_string(temp.path().join("mutants.out/caught.txt")).unwrap(),
+        "src/lib.rs:2:5: replace add_two -> usize with 0\n"
+    );
+}
+
+#[test]
+fn deny_warnings_in_rustflags_is_kept_with_respect_rustflags() {
+    let temp = tree_with_unused_variable_mutant();
+    run()
+        .args([
+            "mutants",
+            "--no-shuffle",
+            "--re",
+            "with 0",
+            "--respect-rustflags",
+            "-d",
+        ])
+        .arg(temp.path())
+        .env("RUSTFLAGS", "-Dwarnings")
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .assert()
+        .code(6) // The only mutant is unviable
+        .stderr(predicate::str::contains("--respect-rustflags").not());
+    assert_eq!(
+        read_to_string(temp.path().join("mutants.out/unviable.txt")).unwrap(),
+        "src/lib.rs:2:5: replace add_two -> usize with 0\n"
+    );
+}
+
+#[test]
+fn rustflags_option_is_added_to_inherited_rustflags() {
+    let temp = tree_with_unused_variable_mutant();
+    write(
+        temp.path().join("src/lib.rs"),
+        indoc! { r#"
+            pub fn add_two(a: usize) -> usize {
+                a + 2
+            }
+
+            #[test]
+            fn test_add_two() {
+                assert_eq!(add_two(10), 12);
+            }
+
+            #[test]
+            fn both_cfgs_are_set() {
+                assert!(cfg!(inherited_flag));
+                assert!(cfg!(option_flag));
+            }
+        "#},
+    )
+    .unwrap();
+    run()
+        .args([
+            "mutants",
+            "--no-shuffle",
+            "--re",
+            "with 0",
+            "--rustflags=--cfg option_flag",
+            "-d",
+        ])
+        .arg(temp.path())
+        .env("RUSTFLAGS", "--cfg inherited_flag")
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .assert()
+        .success();
+    let baseline_log = read_to_string(temp.path().join("mutants.out/log/baseline.log")).unwrap();
+    assert!(
+        baseline_log.contains(
+            r"CARGO_ENCODED_RUSTFLAGS=--cfg\u{1f}inherited_flag\u{1f}--cfg\u{1f}option_flag"
+        ),
+        "{baseline_log}"
+    );
+}
@@ -3,10 +3,69 @@
 //! Test `--shard`
 
 use itertools::Itertools;
+use predicates::prelude::*;
 
 mod util;
 use util::{copy_of_testdata, run};
 
+#[test]
+fn shard_by_file_keeps_files_together() {
+    let tmp = copy_of_testdata("well_tested");
+    let common_args = ["mutants", "--list", "-d", tmp.path().to_str().unwrap()];
+    let n_shards = 3;
+    let shard_lists = (0..n_shards)
+        .map(|k| {
+            String::from_utf8(
+                run()
+                    .args(common_args)
+                    .args(["--shard", &format!("{k}/{n_shards}"), "--shard-by-file"])
+                    .assert()
+                    .success()
+                    .get_output()
+                    .stdout
+                    .clone(),
+            )
+            .unwrap()
+            .lines()
+            .map(ToOwned::to_owned)
+            .collect_vec()
+        })
+        .collect_vec();
+    let files = shard_lists
+        .iter()
+        .map(|list| {
+            list.iter()
+                .map(|name| name.split_once(':').unwrap().0.to_owned())
+                .unique()
+                .collect_vec()
+        })
+        .collect_vec();
+    for i in 0..n_shards {
+        assert!(!files[i].is_empty(), "shard {i} is empty");
+        for j in 0..i {
+            assert!(
+                files[i].iter().all(|f| !files[j].contains(f)),
+                "shards {i} and {j} both contain mutants from {:?}",
+                files[i]
+                    .iter()
+                    .filter(|f| files[j].contains(f))
+                    .collect_vec()
+            );
+        }
+    }
+}
+
+#[test]
+fn shard_by_file_requires_shard() {
+    let tmp = copy_of_testdata("well_tested");
+    run()
+        .args(["mutants", "--list", "--shard-by-file", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("--shard"));
+}
+
 #[test]
 fn shard_divides_all_mutants() {
     // For speed, this only lists the mutants, trusting that the mutants
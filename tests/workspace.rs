@@ -6,7 +6,7 @@ use std::fs::{self, create_dir, read_to_string, write};
 
 use insta::assert_snapshot;
 use itertools::Itertools;
-use predicates::prelude::predicate;
+use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use serde_json::json;
 
@@ -26,17 +26,33 @@ fn open_by_manifest_path() {
         ));
 }
 
+#[test]
+fn manifest_path_of_member_selects_only_that_package() {
+    let tmp = copy_of_testdata("workspace");
+    run()
+        .args(["mutants", "--list", "--line-col=false", "--manifest-path"])
+        .arg(tmp.path().join("utils/Cargo.toml"))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("utils/src/lib.rs"))
+        .stdout(predicates::str::contains("main/src/main.rs").not())
+        .stdout(predicates::str::contains("main2/src/main.rs").not());
+    // The workspace root manifest selects packages in the same way as running from the root.
+    run()
+        .args(["mutants", "--list", "--line-col=false", "--manifest-path"])
+        .arg(tmp.path().join("Cargo.toml"))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("utils/src/lib.rs"))
+        .stdout(predicates::str::contains("main/src/main.rs"));
+}
+
 #[test]
 fn list_warns_about_unmatched_packages() {
+    let tmp = copy_of_testdata("workspace");
     run()
-        .args([
-            "mutants",
-            "--list",
-            "-d",
-            "testdata/workspace",
-            "-p",
-            "notapackage",
-        ])
+        .args(["mutants", "--list", "-p", "notapackage", "-d"])
+        .arg(tmp.path())
         .assert()
         .stderr(predicates::str::contains(
             "Package \"notapackage\" not found in source tree",
@@ -44,6 +60,25 @@ fn list_warns_about_unmatched_packages() {
         .code(0);
 }
 
+#[test]
+fn default_members_are_mutated_by_default() {
+    let tmp = copy_of_testdata("workspace_default_members");
+    run()
+        .args(["mutants", "--list", "--line-col=false", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("core/src/lib.rs: replace double"))
+        .stdout(predicates::str::contains("tools/src/lib.rs").not());
+    run()
+        .args(["mutants", "--list", "--line-col=false", "--workspace", "-d"])
+        .arg(tmp.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("core/src/lib.rs: replace double"))
+        .stdout(predicates::str::contains("tools/src/lib.rs: replace halve"));
+}
+
 #[test]
 fn list_files_json_workspace() {
     // Demonstrates that we get package names in the json listing.